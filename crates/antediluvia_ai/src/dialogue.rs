@@ -3,6 +3,7 @@
 //! Generates NPC responses based on context, lineage, and knowledge base.
 
 use serde::{Deserialize, Serialize};
+use antediluvia_core::entity::{Lineage, Standing};
 use crate::error::Result;
 use crate::knowledge_base::KnowledgeBase;
 
@@ -29,8 +30,10 @@ pub enum Sentiment {
 pub struct DialogueContext {
     pub npc_name: String,
     pub npc_lineage: NPCLineage,
+    pub player_lineage: Lineage,
     pub player_corruption: f32,
     pub world_corruption: f32,
+    pub player_standing: Standing, // With the NPC's faction
 }
 
 /// NPC lineage affects dialogue tone.
@@ -40,6 +43,15 @@ pub enum NPCLineage {
     Cain,  // Deceptive, ambitious
 }
 
+impl From<Lineage> for NPCLineage {
+    fn from(lineage: Lineage) -> Self {
+        match lineage {
+            Lineage::Seth => NPCLineage::Seth,
+            Lineage::Cain => NPCLineage::Cain,
+        }
+    }
+}
+
 /// The dialogue generator.
 #[derive(Clone, Debug)]
 pub struct DialogueGenerator {
@@ -103,35 +115,50 @@ impl DialogueGenerator {
             })
         } else {
             // Fallback response
-            let response = match context.npc_lineage {
-                NPCLineage::Seth => {
-                    format!("{}: I do not know the answer to that. Perhaps the Lord will reveal it in time.", context.npc_name)
-                }
-                NPCLineage::Cain => {
-                    format!("{}: That is not my concern. What matters is power and progress.", context.npc_name)
+            let response = if context.player_standing <= Standing::Hostile {
+                format!("{}: I have nothing to say to you.", context.npc_name)
+            } else {
+                match context.npc_lineage {
+                    NPCLineage::Seth => {
+                        format!("{}: I do not know the answer to that. Perhaps the Lord will reveal it in time.", context.npc_name)
+                    }
+                    NPCLineage::Cain => {
+                        format!("{}: That is not my concern. What matters is power and progress.", context.npc_name)
+                    }
                 }
             };
 
             Ok(DialogueExchange {
                 player_input: query.to_string(),
                 npc_response: response,
-                sentiment: Sentiment::Neutral,
+                sentiment: if context.player_standing <= Standing::Hostile { Sentiment::Hostile } else { Sentiment::Neutral },
             })
         }
     }
 
-    /// Generate a greeting based on player corruption.
+    /// Generate a greeting based on the player's standing and lineage.
     pub fn generate_greeting(&self, context: &DialogueContext) -> String {
+        // Standing outweighs lineage at either extreme
+        match context.player_standing {
+            Standing::Hated | Standing::Hostile => {
+                return format!("{}: You are not welcome here. Leave, before I call the others.", context.npc_name);
+            }
+            Standing::Honored | Standing::Exalted => {
+                return format!("{}: Welcome back, friend. Our house is yours.", context.npc_name);
+            }
+            _ => {}
+        }
+
         match context.npc_lineage {
             NPCLineage::Seth => {
-                if context.player_corruption > 50.0 {
+                if context.player_lineage == Lineage::Cain {
                     format!("{}: I sense the corruption in you. Repent, before it is too late.", context.npc_name)
                 } else {
                     format!("{}: Greetings, traveler. May the Lord guide your path.", context.npc_name)
                 }
             }
             NPCLineage::Cain => {
-                if context.player_corruption > 50.0 {
+                if context.player_lineage == Lineage::Cain {
                     format!("{}: Ah, a kindred spirit. Join us, and we shall reshape the world.", context.npc_name)
                 } else {
                     format!("{}: What brings you to these lands?", context.npc_name)
@@ -157,8 +184,10 @@ mod tests {
         let context = DialogueContext {
             npc_name: "Methuselah".to_string(),
             npc_lineage: NPCLineage::Seth,
+            player_lineage: Lineage::Seth,
             player_corruption: 0.0,
             world_corruption: 25.0,
+            player_standing: Standing::Neutral,
         };
 
         let exchange = gen.generate_response(&context, "Who are the Watchers?").unwrap();
@@ -172,11 +201,45 @@ mod tests {
         let context = DialogueContext {
             npc_name: "Tubal-Cain".to_string(),
             npc_lineage: NPCLineage::Cain,
+            player_lineage: Lineage::Cain,
             player_corruption: 75.0,
             world_corruption: 75.0,
+            player_standing: Standing::Neutral,
         };
 
         let greeting = gen.generate_greeting(&context);
         assert!(greeting.contains("kindred"));
     }
+
+    #[test]
+    fn test_greeting_follows_lineage() {
+        let gen = DialogueGenerator::new();
+        let context = DialogueContext {
+            npc_name: "Methuselah".to_string(),
+            npc_lineage: NPCLineage::Seth,
+            player_lineage: Lineage::Seth,
+            player_corruption: 52.0, // Above 50 but still within the hysteresis band
+            world_corruption: 25.0,
+            player_standing: Standing::Neutral,
+        };
+
+        assert!(gen.generate_greeting(&context).contains("Greetings"));
+    }
+
+    #[test]
+    fn test_greeting_follows_standing() {
+        let gen = DialogueGenerator::new();
+        let mut context = DialogueContext {
+            npc_name: "Noah".to_string(),
+            npc_lineage: NPCLineage::Seth,
+            player_lineage: Lineage::Seth,
+            player_corruption: 0.0,
+            world_corruption: 25.0,
+            player_standing: Standing::Hostile,
+        };
+        assert!(gen.generate_greeting(&context).contains("not welcome"));
+
+        context.player_standing = Standing::Exalted;
+        assert!(gen.generate_greeting(&context).contains("friend"));
+    }
 }
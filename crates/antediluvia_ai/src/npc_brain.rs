@@ -1,8 +1,11 @@
-//! NPC AI brain (state machine).
+//! NPC AI brain.
 //! 
-//! Manages NPC behavior, dialogue, and decision-making.
+//! Manages NPC behavior, dialogue, and decision-making. What a villager does
+//! is decided by its lineage's behaviour tree.
 
 use serde::{Deserialize, Serialize};
+use antediluvia_core::behaviour::{behaviour_library, Action, Blackboard, Decision, Fact};
+use antediluvia_core::entity::{Lineage, Standing};
 use crate::error::Result;
 use crate::dialogue::{DialogueGenerator, DialogueContext, NPCLineage};
 
@@ -24,6 +27,7 @@ pub struct NPCBrain {
     pub state: NPCState,
     pub dialogue_gen: DialogueGenerator,
     pub memory: Vec<String>, // Recent interactions
+    pub decision: Decision, // The last update's, for the debugger
 }
 
 impl NPCBrain {
@@ -35,18 +39,36 @@ impl NPCBrain {
             state: NPCState::Idle,
             dialogue_gen: DialogueGenerator::new(),
             memory: Vec::new(),
+            decision: Decision::default(),
+        }
+    }
+
+    /// Get the name of the behaviour tree this NPC decides with.
+    pub fn tree_name(&self) -> &'static str {
+        match self.lineage {
+            NPCLineage::Seth => "sethite_villager",
+            NPCLineage::Cain => "cainite_villager",
         }
     }
 
     /// Process a player interaction.
-    pub fn interact(&mut self, player_input: &str, player_corruption: f32, world_corruption: f32) -> Result<String> {
+    pub fn interact(
+        &mut self,
+        player_input: &str,
+        player_lineage: Lineage,
+        player_standing: Standing,
+        player_corruption: f32,
+        world_corruption: f32,
+    ) -> Result<String> {
         self.state = NPCState::Talking;
 
         let context = DialogueContext {
             npc_name: self.name.clone(),
             npc_lineage: self.lineage,
+            player_lineage,
             player_corruption,
             world_corruption,
+            player_standing,
         };
 
         let exchange = self.dialogue_gen.generate_response(&context, player_input)?;
@@ -61,31 +83,35 @@ impl NPCBrain {
     }
 
     /// Get a greeting.
-    pub fn greet(&self, player_corruption: f32, world_corruption: f32) -> String {
+    pub fn greet(&self, player_lineage: Lineage, player_standing: Standing, player_corruption: f32, world_corruption: f32) -> String {
         let context = DialogueContext {
             npc_name: self.name.clone(),
             npc_lineage: self.lineage,
+            player_lineage,
             player_corruption,
             world_corruption,
+            player_standing,
         };
 
         self.dialogue_gen.generate_greeting(&context)
     }
 
-    /// Update NPC state based on world conditions.
+    /// Update NPC state based on world conditions. As the world darkens
+    /// Sethites flee and Cainites turn violent; Cainites work harder as it does.
     pub fn update(&mut self, world_corruption: f32) {
-        // If world corruption is high, Sethites flee or pray
-        if world_corruption > 80.0 && self.lineage == NPCLineage::Seth {
-            self.state = NPCState::Fleeing;
-        }
-        // If world corruption is high, Cainites attack or work
-        else if world_corruption > 80.0 && self.lineage == NPCLineage::Cain {
-            self.state = NPCState::Attacking;
-        }
-        // Otherwise, idle or work
-        else if self.state != NPCState::Talking {
-            self.state = NPCState::Idle;
-        }
+        let board = Blackboard::new()
+            .with(Fact::Corruption, world_corruption)
+            .with_flag(Fact::Talking, self.state == NPCState::Talking);
+        let tree = behaviour_library().get(self.tree_name()).expect("villager trees ship with the game");
+        self.decision = tree.decide(&board);
+
+        self.state = match self.decision.action {
+            Some(Action::Flee) => NPCState::Fleeing,
+            Some(Action::Strike) => NPCState::Attacking,
+            Some(Action::Talk) => NPCState::Talking,
+            Some(Action::Work) => NPCState::Working,
+            _ => NPCState::Idle,
+        };
     }
 
     /// Get the NPC's opinion on a topic.
@@ -115,7 +141,7 @@ mod tests {
     #[test]
     fn test_npc_interaction() {
         let mut brain = NPCBrain::new("Methuselah".to_string(), NPCLineage::Seth);
-        let response = brain.interact("Who are the Watchers?", 0.0, 25.0).unwrap();
+        let response = brain.interact("Who are the Watchers?", Lineage::Seth, Standing::Neutral, 0.0, 25.0).unwrap();
         assert!(!response.is_empty());
         assert_eq!(brain.state, NPCState::Talking);
     }
@@ -126,4 +152,16 @@ mod tests {
         brain.update(85.0);
         assert_eq!(brain.state, NPCState::Fleeing);
     }
+
+    #[test]
+    fn test_cainites_work_as_the_world_darkens() {
+        let mut brain = NPCBrain::new("Lamech".to_string(), NPCLineage::Cain);
+        brain.update(20.0);
+        assert_eq!(brain.state, NPCState::Idle);
+        brain.update(60.0);
+        assert_eq!(brain.state, NPCState::Working);
+        assert_eq!(brain.decision.describe().last().unwrap(), "    Work — Running");
+        brain.update(90.0);
+        assert_eq!(brain.state, NPCState::Attacking);
+    }
 }
@@ -0,0 +1,239 @@
+//! Character select and creation.
+//! Sits between AppState::Login and InWorld. Online sessions keep their roster
+//! on the auth server; offline sessions keep a local one.
+
+use bevy::prelude::*;
+use bevy::tasks::{futures::check_ready, IoTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use antediluvia_core::{
+    Character, CharacterAppearance, CharacterRegistry, CreateCharacterRequest, DeleteCharacterRequest,
+    Lineage, RosterRequest, RosterResponse, HAIR_STYLES, HAIR_COLORS, HEIGHT_RANGE, NAME_MAX_LEN, SKIN_TONES,
+};
+use super::AppState;
+use crate::login::{auth_url, Session};
+
+#[derive(Component)]
+pub struct CharacterSelectRoot;
+
+/// The character chosen to enter the world with.
+#[derive(Resource, Clone)]
+pub struct SelectedCharacter(pub Character);
+
+/// Character select screen state.
+#[derive(Resource, Default)]
+pub struct CharacterSelectState {
+    pub roster: Vec<Character>,
+    pub selected: Option<u64>,
+    pub new_name: String,
+    pub new_lineage: Lineage,
+    pub new_appearance: CharacterAppearance,
+    pub error: Option<String>,
+    offline: CharacterRegistry, // Roster for offline sessions
+    request: Option<(Task<RosterResponse>, bool)>, // The roster request in flight, and whether it creates a character
+}
+
+impl CharacterSelectState {
+    fn apply(&mut self, response: RosterResponse) {
+        self.roster = response.characters;
+        self.error = response.error;
+        if self.selected.is_none_or(|id| !self.roster.iter().any(|c| c.id == id)) {
+            self.selected = self.roster.first().map(|c| c.id);
+        }
+    }
+}
+
+/// Post a roster request to the auth server off the main thread, so the
+/// frame never waits on it.
+fn post_roster<T: serde::Serialize + Send + 'static>(path: &str, body: T) -> Task<RosterResponse> {
+    let url = auth_url(path);
+    IoTaskPool::get().spawn(async move {
+        reqwest::blocking::Client::new()
+            .post(url)
+            .json(&body)
+            .send()
+            .and_then(|r| r.json::<RosterResponse>())
+            .unwrap_or_else(|e| RosterResponse {
+                characters: Vec::new(),
+                error: Some(format!("Server unreachable: {}", e)),
+            })
+    })
+}
+
+pub fn spawn_character_select(
+    mut commands: Commands,
+    session: Option<Res<Session>>,
+    mut state: ResMut<CharacterSelectState>,
+) {
+    commands.spawn((Camera2d, CharacterSelectRoot));
+
+    let session = session.map(|s| s.clone()).unwrap_or_default();
+    if session.online {
+        state.request = Some((post_roster("/characters", RosterRequest { token: session.token }), false));
+    } else {
+        let response = RosterResponse {
+            characters: state.offline.roster(session.account_id),
+            error: None,
+        };
+        state.apply(response);
+    }
+}
+
+pub fn despawn_character_select(mut commands: Commands, root: Query<Entity, With<CharacterSelectRoot>>) {
+    for entity in &root {
+        commands.entity(entity).despawn();
+    }
+}
+
+enum RosterAction {
+    Create,
+    Delete(u64),
+    Enter(Character),
+}
+
+pub fn character_select_ui_system(
+    mut contexts: EguiContexts,
+    mut state: ResMut<CharacterSelectState>,
+    session: Option<Res<Session>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let session = session.map(|s| s.clone()).unwrap_or_default();
+    let mut action: Option<RosterAction> = None;
+
+    // Take the server's answer once it comes
+    let answered = state.request.as_mut().and_then(|(task, created)| Some((check_ready(task)?, *created)));
+    if let Some((response, created)) = answered {
+        state.request = None;
+        if created && response.error.is_none() {
+            state.new_name.clear();
+        }
+        state.apply(response);
+    }
+    let waiting = state.request.is_some();
+
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    egui::Window::new("Choose Your Character")
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .resizable(false)
+        .collapsible(false)
+        .min_width(420.0)
+        .show(ctx, |ui| {
+            if !session.online {
+                ui.label(egui::RichText::new("Offline — characters are not saved").color(egui::Color32::GRAY));
+            }
+            if waiting {
+                ui.label(egui::RichText::new("Waiting for the server...").color(egui::Color32::GRAY));
+            }
+
+            // Roster
+            if state.roster.is_empty() {
+                ui.label("You have no characters yet.");
+            }
+            let roster = state.roster.clone();
+            for character in &roster {
+                ui.horizontal(|ui| {
+                    let selected = state.selected == Some(character.id);
+                    if ui.selectable_label(selected, format!("{} ({:?})", character.name, character.lineage)).clicked() {
+                        state.selected = Some(character.id);
+                    }
+                    if ui.small_button("Delete").clicked() {
+                        action = Some(RosterAction::Delete(character.id));
+                    }
+                });
+            }
+
+            let chosen = roster.iter().find(|c| Some(c.id) == state.selected).cloned();
+            if ui.add_enabled(chosen.is_some(), egui::Button::new("Enter World")).clicked() {
+                action = chosen.map(RosterAction::Enter);
+            }
+
+            // Creation
+            ui.separator();
+            ui.heading("New Character");
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.add(egui::TextEdit::singleline(&mut state.new_name).char_limit(NAME_MAX_LEN));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Lineage:");
+                ui.radio_value(&mut state.new_lineage, Lineage::Seth, "House of Seth");
+                ui.radio_value(&mut state.new_lineage, Lineage::Cain, "House of Cain");
+            });
+            let appearance = &mut state.new_appearance;
+            ui.add(egui::Slider::new(&mut appearance.skin_tone, 0..=(SKIN_TONES.len() - 1) as u8).text("Skin tone"));
+            ui.add(egui::Slider::new(&mut appearance.hair_color, 0..=(HAIR_COLORS.len() - 1) as u8).text("Hair color"));
+            ui.add(egui::Slider::new(&mut appearance.hair_style, 0..=HAIR_STYLES - 1).text("Hair style"));
+            ui.add(egui::Slider::new(&mut appearance.height, HEIGHT_RANGE.0..=HEIGHT_RANGE.1).text("Height"));
+            if ui.button("Create").clicked() {
+                action = Some(RosterAction::Create);
+            }
+
+            if let Some(error) = &state.error {
+                ui.separator();
+                ui.label(egui::RichText::new(error.as_str()).color(egui::Color32::from_rgb(220, 80, 80)));
+            }
+        });
+
+    // One request at a time: the roster waits on the server's answer
+    if waiting {
+        return;
+    }
+    match action {
+        Some(RosterAction::Create) if session.online => {
+            let task = post_roster("/characters/create", CreateCharacterRequest {
+                token: session.token.clone(),
+                name: state.new_name.clone(),
+                lineage: state.new_lineage,
+                appearance: state.new_appearance,
+            });
+            state.request = Some((task, true));
+        }
+        Some(RosterAction::Create) => {
+            let (name, lineage, appearance) = (state.new_name.clone(), state.new_lineage, state.new_appearance);
+            let error = state.offline.create(session.account_id, &name, lineage, appearance).err();
+            let response = RosterResponse {
+                characters: state.offline.roster(session.account_id),
+                error: error.map(|e| e.to_string()),
+            };
+            if response.error.is_none() {
+                state.new_name.clear();
+            }
+            state.apply(response);
+        }
+        Some(RosterAction::Delete(character_id)) if session.online => {
+            let task = post_roster("/characters/delete", DeleteCharacterRequest {
+                token: session.token.clone(),
+                character_id,
+            });
+            state.request = Some((task, false));
+        }
+        Some(RosterAction::Delete(character_id)) => {
+            let error = state.offline.delete(session.account_id, character_id).err();
+            let response = RosterResponse {
+                characters: state.offline.roster(session.account_id),
+                error: error.map(|e| e.to_string()),
+            };
+            state.apply(response);
+        }
+        Some(RosterAction::Enter(character)) => {
+            // The character ID is the client ID; the server checks it against the token
+            let connection = if session.online {
+                super::setup_network_connection(character.id, &session.token)
+            } else {
+                super::setup_offline_connection(999999)
+            };
+            match connection {
+                Ok((client, transport)) => {
+                    commands.insert_resource(client);
+                    commands.insert_resource(transport);
+                }
+                Err(e) => println!("WARNING: Network init failed ({}). Running without networking.", e),
+            }
+            println!("{} enters the world.", character.name);
+            commands.insert_resource(SelectedCharacter(character));
+            next_state.set(AppState::InWorld);
+        }
+        None => {}
+    }
+}
@@ -1,9 +1,33 @@
 use bevy::prelude::*;
-use antediluvia_core::combat::{CombatAction, get_skill_chains};
-use antediluvia_core::entity::Job;
+use antediluvia_core::combat::{CombatAction, Defense, JobResources, resolve_hit, CRIT_CHANCE};
+use antediluvia_core::combat_log::{CombatEvent, CombatLog};
+use antediluvia_core::death::{repay_xp_debt, xp_debt, BindPoint, RESPAWN_SECONDS};
+use antediluvia_core::abilities::{ActionModifiers, Loadout, MASTERY_PER_USE};
+use antediluvia_core::entity::{Job, JobMastery, JobTelemetry, Lineage, Reputation, ReputationEvent, Stats, Breath};
+use antediluvia_core::mob::MobType;
+use antediluvia_core::mutation::Mutation;
+use antediluvia_core::projectile::Projectile;
+use antediluvia_core::skill_chain::{skill_chains, SkillChain, MAX_CHAIN_STEPS};
+use antediluvia_core::status::StatusEffects;
+use antediluvia_core::targeting::tab_order;
+use antediluvia_core::threat::ThreatTable;
+use antediluvia_core::variant::MobVariant;
+use antediluvia_core::network::NetworkMessage;
+use bevy_renet::RenetClient;
 use std::collections::HashMap;
-use crate::mob_ai::MobBrain;
+use crate::mob_ai::{rand_simple, LOCAL_PLAYER};
 use crate::inventory::{Satchel, InventoryItem};
+use crate::network::send_message;
+use crate::projectile::Flight;
+use crate::unlocks::JobTelemetryEvent;
+
+/// What the client predicted an action gave or cost, undone if the server refuses it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prediction {
+    pub healed: f32,
+    pub breath: f32,
+    pub mastery: f32,
+}
 
 #[derive(Component, Debug, Clone)]
 pub struct PlayerCombat {
@@ -11,6 +35,7 @@ pub struct PlayerCombat {
     pub max_health: f32,
     pub job: Job,
     pub active_cooldowns: HashMap<CombatAction, f32>,
+    pub predictions: HashMap<CombatAction, Prediction>, // Each action's last use, until the server answers
     pub is_in_combat: bool,
     pub current_target: Option<Entity>,
     pub level: u32,
@@ -19,17 +44,32 @@ pub struct PlayerCombat {
     pub damage_multiplier: f32,
     pub is_dead: bool,
     pub respawn_timer: f32,
-    pub last_action: Option<CombatAction>,
+    pub chain_history: Vec<CombatAction>, // Damaging actions within the combo window
     pub combo_window: f32,
+    pub stats: Stats,
+    pub breath: Breath,
+    pub mastery: JobMastery,
+    pub loadout: Loadout,
+    pub corruption: f32,
+    pub lineage: Lineage,
+    pub reputation: Reputation,
+    pub effects: StatusEffects,
+    pub resources: JobResources,
+    pub xp_debt: f32, // Repaid from experience before it counts
+    pub bind_point: BindPoint,
+    pub fallen: bool, // Perished in the Flood; does not rise this season
+    pub corpse_laid: bool, // This death's corpse is down
 }
 
 impl PlayerCombat {
     pub fn new(job: Job) -> Self {
+        let stats = Stats::default();
         Self {
             health: 100.0,
             max_health: 100.0,
             job,
             active_cooldowns: HashMap::new(),
+            predictions: HashMap::new(),
             is_in_combat: false,
             current_target: None,
             level: 1,
@@ -38,8 +78,21 @@ impl PlayerCombat {
             damage_multiplier: 1.0,
             is_dead: false,
             respawn_timer: 0.0,
-            last_action: None,
+            chain_history: Vec::new(),
             combo_window: 0.0,
+            stats,
+            breath: Breath::from_stats(&stats),
+            mastery: JobMastery::default(),
+            loadout: Loadout::default(),
+            corruption: 0.0,
+            lineage: Lineage::Seth,
+            reputation: Reputation::for_lineage(Lineage::Seth),
+            effects: StatusEffects::new(),
+            resources: JobResources::new(),
+            xp_debt: 0.0,
+            bind_point: BindPoint::default(),
+            fallen: false,
+            corpse_laid: false,
         }
     }
 
@@ -58,7 +111,8 @@ impl PlayerCombat {
         self.health = (self.health - damage).max(0.0);
         if self.health <= 0.0 {
             self.is_dead = true;
-            self.respawn_timer = 5.0;
+            self.respawn_timer = RESPAWN_SECONDS;
+            self.xp_debt += xp_debt(self.xp_to_next_level);
         }
     }
 
@@ -69,7 +123,48 @@ impl PlayerCombat {
         self.health = (self.health + amount).min(self.max_health);
     }
 
+    /// Heal as an action predicts, remembering how much was restored.
+    fn heal_predicted(&mut self, action: CombatAction, amount: f32) {
+        let before = self.health;
+        self.heal(amount);
+        self.predictions.entry(action).or_default().healed += self.health - before;
+    }
+
+    /// Undo what the client predicted for an action the server refused:
+    /// its cooldown, the health it restored, the Breath it spent and the mastery it grew.
+    pub fn unpredict(&mut self, action: CombatAction) {
+        self.active_cooldowns.remove(&action);
+        let Some(prediction) = self.predictions.remove(&action) else {
+            return;
+        };
+        // Taking back a heal never kills
+        self.health = (self.health - prediction.healed).max(self.health.min(1.0));
+        self.breath.refund(prediction.breath);
+        self.mastery.increase(action.job(), -prediction.mastery);
+    }
+
+    pub fn corrupt(&mut self, amount: f32) {
+        // Reputation moves only by the corruption that fit under the cap
+        let before = self.corruption;
+        self.corruption = (self.corruption + amount).clamp(0.0, 100.0);
+        let applied = self.corruption - before;
+        self.reputation.apply(&if applied >= 0.0 {
+            ReputationEvent::Corrupted { amount: applied }
+        } else {
+            ReputationEvent::Redeemed { amount: -applied }
+        });
+        let lineage = self.lineage.resolve(self.corruption);
+        if lineage != self.lineage {
+            self.lineage = lineage;
+            match lineage {
+                Lineage::Cain => println!("The Mark of Cain is upon you. Enoch's gates open; Bethel's close."),
+                Lineage::Seth => println!("You have returned to the House of Seth."),
+            }
+        }
+    }
+
     pub fn award_xp(&mut self, amount: f32) {
+        let amount = repay_xp_debt(&mut self.xp_debt, amount);
         self.experience += amount;
         while self.experience >= self.xp_to_next_level {
             self.experience -= self.xp_to_next_level;
@@ -91,92 +186,53 @@ impl PlayerCombat {
         self.health = self.max_health;
         self.is_dead = false;
         self.respawn_timer = 0.0;
+        self.corpse_laid = false;
         self.is_in_combat = false;
         self.current_target = None;
-        self.last_action = None;
+        self.chain_history.clear();
         self.combo_window = 0.0;
+        self.breath = Breath::from_stats(&self.stats);
+        self.resources = JobResources::new();
     }
 }
 
+/// A mob in the world, spawned from its definition in core.
 #[derive(Component, Debug, Clone)]
 pub struct Mob {
+    pub id: u64, // Matches the server's mob
     pub health: f32,
     pub max_health: f32,
     pub name: String,
     pub level: u32,
     pub damage_per_hit: f32,
     pub xp_reward: f32,
-    pub mob_tier: MobTier,
+    pub mob_type: MobType,
+    pub effects: StatusEffects,
+    pub threat: ThreatTable,
+    pub variant: MobVariant, // Elite or rare, and its affixes
+    pub mutation: Mutation, // How far tainted land has turned it
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum MobTier {
-    Common,
-    Elite,
-    Boss,
-}
-
-impl Mob {
-    pub fn wolf(level: u32) -> Self {
-        Self {
-            health: 50.0 + level as f32 * 10.0,
-            max_health: 50.0 + level as f32 * 10.0,
-            name: "Wolf".to_string(),
-            level,
-            damage_per_hit: 8.0 + level as f32 * 2.0,
-            xp_reward: 25.0 + level as f32 * 10.0,
-            mob_tier: MobTier::Common,
-        }
-    }
-
-    pub fn lion(level: u32) -> Self {
-        Self {
-            health: 100.0 + level as f32 * 20.0,
-            max_health: 100.0 + level as f32 * 20.0,
-            name: "Lion".to_string(),
-            level,
-            damage_per_hit: 15.0 + level as f32 * 3.0,
-            xp_reward: 50.0 + level as f32 * 15.0,
-            mob_tier: MobTier::Common,
-        }
-    }
-
-    pub fn chimera(level: u32) -> Self {
-        Self {
-            health: 200.0 + level as f32 * 30.0,
-            max_health: 200.0 + level as f32 * 30.0,
-            name: "Chimera".to_string(),
-            level,
-            damage_per_hit: 25.0 + level as f32 * 5.0,
-            xp_reward: 100.0 + level as f32 * 25.0,
-            mob_tier: MobTier::Elite,
-        }
-    }
-
-    pub fn corrupted(level: u32) -> Self {
-        Self {
-            health: 150.0 + level as f32 * 25.0,
-            max_health: 150.0 + level as f32 * 25.0,
-            name: "Corrupted".to_string(),
-            level,
-            damage_per_hit: 20.0 + level as f32 * 4.0,
-            xp_reward: 75.0 + level as f32 * 20.0,
-            mob_tier: MobTier::Elite,
-        }
-    }
-
-    pub fn nephilim(level: u32) -> Self {
+impl From<&antediluvia_core::mob::Mob> for Mob {
+    fn from(mob: &antediluvia_core::mob::Mob) -> Self {
         Self {
-            health: 500.0 + level as f32 * 50.0,
-            max_health: 500.0 + level as f32 * 50.0,
-            name: "Nephilim".to_string(),
-            level,
-            damage_per_hit: 40.0 + level as f32 * 8.0,
-            xp_reward: 250.0 + level as f32 * 50.0,
-            mob_tier: MobTier::Boss,
+            id: mob.id,
+            health: mob.health,
+            max_health: mob.max_health,
+            name: mob.name.clone(),
+            level: mob.level,
+            damage_per_hit: mob.get_damage(),
+            xp_reward: mob.xp_reward(),
+            mob_type: mob.mob_type,
+            effects: StatusEffects::new(),
+            threat: ThreatTable::new(),
+            variant: mob.variant.clone(),
+            mutation: mob.mutation.clone(),
         }
     }
+}
 
+impl Mob {
     pub fn take_damage(&mut self, damage: f32) {
         self.health = (self.health - damage).max(0.0);
     }
@@ -184,6 +240,31 @@ impl Mob {
     pub fn is_alive(&self) -> bool {
         self.health > 0.0
     }
+
+    /// Take on the stats of another mob type and a new name, keeping the share of health left.
+    pub fn reshape(&mut self, mob_type: MobType, name: String) {
+        let shape = antediluvia_core::mob::Mob::new(self.id, name, mob_type, Vec3::ZERO, self.level)
+            .with_variant(self.variant.clone());
+        self.health = shape.max_health * self.health / self.max_health;
+        self.max_health = shape.max_health;
+        self.damage_per_hit = shape.get_damage();
+        self.xp_reward = shape.xp_reward();
+        self.mob_type = mob_type;
+        self.name = shape.name;
+    }
+}
+
+/// A hit on a mob: resolved locally when offline, confirmed by the server when online.
+#[derive(Message, Clone, Copy)]
+pub struct MobHit {
+    pub mob: Entity,
+    pub damage: f32,
+    pub health: Option<f32>, // Authoritative health after the hit
+    pub by_player: bool,     // Landed by the local player, who earns the kill
+    pub action: Option<CombatAction>, // Applies its harmful effects (offline)
+    pub chain: Option<&'static SkillChain>, // Closed by the hit; applies its harmful effects (offline)
+    pub critical: bool,
+    pub resisted: f32, // Turned aside by the mob's defenses
 }
 
 #[derive(Component)]
@@ -208,6 +289,10 @@ impl DamageNumber {
     }
 }
 
+/// The recent combat events shown in the combat log panel.
+#[derive(Resource, Default)]
+pub struct CombatLogRes(pub CombatLog);
+
 #[derive(Resource, Default)]
 pub struct ChainNotification {
     pub chain_name: String,
@@ -227,8 +312,9 @@ pub fn update_cooldowns(
         combat.active_cooldowns.retain(|_, v| *v > 0.0);
         combat.combo_window = (combat.combo_window - dt).max(0.0);
         if combat.combo_window <= 0.0 {
-            combat.last_action = None;
+            combat.chain_history.clear();
         }
+        combat.breath.update(dt);
     }
     if chain_notif.timer > 0.0 {
         chain_notif.timer -= dt;
@@ -243,12 +329,12 @@ pub fn player_respawn_system(
         return;
     };
 
-    if combat.is_dead {
+    if combat.is_dead && !combat.fallen {
         combat.respawn_timer -= time.delta_secs();
         if combat.respawn_timer <= 0.0 {
             combat.respawn();
-            transform.translation = Vec3::new(0.0, 5.0, 100.0);
-            println!("You have respawned at the Eden Pillar.");
+            transform.translation = combat.bind_point.respawn_point(combat.lineage);
+            println!("You have respawned at {}.", combat.bind_point.name());
         }
     }
 }
@@ -258,10 +344,13 @@ pub fn combat_input_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut player_q: Query<(&mut PlayerCombat, &Transform)>,
-    mut mob_q: Query<(&mut Mob, &Transform, Entity), Without<PlayerCombat>>,
-    mut satchel_q: Query<&mut Satchel>,
+    mob_q: Query<(&Mob, &Transform, Entity), Without<PlayerCombat>>,
     mut chain_notif: ResMut<ChainNotification>,
-    equipment: Res<crate::Equipment>,
+    mut equipment: ResMut<crate::Equipment>,
+    mut client: Option<ResMut<RenetClient>>,
+    mut hits: MessageWriter<MobHit>,
+    mut combat_log: ResMut<CombatLogRes>,
+    time: Res<Time>,
 ) {
     let Ok((mut player_combat, player_transform)) = player_q.single_mut() else {
         return;
@@ -273,27 +362,89 @@ pub fn combat_input_system(
 
     let player_pos = player_transform.translation;
 
-    let action = if keys.just_pressed(KeyCode::Digit1) {
-        Some(CombatAction::HunterThrust)
-    } else if keys.just_pressed(KeyCode::Digit2) {
-        Some(CombatAction::HunterSlash)
-    } else if keys.just_pressed(KeyCode::Digit3) {
-        Some(CombatAction::LeviteHeal)
-    } else if keys.just_pressed(KeyCode::Digit4) {
-        Some(CombatAction::ForgeSmash)
-    } else {
-        None
-    };
+    // Online, the server resolves the action; we only predict its effects
+    let mut client = client.as_deref_mut().filter(|c| c.is_connected());
+
+    let slot_keys = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4];
+    let action = slot_keys
+        .iter()
+        .position(|k| keys.just_pressed(*k))
+        .and_then(|slot| player_combat.loadout.get(slot));
 
     if let Some(action) = action {
         if !player_combat.can_use_action(action) {
             return;
         }
+        if let Err(refused) = player_combat.resources.check(action) {
+            println!("{}", refused);
+            return;
+        }
 
-        // Heal targets self
-        if action == CombatAction::LeviteHeal {
-            let heal_amount = 50.0;
-            player_combat.heal(heal_amount);
+        // Blows strike the chosen target; with none chosen, they soft-lock
+        // onto the best target in reach
+        let in_reach = |entity: Entity| {
+            mob_q.get(entity).is_ok_and(|(mob, t, _)| mob.is_alive() && player_pos.distance(t.translation) < action.range())
+        };
+        let mob_target = if action.damage() > 0.0 {
+            let target = match player_combat.current_target {
+                Some(entity) if in_reach(entity) => Some(entity),
+                Some(_) => {
+                    println!("Target out of range!");
+                    return;
+                }
+                None => {
+                    let candidates: Vec<(u64, Vec3)> = mob_q
+                        .iter()
+                        .filter(|(_, _, entity)| in_reach(*entity))
+                        .map(|(mob, t, _)| (mob.id, t.translation))
+                        .collect();
+                    tab_order(player_pos, *player_transform.forward(), &candidates)
+                        .first()
+                        .and_then(|id| mob_q.iter().find(|(mob, _, _)| mob.id == *id))
+                        .map(|(_, _, entity)| entity)
+                }
+            };
+            if target.is_none() {
+                println!("No target in range!");
+                return;
+            }
+            target
+        } else {
+            None
+        };
+        let mods = ActionModifiers::for_action(action, &player_combat.mastery);
+        let breath = action.stamina_cost() * mods.stamina;
+        if !player_combat.breath.drain(breath) {
+            println!("You are out of breath!");
+            return;
+        }
+        let mastery = player_combat.mastery.get_level(action.job());
+        if player_combat.mastery.is_unlocked(action.job()) {
+            player_combat.mastery.increase(action.job(), MASTERY_PER_USE);
+        }
+        let mastery = player_combat.mastery.get_level(action.job()) - mastery;
+        player_combat.predictions.insert(action, Prediction { healed: 0.0, breath, mastery });
+        let boost = player_combat.resources.commit(action);
+        equipment.wear_weapon(boost.wear);
+        let cooldown = action.cooldown() * mods.cooldown * boost.cooldown * player_combat.effects.cooldown_multiplier();
+        let power = player_combat.effects.damage_multiplier() * boost.damage;
+
+        // Helpful effects and dispels land on the caster; the server spreads songs to the party
+        for kind in action.status_effects().into_iter().filter(|k| !k.is_harmful()) {
+            player_combat.effects.apply(kind, 0);
+        }
+        player_combat.effects.dispel(action.dispels());
+
+        // Heals target self
+        if action.healing() > 0.0 {
+            let heal_amount = action.healing() * mods.damage;
+            player_combat.heal_predicted(action, heal_amount);
+            if let Some(client) = client.as_deref_mut() {
+                send_message(client, &NetworkMessage::CombatAction {
+                    action_type: format!("{:?}", action),
+                    target_id: 0, // The server heals the caster
+                });
+            }
 
             commands.spawn((
                 Text2d::new(format!("+{:.0}", heal_amount)),
@@ -307,263 +458,263 @@ pub fn combat_input_system(
                 DamageNumber::new(heal_amount, true),
             ));
 
-            player_combat.active_cooldowns.insert(action, action.cooldown());
-            println!("Healed for {:.0}! HP: {:.0}/{:.0}", heal_amount, player_combat.health, player_combat.max_health);
+            player_combat.active_cooldowns.insert(action, cooldown);
+            if client.is_none() {
+                combat_log.0.record(CombatEvent::heal(time.elapsed_secs(), LOCAL_PLAYER, LOCAL_PLAYER, action, heal_amount));
+            }
             return;
         }
 
-        // Check for skill chain
-        let mut chain_bonus = 1.0;
-        if player_combat.combo_window > 0.0 {
-            if let Some(last) = player_combat.last_action {
-                for chain in get_skill_chains() {
-                    if chain.matches(last, action) {
-                        chain_bonus = chain.damage_multiplier;
-                        chain_notif.chain_name = chain.name.clone();
-                        chain_notif.timer = 2.0;
-                        println!("SKILL CHAIN: {} ({:.1}x damage!)", chain.name, chain.damage_multiplier);
-                        break;
-                    }
-                }
+        // Songs and stances need no target
+        if action.damage() <= 0.0 {
+            if let Some(client) = client.as_deref_mut() {
+                send_message(client, &NetworkMessage::CombatAction {
+                    action_type: format!("{:?}", action),
+                    target_id: 0,
+                });
             }
+            player_combat.active_cooldowns.insert(action, cooldown);
+            return;
         }
-        player_combat.last_action = Some(action);
-        player_combat.combo_window = 3.0;
 
-        // Find closest alive mob as target
-        let mut closest_mob: Option<(Entity, f32)> = None;
-        for (mob, mob_transform, entity) in mob_q.iter() {
-            if !mob.is_alive() {
-                continue;
-            }
-            let distance = player_pos.distance(mob_transform.translation);
-            if distance < 200.0 {
-                if closest_mob.is_none() || distance < closest_mob.unwrap().1 {
-                    closest_mob = Some((entity, distance));
-                }
+        // Check for skill chain
+        player_combat.chain_history.push(action);
+        let excess = player_combat.chain_history.len().saturating_sub(MAX_CHAIN_STEPS);
+        player_combat.chain_history.drain(..excess);
+        player_combat.combo_window = 3.0;
+        let chain = skill_chains().closing(&player_combat.chain_history).first().copied();
+        let chain_bonus = chain.map_or(1.0, |c| c.damage_multiplier);
+        if let Some(chain) = chain {
+            chain_notif.chain_name = chain.name.clone();
+            chain_notif.timer = 2.0;
+
+            // Blessings and healing land on the closer; the server spreads songs to the party
+            for kind in chain.blessings() {
+                player_combat.effects.apply(kind, 0);
             }
+            player_combat.heal_predicted(action, chain.healing());
         }
 
-        if let Some((mob_entity, _)) = closest_mob {
-            let damage = action.damage() * player_combat.damage_multiplier * chain_bonus + equipment.weapon_damage_bonus();
-            let mut mob_died = false;
-            let mut mob_name = String::new();
-            let mut mob_xp = 0.0;
-            let mut mob_level = 0u32;
-
-            if let Ok((mut mob, mob_transform, _)) = mob_q.get_mut(mob_entity) {
-                mob.take_damage(damage);
-                mob_name = mob.name.clone();
-                mob_xp = mob.xp_reward;
-                mob_level = mob.level;
-                println!("{} takes {:.0} damage! ({:.0}/{:.0} HP)", mob.name, damage, mob.health, mob.max_health);
-
-                if !mob.is_alive() {
-                    mob_died = true;
-                    println!("{} defeated! +{:.0} XP", mob.name, mob.xp_reward);
-                }
-
-                // Spawn damage number
-                commands.spawn((
-                    Text2d::new(format!("{:.0}", damage)),
-                    TextFont {
-                        font: asset_server.load("FiraSans-Bold.ttf"),
-                        font_size: 28.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(1.0, 0.3, 0.1)),
-                    Transform::from_translation(mob_transform.translation + Vec3::Y * 15.0),
-                    DamageNumber::new(damage, false),
-                ));
+        if let Some(mob_entity) = mob_target {
+            let base = action.damage() * mods.damage * power * player_combat.damage_multiplier * chain_bonus + equipment.weapon_damage_bonus();
+            player_combat.is_in_combat = true;
+            player_combat.current_target = Some(mob_entity);
+            player_combat.active_cooldowns.insert(action, cooldown);
+
+            let Ok((mob, mob_transform, _)) = mob_q.get(mob_entity) else { return; };
+            let roll = client.is_none().then(|| rand_simple(time.elapsed_secs()));
+            if let Some(client) = client.as_deref_mut() {
+                send_message(client, &NetworkMessage::CombatAction {
+                    action_type: format!("{:?}", action),
+                    target_id: mob.id,
+                });
             }
 
-            if mob_died {
-                player_combat.award_xp(mob_xp);
-                player_combat.is_in_combat = false;
-                player_combat.current_target = None;
-
-                // Loot drop
-                let loot = get_loot_for_mob(&mob_name, mob_level);
-                if !loot.is_empty() {
-                    if let Ok(mut satchel) = satchel_q.single_mut() {
-                        for item in &loot {
-                            if satchel.add_item(item.clone()) {
-                                println!("  Loot: {} x{}", item.name, item.quantity);
-                            } else {
-                                println!("  Satchel full! {} dropped on the ground.", item.name);
-                            }
-                        }
-                    }
-                }
-            } else {
-                player_combat.is_in_combat = true;
-                player_combat.current_target = Some(mob_entity);
+            // Hurled blows fly, and land on whatever they strike
+            if let Some(kind) = action.projectile() {
+                let projectile = Projectile::launch(0, LOCAL_PLAYER, action, kind, player_pos, mob_transform.translation);
+                commands.spawn((Transform::from_translation(player_pos), Flight::thrown(projectile, base, chain)));
+                return;
             }
-
-            player_combat.active_cooldowns.insert(action, action.cooldown());
+            let (damage, critical) = strike_mob(mob_entity, mob, base, action, chain, roll, &mut hits);
+            spawn_hit_number(&mut commands, &asset_server, damage, critical, mob_transform.translation);
         }
     }
 }
 
-fn get_loot_for_mob(mob_name: &str, level: u32) -> Vec<InventoryItem> {
-    let mut loot = Vec::new();
+/// Resolve a blow of `base` damage on a mob, returning the damage and whether
+/// it was critical. Offline the hit is rolled with `roll` and applied here;
+/// online (no roll) the server rolls for critical hits, so this only predicts
+/// the damage.
+pub fn strike_mob(
+    entity: Entity,
+    mob: &Mob,
+    base: f32,
+    action: CombatAction,
+    chain: Option<&'static SkillChain>,
+    roll: Option<f32>,
+    hits: &mut MessageWriter<MobHit>,
+) -> (f32, bool) {
+    let base = base * mob.effects.damage_taken_multiplier();
+    let defense = Defense::for_variant(mob.mob_type, mob.level, &mob.variant);
+    let Some(roll) = roll else {
+        return (defense.mitigate(base, action.damage_type()), false);
+    };
+    let hit = resolve_hit(base, action.damage_type(), &defense, CRIT_CHANCE, roll);
+    hits.write(MobHit {
+        mob: entity,
+        damage: hit.damage,
+        health: None,
+        by_player: true,
+        action: Some(action),
+        chain,
+        critical: hit.critical,
+        resisted: hit.resisted,
+    });
+    (hit.damage, hit.critical)
+}
 
-    match mob_name {
-        "Wolf" => {
-            loot.push(InventoryItem {
-                name: "Wolf Pelt".to_string(),
-                quantity: 1,
-                weight: 3.0,
-            });
-            if level >= 2 {
-                loot.push(InventoryItem {
-                    name: "Wolf Fang".to_string(),
-                    quantity: 1,
-                    weight: 0.5,
-                });
-            }
+/// Float a damage number over whatever was struck. Critical hits are marked.
+pub fn spawn_hit_number(commands: &mut Commands, asset_server: &AssetServer, damage: f32, critical: bool, position: Vec3) {
+    commands.spawn((
+        Text2d::new(if critical { format!("{:.0}!", damage) } else { format!("{:.0}", damage) }),
+        TextFont {
+            font: asset_server.load("FiraSans-Bold.ttf"),
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.1)),
+        Transform::from_translation(position + Vec3::Y * 15.0),
+        DamageNumber::new(damage, false),
+    ));
+}
+
+/// Apply hits to mobs, and reward the player for kills.
+pub fn mob_hit_system(
+    mut hits: MessageReader<MobHit>,
+    mut player_q: Query<&mut PlayerCombat>,
+    mut mob_q: Query<&mut Mob>,
+    mut satchel_q: Query<&mut Satchel>,
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
+    mut combat_log: ResMut<CombatLogRes>,
+    time: Res<Time>,
+) {
+    let Ok(mut player_combat) = player_q.single_mut() else {
+        return;
+    };
+
+    for hit in hits.read() {
+        let Ok(mut mob) = mob_q.get_mut(hit.mob) else { continue; };
+        if !mob.is_alive() {
+            continue;
         }
-        "Lion" => {
-            loot.push(InventoryItem {
-                name: "Lion Mane".to_string(),
-                quantity: 1,
-                weight: 4.0,
-            });
-            loot.push(InventoryItem {
-                name: "Raw Meat".to_string(),
-                quantity: 2,
-                weight: 1.0,
-            });
+        match hit.health {
+            Some(health) => mob.health = health.min(mob.max_health),
+            None => mob.take_damage(hit.damage),
         }
-        "Chimera" => {
-            loot.push(InventoryItem {
-                name: "Chimera Scale".to_string(),
-                quantity: 1,
-                weight: 5.0,
-            });
-            loot.push(InventoryItem {
-                name: "Bronze Ingot".to_string(),
-                quantity: 1,
-                weight: 4.0,
-            });
+        let chained = hit.chain.into_iter().flat_map(|c| c.inflicted());
+        for kind in hit.action.iter().flat_map(|a| a.status_effects()).filter(|k| k.is_harmful()).chain(chained) {
+            mob.effects.apply(kind, 0);
         }
-        "Corrupted" => {
-            loot.push(InventoryItem {
-                name: "Dark Essence".to_string(),
-                quantity: 1,
-                weight: 1.0,
-            });
-            loot.push(InventoryItem {
-                name: "Iron Ingot".to_string(),
-                quantity: 1,
-                weight: 5.0,
-            });
+        if !hit.by_player {
+            continue;
         }
-        "Nephilim" => {
-            loot.push(InventoryItem {
-                name: "Giant's Bone".to_string(),
-                quantity: 1,
-                weight: 10.0,
-            });
-            loot.push(InventoryItem {
-                name: "Ancient Relic".to_string(),
-                quantity: 1,
-                weight: 2.0,
-            });
-            loot.push(InventoryItem {
-                name: "Leather Grip".to_string(),
-                quantity: 2,
-                weight: 1.0,
+        match hit.action {
+            Some(action) => mob.threat.record(LOCAL_PLAYER, action, hit.damage, 0.0),
+            None => mob.threat.add(LOCAL_PLAYER, hit.damage),
+        }
+
+        // Online, the server's own report of the hit goes in the log
+        if hit.health.is_none() {
+            combat_log.0.record(CombatEvent {
+                chain: hit.chain.map(|c| c.name.clone()),
+                critical: hit.critical,
+                resisted: hit.resisted,
+                killing_blow: !mob.is_alive(),
+                ..CombatEvent::blow(time.elapsed_secs(), LOCAL_PLAYER, mob.id, hit.action, hit.damage)
             });
         }
-        _ => {}
-    }
+        telemetry.write(JobTelemetryEvent(JobTelemetry::MobDamaged {
+            mob_id: hit.mob.to_bits(),
+            mob_type: mob.mob_type,
+            damage: hit.damage,
+        }));
+        if mob.is_alive() {
+            continue;
+        }
 
-    loot
+        // Online, the server keeps the player's standing
+        if hit.health.is_none() {
+            player_combat.reputation.apply(&ReputationEvent::MobKilled { mob_type: mob.mob_type });
+        }
+        telemetry.write(JobTelemetryEvent(JobTelemetry::MobKilled {
+            mob_id: hit.mob.to_bits(),
+            mob_type: mob.mob_type,
+            max_health: mob.max_health,
+        }));
+
+        player_combat.is_in_combat = false;
+        player_combat.current_target = None;
+
+        // Online, the server splits the experience and shares out the loot
+        if hit.health.is_some() {
+            continue;
+        }
+        println!("  +{:.0} XP", mob.xp_reward);
+        player_combat.award_xp(mob.xp_reward);
+
+        // Loot drop
+        let loot = get_loot_for_mob(mob);
+        if !loot.is_empty() {
+            if let Ok(mut satchel) = satchel_q.single_mut() {
+                for item in &loot {
+                    if satchel.add_item(item.clone()) {
+                        println!("  Loot: {} x{}", item.name, item.quantity);
+                    } else {
+                        println!("  Satchel full! {} dropped on the ground.", item.name);
+                    }
+                }
+            }
+        }
+    }
 }
 
-pub fn spawn_mobs(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+/// Run status effects on the player and mobs, and the player's job resource. Offline, bleeds and burns on
+/// mobs are the player's doing; online the server reports their damage.
+pub fn status_effect_system(
+    time: Res<Time>,
+    mut player_q: Query<&mut PlayerCombat>,
+    mut mob_q: Query<(Entity, &mut Mob)>,
+    client: Option<Res<RenetClient>>,
+    mut hits: MessageWriter<MobHit>,
 ) {
-    struct MobSpawn {
-        pos: Vec3,
-        mob: Mob,
-        color: Color,
-        radius: f32,
-        aggro: f32,
-        speed: f32,
-    }
+    let dt = time.delta_secs();
+    let online = client.is_some_and(|c| c.is_connected());
 
-    let spawns = vec![
-        // Wolf pack near player start
-        MobSpawn { pos: Vec3::new(50.0, 5.0, 50.0), mob: Mob::wolf(1), color: Color::srgb(0.6, 0.4, 0.3), radius: 3.0, aggro: 50.0, speed: 25.0 },
-        MobSpawn { pos: Vec3::new(80.0, 5.0, 60.0), mob: Mob::wolf(1), color: Color::srgb(0.55, 0.35, 0.25), radius: 3.0, aggro: 50.0, speed: 25.0 },
-        MobSpawn { pos: Vec3::new(65.0, 5.0, 80.0), mob: Mob::wolf(2), color: Color::srgb(0.5, 0.3, 0.2), radius: 3.5, aggro: 55.0, speed: 27.0 },
-        // Wolves south
-        MobSpawn { pos: Vec3::new(-60.0, 5.0, -80.0), mob: Mob::wolf(2), color: Color::srgb(0.5, 0.35, 0.25), radius: 3.0, aggro: 50.0, speed: 25.0 },
-        MobSpawn { pos: Vec3::new(-40.0, 5.0, -100.0), mob: Mob::wolf(1), color: Color::srgb(0.6, 0.4, 0.3), radius: 3.0, aggro: 50.0, speed: 25.0 },
-        // Lion pride east
-        MobSpawn { pos: Vec3::new(160.0, 5.0, 20.0), mob: Mob::lion(2), color: Color::srgb(0.85, 0.7, 0.3), radius: 5.0, aggro: 75.0, speed: 22.0 },
-        MobSpawn { pos: Vec3::new(180.0, 5.0, -10.0), mob: Mob::lion(3), color: Color::srgb(0.8, 0.65, 0.25), radius: 5.5, aggro: 75.0, speed: 22.0 },
-        // Corrupted west
-        MobSpawn { pos: Vec3::new(-150.0, 5.0, 50.0), mob: Mob::corrupted(3), color: Color::srgb(0.4, 0.0, 0.5), radius: 5.0, aggro: 80.0, speed: 20.0 },
-        MobSpawn { pos: Vec3::new(-170.0, 5.0, 80.0), mob: Mob::corrupted(4), color: Color::srgb(0.35, 0.0, 0.45), radius: 5.5, aggro: 80.0, speed: 20.0 },
-        // Chimera far southeast
-        MobSpawn { pos: Vec3::new(120.0, 5.0, -150.0), mob: Mob::chimera(4), color: Color::srgb(0.9, 0.1, 0.5), radius: 6.0, aggro: 100.0, speed: 18.0 },
-        // Nephilim boss far north
-        MobSpawn { pos: Vec3::new(0.0, 8.0, -250.0), mob: Mob::nephilim(5), color: Color::srgb(0.3, 0.0, 0.0), radius: 10.0, aggro: 200.0, speed: 15.0 },
-    ];
-
-    for s in spawns {
-        let brain = MobBrain::new(s.aggro, s.speed, s.pos);
-
-        let (body_mesh, body_scale, head_offset, head_size) = match s.mob.mob_tier {
-            MobTier::Common => (
-                meshes.add(Capsule3d::new(s.radius * 0.5, s.radius * 0.4)),
-                Vec3::new(1.2, 1.0, 1.0),
-                Vec3::new(0.0, s.radius * 0.2, s.radius * 0.5),
-                s.radius * 0.35,
-            ),
-            MobTier::Elite => (
-                meshes.add(Capsule3d::new(s.radius * 0.45, s.radius * 0.8)),
-                Vec3::ONE,
-                Vec3::new(0.0, s.radius * 0.8, 0.0),
-                s.radius * 0.3,
-            ),
-            MobTier::Boss => (
-                meshes.add(Capsule3d::new(s.radius * 0.4, s.radius * 1.2)),
-                Vec3::ONE,
-                Vec3::new(0.0, s.radius * 1.3, 0.0),
-                s.radius * 0.35,
-            ),
-        };
+    if let Ok(mut combat) = player_q.single_mut() {
+        let tick = combat.effects.update(dt);
+        combat.take_damage(tick.damage);
+        combat.heal(tick.healing);
 
-        let mob_mat = materials.add(StandardMaterial {
-            base_color: s.color,
-            metallic: 0.1,
-            perceptual_roughness: 0.8,
-            ..default()
-        });
+        // A channelled song renews itself; online, the server spreads it to the party
+        if let Some(song) = combat.resources.update(dt) {
+            for kind in song.status_effects() {
+                combat.effects.apply(kind, 0);
+            }
+        }
+    }
 
-        commands.spawn((
-            Mesh3d(body_mesh),
-            MeshMaterial3d(mob_mat.clone()),
-            Transform::from_translation(s.pos).with_scale(body_scale),
-            s.mob,
-            brain,
-            Name::new("Mob"),
-        )).with_children(|parent| {
-            parent.spawn((
-                Mesh3d(meshes.add(Sphere::new(head_size))),
-                MeshMaterial3d(mob_mat),
-                Transform::from_translation(head_offset),
-            ));
-        });
+    for (entity, mut mob) in mob_q.iter_mut() {
+        if mob.is_alive() {
+            let regeneration = mob.variant.regeneration(mob.max_health) * dt;
+            mob.health = (mob.health + regeneration).min(mob.max_health);
+        }
+        let tick = mob.effects.update(dt);
+        if tick.damage > 0.0 && !online && mob.is_alive() {
+            hits.write(MobHit {
+                mob: entity,
+                damage: tick.damage,
+                health: None,
+                by_player: true,
+                action: None,
+                chain: None,
+                critical: false,
+                resisted: 0.0,
+            });
+        }
     }
+}
 
-    println!("Spawned 11 mobs across the world. Hunt them down!");
+fn get_loot_for_mob(mob: &Mob) -> Vec<InventoryItem> {
+    mob.variant
+        .loot(mob.mob_type, mob.level)
+        .into_iter()
+        .map(|drop| InventoryItem {
+            name: drop.name,
+            quantity: drop.quantity,
+            weight: drop.weight,
+        })
+        .collect()
 }
 
 pub fn update_mob_health_display(
@@ -0,0 +1,200 @@
+//! Dying and rising: the corpse left behind and the run back to it, and
+//! binding the soul to the altar or campfire it rises at. Online the server
+//! lays the corpse and gives back what it holds; offline it is done here.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use antediluvia_core::death::{corpse_share, BindPoint, Corpse, DEATH_WEAR};
+use antediluvia_core::mob::LootDrop;
+use antediluvia_core::network::NetworkMessage;
+use antediluvia_core::world::FloodStage;
+use crate::combat::PlayerCombat;
+use crate::inventory::{InventoryItem, Satchel};
+use crate::mob_ai::LOCAL_PLAYER;
+use crate::network::send_message;
+use crate::player::PlayerCamera;
+use crate::{Equipment, WorldState};
+
+/// A corpse the local player left behind.
+#[derive(Component)]
+pub struct PlayerCorpse(pub Corpse);
+
+/// What the server says of the local player's corpses.
+#[derive(Message, Clone)]
+pub enum CorpseNews {
+    /// A corpse was laid holding `items`, and the weapon worn by `wear`.
+    Laid { position: Vec3, items: Vec<LootDrop>, wear: f32 },
+
+    /// The corpse in reach was recovered; what it held follows as loot.
+    Recovered,
+}
+
+/// Lay a corpse's body down where it fell.
+fn spawn_corpse(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    corpse: Corpse,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Capsule3d::new(1.0, 3.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.32, 0.3),
+            perceptual_roughness: 1.0,
+            ..default()
+        })),
+        Transform::from_translation(corpse.position).with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+        PlayerCorpse(corpse),
+    ));
+}
+
+/// Lay the player's corpse down when they fall, worn weapon and all. Fallen
+/// once the Flood has come, they will not rise again.
+pub fn player_death_system(
+    mut commands: Commands,
+    client: Option<Res<RenetClient>>,
+    mut news: MessageReader<CorpseNews>,
+    mut player_q: Query<(&mut PlayerCombat, &Transform), With<PlayerCamera>>,
+    mut satchel_q: Query<&mut Satchel>,
+    mut equipment: ResMut<Equipment>,
+    world_state: Res<WorldState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // The server's corpse holds what it took from the satchel it was told of
+    for news in news.read() {
+        let CorpseNews::Laid { position, items, wear } = news else {
+            continue;
+        };
+        if let Ok(mut satchel) = satchel_q.single_mut() {
+            for item in items {
+                satchel.remove_item(&item.name, item.quantity);
+            }
+        }
+        equipment.wear_weapon(*wear);
+        spawn_corpse(&mut commands, &mut meshes, &mut materials, Corpse::new(LOCAL_PLAYER, *position, items.clone()));
+    }
+
+    let Ok((mut combat, transform)) = player_q.single_mut() else {
+        return;
+    };
+    if !combat.is_dead || combat.corpse_laid {
+        return;
+    }
+    combat.corpse_laid = true;
+    if world_state.flood_stage == FloodStage::TheFlood {
+        combat.fallen = true;
+    }
+
+    if client.is_none_or(|c| !c.is_connected()) {
+        let mut items = Vec::new();
+        if let Ok(mut satchel) = satchel_q.single_mut() {
+            for item in satchel.items.clone() {
+                let share = corpse_share(item.quantity);
+                if satchel.remove_item(&item.name, share) {
+                    items.push(LootDrop { name: item.name, quantity: share, weight: item.weight });
+                }
+            }
+        }
+        equipment.wear_weapon(DEATH_WEAR);
+        spawn_corpse(&mut commands, &mut meshes, &mut materials, Corpse::new(LOCAL_PLAYER, transform.translation, items));
+    }
+
+    if combat.fallen {
+        println!("You have perished in the Flood. Your soul will not rise again this season.");
+    } else {
+        println!("You have died. Half of what you carried lies with your corpse, and you owe {:.0} experience.", combat.xp_debt);
+    }
+}
+
+/// Let corpses crumble, and give back what one holds when its owner reaches it.
+/// Online the server decides when that is.
+pub fn corpse_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    client: Option<Res<RenetClient>>,
+    mut news: MessageReader<CorpseNews>,
+    mut corpse_q: Query<(Entity, &mut PlayerCorpse)>,
+    player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    mut satchel_q: Query<&mut Satchel>,
+) {
+    let player = player_q
+        .single()
+        .ok()
+        .filter(|(combat, _)| !combat.is_dead)
+        .map(|(_, transform)| transform.translation);
+    let online = client.is_some_and(|c| c.is_connected());
+
+    // Online the server hands back what a recovered corpse held
+    for news in news.read() {
+        if !matches!(news, CorpseNews::Recovered) {
+            continue;
+        }
+        let recovered = corpse_q
+            .iter()
+            .find(|(_, corpse)| player.is_some_and(|position| corpse.0.can_recover(LOCAL_PLAYER, position)));
+        if let Some((entity, _)) = recovered {
+            commands.entity(entity).despawn();
+        }
+        println!("You recover what your corpse held.");
+    }
+
+    for (entity, mut corpse) in corpse_q.iter_mut() {
+        if !corpse.0.update(time.delta_secs()) {
+            commands.entity(entity).despawn();
+            println!("Your corpse has crumbled to dust, and all it held is lost.");
+            continue;
+        }
+        if online || !player.is_some_and(|position| corpse.0.can_recover(LOCAL_PLAYER, position)) {
+            continue;
+        }
+        let Ok(mut satchel) = satchel_q.single_mut() else {
+            continue;
+        };
+
+        // Take back whatever fits; the rest waits on the corpse
+        let held = corpse.0.items.len();
+        corpse.0.items.retain(|item| {
+            !satchel.add_item(InventoryItem {
+                name: item.name.clone(),
+                quantity: item.quantity,
+                weight: item.weight,
+            })
+        });
+        if corpse.0.items.is_empty() {
+            commands.entity(entity).despawn();
+            println!("You recover what your corpse held.");
+        } else if corpse.0.items.len() < held {
+            println!("Your satchel is full; the rest stays with your corpse.");
+        }
+    }
+}
+
+/// Bind the soul to the altar or campfire the player stands by (B).
+pub fn soul_binding_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut player_q: Query<(&mut PlayerCombat, &Transform), With<PlayerCamera>>,
+    mut client: Option<ResMut<RenetClient>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+    let Ok((mut combat, transform)) = player_q.single_mut() else {
+        return;
+    };
+    if combat.is_dead {
+        return;
+    }
+
+    match BindPoint::near(transform.translation) {
+        Some(point) if point.admits(combat.lineage) => {
+            combat.bind_point = point;
+            if let Some(client) = client.as_deref_mut().filter(|c| c.is_connected()) {
+                send_message(client, &NetworkMessage::BindSoul);
+            }
+            println!("Your soul is bound to {}.", point.name());
+        }
+        Some(point) => println!("Your house may not bind its souls to {}.", point.name()),
+        None => println!("There is no altar or campfire near enough to bind your soul to."),
+    }
+}
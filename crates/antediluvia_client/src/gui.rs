@@ -1,15 +1,34 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use crate::AppState;
-use crate::combat::{PlayerCombat, Mob, MobTier};
+use crate::combat::{PlayerCombat, Mob};
 use crate::player::PlayerCamera;
 use crate::npc::{NPCEntity, NPCInteraction};
 use crate::inventory::{Satchel, InventoryItem};
-use crate::{WorldState, CraftingRes, Equipment, DayNightCycle};
-use crate::combat::ChainNotification;
-use crate::gathering::GatheringNode;
+use crate::{WorldState, CraftingRes, Equipment, DayNightCycle, WEAPON_DURABILITY};
+use crate::combat::{ChainNotification, CombatLogRes};
+use crate::gathering::GatheringRes;
 use crate::graphics_settings::{GraphicsSettings, QualityTier};
-use antediluvia_core::combat::CombatAction;
+use crate::unlocks::JobTelemetryEvent;
+use crate::character_select::SelectedCharacter;
+use crate::network::send_message;
+use crate::party::PartyState;
+use crate::pvp::PvpState;
+use crate::targeting::RemoteTargets;
+use crate::mob_ai::{MobBrain, LOCAL_PLAYER};
+use antediluvia_core::network::NetworkMessage;
+use antediluvia_core::combat_log::replay;
+use antediluvia_core::gathering::gathering_sites;
+use antediluvia_core::party::{LootChoice, LootRule};
+use antediluvia_core::status::StatusEffects;
+use bevy_renet::RenetClient;
+use antediluvia_core::combat::{armor_mitigation, is_weapon, CombatAction, MAX_HEAT, MAX_INCENSE, MAX_MOMENTUM};
+use antediluvia_core::abilities::{AbilityEffect, ActionModifiers, Modifier, SkillTree, LOADOUT_SLOTS};
+use antediluvia_core::entity::{Job, JobTelemetry};
+use antediluvia_core::crafting::crafting_skill;
+use std::collections::HashMap;
+use antediluvia_core::mob::MobTier;
+use antediluvia_core::variant::MobRank;
 use antediluvia_core::world::FloodStage;
 
 pub struct GuiPlugin;
@@ -28,6 +47,10 @@ impl Plugin for GuiPlugin {
                equipment_panel_system,
                gathering_prompt_system,
                graphics_settings_panel_system,
+               skill_tree_panel_system,
+               party_panel_system,
+               pvp_panel_system,
+               combat_log_panel_system,
            ));
     }
 }
@@ -40,6 +63,10 @@ pub struct GuiState {
     pub show_map: bool,
     pub show_equipment: bool,
     pub show_graphics: bool,
+    pub show_skills: bool,
+    pub show_party: bool,
+    pub show_pvp: bool,
+    pub show_combat_log: bool,
 }
 
 impl Default for GuiState {
@@ -51,6 +78,10 @@ impl Default for GuiState {
             show_map: false,
             show_equipment: false,
             show_graphics: false,
+            show_skills: false,
+            show_party: false,
+            show_pvp: false,
+            show_combat_log: false,
         }
     }
 }
@@ -64,6 +95,7 @@ fn dev_panel_system(
     app_state: Res<State<AppState>>,
     keys: Res<ButtonInput<KeyCode>>,
     player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    brain_q: Query<(&Mob, &MobBrain)>,
     world_state: Option<Res<WorldState>>,
 ) {
     if keys.just_pressed(KeyCode::F3) {
@@ -90,10 +122,26 @@ fn dev_panel_system(
                 ui.label(format!("Pos: ({:.0}, {:.0}, {:.0})", transform.translation.x, transform.translation.y, transform.translation.z));
                 ui.label(format!("Level: {} | XP: {:.0}/{:.0}", combat.level, combat.experience, combat.xp_to_next_level));
                 ui.label(format!("HP: {:.0}/{:.0}", combat.health, combat.max_health));
+                ui.label(format!("Breath: {:.0}/{:.0}", combat.breath.current, combat.breath.max));
+                ui.label(format!(
+                    "STR {:.0} | END {:.0} | FTH {:.0} | CUN {:.0}",
+                    combat.stats.strength, combat.stats.endurance, combat.stats.faith, combat.stats.cunning
+                ));
                 ui.label(format!("Damage: {:.0}%", combat.damage_multiplier * 100.0));
                 ui.label(format!("In Combat: {}", combat.is_in_combat));
             }
 
+            // The target's behaviour tree as it last decided
+            let target = player_q.single().ok().and_then(|(combat, _)| combat.current_target);
+            if let Some((mob, brain)) = target.and_then(|entity| brain_q.get(entity).ok()) {
+                ui.separator();
+                ui.heading(format!("{} AI ({})", mob.name, brain.tree.name));
+                ui.label(format!("Action: {:?} | State: {:?}", brain.decision.action, brain.state));
+                for line in brain.decision.describe() {
+                    ui.monospace(line);
+                }
+            }
+
             if let Some(ws) = &world_state {
                 ui.separator();
                 ui.heading("World");
@@ -131,7 +179,12 @@ fn hud_system(
                     ui.label(egui::RichText::new("YOU HAVE FALLEN")
                         .size(48.0).color(egui::Color32::RED).strong());
                     ui.add_space(10.0);
-                    ui.label(egui::RichText::new(format!("Respawning in {:.1}s...", combat.respawn_timer))
+                    let status = if combat.fallen {
+                        "You perished in the Flood, and will not rise again this season.".to_string()
+                    } else {
+                        format!("Rising at {} in {:.1}s...", combat.bind_point.name(), combat.respawn_timer.max(0.0))
+                    };
+                    ui.label(egui::RichText::new(status)
                         .size(24.0).color(egui::Color32::WHITE));
                 });
             });
@@ -143,6 +196,8 @@ fn hud_system(
         .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -60.0])
         .show(ctx, |ui| {
             ui.vertical_centered(|ui| {
+                effect_icons(ui, &combat.effects);
+
                 let hp_pct = combat.health / combat.max_health;
                 let hp_color = if hp_pct > 0.5 {
                     egui::Color32::from_rgb(50, 200, 50)
@@ -161,6 +216,25 @@ fn hud_system(
                 painter.text(rect.center(), egui::Align2::CENTER_CENTER,
                     format!("HP: {:.0} / {:.0}", combat.health, combat.max_health),
                     egui::FontId::proportional(14.0), egui::Color32::WHITE);
+
+                // The Breath (stamina)
+                let breath_pct = combat.breath.fraction();
+                let breath_color = if combat.breath.is_exhausted() {
+                    egui::Color32::from_rgb(120, 120, 120)
+                } else {
+                    egui::Color32::from_rgb(220, 200, 90)
+                };
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(bar_width, 6.0), egui::Sense::hover());
+                let painter = ui.painter();
+                painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(30, 30, 30));
+                let filled = egui::Rect::from_min_size(rect.min, egui::vec2(bar_width * breath_pct, 6.0));
+                painter.rect_filled(filled, 2.0, breath_color);
+
+                let resources = job_resource_labels(&combat);
+                if !resources.is_empty() {
+                    ui.label(egui::RichText::new(resources.join("   "))
+                        .size(12.0).color(egui::Color32::from_rgb(230, 200, 140)));
+                }
             });
         });
 
@@ -169,12 +243,14 @@ fn hud_system(
         .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -20.0])
         .show(ctx, |ui| {
             ui.horizontal(|ui| {
-                let abilities = [
-                    (CombatAction::HunterThrust, "1: Thrust", 2.5f32),
-                    (CombatAction::HunterSlash, "2: Slash", 2.0),
-                    (CombatAction::LeviteHeal, "3: Heal", 4.0),
-                    (CombatAction::ForgeSmash, "4: Smash", 3.5),
-                ];
+                let abilities: Vec<(CombatAction, String, f32)> = combat.loadout.slots.iter()
+                    .enumerate()
+                    .filter_map(|(i, slot)| slot.map(|a| (i, a)))
+                    .map(|(i, a)| {
+                        let max_cd = a.cooldown() * ActionModifiers::for_action(a, &combat.mastery).cooldown;
+                        (a, format!("{}: {}", i + 1, action_label(a)), max_cd)
+                    })
+                    .collect();
                 for (action, label, max_cd) in &abilities {
                     let remaining = combat.get_cooldown_remaining(*action);
                     let ready = remaining <= 0.0;
@@ -210,6 +286,10 @@ fn hud_system(
                 painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(20, 20, 40));
                 let filled = egui::Rect::from_min_size(rect.min, egui::vec2(bar_width * xp_pct, bar_height));
                 painter.rect_filled(filled, 2.0, egui::Color32::from_rgb(100, 100, 255));
+                if combat.xp_debt > 0.0 {
+                    ui.label(egui::RichText::new(format!("Experience debt: {:.0}", combat.xp_debt))
+                        .size(12.0).color(egui::Color32::from_rgb(200, 120, 120)));
+                }
             });
         });
 
@@ -288,6 +368,8 @@ fn target_info_system(
     app_state: Res<State<AppState>>,
     player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
     mob_q: Query<(&Mob, &Transform), Without<PlayerCamera>>,
+    remote_targets: Res<RemoteTargets>,
+    selected: Option<Res<SelectedCharacter>>,
 ) {
     if !gui_state.show_hud || *app_state.get() != AppState::InWorld { return; }
 
@@ -297,31 +379,34 @@ fn target_info_system(
     };
     if combat.is_dead { return; }
 
-    let player_pos = player_transform.translation;
-    let mut nearest: Option<(&Mob, f32)> = None;
-    for (mob, mob_transform) in mob_q.iter() {
-        if !mob.is_alive() { continue; }
-        let dist = player_pos.distance(mob_transform.translation);
-        if dist < 200.0 {
-            if nearest.is_none() || dist < nearest.unwrap().1 {
-                nearest = Some((mob, dist));
-            }
-        }
-    }
+    let target = combat.current_target
+        .and_then(|entity| mob_q.get(entity).ok())
+        .filter(|(mob, _)| mob.is_alive())
+        .map(|(mob, mob_transform)| (mob, player_transform.translation.distance(mob_transform.translation)));
+
+    // Target of target: the server's word online; offline, whoever tops the mob's threat
+    let player_id = selected.map(|s| s.0.id);
+    let target_of_target = target.and_then(|(mob, _)| remote_targets.targets.get(&mob.id).copied().or(mob.threat.target))
+        .map(|id| if id == LOCAL_PLAYER || Some(id) == player_id { "You".to_string() } else { format!("Player {}", id) });
 
-    if let Some((mob, distance)) = nearest {
+    if let Some((mob, distance)) = target {
         let Ok(ctx) = contexts.ctx_mut() else { return; };
         egui::Area::new("target_info".into())
             .anchor(egui::Align2::CENTER_TOP, [0.0, 50.0])
             .show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
-                    let tier_color = match mob.mob_tier {
+                    let tier_color = match mob.mob_type.tier() {
                         MobTier::Common => egui::Color32::WHITE,
                         MobTier::Elite => egui::Color32::from_rgb(180, 130, 255),
                         MobTier::Boss => egui::Color32::from_rgb(255, 80, 80),
                     };
                     ui.label(egui::RichText::new(format!("{} (Lv{})", mob.name, mob.level))
                         .size(16.0).color(tier_color).strong());
+                    if mob.variant.rank != MobRank::Normal {
+                        let affixes: Vec<String> = mob.variant.affixes.iter().map(|a| format!("{:?}", a)).collect();
+                        ui.label(egui::RichText::new(format!("{:?} — {}", mob.variant.rank, affixes.join(", ")))
+                            .size(12.0).color(egui::Color32::from_rgb(255, 200, 60)));
+                    }
 
                     let hp_pct = mob.health / mob.max_health;
                     let bar_width = 180.0;
@@ -335,6 +420,11 @@ fn target_info_system(
                         format!("{:.0}/{:.0}", mob.health, mob.max_health),
                         egui::FontId::proportional(10.0), egui::Color32::WHITE);
 
+                    effect_icons(ui, &mob.effects);
+                    if let Some(name) = &target_of_target {
+                        ui.label(egui::RichText::new(format!("Target: {}", name))
+                            .size(12.0).color(egui::Color32::from_rgb(230, 200, 140)));
+                    }
                     ui.label(egui::RichText::new(format!("{:.0}m", distance))
                         .size(11.0).color(egui::Color32::GRAY));
                 });
@@ -342,6 +432,51 @@ fn target_info_system(
     }
 }
 
+/// The job resources behind the actions in the player's loadout.
+fn job_resource_labels(combat: &PlayerCombat) -> Vec<String> {
+    let mut jobs: Vec<Job> = Vec::new();
+    for job in combat.loadout.slots.iter().flatten().map(|a| a.job()) {
+        if !jobs.contains(&job) {
+            jobs.push(job);
+        }
+    }
+    let res = &combat.resources;
+    jobs.into_iter()
+        .filter_map(|job| match job {
+            Job::Levite => Some(format!("Incense {:.0}/{:.0}", res.incense, MAX_INCENSE)),
+            Job::Hunter => Some(format!("Momentum {}/{}", res.momentum, MAX_MOMENTUM)),
+            Job::Forge => Some(format!("Heat {:.0}/{:.0}", res.heat, MAX_HEAT)),
+            Job::Psalmist => res.channel.map(|c| format!("{} {:.0}s", action_label(c.song), c.remaining.ceil())),
+            Job::Shepherd => None,
+        })
+        .collect()
+}
+
+/// A row of status effect icons with stacks and seconds left. Red for harmful, green for helpful.
+fn effect_icons(ui: &mut egui::Ui, effects: &StatusEffects) {
+    if effects.effects.is_empty() { return; }
+    ui.horizontal(|ui| {
+        for effect in &effects.effects {
+            let color = if effect.kind.is_harmful() {
+                egui::Color32::from_rgb(200, 60, 50)
+            } else {
+                egui::Color32::from_rgb(60, 170, 80)
+            };
+            let stacks = if effect.stacks > 1 { format!("x{}", effect.stacks) } else { String::new() };
+            egui::Frame::none()
+                .fill(color)
+                .inner_margin(3.0)
+                .rounding(3.0)
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(format!("{}{} {:.0}s", effect.kind.icon(), stacks, effect.remaining.ceil()))
+                        .size(11.0).color(egui::Color32::WHITE).strong());
+                })
+                .response
+                .on_hover_text(format!("{:?}", effect.kind));
+        }
+    });
+}
+
 // ─── NPC Dialogue ───────────────────────────────────────
 
 fn npc_dialogue_system(
@@ -411,8 +546,10 @@ fn crafting_panel_system(
     mut gui_state: ResMut<GuiState>,
     keys: Res<ButtonInput<KeyCode>>,
     crafting: Option<Res<CraftingRes>>,
-    player_q: Query<&PlayerCombat, With<PlayerCamera>>,
+    client: Option<ResMut<RenetClient>>,
+    mut player_q: Query<&mut PlayerCombat, With<PlayerCamera>>,
     mut satchel_q: Query<&mut Satchel>,
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
 ) {
     if keys.just_pressed(KeyCode::KeyC) {
         gui_state.show_crafting = !gui_state.show_crafting;
@@ -424,7 +561,8 @@ fn crafting_panel_system(
         None => return,
     };
 
-    let skill_level = player_q.single().map(|c| c.level * 5).unwrap_or(5);
+    let skill_level = crafting_skill(player_q.single().map(|c| c.level).unwrap_or(1));
+    let lineage = player_q.single().map(|c| c.lineage).unwrap_or_default();
 
     // Snapshot satchel for display
     let satchel_items: Vec<(String, u32)> = if let Ok(satchel) = satchel_q.single() {
@@ -443,7 +581,7 @@ fn crafting_panel_system(
     }
 
     let recipe_data: Vec<RecipeInfo> = crafting.0
-        .get_all_recipes()
+        .recipes_for(lineage)
         .into_iter()
         .map(|r| RecipeInfo {
             name: r.name.clone(),
@@ -517,26 +655,38 @@ fn crafting_panel_system(
             ui.label(egui::RichText::new("Press C to close").size(11.0).color(egui::Color32::GRAY));
         });
 
-    // Execute crafting
+    // Execute crafting; online the server judges it, and corrupts the crafter
     if let Some(item_name) = craft_name {
+        if let Some(mut client) = client.filter(|c| c.is_connected()) {
+            send_message(&mut client, &NetworkMessage::Craft { item: item_name });
+            return;
+        }
         if let Some(recipe_item) = crafting.0.get_recipe(&item_name) {
             if let Ok(mut satchel) = satchel_q.single_mut() {
+                let owned: HashMap<String, u32> = satchel.items.iter()
+                    .map(|i| (i.name.clone(), i.quantity))
+                    .collect();
+                if let Some(crafted) = crafting.0.craft(&item_name, skill_level, &owned) {
+                    telemetry.write(JobTelemetryEvent(JobTelemetry::ItemCrafted {
+                        quality: crafted.quality,
+                        is_weapon: is_weapon(&item_name),
+                    }));
+                }
                 for (ingredient, qty) in &recipe_item.recipe.ingredients {
                     satchel.remove_item(ingredient, *qty);
                 }
-                let weight = match item_name.as_str() {
-                    "Bronze Sword" => 8.0,
-                    "Iron Sword" => 10.0,
-                    "Linen Tunic" => 3.0,
-                    _ => 5.0,
-                };
                 satchel.add_item(InventoryItem {
                     name: item_name.clone(),
                     quantity: 1,
-                    weight,
+                    weight: recipe_item.weight,
                 });
                 println!("Crafted: {}!", item_name);
             }
+            if recipe_item.corruption_cost > 0.0 {
+                if let Ok(mut combat) = player_q.single_mut() {
+                    combat.corrupt(recipe_item.corruption_cost);
+                }
+            }
         }
     }
 }
@@ -602,7 +752,7 @@ fn map_overlay_system(
                 if mob.is_alive() {
                     let pos = to_map(t.translation.x, t.translation.z);
                     if rect.contains(pos) {
-                        let color = match mob.mob_tier {
+                        let color = match mob.mob_type.tier() {
                             MobTier::Common => egui::Color32::from_rgb(200, 60, 60),
                             MobTier::Elite => egui::Color32::from_rgb(180, 60, 200),
                             MobTier::Boss => egui::Color32::from_rgb(255, 30, 30),
@@ -639,6 +789,228 @@ fn map_overlay_system(
         });
 }
 
+// ─── Skill Tree Panel ───────────────────────────────────
+
+fn skill_tree_panel_system(
+    mut contexts: EguiContexts,
+    mut gui_state: ResMut<GuiState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut player_q: Query<&mut PlayerCombat, With<PlayerCamera>>,
+    client: Option<ResMut<RenetClient>>,
+) {
+    if keys.just_pressed(KeyCode::KeyK) {
+        gui_state.show_skills = !gui_state.show_skills;
+    }
+    if !gui_state.show_skills { return; }
+
+    let Ok(mut combat) = player_q.single_mut() else { return; };
+    let mut assign: Option<(usize, Option<CombatAction>)> = None;
+
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    egui::Window::new("Skill Trees")
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .resizable(false)
+        .collapsible(false)
+        .min_width(360.0)
+        .show(ctx, |ui| {
+            ui.heading("Loadout");
+            ui.horizontal(|ui| {
+                for slot in 0..LOADOUT_SLOTS {
+                    let label = combat.loadout.get(slot).map(action_label).unwrap_or_else(|| "(empty)".to_string());
+                    if ui.button(format!("{}: {}", slot + 1, label)).on_hover_text("Click to clear").clicked() {
+                        assign = Some((slot, None));
+                    }
+                }
+            });
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for job in [Job::Shepherd, Job::Levite, Job::Hunter, Job::Forge, Job::Psalmist] {
+                    let level = combat.mastery.get_level(job);
+                    ui.separator();
+                    ui.label(egui::RichText::new(format!("{:?} — mastery {:.1}", job, level)).strong());
+
+                    for node in &SkillTree::for_job(job).nodes {
+                        let unlocked = node.is_unlocked(&combat.mastery);
+                        let color = if unlocked { egui::Color32::WHITE } else { egui::Color32::GRAY };
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("[{:.0}] {}", node.mastery_required, node.name)).color(color));
+                            ui.label(egui::RichText::new(effect_label(&node.effect)).size(11.0).color(egui::Color32::GRAY));
+                            if let (true, AbilityEffect::Action(action)) = (unlocked, node.effect) {
+                                for slot in 0..LOADOUT_SLOTS {
+                                    if ui.small_button(format!("{}", slot + 1)).clicked() {
+                                        assign = Some((slot, Some(action)));
+                                    }
+                                }
+                            }
+                        });
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.label(egui::RichText::new("Press K to close").size(11.0).color(egui::Color32::GRAY));
+        });
+
+    if let Some((slot, action)) = assign {
+        let mastery = combat.mastery.clone();
+        if let Err(e) = combat.loadout.set(slot, action, &mastery) {
+            println!("{}", e);
+            return;
+        }
+        // The server keeps the loadout it checks actions against, and echoes it back
+        if let Some(mut client) = client {
+            send_message(&mut client, &NetworkMessage::SetLoadout { slots: combat.loadout.names() });
+        }
+    }
+}
+
+/// Display name of an action, taken from its skill tree node.
+fn action_label(action: CombatAction) -> String {
+    SkillTree::for_job(action.job())
+        .nodes
+        .into_iter()
+        .find(|n| n.effect == AbilityEffect::Action(action))
+        .map(|n| n.name)
+        .unwrap_or_else(|| format!("{:?}", action))
+}
+
+fn effect_label(effect: &AbilityEffect) -> String {
+    let modifier_label = |m: &Modifier| match m {
+        Modifier::Damage(f) => format!("+{:.0}% power", f * 100.0),
+        Modifier::Cooldown(f) => format!("-{:.0}% cooldown", f * 100.0),
+        Modifier::Stamina(f) => format!("-{:.0}% Breath cost", f * 100.0),
+    };
+    match effect {
+        AbilityEffect::Action(_) => "Ability".to_string(),
+        AbilityEffect::Passive(m) => format!("Passive: {}", modifier_label(m)),
+        AbilityEffect::Modifier { action, modifier } => format!("{}: {}", action_label(*action), modifier_label(modifier)),
+    }
+}
+
+// ─── Party Panel ────────────────────────────────────────
+
+fn party_panel_system(
+    mut contexts: EguiContexts,
+    mut gui_state: ResMut<GuiState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut party: ResMut<PartyState>,
+    selected: Option<Res<SelectedCharacter>>,
+    client: Option<ResMut<RenetClient>>,
+) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        gui_state.show_party = !gui_state.show_party;
+    }
+
+    // Open rolls demand an answer even with the panel closed
+    if !gui_state.show_party && party.rolls.is_empty() && party.invite_from.is_none() { return; }
+
+    let player_id = selected.map(|s| s.0.id).unwrap_or_default();
+    let is_leader = party.in_party() && party.leader == player_id;
+    let online = client.as_ref().is_some_and(|c| c.is_connected());
+    let mut outgoing: Vec<NetworkMessage> = Vec::new();
+
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    egui::Window::new("Party")
+        .anchor(egui::Align2::LEFT_TOP, [10.0, 200.0])
+        .resizable(false)
+        .collapsible(false)
+        .min_width(240.0)
+        .show(ctx, |ui| {
+            if !online {
+                ui.label(egui::RichText::new("Parties need a connection to the server").color(egui::Color32::GRAY));
+                return;
+            }
+
+            if let Some(from) = party.invite_from {
+                ui.label(format!("{} invites you to their party", from));
+                ui.horizontal(|ui| {
+                    if ui.button("Accept").clicked() {
+                        outgoing.push(NetworkMessage::PartyAccept);
+                        party.invite_from = None;
+                    }
+                    if ui.button("Decline").clicked() {
+                        outgoing.push(NetworkMessage::PartyDecline);
+                        party.invite_from = None;
+                    }
+                });
+                ui.separator();
+            }
+
+            if party.in_party() {
+                for member in party.members.clone() {
+                    ui.horizontal(|ui| {
+                        let crown = if member == party.leader { " (leader)" } else { "" };
+                        let you = if member == player_id { " — you" } else { "" };
+                        ui.label(format!("{}{}{}", member, crown, you));
+                        if is_leader && member != player_id && ui.small_button("Promote").clicked() {
+                            outgoing.push(NetworkMessage::PartyPromote { player_id: member });
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Loot:");
+                    for (rule, label) in [(LootRule::RoundRobin, "Round robin"), (LootRule::NeedGreed, "Need/greed")] {
+                        let button = egui::Button::selectable(party.loot_rule == rule, label);
+                        if ui.add_enabled(is_leader, button).clicked() && party.loot_rule != rule {
+                            outgoing.push(NetworkMessage::PartySetLootRule { rule: format!("{:?}", rule) });
+                        }
+                    }
+                });
+                if ui.button("Leave Party").clicked() {
+                    outgoing.push(NetworkMessage::PartyLeave);
+                }
+            } else {
+                ui.label("You are not in a party.");
+            }
+
+            if !party.in_party() || is_leader {
+                ui.horizontal(|ui| {
+                    ui.label("Player ID:");
+                    ui.text_edit_singleline(&mut party.invite_id);
+                    if ui.button("Invite").clicked() {
+                        match party.invite_id.trim().parse::<u64>() {
+                            Ok(id) => outgoing.push(NetworkMessage::PartyInvite { player_id: id }),
+                            Err(_) => party.notice = Some("Enter a player ID to invite".to_string()),
+                        }
+                    }
+                });
+            }
+
+            // Need/greed rolls
+            let mut answered = Vec::new();
+            for roll in &party.rolls {
+                ui.separator();
+                ui.label(egui::RichText::new(format!("{} x{}", roll.item, roll.quantity)).strong());
+                ui.horizontal(|ui| {
+                    for choice in [LootChoice::Need, LootChoice::Greed, LootChoice::Pass] {
+                        if ui.button(format!("{:?}", choice)).clicked() {
+                            outgoing.push(NetworkMessage::LootRollChoice { roll_id: roll.roll_id, choice: format!("{:?}", choice) });
+                            answered.push(roll.roll_id);
+                        }
+                    }
+                });
+            }
+            party.rolls.retain(|r| !answered.contains(&r.roll_id));
+
+            if let Some(notice) = &party.notice {
+                ui.separator();
+                ui.label(egui::RichText::new(notice.as_str()).color(egui::Color32::from_rgb(230, 180, 30)));
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new("Press P to close").size(11.0).color(egui::Color32::GRAY));
+        });
+
+    if let Some(mut client) = client {
+        for message in &outgoing {
+            send_message(&mut client, message);
+        }
+    }
+}
+
 // ─── Equipment Panel ────────────────────────────────────
 
 fn equipment_panel_system(
@@ -649,7 +1021,7 @@ fn equipment_panel_system(
     mut satchel_q: Query<&mut Satchel>,
     mut player_q: Query<&mut PlayerCombat, With<PlayerCamera>>,
 ) {
-    if keys.just_pressed(KeyCode::Tab) {
+    if keys.just_pressed(KeyCode::KeyG) {
         gui_state.show_equipment = !gui_state.show_equipment;
     }
     if !gui_state.show_equipment { return; }
@@ -707,7 +1079,12 @@ fn equipment_panel_system(
             // Stats
             ui.separator();
             ui.label(format!("Weapon bonus: +{:.0} damage", equipment.weapon_damage_bonus()));
+            if equipment.weapon.is_some() {
+                ui.label(format!("Durability: {:.0}/{:.0}", equipment.weapon_durability, WEAPON_DURABILITY));
+            }
             ui.label(format!("Armor bonus: +{:.0} HP", equipment.armor_hp_bonus()));
+            let armor = equipment.defense().armor;
+            ui.label(format!("Armor: {:.0} (-{:.0}% physical damage)", armor, armor_mitigation(armor) * 100.0));
 
             // Available items
             if !equippable.is_empty() {
@@ -725,7 +1102,7 @@ fn equipment_panel_system(
             }
 
             ui.separator();
-            ui.label(egui::RichText::new("Press Tab to close").size(11.0).color(egui::Color32::GRAY));
+            ui.label(egui::RichText::new("Press G to close").size(11.0).color(egui::Color32::GRAY));
         });
 
     // Execute unequip
@@ -759,6 +1136,7 @@ fn equipment_panel_system(
             satchel.remove_item(&name, 1);
             // Put old item back in satchel
             let old = if is_w {
+                equipment.weapon_durability = WEAPON_DURABILITY;
                 equipment.weapon.replace(name.clone())
             } else {
                 equipment.armor.replace(name.clone())
@@ -783,10 +1161,6 @@ fn is_equippable(name: &str) -> bool {
     matches!(name, "Bronze Sword" | "Iron Sword" | "Linen Tunic")
 }
 
-fn is_weapon(name: &str) -> bool {
-    matches!(name, "Bronze Sword" | "Iron Sword")
-}
-
 fn equip_weight(name: &str) -> f32 {
     match name {
         "Bronze Sword" => 8.0,
@@ -801,32 +1175,27 @@ fn equip_weight(name: &str) -> f32 {
 fn gathering_prompt_system(
     mut contexts: EguiContexts,
     player_q: Query<&Transform, With<PlayerCamera>>,
-    node_q: Query<(&GatheringNode, &Transform, &Name), Without<PlayerCamera>>,
+    gathering: Res<GatheringRes>,
 ) {
     let player_pos = match player_q.single() {
         Ok(t) => t.translation,
         Err(_) => return,
     };
 
-    let mut nearest: Option<(&GatheringNode, &Name, f32)> = None;
-    for (node, t, name) in node_q.iter() {
-        if node.remaining == 0 { continue; }
-        let dist = player_pos.distance(t.translation);
-        if dist < 20.0 {
-            if nearest.is_none() || dist < nearest.unwrap().2 {
-                nearest = Some((node, name, dist));
-            }
-        }
-    }
+    let nearest = gathering_sites()
+        .iter()
+        .enumerate()
+        .filter(|(site, found)| gathering.0.remaining(*site) > 0 && found.in_reach(player_pos))
+        .min_by(|(_, a), (_, b)| a.position.distance(player_pos).total_cmp(&b.position.distance(player_pos)));
 
-    if let Some((node, name, _)) = nearest {
+    if let Some((site, found)) = nearest {
         let Ok(ctx) = contexts.ctx_mut() else { return; };
         egui::Area::new("gather_prompt".into())
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 110.0])
             .show(ctx, |ui| {
                 ui.label(egui::RichText::new(
                     format!("Press F to gather {} ({}/{})",
-                        name, node.remaining, node.max_remaining))
+                        found.name, gathering.0.remaining(site), found.capacity))
                     .size(16.0).color(egui::Color32::from_rgb(180, 220, 130)).strong());
             });
     }
@@ -927,3 +1296,165 @@ fn graphics_settings_panel_system(
             ui.label(egui::RichText::new("Press F4 to close").size(11.0).color(egui::Color32::GRAY));
         });
 }
+
+// ─── PVP Panel ──────────────────────────────────────────
+
+fn pvp_panel_system(
+    mut contexts: EguiContexts,
+    mut gui_state: ResMut<GuiState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut pvp: ResMut<PvpState>,
+    selected: Option<Res<SelectedCharacter>>,
+    world_state: Option<Res<WorldState>>,
+    client: Option<ResMut<RenetClient>>,
+) {
+    if keys.just_pressed(KeyCode::KeyV) {
+        gui_state.show_pvp = !gui_state.show_pvp;
+    }
+    if !gui_state.show_pvp { return; }
+
+    let player_id = selected.map(|s| s.0.id).unwrap_or_default();
+    let online = client.as_ref().is_some_and(|c| c.is_connected());
+    let mut outgoing: Vec<NetworkMessage> = Vec::new();
+
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    egui::Window::new("Blood Feuds")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 200.0])
+        .resizable(false)
+        .collapsible(false)
+        .min_width(240.0)
+        .show(ctx, |ui| {
+            if !online {
+                ui.label(egui::RichText::new("Fighting other players needs a connection to the server").color(egui::Color32::GRAY));
+                return;
+            }
+
+            let rules = match world_state.as_ref().map_or(FloodStage::Innocence, |ws| ws.flood_stage) {
+                FloodStage::Innocence => "Only those flagged for battle may fight, outside Havilah and the Pillar.",
+                FloodStage::Violence => "The wilds are open to all. Havilah and the Pillar are still safe.",
+                FloodStage::Judgment => "Only the Eden Pillar still shelters the peaceful.",
+                FloodStage::TheFlood => "Nowhere is safe.",
+            };
+            ui.label(egui::RichText::new(rules).color(egui::Color32::LIGHT_GRAY));
+            ui.separator();
+
+            if pvp.murderer {
+                ui.label(egui::RichText::new("MURDERER").color(egui::Color32::RED).strong());
+            }
+            let label = if pvp.flagged { "Lower your flag" } else { "Flag for battle" };
+            if ui.button(label).clicked() {
+                outgoing.push(NetworkMessage::SetPvpFlag { flagged: !pvp.flagged });
+            }
+
+            // Contracts on the player's murderers
+            for murderer in pvp.grievances.clone() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Murdered by {}", murderer));
+                    if ui.small_button("Set contract").clicked() {
+                        outgoing.push(NetworkMessage::PostBounty { target_id: murderer });
+                        pvp.grievances.retain(|id| *id != murderer);
+                    }
+                });
+            }
+
+            if !pvp.bounties.is_empty() {
+                ui.separator();
+                ui.label(egui::RichText::new("Bounties").strong());
+                let mut bounties: Vec<(u64, f32)> = pvp.bounties.iter().map(|(id, reward)| (*id, *reward)).collect();
+                bounties.sort_by(|a, b| b.1.total_cmp(&a.1));
+                for (id, reward) in bounties {
+                    let you = if id == player_id { " — you" } else { "" };
+                    ui.label(format!("{}{}: {:.0} XP", id, you, reward));
+                }
+            }
+
+            if let Some(notice) = &pvp.notice {
+                ui.separator();
+                ui.label(egui::RichText::new(notice.as_str()).color(egui::Color32::from_rgb(230, 180, 30)));
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new("Press V to close").size(11.0).color(egui::Color32::GRAY));
+        });
+
+    if let Some(mut client) = client {
+        for message in &outgoing {
+            send_message(&mut client, message);
+        }
+    }
+}
+
+// ─── Combat Log ─────────────────────────────────────────
+
+/// Lines of the combat log shown at once.
+const COMBAT_LOG_LINES: usize = 30;
+
+fn combat_log_panel_system(
+    mut contexts: EguiContexts,
+    mut gui_state: ResMut<GuiState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut combat_log: ResMut<CombatLogRes>,
+    mob_q: Query<&Mob>,
+    selected: Option<Res<SelectedCharacter>>,
+) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        gui_state.show_combat_log = !gui_state.show_combat_log;
+    }
+    if !gui_state.show_combat_log { return; }
+
+    let player_id = selected.map(|s| s.0.id);
+    let mob_names: HashMap<u64, String> = mob_q.iter().map(|m| (m.id, m.name.clone())).collect();
+    let name = |id: u64| {
+        if id == LOCAL_PLAYER || Some(id) == player_id {
+            "You".to_string()
+        } else if let Some(name) = mob_names.get(&id) {
+            name.clone()
+        } else {
+            format!("Player {}", id)
+        }
+    };
+
+    let Ok(ctx) = contexts.ctx_mut() else { return; };
+
+    egui::Window::new("Combat Log")
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -160.0])
+        .resizable(false)
+        .collapsible(false)
+        .min_width(360.0)
+        .show(ctx, |ui| {
+            // Damage meters over everything still in the log
+            let mut meters: Vec<(u64, f32, f32)> = replay(combat_log.0.iter())
+                .into_iter()
+                .map(|(id, meter)| (id, meter.dps(), meter.hps()))
+                .collect();
+            meters.sort_by(|a, b| b.1.total_cmp(&a.1));
+            for (id, dps, hps) in meters.iter().take(5) {
+                ui.label(format!("{}: {:.1} DPS, {:.1} HPS", name(*id), dps, hps));
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(240.0).stick_to_bottom(true).show(ui, |ui| {
+                let skip = combat_log.0.len().saturating_sub(COMBAT_LOG_LINES);
+                for event in combat_log.0.iter().skip(skip) {
+                    let color = if event.healing > 0.0 {
+                        egui::Color32::from_rgb(100, 220, 100)
+                    } else if event.critical {
+                        egui::Color32::from_rgb(255, 140, 40)
+                    } else {
+                        egui::Color32::LIGHT_GRAY
+                    };
+                    let line = format!("[{:>6.1}] {}", event.timestamp, event.describe(name));
+                    ui.label(egui::RichText::new(line).size(12.0).color(color));
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Clear").clicked() {
+                    combat_log.0.clear();
+                }
+                ui.label(egui::RichText::new("Press L to close").size(11.0).color(egui::Color32::GRAY));
+            });
+        });
+}
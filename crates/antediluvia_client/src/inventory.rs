@@ -57,7 +57,6 @@ impl Satchel {
     }
 
     /// Remove an item from the satchel.
-    #[allow(dead_code)]
     pub fn remove_item(&mut self, name: &str, quantity: u32) -> bool {
         if let Some(pos) = self.items.iter().position(|i| i.name == name && i.quantity >= quantity) {
             self.items[pos].quantity -= quantity;
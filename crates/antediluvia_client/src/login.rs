@@ -1,7 +1,8 @@
 //! Simple login UI placeholder.
-//! Press Enter to enter the world. Hooks into AppState::Login -> InWorld.
+//! Press Enter to log in. Hooks into AppState::Login -> CharacterSelect.
 
 use bevy::prelude::*;
+use bevy::tasks::{futures::check_ready, IoTaskPool, Task};
 use super::AppState;
 use serde::{Deserialize, Serialize};
 
@@ -11,7 +12,7 @@ pub struct LoginRoot;
 #[derive(Resource, Default)]
 pub struct LoginStatus {
     message: String,
-    connecting: bool,
+    request: Option<Task<Result<LoginResponse, String>>>, // The login in flight, off the main thread
 }
 
 #[derive(Serialize)]
@@ -23,13 +24,45 @@ struct LoginRequest {
 #[derive(Deserialize)]
 struct LoginResponse {
     token: String,
-    player_id: u64,
+    account_id: u64,
+}
+
+/// The logged-in account. Offline sessions have no token.
+#[derive(Resource, Clone, Default)]
+pub struct Session {
+    pub token: String,
+    pub account_id: u64,
+    pub online: bool,
+}
+
+/// URL of an endpoint on the auth server.
+pub fn auth_url(path: &str) -> String {
+    let addr = std::env::var("CLIENT_SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = std::env::var("CLIENT_AUTH_PORT").unwrap_or_else(|_| "8081".to_string());
+    format!("http://{}:{}{}", addr, port, path)
+}
+
+/// The account to log in as.
+fn username() -> String {
+    std::env::var("ANTEDILUVIA_USER").unwrap_or_else(|_| "player".to_string())
+}
+
+fn request_login(username: &str) -> Result<LoginResponse, String> {
+    reqwest::blocking::Client::new()
+        .post(auth_url("/auth/login"))
+        .json(&LoginRequest {
+            username: username.to_string(),
+            password: String::new(),
+        })
+        .send()
+        .and_then(|r| r.json::<LoginResponse>())
+        .map_err(|e| e.to_string())
 }
 
 pub fn spawn_login_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(LoginStatus {
         message: "Press Enter to Login".to_string(),
-        connecting: false,
+        request: None,
     });
 
     let font = asset_server.load("FiraSans-Bold.ttf");
@@ -51,7 +84,7 @@ pub fn spawn_login_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text::new("Antediluvia Login\n\nPress Enter to enter the world.\n(This is a placeholder UI.)"),
+                Text::new("Antediluvia Login\n\nPress Enter to log in.\n(This is a placeholder UI.)"),
                 TextFont {
                     font,
                     font_size: 32.0,
@@ -80,34 +113,36 @@ pub fn login_input_system(
     mut text_q: Query<&mut Text, With<LoginStatusText>>,
     mut commands: Commands,
 ) {
-    if status.connecting {
-        return;
-    }
-
-    if keys.just_pressed(KeyCode::Enter) {
-        status.connecting = true;
-        status.message = "Connecting...".to_string();
-        if let Ok(mut txt) = text_q.single_mut() {
-            **txt = status.message.clone();
-        }
-
-        // Setup network connection - try online, fallback to offline
-        if let Ok((client, transport)) = super::setup_network_connection(12345, "") {
-            commands.insert_resource(client);
-            commands.insert_resource(transport);
-            info!("Connected to server");
-            next_state.set(AppState::InWorld);
-        } else if let Ok((client, transport)) = super::setup_offline_connection(999999) {
-            commands.insert_resource(client);
-            commands.insert_resource(transport);
-            info!("Running in offline mode");
-            next_state.set(AppState::InWorld);
-        } else {
-            status.connecting = false;
-            status.message = "Connection failed. Try again.".to_string();
+    let Some(task) = status.request.as_mut() else {
+        if keys.just_pressed(KeyCode::Enter) {
+            let username = username();
+            status.message = "Connecting...".to_string();
             if let Ok(mut txt) = text_q.single_mut() {
                 **txt = status.message.clone();
             }
+            status.request = Some(IoTaskPool::get().spawn(async move { request_login(&username) }));
         }
+        return;
+    };
+
+    // Log in once the server answers - fall back to an offline session
+    if let Some(result) = check_ready(task) {
+        status.request = None;
+        let session = match result {
+            Ok(response) => {
+                info!("Logged in as {}", username());
+                Session {
+                    token: response.token,
+                    account_id: response.account_id,
+                    online: true,
+                }
+            }
+            Err(e) => {
+                info!("Login failed ({}). Continuing offline.", e);
+                Session::default()
+            }
+        };
+        commands.insert_resource(session);
+        next_state.set(AppState::CharacterSelect);
     }
 }
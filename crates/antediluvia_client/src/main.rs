@@ -3,6 +3,7 @@ mod player;
 mod npc;
 mod inventory;
 mod login;
+mod character_select;
 mod gui;
 mod combat;
 mod physics;
@@ -12,30 +13,55 @@ mod terrain_mesh;
 mod water;
 mod foliage;
 mod particles;
+mod unlocks;
+mod network;
+mod party;
+mod targeting;
+mod projectile;
+mod death;
+mod pvp;
+mod spawner;
+mod mutation;
 pub mod graphics_settings;
 pub mod rendering;
 
 use bevy::prelude::*;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use antediluvia_core::world::PangeaGenerator;
-use antediluvia_core::world::FloodStage;
+use antediluvia_core::world::{FloodStage, FIRST_HOUR, HOURS_PER_SECOND};
 use antediluvia_core::crafting::CraftingSystem;
-use antediluvia_core::entity::Job;
+use antediluvia_core::combat::Defense;
+use antediluvia_core::entity::{Job, Reputation};
+use antediluvia_core::character::starting_satchel;
+use antediluvia_core::network::SATCHEL_MAX_WEIGHT;
 use map::{map_input_system, map_render_system};
-use player::{player_movement_system, player_look_system, cursor_grab_system, camera_follow_system, PlayerCamera, FollowCamera};
+use player::{player_movement_system, player_look_system, cursor_grab_system, camera_follow_system, lineage_appearance_system, PlayerCamera, FollowCamera};
 use npc::{spawn_noah, spawn_elder, spawn_merchant, npc_interaction_system, NPCInteraction};
 use inventory::{inventory_input_system, Satchel, InventoryItem};
 use login::{spawn_login_ui, despawn_login_ui, login_input_system, LoginStatus};
+use character_select::{
+    spawn_character_select, despawn_character_select, character_select_ui_system, CharacterSelectState,
+    SelectedCharacter,
+};
 use gui::GuiPlugin;
 use combat::{
-    PlayerCombat, ChainNotification, update_cooldowns, combat_input_system, spawn_mobs,
-    update_mob_health_display, update_damage_numbers, player_respawn_system,
+    PlayerCombat, ChainNotification, CombatLogRes, update_cooldowns, combat_input_system,
+    update_mob_health_display, update_damage_numbers, player_respawn_system, mob_hit_system, status_effect_system, MobHit,
 };
-use mob_ai::{mob_ai_system, mob_attack_system, mob_death_system, death_effect_system, MobBrain};
-use gathering::{spawn_gathering_nodes, gathering_system, node_respawn_system};
+use network::{equipment_sync_system, network_receive_system};
+use party::PartyState;
+use pvp::PvpState;
+use targeting::{targeting_system, RemoteTargets};
+use projectile::{projectile_system, projectile_visual_system};
+use death::{player_death_system, corpse_system, soul_binding_system, CorpseNews};
+use spawner::{mob_spawner_system, spawn_mob_system, MobSpawnerRes, SpawnMob};
+use mutation::{mob_mutation_system, mutate_mob_system, offering_system, MutateMob, RegionsRes};
+use mob_ai::{mob_ai_system, mob_attack_system, mob_death_system, mob_follow_system, death_effect_system, pack_tactics_system, MobBrain, MobSnapshots, PackTactics};
+use gathering::{spawn_gathering_nodes, gathering_system, node_respawn_system, GatheringRes, SiteNews};
 use physics::{physics_system, collision_system, collision_response_system, CollisionEvent};
 use graphics_settings::{GraphicsSettingsPlugin, GraphicsSettings, QualityTier};
 use rendering::RenderingPlugin;
+use unlocks::{job_unlock_system, defend_telemetry_system, lyre_system, npc_peril_system, tend_stranger_system, JobTelemetryEvent, JobUnlocks, Lyre};
 
 use bevy_renet::RenetClientPlugin;
 use bevy_renet::netcode::{NetcodeClientPlugin, NetcodeClientTransport, ClientAuthentication};
@@ -44,7 +70,6 @@ use bevy_renet::renet::ConnectionConfig;
 use bevy::light::GlobalAmbientLight;
 use std::net::UdpSocket;
 use std::time::SystemTime;
-use serde::{Deserialize, Serialize};
 
 // ─── Resources ──────────────────────────────────────────
 
@@ -73,14 +98,31 @@ impl Default for WorldState {
     }
 }
 
-#[derive(Resource, Default)]
+/// Durability of a freshly equipped weapon.
+pub const WEAPON_DURABILITY: f32 = 100.0;
+
+#[derive(Resource)]
 pub struct Equipment {
     pub weapon: Option<String>,
     pub armor: Option<String>,
+    pub weapon_durability: f32, // A broken weapon gives no bonus
+}
+
+impl Default for Equipment {
+    fn default() -> Self {
+        Self {
+            weapon: None,
+            armor: None,
+            weapon_durability: WEAPON_DURABILITY,
+        }
+    }
 }
 
 impl Equipment {
     pub fn weapon_damage_bonus(&self) -> f32 {
+        if self.weapon_durability <= 0.0 {
+            return 0.0;
+        }
         match self.weapon.as_deref() {
             Some("Bronze Sword") => 10.0,
             Some("Iron Sword") => 20.0,
@@ -88,6 +130,18 @@ impl Equipment {
         }
     }
 
+    /// Wear the weapon down, e.g. by striking with a hot forge.
+    pub fn wear_weapon(&mut self, amount: f32) {
+        if self.weapon.is_some() {
+            self.weapon_durability = (self.weapon_durability - amount).max(0.0);
+        }
+    }
+
+    /// The wearer's defense against blows.
+    pub fn defense(&self) -> Defense {
+        Defense::from_equipment(self.armor.as_deref())
+    }
+
     pub fn armor_hp_bonus(&self) -> f32 {
         match self.armor.as_deref() {
             Some("Linen Tunic") => 25.0,
@@ -105,8 +159,8 @@ pub struct DayNightCycle {
 impl Default for DayNightCycle {
     fn default() -> Self {
         Self {
-            time_of_day: 8.0,
-            day_speed: 0.1,
+            time_of_day: FIRST_HOUR,
+            day_speed: HOURS_PER_SECOND,
         }
     }
 }
@@ -121,36 +175,43 @@ pub struct CloudEntity {
 
 // ─── App State ──────────────────────────────────────────
 
-#[derive(Serialize)]
-struct LoginRequest {
-    username: String,
-    password: String,
-}
-
-#[derive(Deserialize)]
-struct LoginResponse {
-    token: String,
-    player_id: u64,
-}
-
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub enum AppState {
     #[default]
     InWorld,
     Login,
+    CharacterSelect,
 }
 
 fn main() {
     App::new()
         .init_state::<AppState>()
         .init_resource::<LoginStatus>()
+        .init_resource::<CharacterSelectState>()
         .init_resource::<NPCInteraction>()
         .init_resource::<WorldState>()
         .init_resource::<Equipment>()
         .init_resource::<DayNightCycle>()
         .init_resource::<ChainNotification>()
+        .init_resource::<CombatLogRes>()
+        .init_resource::<JobUnlocks>()
+        .init_resource::<Lyre>()
+        .init_resource::<GatheringRes>()
+        .init_resource::<PartyState>()
+        .init_resource::<PvpState>()
+        .init_resource::<RemoteTargets>()
+        .init_resource::<MobSpawnerRes>()
+        .init_resource::<PackTactics>()
+        .init_resource::<RegionsRes>()
         .insert_resource(CraftingRes(CraftingSystem::new()))
         .add_message::<CollisionEvent>()
+        .add_message::<JobTelemetryEvent>()
+        .add_message::<MobHit>()
+        .add_message::<SpawnMob>()
+        .add_message::<MutateMob>()
+        .add_message::<CorpseNews>()
+        .add_message::<SiteNews>()
+        .add_message::<MobSnapshots>()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Antediluvia: The Tenth Generation".to_string(),
@@ -170,10 +231,14 @@ fn main() {
         .add_systems(OnEnter(AppState::Login), spawn_login_ui)
         .add_systems(OnExit(AppState::Login), despawn_login_ui)
         .add_systems(Update, login_input_system.run_if(in_state(AppState::Login)))
+        // Character select
+        .add_systems(OnEnter(AppState::CharacterSelect), spawn_character_select)
+        .add_systems(OnExit(AppState::CharacterSelect), despawn_character_select)
+        .add_systems(Update, character_select_ui_system.run_if(in_state(AppState::CharacterSelect)))
         // World enter
         .add_systems(
             OnEnter(AppState::InWorld),
-            (setup, connect_to_server, spawn_noah, spawn_elder, spawn_merchant, spawn_player_satchel, spawn_mobs, spawn_gathering_nodes),
+            (setup, connect_to_server, spawn_noah, spawn_elder, spawn_merchant, spawn_player_satchel, spawn_gathering_nodes),
         )
         // Update systems - split into groups (Bevy tuple limit = 12)
         .add_systems(
@@ -225,9 +290,44 @@ fn main() {
                 particles::particle_update_system,
                 particles::dust_mote_system,
                 cloud_system,
+                defend_telemetry_system,
+                job_unlock_system,
             )
             .run_if(in_state(AppState::InWorld)),
         )
+        .add_systems(
+            Update,
+            (
+                lineage_appearance_system,
+                network_receive_system,
+                mob_hit_system,
+                status_effect_system,
+                targeting_system,
+                projectile_system,
+                projectile_visual_system,
+                player_death_system,
+                corpse_system,
+                soul_binding_system,
+                mob_spawner_system,
+                spawn_mob_system,
+            )
+                .run_if(in_state(AppState::InWorld)),
+        )
+        .add_systems(Update, pack_tactics_system.before(mob_ai_system).run_if(in_state(AppState::InWorld)))
+        .add_systems(Update, mob_follow_system.after(network_receive_system).run_if(in_state(AppState::InWorld)))
+        .add_systems(
+            Update,
+            (mob_mutation_system, offering_system, mutate_mob_system)
+                .chain()
+                .run_if(in_state(AppState::InWorld)),
+        )
+        .add_systems(
+            Update,
+            (npc_peril_system, tend_stranger_system, lyre_system)
+                .before(job_unlock_system)
+                .run_if(in_state(AppState::InWorld)),
+        )
+        .add_systems(Update, equipment_sync_system.run_if(in_state(AppState::InWorld)))
         .run();
 }
 
@@ -352,7 +452,11 @@ fn cloud_system(
 
 // ─── Networking ─────────────────────────────────────────
 
-fn connect_to_server(mut commands: Commands) {
+fn connect_to_server(mut commands: Commands, existing: Option<Res<RenetClient>>) {
+    // Already connected from character select
+    if existing.is_some() {
+        return;
+    }
     println!("Initiating Dev Connection...");
     let player_id = 12345u64;
     let token = String::new();
@@ -497,27 +601,38 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
     gfx_settings: Res<GraphicsSettings>,
+    selected: Option<Res<SelectedCharacter>>,
 ) {
-    let player_combat = PlayerCombat::new(Job::Hunter);
+    let mut player_combat = PlayerCombat::new(Job::Hunter);
+    let character = selected.map(|s| s.0.clone());
+    if let Some(character) = &character {
+        player_combat.lineage = character.lineage;
+        player_combat.corruption = character.lineage.starting_corruption();
+        player_combat.reputation = Reputation::for_lineage(character.lineage);
+    }
+    let appearance = player_combat.lineage.appearance();
+    let chosen = character.as_ref().map(|c| c.appearance).unwrap_or_default();
 
     // ── Player body (visible third-person character) ──
 
+    let [r, g, b] = appearance.robe_color;
     let tunic_mat = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.3, 0.25, 0.55),
+        base_color: Color::srgb(r, g, b),
         ..default()
     });
+    let [r, g, b] = if character.is_some() { chosen.skin_color() } else { appearance.skin_tint };
     let skin_mat = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.72, 0.56, 0.42),
+        base_color: Color::srgb(r, g, b),
         ..default()
     });
 
     commands.spawn((
         Mesh3d(meshes.add(Capsule3d::new(1.2, 2.5))),
         MeshMaterial3d(tunic_mat),
-        Transform::from_xyz(0.0, 5.0, 100.0),
+        Transform::from_xyz(0.0, 5.0, 100.0).with_scale(Vec3::splat(chosen.height)),
         PlayerCamera,
         player_combat,
-        Name::new("Player"),
+        Name::new(character.map(|c| c.name).unwrap_or_else(|| "Player".to_string())),
     )).with_children(|parent| {
         // Head
         parent.spawn((
@@ -865,13 +980,13 @@ fn setup(
     println!("  WASD: Move | Shift: Sprint | Mouse: Look");
     println!("  1-4: Abilities | 3: Heal");
     println!("  E: Talk to NPC | I: Inventory | C: Crafting | M: Map");
-    println!("  Tab: Equipment | F: Gather | F3: Debug panel");
+    println!("  Tab: Cycle targets | Click: Target | G: Equipment | F: Gather | F3: Debug panel");
     println!("Click to capture mouse. ESC to release.");
 
     // Controls overlay
     commands.spawn((
         Text::new(
-            "WASD: Move | Shift: Sprint | 1-4: Abilities\nE: Talk | I: Inventory | C: Craft | M: Map | Tab: Gear\nF: Gather | F3: Debug | Click mouse to lock | ESC unlock",
+            "WASD: Move | Shift: Sprint | 1-4: Abilities\nE: Talk | I: Inventory | C: Craft | M: Map | G: Gear\nTab: Target | F: Gather | F3: Debug | Click mouse to lock | ESC unlock",
         ),
         TextFont {
             font: asset_server.load("FiraSans-Bold.ttf"),
@@ -889,38 +1004,11 @@ fn setup(
 }
 
 fn spawn_player_satchel(mut commands: Commands) {
-    let mut satchel = Satchel::new(100.0);
-
-    satchel.add_item(InventoryItem {
-        name: "Gopher Wood".to_string(),
-        quantity: 5,
-        weight: 10.0,
-    });
-
-    satchel.add_item(InventoryItem {
-        name: "Bread".to_string(),
-        quantity: 10,
-        weight: 0.5,
-    });
-
-    satchel.add_item(InventoryItem {
-        name: "Waterskin".to_string(),
-        quantity: 1,
-        weight: 2.0,
-    });
-
-    // Starting crafting materials
-    satchel.add_item(InventoryItem {
-        name: "Bronze Ingot".to_string(),
-        quantity: 3,
-        weight: 4.0,
-    });
-
-    satchel.add_item(InventoryItem {
-        name: "Leather Grip".to_string(),
-        quantity: 2,
-        weight: 1.0,
-    });
+    // Online, the server's satchel replaces this one on joining
+    let mut satchel = Satchel::new(SATCHEL_MAX_WEIGHT);
+    for item in starting_satchel() {
+        satchel.add_item(InventoryItem { name: item.name, quantity: item.quantity, weight: item.weight });
+    }
 
     commands.spawn(satchel);
     println!("Player satchel initialized with starting items + crafting materials.");
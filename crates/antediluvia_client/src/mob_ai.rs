@@ -1,6 +1,26 @@
 use bevy::prelude::*;
-use crate::combat::{Mob, PlayerCombat};
+use bevy_renet::RenetClient;
+use std::collections::HashMap;
+use crate::combat::{CombatLogRes, Mob, PlayerCombat};
 use crate::player::PlayerCamera;
+use crate::spawner::MobSpawnerRes;
+use crate::Equipment;
+use antediluvia_core::behaviour::{behaviour_library, Action, BehaviourTree, Blackboard, Decision, Fact};
+use antediluvia_core::combat::{Defense, Resistances};
+use antediluvia_core::combat_log::CombatEvent;
+use antediluvia_core::mob::{MobBehaviour, PackMember, PackOrder, PackOrders, PackTacticsAI, MOB_ATTACK_RANGE};
+use antediluvia_core::network::MobSnapshot;
+use antediluvia_core::threat::AGGRO_THREAT;
+
+/// Threat-table entry for the local player, the only one the client's mobs see.
+pub const LOCAL_PLAYER: u64 = 0;
+
+/// Share of the way to the server's position a mob closes each second, online.
+const FOLLOW_RATE: f32 = 10.0;
+
+/// The server's word on where its mobs stand and whom they are after.
+#[derive(Message, Clone)]
+pub struct MobSnapshots(pub Vec<MobSnapshot>);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MobState {
@@ -24,24 +44,78 @@ pub struct MobBrain {
     pub death_timer: f32,
     pub home_position: Vec3,
     pub leash_range: f32,
+    pub tree: &'static BehaviourTree,
+    pub decision: Decision, // The last frame's, for the debugger
+    pub server_position: Option<Vec3>, // Where the server last had it, online
 }
 
 impl MobBrain {
-    pub fn new(aggro_range: f32, move_speed: f32, home: Vec3) -> Self {
+    /// A brain that hunts as its mob type's definition says.
+    pub fn new(behaviour: &MobBehaviour, home: Vec3) -> Self {
         Self {
             state: MobState::Idle,
-            aggro_range,
-            attack_range: 15.0,
-            attack_cooldown: 2.0,
+            aggro_range: behaviour.aggro_range,
+            attack_range: MOB_ATTACK_RANGE,
+            attack_cooldown: behaviour.attack_cooldown,
             attack_timer: 0.0,
-            move_speed,
+            move_speed: behaviour.move_speed,
             patrol_target: None,
             patrol_timer: 0.0,
             death_timer: 3.0,
             home_position: home,
-            leash_range: aggro_range * 3.0,
+            leash_range: behaviour.aggro_range * 3.0,
+            tree: behaviour_library().get(&behaviour.tree).expect("mob trees are checked when mobs load"),
+            decision: Decision::default(),
+            server_position: None,
         }
     }
+
+    /// Idle about home, now and then strolling somewhere near it.
+    fn wander(&mut self, transform: &mut Transform, seed: f32, dt: f32) {
+        let mob_pos = transform.translation;
+        if !matches!(self.state, MobState::Idle | MobState::Patrol) {
+            self.state = MobState::Idle;
+            self.patrol_timer = 1.0;
+        }
+
+        if self.state == MobState::Idle {
+            self.patrol_timer -= dt;
+            if self.patrol_timer <= 0.0 {
+                let offset = Vec3::new(
+                    (rand_simple(mob_pos.x + seed) - 0.5) * 60.0,
+                    0.0,
+                    (rand_simple(mob_pos.z + seed) - 0.5) * 60.0,
+                );
+                self.patrol_target = Some(self.home_position + offset);
+                self.patrol_timer = 3.0 + rand_simple(mob_pos.x) * 4.0;
+                self.state = MobState::Patrol;
+            }
+        } else if let Some(target) = self.patrol_target {
+            walk(transform, target, self.move_speed * 0.5, dt);
+            if mob_pos.distance(target) < 5.0 {
+                self.patrol_target = None;
+                self.state = MobState::Idle;
+                self.patrol_timer = 2.0 + rand_simple(mob_pos.z) * 3.0;
+            }
+        } else {
+            self.state = MobState::Idle;
+        }
+    }
+}
+
+/// Step toward `destination` at `speed`, keeping to the ground.
+fn walk(transform: &mut Transform, destination: Vec3, speed: f32, dt: f32) {
+    let mob_pos = transform.translation;
+    let step = (speed * dt).min(mob_pos.distance(destination));
+    transform.translation += (destination - mob_pos).normalize_or_zero() * step;
+    transform.translation.y = 5.0;
+}
+
+/// The packs among the mobs this client simulates, and this frame's orders.
+#[derive(Resource, Default)]
+pub struct PackTactics {
+    pub ai: PackTacticsAI,
+    pub orders: PackOrders,
 }
 
 #[derive(Component)]
@@ -50,18 +124,62 @@ pub struct DeathEffect {
     pub original_scale: Vec3,
 }
 
+/// Lead the packs: pick their prey and tell each member where to be.
+pub fn pack_tactics_system(
+    mob_q: Query<(&Mob, &MobBrain, &Transform)>,
+    player_q: Query<&Transform, With<PlayerCamera>>,
+    mut packs: ResMut<PackTactics>,
+    client: Option<Res<RenetClient>>,
+    time: Res<Time>,
+) {
+    // Online the server leads the packs
+    if client.is_some_and(|c| c.is_connected()) {
+        return;
+    }
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let members: Vec<PackMember> = mob_q
+        .iter()
+        .map(|(mob, brain, transform)| PackMember {
+            id: mob.id,
+            mob_type: mob.mob_type,
+            position: transform.translation,
+            home: brain.home_position,
+            health: mob.health,
+            max_health: mob.max_health,
+            target: mob.threat.contains(LOCAL_PLAYER).then_some(LOCAL_PLAYER),
+        })
+        .collect();
+    let players = [(LOCAL_PLAYER, player_transform.translation)];
+    packs.orders = packs.ai.update(&members, &players, time.delta_secs());
+
+    for leader in &packs.orders.howls {
+        if let Some((mob, _, _)) = mob_q.iter().find(|(m, _, _)| m.id == *leader) {
+            println!("The {} howls, calling its kin to the hunt!", mob.name);
+        }
+    }
+}
+
 pub fn mob_ai_system(
     mut mob_q: Query<(&mut MobBrain, &mut Mob, &mut Transform, Entity), Without<PlayerCamera>>,
     player_q: Query<&Transform, With<PlayerCamera>>,
+    packs: Res<PackTactics>,
+    client: Option<Res<RenetClient>>,
     time: Res<Time>,
 ) {
+    // Online the mobs follow the server's snapshots instead
+    if client.is_some_and(|c| c.is_connected()) {
+        return;
+    }
     let Ok(player_transform) = player_q.single() else {
         return;
     };
     let player_pos = player_transform.translation;
+    let players = [(LOCAL_PLAYER, player_pos)];
     let dt = time.delta_secs();
 
-    for (mut brain, mob, mut transform, _entity) in mob_q.iter_mut() {
+    for (mut brain, mut mob, mut transform, _entity) in mob_q.iter_mut() {
         if !mob.is_alive() {
             brain.state = MobState::Dead;
             continue;
@@ -70,94 +188,119 @@ pub fn mob_ai_system(
         let mob_pos = transform.translation;
         let distance_to_player = mob_pos.distance(player_pos);
         let distance_to_home = mob_pos.distance(brain.home_position);
+        let order = packs.orders.get(mob.id);
+        let retreating = matches!(order, Some(PackOrder::Retreat { .. }));
 
-        match brain.state {
-            MobState::Idle => {
-                brain.patrol_timer -= dt;
-                if brain.patrol_timer <= 0.0 {
-                    let offset = Vec3::new(
-                        (rand_simple(mob_pos.x + time.elapsed_secs()) - 0.5) * 60.0,
-                        0.0,
-                        (rand_simple(mob_pos.z + time.elapsed_secs()) - 0.5) * 60.0,
-                    );
-                    brain.patrol_target = Some(brain.home_position + offset);
-                    brain.patrol_timer = 3.0 + rand_simple(mob_pos.x) * 4.0;
-                    brain.state = MobState::Patrol;
-                }
-
-                if distance_to_player < brain.aggro_range {
-                    brain.state = MobState::Aggro;
-                }
-            }
+        // Straying into aggro range angers the mob; so does hitting it from
+        // afar, or its pack going after you. A fleeing pack is deaf to it all.
+        mob.threat.update(dt);
+        let called = order.is_some() && !retreating && distance_to_home <= brain.leash_range;
+        if !retreating && (distance_to_player < brain.aggro_range || called) && !mob.threat.contains(LOCAL_PLAYER) {
+            mob.threat.add(LOCAL_PLAYER, AGGRO_THREAT);
+        }
+        let provoked = mob.threat.select_target(mob_pos, &players).is_some();
+        let in_reach = distance_to_player < brain.attack_range
+            || (brain.state == MobState::Attacking && distance_to_player <= brain.attack_range * 1.5);
 
-            MobState::Patrol => {
-                if let Some(target) = brain.patrol_target {
-                    let dir = (target - mob_pos).normalize_or_zero();
-                    transform.translation += dir * brain.move_speed * 0.5 * dt;
-                    transform.translation.y = 5.0;
-
-                    if mob_pos.distance(target) < 5.0 {
-                        brain.patrol_target = None;
-                        brain.state = MobState::Idle;
-                        brain.patrol_timer = 2.0 + rand_simple(mob_pos.z) * 3.0;
-                    }
-                } else {
-                    brain.state = MobState::Idle;
-                }
+        let board = Blackboard::new()
+            .with_flag(Fact::Provoked, provoked)
+            .with_flag(Fact::InReach, in_reach)
+            .with(Fact::TargetDistance, distance_to_player)
+            .with(Fact::HomeDistance, distance_to_home)
+            .with_flag(Fact::Leashed, distance_to_home > brain.leash_range)
+            .with(Fact::Health, mob.health / mob.max_health)
+            .with_flag(Fact::PackAttack, matches!(order, Some(PackOrder::Attack { .. })))
+            .with_flag(Fact::PackFlank, matches!(order, Some(PackOrder::Flank { .. })))
+            .with_flag(Fact::PackRetreat, retreating);
+        brain.decision = brain.tree.decide(&board);
 
-                if distance_to_player < brain.aggro_range {
-                    brain.state = MobState::Aggro;
-                }
+        let look_target = Vec3::new(player_pos.x, 5.0, player_pos.z);
+        match brain.decision.action {
+            Some(Action::Retreat | Action::ReturnHome) => {
+                let (home, speed) = match order {
+                    Some(PackOrder::Retreat { home }) => (home, brain.move_speed),
+                    _ => (brain.home_position, brain.move_speed * 0.5),
+                };
+                mob.threat.wipe();
+                brain.state = MobState::Patrol;
+                brain.patrol_target = Some(home);
+                walk(&mut transform, home, speed, dt);
             }
-
-            MobState::Aggro => {
-                if distance_to_home > brain.leash_range {
-                    brain.state = MobState::Patrol;
-                    brain.patrol_target = Some(brain.home_position);
-                    return;
-                }
-
-                if distance_to_player > brain.aggro_range * 1.5 {
-                    brain.state = MobState::Idle;
-                    brain.patrol_timer = 1.0;
-                    return;
+            Some(Action::Flank) => {
+                // Flankers circle to their place in the pack
+                if let Some(PackOrder::Flank { position, .. }) = order {
+                    walk(&mut transform, position, brain.move_speed, dt);
                 }
-
-                let dir = (player_pos - mob_pos).normalize_or_zero();
-                transform.translation += dir * brain.move_speed * dt;
-                transform.translation.y = 5.0;
-
-                let look_target = Vec3::new(player_pos.x, transform.translation.y, player_pos.z);
                 transform.look_at(look_target, Vec3::Y);
-
-                if distance_to_player < brain.attack_range {
-                    brain.state = MobState::Attacking;
-                }
+                brain.state = MobState::Aggro;
             }
-
-            MobState::Attacking => {
-                if distance_to_player > brain.attack_range * 1.5 {
-                    brain.state = MobState::Aggro;
-                    return;
-                }
-
+            Some(Action::Chase) => {
+                walk(&mut transform, player_pos, brain.move_speed, dt);
+                transform.look_at(look_target, Vec3::Y);
+                brain.state = MobState::Aggro;
+            }
+            Some(Action::Strike) => {
+                brain.state = MobState::Attacking;
                 brain.attack_timer -= dt;
-
-                let dir = (player_pos - mob_pos).normalize_or_zero();
-                transform.translation += dir * brain.move_speed * 0.3 * dt;
-                transform.translation.y = 5.0;
+                walk(&mut transform, player_pos, brain.move_speed * 0.3, dt);
             }
+            Some(Action::Wander) => brain.wander(&mut transform, time.elapsed_secs(), dt),
+            _ => brain.state = MobState::Idle,
+        }
+    }
+}
+
+/// Online, ease each mob toward where the server last had it and take up its
+/// hunt, in place of deciding for itself.
+pub fn mob_follow_system(
+    mut snapshots: MessageReader<MobSnapshots>,
+    mut mob_q: Query<(&mut MobBrain, &Mob, &mut Transform), Without<PlayerCamera>>,
+    client: Option<Res<RenetClient>>,
+    time: Res<Time>,
+) {
+    let latest: HashMap<u64, MobSnapshot> = snapshots
+        .read()
+        .flat_map(|MobSnapshots(mobs)| mobs.iter().map(|m| (m.mob_id, *m)))
+        .collect();
+    if !client.is_some_and(|c| c.is_connected()) {
+        return;
+    }
 
-            MobState::Dead => {}
+    let share = (FOLLOW_RATE * time.delta_secs()).min(1.0);
+    for (mut brain, mob, mut transform) in mob_q.iter_mut() {
+        if !mob.is_alive() {
+            brain.state = MobState::Dead;
+            continue;
+        }
+        if let Some(snapshot) = latest.get(&mob.id) {
+            brain.server_position = Some(snapshot.position);
+            brain.state = if snapshot.target_id.is_some() { MobState::Aggro } else { MobState::Patrol };
         }
+        let Some(position) = brain.server_position else { continue; };
+
+        // Kept to the ground, as the client's own mobs are
+        let destination = Vec3::new(position.x, 5.0, position.z);
+        let heading = Vec3::new(destination.x - transform.translation.x, 0.0, destination.z - transform.translation.z);
+        if heading.length() > 0.1 {
+            transform.look_to(heading, Vec3::Y);
+        }
+        transform.translation = transform.translation.lerp(destination, share);
     }
 }
 
+/// Let mobs in reach strike the player. Online the server lands their blows
+/// and sends them as combat results.
 pub fn mob_attack_system(
     mut mob_q: Query<(&mut MobBrain, &Mob, &Transform), Without<PlayerCamera>>,
     mut player_q: Query<(&mut PlayerCombat, &Transform), With<PlayerCamera>>,
+    equipment: Res<Equipment>,
+    mut combat_log: ResMut<CombatLogRes>,
+    client: Option<Res<RenetClient>>,
     time: Res<Time>,
 ) {
+    if client.is_some_and(|c| c.is_connected()) {
+        return;
+    }
     let Ok((mut player_combat, player_transform)) = player_q.single_mut() else {
         return;
     };
@@ -175,11 +318,20 @@ pub fn mob_attack_system(
         brain.attack_timer -= dt;
         if brain.attack_timer <= 0.0 {
             let distance = mob_transform.translation.distance(player_pos);
-            if distance < brain.attack_range * 1.5 {
-                player_combat.take_damage(mob.damage_per_hit);
+            if distance < brain.attack_range * 1.5 && !player_combat.is_dead {
+                // Armor, the Bulwark and hymns soften the blow; a lament weakens it
+                let defense = Defense::new(equipment.defense().armor + player_combat.effects.armor_bonus(), Resistances::NEUTRAL);
+                let blow = mob.damage_per_hit * mob.effects.damage_multiplier() * player_combat.effects.damage_taken_multiplier();
+                let damage = defense.mitigate(blow, mob.mob_type.attack_type());
+                player_combat.take_damage(damage);
+                combat_log.0.record(CombatEvent {
+                    resisted: blow - damage,
+                    killing_blow: player_combat.health <= 0.0,
+                    ..CombatEvent::blow(time.elapsed_secs(), mob.id, LOCAL_PLAYER, None, damage)
+                });
                 println!(
                     "{} attacks you for {:.0} damage! HP: {:.0}/{:.0}",
-                    mob.name, mob.damage_per_hit, player_combat.health, player_combat.max_health
+                    mob.name, damage, player_combat.health, player_combat.max_health
                 );
             }
             brain.attack_timer = brain.attack_cooldown;
@@ -194,7 +346,7 @@ pub fn mob_death_system(
     for (entity, mob, brain, mut transform) in mob_q.iter_mut() {
         if !mob.is_alive() && brain.state == MobState::Dead {
             commands.entity(entity).insert(DeathEffect {
-                timer: 30.0,
+                timer: 2.0,
                 original_scale: transform.scale,
             });
             transform.translation.y -= 0.5;
@@ -202,37 +354,29 @@ pub fn mob_death_system(
     }
 }
 
+/// Shrink a slain mob away, then take it out of the world. Offline its zone
+/// refills the place in time; online the server's spawner does.
 pub fn death_effect_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut DeathEffect, &mut Transform, &mut Mob, &mut MobBrain)>,
+    mut query: Query<(Entity, &mut DeathEffect, &mut Transform, &Mob)>,
+    mut spawner: ResMut<MobSpawnerRes>,
     time: Res<Time>,
 ) {
     let dt = time.delta_secs();
-    for (entity, mut effect, mut transform, mut mob, mut brain) in query.iter_mut() {
+    for (entity, mut effect, mut transform, mob) in query.iter_mut() {
         effect.timer -= dt;
 
-        if effect.timer > 28.0 {
-            // First 2 seconds: shrink animation
-            let progress = 1.0 - ((effect.timer - 28.0) / 2.0).clamp(0.0, 1.0);
+        if effect.timer > 0.0 {
+            let progress = 1.0 - (effect.timer / 2.0).clamp(0.0, 1.0);
             transform.scale = effect.original_scale * (1.0 - progress);
-        } else if effect.timer > 0.0 {
-            // Waiting to respawn - stay invisible
-            transform.scale = Vec3::ZERO;
         } else {
-            // Respawn at home position
-            mob.health = mob.max_health;
-            brain.state = MobState::Idle;
-            brain.patrol_timer = 3.0;
-            brain.attack_timer = 0.0;
-            transform.translation = brain.home_position;
-            transform.scale = effect.original_scale;
-            commands.entity(entity).remove::<DeathEffect>();
-            println!("A {} has respawned!", mob.name);
+            spawner.0.despawn(mob.id);
+            commands.entity(entity).despawn();
         }
     }
 }
 
-fn rand_simple(seed: f32) -> f32 {
+pub(crate) fn rand_simple(seed: f32) -> f32 {
     let x = (seed * 12.9898 + 78.233).sin() * 43758.5453;
     x - x.floor()
 }
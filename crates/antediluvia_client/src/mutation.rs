@@ -0,0 +1,173 @@
+//! Beasts turning in tainted land, and turning back when it is redeemed.
+//! Online the server decides who turns and says so; offline the regions'
+//! corruption is kept here, and offerings are judged here too.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use antediluvia_core::error::AntediluviaError;
+use antediluvia_core::mob::MobType;
+use antediluvia_core::mutation::{mutant_name, region_of, RegionalCorruption, OFFERING};
+use antediluvia_core::network::NetworkMessage;
+use antediluvia_core::world::CorruptionEvent;
+use crate::combat::{Mob, PlayerCombat};
+use crate::inventory::Satchel;
+use crate::mob_ai::MobBrain;
+use crate::network::send_message;
+use crate::physics::Collider;
+use crate::player::PlayerCamera;
+use crate::spawner::MobBody;
+use crate::WorldState;
+
+/// The regions' corruption, kept while offline.
+#[derive(Resource, Default)]
+pub struct RegionsRes(pub RegionalCorruption);
+
+/// A mob turning into another type, or back: decided offline, or reported by the server.
+#[derive(Message, Clone)]
+pub struct MutateMob {
+    pub mob_id: u64,
+    pub mob_type: MobType,
+    pub name: String,
+    pub health: Option<f32>, // Authoritative health after the change
+}
+
+/// Soak mobs in the corruption of the land they stand in while offline.
+pub fn mob_mutation_system(
+    time: Res<Time>,
+    client: Option<Res<RenetClient>>,
+    world_state: Res<WorldState>,
+    mut regions: ResMut<RegionsRes>,
+    mut mob_q: Query<(&mut Mob, &Transform)>,
+    mut mutations: MessageWriter<MutateMob>,
+) {
+    if client.is_some_and(|c| !c.is_disconnected()) {
+        return;
+    }
+    let dt = time.delta_secs();
+    regions.0.update(dt);
+    for (mut mob, transform) in &mut mob_q {
+        if !mob.is_alive() {
+            continue;
+        }
+        let mob = &mut *mob;
+        let corruption = regions.0.corruption_at(world_state.corruption, transform.translation);
+        if let Some(into) = mob.mutation.expose(mob.mob_type, corruption, dt) {
+            mob.mutation.turn(mob.mob_type, &mob.name);
+            mutations.write(MutateMob { mob_id: mob.id, mob_type: into, name: mutant_name(into, &mob.name), health: None });
+        }
+    }
+}
+
+/// Make a burnt offering with O before an idol, redeeming its region, or at
+/// an altar, redeeming the region nearest it. Online the server judges the offering and
+/// takes it; offline it is judged and taken here.
+pub fn offering_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    mob_q: Query<(&Mob, &MobBrain)>,
+    mut client: Option<ResMut<RenetClient>>,
+    mut world_state: ResMut<WorldState>,
+    mut regions: ResMut<RegionsRes>,
+    mut satchel_q: Query<&mut Satchel>,
+    mut mutations: MessageWriter<MutateMob>,
+) {
+    if !keys.just_pressed(KeyCode::KeyO) {
+        return;
+    }
+    let Ok((combat, transform)) = player_q.single() else {
+        return;
+    };
+    if combat.is_dead {
+        return;
+    }
+
+    let event = CorruptionEvent::Sacrifice;
+    if let Some(client) = client.as_deref_mut().filter(|c| c.is_connected()) {
+        send_message(client, &NetworkMessage::PlayerAction { action: format!("{:?}", event), target: None });
+        return;
+    }
+    let Ok(mut satchel) = satchel_q.single_mut() else {
+        return;
+    };
+    let offered = regions.0.accepts(event, transform.translation).and_then(|_| {
+        if satchel.remove_item(OFFERING, 1) {
+            Ok(())
+        } else {
+            Err(AntediluviaError::RedemptionError(format!("you carry no {} to offer", OFFERING)))
+        }
+    });
+    match offered.and_then(|_| regions.0.redeem(event, transform.translation)) {
+        Ok(zone) => {
+            world_state.corruption = (world_state.corruption + event.delta()).max(0.0);
+            println!("Your offering is accepted. {} is redeemed.", zone.name);
+            for (mob, brain) in &mob_q {
+                if !mob.is_alive() || region_of(brain.home_position).is_none_or(|z| z.name != zone.name) {
+                    continue;
+                }
+                if let Some((mob_type, name)) = mob.mutation.from.clone() {
+                    mutations.write(MutateMob { mob_id: mob.id, mob_type, name, health: None });
+                }
+            }
+        }
+        Err(e) => println!("{}", e),
+    }
+}
+
+/// Give each turning mob the stats, body and colour of what it has become.
+pub fn mutate_mob_system(
+    mut mutations: MessageReader<MutateMob>,
+    mut mob_q: Query<(
+        &mut Mob,
+        &mut MobBrain,
+        &mut Transform,
+        &mut Mesh3d,
+        &MeshMaterial3d<StandardMaterial>,
+        &mut Collider,
+        &Children,
+    )>,
+    mut head_q: Query<(&mut Mesh3d, &mut Transform), Without<Mob>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for event in mutations.read() {
+        let Some((mut mob, mut brain, mut transform, mut mesh, material, mut collider, children)) =
+            mob_q.iter_mut().find(|(m, ..)| m.id == event.mob_id)
+        else {
+            continue;
+        };
+
+        // Remember what it was as it turns, and forget once it is itself again
+        let mob = &mut *mob;
+        if mob.mutation.from.as_ref().is_some_and(|(from, _)| *from == event.mob_type) {
+            mob.mutation.redeem();
+        } else if !mob.mutation.is_mutated() {
+            mob.mutation.turn(mob.mob_type, &mob.name);
+        }
+        let before = mob.name.clone();
+        mob.reshape(event.mob_type, event.name.clone());
+        if let Some(health) = event.health {
+            mob.health = health;
+        }
+        println!("{} has become {}!", before, mob.name);
+
+        let behaviour = &event.mob_type.definition().behaviour;
+        brain.move_speed = event.mob_type.move_speed() * mob.variant.speed_multiplier();
+        brain.aggro_range = behaviour.aggro_range;
+        brain.leash_range = behaviour.aggro_range * 3.0;
+        brain.attack_cooldown = behaviour.attack_cooldown;
+
+        let body = MobBody::new(&mut meshes, event.mob_type, mob.variant.rank);
+        mesh.0 = body.mesh;
+        transform.scale = body.scale;
+        collider.radius = body.radius;
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = body.color;
+        }
+        for child in children.iter() {
+            if let Ok((mut head, mut head_transform)) = head_q.get_mut(child) {
+                head.0 = meshes.add(Sphere::new(body.head_size));
+                head_transform.translation = body.head_offset;
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Client side of the game protocol.
+//! Sends actions to the server and applies its authoritative results over
+//! the effects the client predicted.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use antediluvia_core::abilities::Loadout;
+use antediluvia_core::combat::CombatAction;
+use antediluvia_core::combat_log::CombatEvent;
+use antediluvia_core::death::BindPoint;
+use antediluvia_core::entity::Lineage;
+use antediluvia_core::mob::{Mob as CoreMob, MobType};
+use antediluvia_core::network::NetworkMessage;
+use antediluvia_core::projectile::Projectile;
+use antediluvia_core::party::LootRule;
+use antediluvia_core::status::StatusEffects;
+use antediluvia_core::variant::{Affix, MobRank, MobVariant};
+use crate::character_select::SelectedCharacter;
+use crate::Equipment;
+use crate::combat::{CombatLogRes, Mob, MobHit, PlayerCombat};
+use crate::death::CorpseNews;
+use crate::gathering::SiteNews;
+use crate::inventory::{InventoryItem, Satchel};
+use crate::mob_ai::MobSnapshots;
+use crate::mutation::MutateMob;
+use crate::party::{OpenRoll, PartyState};
+use crate::player::{PlayerCamera, CORRECTION_DISTANCE};
+use crate::projectile::Flight;
+use crate::spawner::SpawnMob;
+use crate::pvp::PvpState;
+use crate::targeting::RemoteTargets;
+
+/// Send a message to the server (channel 0).
+pub fn send_message(client: &mut RenetClient, message: &NetworkMessage) {
+    match bincode::serialize(message) {
+        Ok(payload) => client.send_message(0, payload),
+        Err(e) => println!("WARNING: Failed to encode {:?}: {}", message, e),
+    }
+}
+
+/// Apply messages from the server.
+pub fn network_receive_system(
+    mut commands: Commands,
+    client: Option<ResMut<RenetClient>>,
+    selected: Option<Res<SelectedCharacter>>,
+    mut mob_q: Query<(Entity, &mut Mob)>,
+    mut player_q: Query<&mut PlayerCombat>,
+    mut player_transform_q: Query<&mut Transform, With<PlayerCamera>>,
+    mut satchel_q: Query<&mut Satchel>,
+    mut party: ResMut<PartyState>,
+    mut pvp: ResMut<PvpState>,
+    mut remote_targets: ResMut<RemoteTargets>,
+    mut flight_q: Query<&mut Flight>,
+    mut hits: MessageWriter<MobHit>,
+    mut spawns: MessageWriter<SpawnMob>,
+    mut mutations: MessageWriter<MutateMob>,
+    mut combat_log: ResMut<CombatLogRes>,
+    time: Res<Time>,
+) {
+    let Some(mut client) = client else { return; };
+    let player_id = selected.map(|s| s.0.id);
+
+    while let Some(raw) = client.receive_message(0) {
+        let Ok(message) = bincode::deserialize::<NetworkMessage>(&raw) else { continue; };
+        match message {
+            NetworkMessage::CombatResult {
+                attacker_id,
+                target_id,
+                action_type,
+                damage,
+                healing,
+                target_health,
+                skill_chain,
+                critical,
+                resisted,
+            } => {
+                combat_log.0.record(CombatEvent {
+                    timestamp: time.elapsed_secs(),
+                    attacker: attacker_id,
+                    target: target_id,
+                    action: CombatAction::from_name(&action_type),
+                    damage,
+                    healing,
+                    chain: skill_chain,
+                    critical,
+                    resisted,
+                    killing_blow: damage > 0.0 && target_health <= 0.0,
+                });
+
+                // Struck by another player, or by a mob
+                if Some(target_id) == player_id && damage > 0.0 {
+                    if let Ok(mut combat) = player_q.single_mut() {
+                        combat.take_damage(damage);
+                    }
+                    continue;
+                }
+                let Some((entity, _)) = mob_q.iter().find(|(_, m)| m.id == target_id) else { continue; };
+                hits.write(MobHit {
+                    mob: entity,
+                    damage,
+                    health: Some(target_health),
+                    by_player: Some(attacker_id) == player_id,
+                    action: None,
+                    chain: None,
+                    critical,
+                    resisted,
+                });
+            }
+            NetworkMessage::CombatRejected { action_type, reason } => {
+                // The prediction was wrong; take back everything it gave and cost
+                if let (Some(action), Ok(mut combat)) = (CombatAction::from_name(&action_type), player_q.single_mut()) {
+                    combat.unpredict(action);
+                }
+                println!("{} failed: {}", action_type, reason);
+            }
+            NetworkMessage::LoadoutUpdate { slots } => {
+                // The server's loadout is the one actions are checked against
+                match (Loadout::parse(&slots), player_q.single_mut()) {
+                    (Ok(loadout), Ok(mut combat)) => combat.loadout = loadout,
+                    (Err(e), _) => println!("WARNING: Bad loadout from server: {}", e),
+                    _ => {}
+                }
+            }
+            NetworkMessage::ProjectileLaunched { projectile_id, owner_id, action_type, position, velocity } => {
+                // Our own throws are already in flight
+                if Some(owner_id) == player_id {
+                    continue;
+                }
+                let Some((action, kind)) = CombatAction::from_name(&action_type).and_then(|a| a.projectile().map(|k| (a, k))) else { continue; };
+                let projectile = Projectile::resume(projectile_id, owner_id, action, kind, position, velocity);
+                commands.spawn((Transform::from_translation(position), Flight::new(projectile)));
+            }
+            NetworkMessage::ProjectileLanded { projectile_id, position, .. } => {
+                if let Some(mut flight) = flight_q.iter_mut().find(|f| f.damage.is_none() && f.projectile.id == projectile_id) {
+                    flight.land(position);
+                }
+            }
+            NetworkMessage::StatusUpdate { target_id, effects } => {
+                let effects = StatusEffects::from_snapshot(&effects);
+                if Some(target_id) == player_id {
+                    if let Ok(mut combat) = player_q.single_mut() {
+                        combat.effects = effects;
+                    }
+                } else if let Some((_, mut mob)) = mob_q.iter_mut().find(|(_, m)| m.id == target_id) {
+                    mob.effects = effects;
+                }
+            }
+            NetworkMessage::TargetChanged { entity_id, target_id } => match target_id {
+                Some(target_id) => {
+                    remote_targets.targets.insert(entity_id, target_id);
+                }
+                None => {
+                    remote_targets.targets.remove(&entity_id);
+                }
+            },
+            NetworkMessage::ExperienceGained { amount } => {
+                if let Ok(mut combat) = player_q.single_mut() {
+                    println!("  +{:.0} XP", amount);
+                    combat.award_xp(amount);
+                }
+            }
+            NetworkMessage::SatchelUpdate { items } => {
+                // The server's satchel stands over whatever was carried offline
+                let Ok(mut satchel) = satchel_q.single_mut() else { continue; };
+                satchel.items = items
+                    .into_iter()
+                    .map(|item| InventoryItem { name: item.name, quantity: item.quantity, weight: item.weight })
+                    .collect();
+            }
+            NetworkMessage::LootAwarded { item, quantity, weight } => {
+                let Ok(mut satchel) = satchel_q.single_mut() else { continue; };
+                if satchel.add_item(InventoryItem { name: item.clone(), quantity, weight }) {
+                    println!("  Loot: {} x{}", item, quantity);
+                } else {
+                    println!("  Satchel full! {} dropped on the ground.", item);
+                }
+            }
+            NetworkMessage::SiteUpdate { site, remaining } => {
+                commands.write_message(SiteNews { site, remaining });
+            }
+            NetworkMessage::GatherRefused { reason } => {
+                println!("  Cannot gather: {}", reason);
+            }
+            NetworkMessage::Crafted { item, quality } => {
+                println!("Crafted: {} ({})!", item, quality);
+            }
+            NetworkMessage::CraftRefused { reason } => {
+                println!("  Cannot craft: {}", reason);
+            }
+            NetworkMessage::CorruptionUpdate { corruption, lineage } => {
+                // The server's reckoning stands over the client's, house and all
+                let Ok(mut combat) = player_q.single_mut() else { continue; };
+                let change = corruption - combat.corruption;
+                combat.corrupt(change);
+                if let Some(lineage) = Lineage::from_name(&lineage) {
+                    combat.lineage = lineage;
+                }
+            }
+            NetworkMessage::ArmorRefused { reason } => {
+                println!("  Cannot wear that: {}", reason);
+            }
+            NetworkMessage::SoulBound { point } => {
+                if let (Some(point), Ok(mut combat)) = (BindPoint::from_name(&point), player_q.single_mut()) {
+                    combat.bind_point = point;
+                }
+            }
+            NetworkMessage::PlayerDied { player_id: fallen_id, permanent, .. } if Some(fallen_id) == player_id => {
+                let Ok(mut combat) = player_q.single_mut() else { continue; };
+                if !combat.is_dead {
+                    let health = combat.health;
+                    combat.take_damage(health);
+                }
+                combat.fallen |= permanent;
+            }
+            NetworkMessage::CorpseLaid { position, items, wear } => {
+                commands.write_message(CorpseNews::Laid { position, items, wear });
+            }
+            NetworkMessage::CorpseRecovered => {
+                commands.write_message(CorpseNews::Recovered);
+            }
+            NetworkMessage::XpDebt { debt } => {
+                if let Ok(mut combat) = player_q.single_mut() {
+                    combat.xp_debt = debt;
+                }
+            }
+            NetworkMessage::PlayerStateUpdate { player_id: id, health, position } if Some(id) == player_id => {
+                // The server's health stands; its word on position only once we have strayed too far
+                if let Ok(mut combat) = player_q.single_mut() {
+                    if !combat.is_dead {
+                        combat.health = health.clamp(0.0, combat.max_health);
+                    }
+                }
+                if let Ok(mut transform) = player_transform_q.single_mut() {
+                    if transform.translation.distance(position) > CORRECTION_DISTANCE {
+                        transform.translation = position;
+                    }
+                }
+            }
+            NetworkMessage::PlayerRespawned { player_id: risen_id, position } if Some(risen_id) == player_id => {
+                let Ok(mut combat) = player_q.single_mut() else { continue; };
+                if combat.is_dead && !combat.fallen {
+                    combat.respawn();
+                }
+                if let Ok(mut transform) = player_transform_q.single_mut() {
+                    transform.translation = position;
+                }
+            }
+            NetworkMessage::PvpStatusUpdate { player_id: id, flagged, murderer } if Some(id) == player_id => {
+                if murderer && !pvp.murderer {
+                    pvp.notice = Some("You bear the murderer's mark. Anyone may strike you, anywhere.".to_string());
+                } else if !murderer && pvp.murderer {
+                    pvp.notice = Some("The murderer's mark has faded.".to_string());
+                }
+                pvp.flagged = flagged;
+                pvp.murderer = murderer;
+            }
+            NetworkMessage::PvpRefused { reason } => {
+                pvp.notice = Some(reason);
+            }
+            NetworkMessage::PlayerKilled { killer_id, victim_id, murder } => {
+                if Some(victim_id) == player_id && murder {
+                    pvp.grievances.push(killer_id);
+                    pvp.notice = Some(format!("You were murdered by {}. You may set a contract on them.", killer_id));
+                } else if Some(killer_id) == player_id {
+                    pvp.notice = Some(format!("You slew {}.", victim_id));
+                }
+            }
+            NetworkMessage::ReputationUpdate { reputation } => {
+                let Ok(mut combat) = player_q.single_mut() else { continue; };
+                combat.reputation = reputation;
+            }
+            NetworkMessage::JobUnlocked { job } => {
+                println!("A Hidden Master has taken notice of you...");
+                println!("Job revealed: {}", job);
+            }
+            NetworkMessage::MasteryUpdate { mastery } => {
+                let Ok(mut combat) = player_q.single_mut() else { continue; };
+                combat.mastery = mastery;
+            }
+            NetworkMessage::BountyPosted { target_id, reward } => {
+                pvp.bounties.insert(target_id, reward);
+                if Some(target_id) == player_id {
+                    pvp.notice = Some(format!("A bounty of {:.0} XP is on your head.", reward));
+                }
+            }
+            NetworkMessage::BountyClaimed { hunter_id, target_id, reward } => {
+                pvp.bounties.remove(&target_id);
+                if Some(hunter_id) == player_id {
+                    pvp.notice = Some(format!("You collected the bounty on {}: {:.0} XP.", target_id, reward));
+                }
+            }
+            NetworkMessage::MobSpawned { mob_id, mob_type, name, level, rank, affixes, position } => {
+                let Some(mob_type) = MobType::from_name(&mob_type) else { continue; };
+                let variant = MobVariant {
+                    rank: MobRank::from_name(&rank).unwrap_or_default(),
+                    affixes: affixes.iter().filter_map(|a| Affix::from_name(a)).collect(),
+                    trophy: None, // The server shares out the loot
+                };
+                spawns.write(SpawnMob(CoreMob::new(mob_id, name, mob_type, position, level).with_variant(variant)));
+            }
+            NetworkMessage::MobPositions { mobs } => {
+                commands.write_message(MobSnapshots(mobs));
+            }
+            NetworkMessage::MobMutated { mob_id, mob_type, name, health, .. } => {
+                let Some(mob_type) = MobType::from_name(&mob_type) else { continue; };
+                mutations.write(MutateMob { mob_id, mob_type, name, health: Some(health) });
+            }
+            NetworkMessage::LandRedeemed { region } => {
+                println!("An offering has been accepted. {} is redeemed.", region);
+            }
+            NetworkMessage::RedemptionRefused { reason } => {
+                println!("Your offering is refused: {}", reason);
+            }
+            NetworkMessage::OfferingBurnt { item } => {
+                if let Ok(mut satchel) = satchel_q.single_mut() {
+                    satchel.remove_item(&item, 1);
+                }
+            }
+            NetworkMessage::PartyInvited { from } => {
+                party.invite_from = Some(from);
+            }
+            NetworkMessage::PartyUpdate { leader, members, loot_rule } => {
+                party.leader = leader;
+                party.members = members;
+                party.loot_rule = LootRule::from_name(&loot_rule).unwrap_or_default();
+                party.invite_from = None;
+                if !party.in_party() {
+                    party.rolls.clear();
+                }
+            }
+            NetworkMessage::PartyRefused { reason } => {
+                party.notice = Some(reason);
+            }
+            NetworkMessage::LootRollStart { roll_id, item, quantity } => {
+                party.rolls.push(OpenRoll { roll_id, item, quantity });
+            }
+            NetworkMessage::LootRollResult { roll_id, item, winner } => {
+                party.rolls.retain(|r| r.roll_id != roll_id);
+                party.notice = Some(match winner {
+                    Some(id) if Some(id) == player_id => format!("You won {}", item),
+                    Some(id) => format!("{} won {}", id, item),
+                    None => format!("Everyone passed on {}", item),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tell the server what armor the player wears whenever it changes, and on
+/// connecting, so blows from other players are softened as they are here.
+pub fn equipment_sync_system(
+    client: Option<ResMut<RenetClient>>,
+    equipment: Res<Equipment>,
+    mut sent: Local<Option<Option<String>>>,
+) {
+    let Some(mut client) = client.filter(|c| c.is_connected()) else {
+        *sent = None;
+        return;
+    };
+    if sent.as_ref() == Some(&equipment.armor) {
+        return;
+    }
+    *sent = Some(equipment.armor.clone());
+    send_message(&mut client, &NetworkMessage::EquipArmor { armor: equipment.armor.clone() });
+}
+
+/// Tell the server the player has used up goods from their satchel, if
+/// connected. The server keeps what they carry, and only ever hears of it
+/// shrinking this way.
+pub fn report_used(client: Option<&mut RenetClient>, item: &str, quantity: u32) {
+    if let Some(client) = client.filter(|c| c.is_connected()) {
+        send_message(client, &NetworkMessage::ItemUsed { item: item.to_string(), quantity });
+    }
+}
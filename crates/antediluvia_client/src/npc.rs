@@ -1,7 +1,12 @@
 //! NPC spawning, management, and interaction.
 
 use bevy::prelude::*;
-use antediluvia_core::entity::{NPC, NPCType, EntityId};
+use bevy_renet::RenetClient;
+use antediluvia_core::entity::{NPC, NPCType, EntityId, Errand, Faction, Lineage, Reputation, Standing};
+use antediluvia_core::network::NetworkMessage;
+use crate::combat::PlayerCombat;
+use crate::inventory::Satchel;
+use crate::network::send_message;
 use crate::player::PlayerCamera;
 
 /// Component for NPCs in the world.
@@ -30,7 +35,7 @@ pub fn spawn_noah(
         "Noah".to_string(),
         NPCType::Noah,
         Vec3::new(30.0, 5.0, 40.0),
-    );
+    ).with_lineage(Lineage::Seth).with_faction(Faction::NoahsHousehold);
 
     let noah_skin = materials.add(StandardMaterial {
         base_color: Color::srgb(0.72, 0.56, 0.42),
@@ -68,7 +73,7 @@ pub fn spawn_elder(
         "Methuselah".to_string(),
         NPCType::Elder,
         Vec3::new(-40.0, 5.0, 30.0),
-    );
+    ).with_lineage(Lineage::Seth).with_faction(Faction::SethiteVillages);
 
     let elder_skin = materials.add(StandardMaterial {
         base_color: Color::srgb(0.68, 0.52, 0.38),
@@ -106,7 +111,7 @@ pub fn spawn_merchant(
         "Jubal".to_string(),
         NPCType::Merchant,
         Vec3::new(10.0, 5.0, -30.0),
-    );
+    ).with_lineage(Lineage::Cain).with_faction(Faction::CityOfEnoch);
 
     let merchant_skin = materials.add(StandardMaterial {
         base_color: Color::srgb(0.72, 0.56, 0.42),
@@ -137,8 +142,10 @@ pub fn spawn_merchant(
 pub fn npc_interaction_system(
     keys: Res<ButtonInput<KeyCode>>,
     mut interaction: ResMut<NPCInteraction>,
-    player_q: Query<&Transform, With<PlayerCamera>>,
+    mut player_q: Query<(&Transform, &mut PlayerCombat), With<PlayerCamera>>,
     npc_q: Query<(&NPCEntity, &Transform)>,
+    mut satchel_q: Query<&mut Satchel>,
+    mut client: Option<ResMut<RenetClient>>,
 ) {
     if keys.just_pressed(KeyCode::KeyE) {
         if interaction.is_open {
@@ -146,10 +153,10 @@ pub fn npc_interaction_system(
             return;
         }
 
-        let player_pos = match player_q.single() {
-            Ok(t) => t.translation,
-            Err(_) => return,
+        let Ok((player_transform, mut combat)) = player_q.single_mut() else {
+            return;
         };
+        let player_pos = player_transform.translation;
 
         let mut nearest: Option<(&NPCEntity, f32)> = None;
         for (npc_entity, npc_transform) in npc_q.iter() {
@@ -163,7 +170,12 @@ pub fn npc_interaction_system(
 
         if let Some((npc_comp, _)) = nearest {
             interaction.npc_name = npc_comp.npc.name.clone();
-            interaction.dialogue_lines = get_npc_dialogue(&npc_comp.npc);
+            interaction.dialogue_lines = get_npc_dialogue(&npc_comp.npc, combat.lineage, &combat.reputation);
+            if let Ok(mut satchel) = satchel_q.single_mut() {
+                if let Some(line) = run_errand(&npc_comp.npc, &mut combat, &mut satchel, client.as_deref_mut()) {
+                    interaction.dialogue_lines.push(line);
+                }
+            }
             interaction.current_line = 0;
             interaction.is_open = true;
             println!("Speaking with {}...", npc_comp.npc.name);
@@ -179,7 +191,77 @@ pub fn npc_interaction_system(
     }
 }
 
-fn get_npc_dialogue(npc: &NPC) -> Vec<String> {
+/// Hand over the goods the NPC's faction asked for if the player carries
+/// them, or else ask for them. Nothing is asked of those the faction shuns.
+/// Online the server takes the goods and grants the standing.
+fn run_errand(npc: &NPC, combat: &mut PlayerCombat, satchel: &mut Satchel, client: Option<&mut RenetClient>) -> Option<String> {
+    let faction = npc.faction?;
+    if combat.reputation.standing(faction) <= Standing::Hostile {
+        return None;
+    }
+    let errand = Errand::for_faction(faction)?;
+    let carried = satchel.items.iter().find(|i| i.name == errand.item).map_or(0, |i| i.quantity);
+    let Some(completed) = errand.complete(carried) else {
+        return Some(format!("{}: Bring us {} {}, and we will remember it.", npc.name, errand.quantity, errand.item));
+    };
+    if let Some(client) = client.filter(|c| c.is_connected()) {
+        send_message(client, &NetworkMessage::RunErrand { faction: format!("{:?}", faction) });
+    } else {
+        satchel.remove_item(errand.item, errand.quantity);
+        combat.reputation.apply(&completed);
+    }
+    println!("Errand complete: {} {} for {:?}", errand.quantity, errand.item, faction);
+    Some(format!("{}: {} {}! You have done us a great service.", npc.name, errand.quantity, errand.item))
+}
+
+fn get_npc_dialogue(npc: &NPC, player_lineage: Lineage, reputation: &Reputation) -> Vec<String> {
+    // Standing with the NPC's faction; the unaffiliated go by lineage alone
+    let standing = match npc.faction {
+        Some(faction) => reputation.standing(faction),
+        None => Standing::from_value(player_lineage.reputation_with(npc.lineage)),
+    };
+    if standing <= Standing::Hostile {
+        return match npc.lineage {
+            Some(Lineage::Seth) => vec![
+                format!("{}: I see the mark upon you, child of Cain.", npc.name),
+                format!("{}: Repent and turn from your ways, and then we may speak.", npc.name),
+            ],
+            _ => vec![
+                format!("{}: My wares are not for the likes of you.", npc.name),
+                format!("{}: Come back when you have learned the value of progress.", npc.name),
+            ],
+        };
+    }
+
+    let mut lines = dialogue_for_type(npc);
+    match npc.npc_type {
+        NPCType::Merchant => {
+            if let Some(multiplier) = standing.price_multiplier() {
+                let note = if multiplier < 1.0 {
+                    format!("{}: For a friend, {:.0}% off everything.", npc.name, (1.0 - multiplier) * 100.0)
+                } else if multiplier > 1.0 {
+                    format!("{}: For you, prices are {:.0}% higher. Trust is earned.", npc.name, (multiplier - 1.0) * 100.0)
+                } else {
+                    format!("{}: Fair prices, as for anyone.", npc.name)
+                };
+                lines.insert(1, note);
+            }
+        }
+        NPCType::Trainer if !standing.can_train() => {
+            lines = vec![format!("{}: I teach only those my people trust. Prove yourself first.", npc.name)];
+        }
+        _ => {}
+    }
+
+    if standing >= Standing::Honored {
+        lines.insert(0, format!("{}: Welcome back, friend. Our house is yours.", npc.name));
+    } else if player_lineage == Lineage::Cain && npc.lineage == Some(Lineage::Cain) {
+        lines.insert(0, format!("{}: Ah, one of our own. Enoch's gates are open to you.", npc.name));
+    }
+    lines
+}
+
+fn dialogue_for_type(npc: &NPC) -> Vec<String> {
     match npc.npc_type {
         NPCType::Noah => vec![
             format!("{}: The Lord has shown me what is to come.", npc.name),
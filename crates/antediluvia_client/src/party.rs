@@ -0,0 +1,32 @@
+//! Client view of the player's party.
+//! The server owns parties; this mirrors what it last told us.
+
+use bevy::prelude::*;
+use antediluvia_core::party::LootRule;
+
+/// A need/greed roll waiting on the player's choice.
+#[derive(Clone, Debug)]
+pub struct OpenRoll {
+    pub roll_id: u64,
+    pub item: String,
+    pub quantity: u32,
+}
+
+/// The player's party, pending invitation and open loot rolls.
+#[derive(Resource, Default)]
+pub struct PartyState {
+    pub leader: u64,
+    pub members: Vec<u64>, // Empty when not in a party
+    pub loot_rule: LootRule,
+    pub invite_from: Option<u64>,
+    pub rolls: Vec<OpenRoll>,
+    pub notice: Option<String>,
+    pub invite_id: String, // Player ID typed into the invite box
+}
+
+impl PartyState {
+    /// Check if the player is in a party.
+    pub fn in_party(&self) -> bool {
+        !self.members.is_empty()
+    }
+}
@@ -1,7 +1,19 @@
 use bevy::prelude::*;
 use bevy::input::mouse::MouseMotion;
 use bevy::window::{CursorGrabMode, CursorOptions};
+use bevy_renet::RenetClient;
+use antediluvia_core::entity::{Lineage, Zone, BASE_MOVE_SPEED, SPRINT_MULTIPLIER};
+use antediluvia_core::network::NetworkMessage;
 use crate::combat::PlayerCombat;
+use crate::network::send_message;
+
+/// Seconds between the moves sent to the server.
+pub const MOVE_SEND_SECONDS: f32 = 0.1;
+
+/// How far the server may place the player from where the client has them
+/// before the client gives way: three sends' worth of sprinting, so lag alone
+/// never pulls the player back.
+pub const CORRECTION_DISTANCE: f32 = BASE_MOVE_SPEED * SPRINT_MULTIPLIER * MOVE_SEND_SECONDS * 3.0;
 
 /// Marker component for the player entity (body mesh + combat).
 /// Used by all systems to identify the player.
@@ -24,25 +36,24 @@ impl Default for FollowCamera {
     }
 }
 
-const BASE_SPEED: f32 = 50.0;
-const SPRINT_MULTIPLIER: f32 = 3.0;
-
 pub fn player_movement_system(
     keyboard: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
-    mut query: Query<(&mut Transform, Option<&PlayerCombat>), With<PlayerCamera>>,
+    mut query: Query<(&mut Transform, Option<&mut PlayerCombat>), With<PlayerCamera>>,
+    client: Option<ResMut<RenetClient>>,
+    mut send_timer: Local<f32>,
+    mut last_sent: Local<Option<Vec3>>,
 ) {
-    let Ok((mut transform, combat)) = query.single_mut() else {
+    let Ok((mut transform, mut combat)) = query.single_mut() else {
         return;
     };
 
-    if let Some(combat) = combat {
+    if let Some(combat) = &combat {
         if combat.is_dead { return; }
     }
 
-    let is_sprinting = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
-    let speed_mult = if is_sprinting { SPRINT_MULTIPLIER } else { 1.0 };
-    let speed = BASE_SPEED * speed_mult * time.delta_secs();
+    let dt = time.delta_secs();
+    let wants_sprint = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
 
     let forward = transform.forward();
     let forward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
@@ -55,11 +66,64 @@ pub fn player_movement_system(
     if keyboard.pressed(KeyCode::KeyD) { movement += right_flat; }
 
     if movement != Vec3::ZERO {
+        // Sprinting spends the Breath
+        let is_sprinting = wants_sprint
+            && combat.as_mut().is_none_or(|c| c.breath.sprint(dt));
+        let speed_mult = if is_sprinting { SPRINT_MULTIPLIER } else { 1.0 };
+        let speed = BASE_MOVE_SPEED * speed_mult * dt;
+        let previous = transform.translation;
         transform.translation += movement.normalize() * speed;
+
+        // Each house bars its city to the other
+        if let (Some(combat), Some(zone)) = (&combat, Zone::at(transform.translation)) {
+            if !combat.lineage.can_enter(zone) && Zone::at(previous) != Some(zone) {
+                transform.translation = previous;
+            }
+        }
     }
 
     // Keep player on ground
     transform.translation.y = 5.0;
+
+    // Report where we are at a fixed rate, whenever we have moved
+    *send_timer -= dt;
+    let Some(mut client) = client.filter(|c| c.is_connected()) else {
+        return;
+    };
+    if *send_timer > 0.0 || *last_sent == Some(transform.translation) {
+        return;
+    }
+    *send_timer = MOVE_SEND_SECONDS;
+    *last_sent = Some(transform.translation);
+    let (rotation, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    send_message(&mut client, &NetworkMessage::PlayerMove { position: transform.translation, rotation });
+}
+
+/// Recolor the player's robe when their lineage changes.
+pub fn lineage_appearance_system(
+    player_q: Query<(&PlayerCombat, &MeshMaterial3d<StandardMaterial>), With<PlayerCamera>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut shown: Local<Option<Lineage>>,
+) {
+    let Ok((combat, material)) = player_q.single() else {
+        return;
+    };
+    if *shown == Some(combat.lineage) {
+        return;
+    }
+    *shown = Some(combat.lineage);
+
+    let appearance = combat.lineage.appearance();
+    if let Some(mat) = materials.get_mut(&material.0) {
+        let [r, g, b] = appearance.robe_color;
+        mat.base_color = Color::srgb(r, g, b);
+        // The Mark of Cain smoulders faintly
+        mat.emissive = if appearance.marked {
+            LinearRgba::rgb(0.3, 0.02, 0.0)
+        } else {
+            LinearRgba::BLACK
+        };
+    }
 }
 
 pub fn player_look_system(
@@ -0,0 +1,147 @@
+//! Projectiles in flight. Each flies its own copy of the flight (the local
+//! player's from the throw, everyone else's from the server's launch) and is
+//! drawn easing after it, so the server's word on where one landed never
+//! snaps it across the sky.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use antediluvia_core::projectile::{Impact, Projectile, ProjectileKind};
+use antediluvia_core::skill_chain::SkillChain;
+use crate::combat::{spawn_hit_number, strike_mob, Mob, MobHit};
+use crate::mob_ai::rand_simple;
+use crate::physics::Collider;
+use crate::terrain_mesh;
+use crate::TerrainData;
+
+/// How quickly a projectile's drawn position catches up with its flight.
+const PROJECTILE_SMOOTHING: f32 = 20.0;
+
+/// Seconds a projectile stays on show where it landed.
+const PROJECTILE_LINGER: f32 = 0.2;
+
+#[derive(Component, Debug, Clone)]
+pub struct Flight {
+    pub projectile: Projectile,
+    pub damage: Option<f32>, // The local player's own blow, before the foe's defenses
+    pub chain: Option<&'static SkillChain>,
+    pub linger: Option<f32>, // Seconds left on show, once landed
+}
+
+impl Flight {
+    /// Someone else's projectile, launched by the server.
+    pub fn new(projectile: Projectile) -> Self {
+        Self {
+            projectile,
+            damage: None,
+            chain: None,
+            linger: None,
+        }
+    }
+
+    /// The local player's own projectile, carrying a blow.
+    pub fn thrown(projectile: Projectile, damage: f32, chain: Option<&'static SkillChain>) -> Self {
+        Self {
+            damage: Some(damage),
+            chain,
+            ..Self::new(projectile)
+        }
+    }
+
+    /// Bring the projectile down at `position`.
+    pub fn land(&mut self, position: Vec3) {
+        self.projectile.position = position;
+        self.linger.get_or_insert(PROJECTILE_LINGER);
+    }
+}
+
+/// Give new projectiles a shape.
+pub fn projectile_visual_system(
+    mut commands: Commands,
+    new_q: Query<(Entity, &Flight), Added<Flight>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, flight) in new_q.iter() {
+        let (mesh, material) = match flight.projectile.kind {
+            ProjectileKind::SlingStone => (
+                meshes.add(Sphere::new(0.5)),
+                StandardMaterial {
+                    base_color: Color::srgb(0.45, 0.42, 0.38),
+                    perceptual_roughness: 0.9,
+                    ..default()
+                },
+            ),
+            ProjectileKind::Spear => (
+                meshes.add(Capsule3d::new(0.2, 4.0)),
+                StandardMaterial {
+                    base_color: Color::srgb(0.5, 0.35, 0.2),
+                    perceptual_roughness: 0.8,
+                    ..default()
+                },
+            ),
+            ProjectileKind::Fire => (
+                meshes.add(Sphere::new(1.5)),
+                StandardMaterial {
+                    base_color: Color::srgba(1.0, 0.5, 0.1, 0.9),
+                    emissive: LinearRgba::new(3.0, 1.2, 0.2, 1.0),
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                },
+            ),
+        };
+        commands.entity(entity).insert((Mesh3d(mesh), MeshMaterial3d(materials.add(material))));
+    }
+}
+
+/// Fly projectiles on. The local player's own blows land on whatever mob
+/// they strike: offline they are resolved here, online the server's result
+/// follows.
+pub fn projectile_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    terrain_data: Option<Res<TerrainData>>,
+    mut flight_q: Query<(Entity, &mut Flight, &mut Transform)>,
+    mob_q: Query<(Entity, &Mob, &Transform, Option<&Collider>), Without<Flight>>,
+    client: Option<Res<RenetClient>>,
+    mut hits: MessageWriter<MobHit>,
+) {
+    let dt = time.delta_secs();
+    let online = client.is_some_and(|c| c.is_connected());
+    let ground = |x: f32, z: f32| {
+        terrain_data
+            .as_ref()
+            .map_or(0.0, |data| terrain_mesh::get_terrain_height(&data.generator, x, z, data.base_offset))
+    };
+    let colliders: Vec<(u64, Vec3, f32)> = mob_q
+        .iter()
+        .filter(|(_, mob, _, _)| mob.is_alive())
+        .map(|(_, mob, transform, collider)| (mob.id, transform.translation, collider.map_or(mob.mob_type.hit_radius(), |c| c.radius)))
+        .collect();
+
+    let ease = 1.0 - (-PROJECTILE_SMOOTHING * dt).exp();
+    for (entity, mut flight, mut transform) in flight_q.iter_mut() {
+        match flight.linger {
+            Some(left) if left <= 0.0 => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+            Some(left) => flight.linger = Some(left - dt),
+            None => {
+                if let Some(impact) = flight.projectile.update(dt, ground, &colliders) {
+                    let position = flight.projectile.position;
+                    flight.land(position);
+                    if let (Impact::Hit { id, .. }, Some(damage)) = (impact, flight.damage) {
+                        if let Some((mob_entity, mob, mob_transform, _)) = mob_q.iter().find(|(_, mob, _, _)| mob.id == id) {
+                            let roll = (!online).then(|| rand_simple(time.elapsed_secs()));
+                            let (dealt, critical) = strike_mob(mob_entity, mob, damage, flight.projectile.action, flight.chain, roll, &mut hits);
+                            spawn_hit_number(&mut commands, &asset_server, dealt, critical, mob_transform.translation);
+                        }
+                    }
+                }
+            }
+        }
+        transform.translation = transform.translation.lerp(flight.projectile.position, ease);
+    }
+}
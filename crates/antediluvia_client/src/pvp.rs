@@ -0,0 +1,16 @@
+//! Client view of the fighting between players.
+//! The server judges who may strike whom; this mirrors what it last told us.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// The player's flag, murderer's mark, the bounties posted, and the murderers
+/// the player may set a contract on.
+#[derive(Resource, Default)]
+pub struct PvpState {
+    pub flagged: bool,
+    pub murderer: bool,
+    pub bounties: HashMap<u64, f32>, // Player -> experience on their head
+    pub grievances: Vec<u64>, // Who murdered the player, not yet under contract
+    pub notice: Option<String>,
+}
@@ -0,0 +1,139 @@
+//! Mobs entering the world. Online the server's spawner stocks the wilds and
+//! says where each mob appears; offline the same spawner runs here.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use antediluvia_core::mob::{Mob as CoreMob, MobTier, MobType};
+use antediluvia_core::spawner::{spawn_data, MobSpawner, SpawnConditions};
+use antediluvia_core::variant::MobRank;
+use antediluvia_core::world::WeatherState;
+use crate::combat::Mob;
+use crate::mob_ai::{rand_simple, MobBrain};
+use crate::physics::Collider;
+use crate::{DayNightCycle, WorldState};
+
+/// The offline spawner.
+#[derive(Resource, Default)]
+pub struct MobSpawnerRes(pub MobSpawner);
+
+/// A mob to bring into the world: spawned offline, or reported by the server.
+#[derive(Message, Clone)]
+pub struct SpawnMob(pub CoreMob);
+
+/// The shape of a mob's body, drawn by its tier and model and grown by its rank.
+pub struct MobBody {
+    pub mesh: Handle<Mesh>,
+    pub scale: Vec3,
+    pub head_offset: Vec3,
+    pub head_size: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+impl MobBody {
+    pub fn new(meshes: &mut Assets<Mesh>, mob_type: MobType, rank: MobRank) -> Self {
+        let definition = mob_type.definition();
+        let radius = definition.model.radius * rank.scale();
+        let (mesh, scale, head_offset, head_size) = match definition.tier {
+            MobTier::Common => (
+                meshes.add(Capsule3d::new(radius * 0.5, radius * 0.4)),
+                Vec3::new(1.2, 1.0, 1.0),
+                Vec3::new(0.0, radius * 0.2, radius * 0.5),
+                radius * 0.35,
+            ),
+            MobTier::Elite => (
+                meshes.add(Capsule3d::new(radius * 0.45, radius * 0.8)),
+                Vec3::ONE,
+                Vec3::new(0.0, radius * 0.8, 0.0),
+                radius * 0.3,
+            ),
+            MobTier::Boss => (
+                meshes.add(Capsule3d::new(radius * 0.4, radius * 1.2)),
+                Vec3::ONE,
+                Vec3::new(0.0, radius * 1.3, 0.0),
+                radius * 0.35,
+            ),
+        };
+        let [r, g, b] = definition.model.color;
+        Self { mesh, scale, head_offset, head_size, radius, color: Color::srgb(r, g, b) }
+    }
+}
+
+/// Run the spawner while offline. Nothing spawns while connecting, so the
+/// server's mobs are never doubled up.
+pub fn mob_spawner_system(
+    time: Res<Time>,
+    client: Option<Res<RenetClient>>,
+    cycle: Res<DayNightCycle>,
+    world_state: Res<WorldState>,
+    mut spawner: ResMut<MobSpawnerRes>,
+    mut spawns: MessageWriter<SpawnMob>,
+) {
+    if client.is_some_and(|c| !c.is_disconnected()) {
+        return;
+    }
+    let conditions = SpawnConditions::new(
+        cycle.time_of_day,
+        WeatherState::from_corruption(world_state.corruption),
+        world_state.corruption,
+    );
+    let mut seed = time.elapsed_secs();
+    let mut roll = || {
+        seed += 1.0;
+        rand_simple(seed)
+    };
+    for mob in spawner.0.update(time.delta_secs(), &conditions, &mut roll) {
+        spawns.write(SpawnMob(mob));
+    }
+}
+
+/// Give each mob entering the world its body. A rare's coming is cried abroad.
+pub fn spawn_mob_system(
+    mut commands: Commands,
+    mut spawns: MessageReader<SpawnMob>,
+    mob_q: Query<&Mob>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for SpawnMob(spawned) in spawns.read() {
+        if mob_q.iter().any(|m| m.id == spawned.id) {
+            continue;
+        }
+        let definition = spawned.mob_type.definition();
+        let body = MobBody::new(&mut meshes, spawned.mob_type, spawned.variant.rank);
+        if spawned.variant.rank == MobRank::Rare {
+            let haunt = spawn_data()
+                .zones()
+                .iter()
+                .find(|z| z.center.distance(spawned.position) <= z.radius + 1.0)
+                .map_or("the wilds", |z| z.name.as_str());
+            println!("{} has been sighted in {}!", spawned.name, haunt);
+        }
+
+        let mob_mat = materials.add(StandardMaterial {
+            base_color: body.color,
+            metallic: 0.1,
+            perceptual_roughness: 0.8,
+            ..default()
+        });
+
+        commands.spawn((
+            Mesh3d(body.mesh),
+            MeshMaterial3d(mob_mat.clone()),
+            Transform::from_translation(spawned.position).with_scale(body.scale),
+            Mob::from(spawned),
+            MobBrain {
+                move_speed: spawned.move_speed(),
+                ..MobBrain::new(&definition.behaviour, spawned.home)
+            },
+            Collider::new(body.radius),
+            Name::new("Mob"),
+        )).with_children(|parent| {
+            parent.spawn((
+                Mesh3d(meshes.add(Sphere::new(body.head_size))),
+                MeshMaterial3d(mob_mat),
+                Transform::from_translation(body.head_offset),
+            ));
+        });
+    }
+}
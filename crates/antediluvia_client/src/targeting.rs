@@ -0,0 +1,74 @@
+//! Choosing a target: Tab cycles through mobs in view, and clicking picks the
+//! one under the cursor (or the crosshair while the mouse is captured).
+//! Online, every change is sent to the server.
+
+use bevy::prelude::*;
+use bevy::window::{CursorOptions, PrimaryWindow};
+use bevy_renet::RenetClient;
+use std::collections::HashMap;
+use antediluvia_core::network::NetworkMessage;
+use antediluvia_core::targeting::{next_tab_target, pick_target, tab_order, TARGET_RANGE};
+use crate::combat::{Mob, PlayerCombat};
+use crate::network::send_message;
+use crate::player::{FollowCamera, PlayerCamera};
+
+/// What each player and mob is targeting, as the server last told us.
+#[derive(Resource, Default)]
+pub struct RemoteTargets {
+    pub targets: HashMap<u64, u64>, // Entity ID -> target ID
+}
+
+pub fn targeting_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window_q: Query<(&Window, &CursorOptions), With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<FollowCamera>>,
+    mut player_q: Query<(&mut PlayerCombat, &Transform), With<PlayerCamera>>,
+    mob_q: Query<(Entity, &Mob, &Transform), Without<PlayerCamera>>,
+    mut client: Option<ResMut<RenetClient>>,
+    mut synced: Local<Option<u64>>,
+) {
+    let Ok((mut combat, player_transform)) = player_q.single_mut() else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+
+    let candidates: Vec<(u64, Vec3)> = mob_q
+        .iter()
+        .filter(|(_, mob, _)| mob.is_alive())
+        .map(|(_, mob, transform)| (mob.id, transform.translation))
+        .collect();
+    let entity_of = |id: u64| mob_q.iter().find(|(_, mob, _)| mob.id == id).map(|(entity, _, _)| entity);
+
+    // Forget a target that has died or been left behind
+    let current = combat
+        .current_target
+        .and_then(|entity| mob_q.get(entity).ok())
+        .filter(|(_, mob, transform)| mob.is_alive() && player_pos.distance(transform.translation) <= TARGET_RANGE);
+    combat.current_target = current.map(|(entity, _, _)| entity);
+    let current_id = current.map(|(_, mob, _)| mob.id);
+
+    if keys.just_pressed(KeyCode::Tab) {
+        let order = tab_order(player_pos, *player_transform.forward(), &candidates);
+        combat.current_target = next_tab_target(current_id, &order).and_then(entity_of);
+    }
+
+    // Clicking empty ground keeps the current target
+    if mouse.just_pressed(MouseButton::Left) {
+        let ray = window_q.single().ok().zip(camera_q.single().ok()).and_then(|((window, cursor), (camera, camera_transform))| {
+            let point = if cursor.visible { window.cursor_position()? } else { window.size() / 2.0 };
+            camera.viewport_to_world(camera_transform, point).ok()
+        });
+        if let Some(picked) = ray.and_then(|ray| pick_target(ray.origin, *ray.direction, &candidates)) {
+            combat.current_target = entity_of(picked);
+        }
+    }
+
+    let target_id = combat.current_target.and_then(|entity| mob_q.get(entity).ok()).map(|(_, mob, _)| mob.id);
+    if target_id != *synced {
+        if let Some(client) = client.as_deref_mut().filter(|c| c.is_connected()) {
+            send_message(client, &NetworkMessage::SetTarget { target_id });
+        }
+        *synced = target_id;
+    }
+}
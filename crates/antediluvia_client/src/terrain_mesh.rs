@@ -7,12 +7,7 @@
 use bevy::prelude::*;
 use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::asset::RenderAssetUsages;
-use antediluvia_core::world::PangeaGenerator;
-
-/// Offset applied to game coordinates to place starting area within Havilah.
-/// Havilah spans distances 500-2000 from world origin in PangeaGenerator.
-const WORLD_OFFSET_X: f64 = 1000.0;
-const WORLD_OFFSET_Z: f64 = 0.0;
+use antediluvia_core::world::{PangeaGenerator, LOCAL_OFFSET_X, LOCAL_OFFSET_Z};
 
 /// Component to tag terrain mesh entities.
 #[derive(Component)]
@@ -20,15 +15,15 @@ pub struct TerrainChunk;
 
 /// Get terrain height at a local game coordinate.
 pub fn get_terrain_height(generator: &PangeaGenerator, local_x: f32, local_z: f32, base_offset: f32) -> f32 {
-    let world_x = local_x as f64 + WORLD_OFFSET_X;
-    let world_z = local_z as f64 + WORLD_OFFSET_Z;
+    let world_x = local_x as f64 + LOCAL_OFFSET_X;
+    let world_z = local_z as f64 + LOCAL_OFFSET_Z;
     generator.get_height(world_x, world_z) - base_offset
 }
 
 /// Compute height offset at the player spawn point so terrain is near y=0 there.
 pub fn compute_base_offset(generator: &PangeaGenerator, spawn_x: f32, spawn_z: f32) -> f32 {
-    let world_x = spawn_x as f64 + WORLD_OFFSET_X;
-    let world_z = spawn_z as f64 + WORLD_OFFSET_Z;
+    let world_x = spawn_x as f64 + LOCAL_OFFSET_X;
+    let world_z = spawn_z as f64 + LOCAL_OFFSET_Z;
     generator.get_height(world_x, world_z)
 }
 
@@ -60,8 +55,8 @@ pub fn generate_terrain_mesh(
             let local_x = -half_size + x_idx as f32 * step;
             let local_z = -half_size + z_idx as f32 * step;
 
-            let world_x = local_x as f64 + WORLD_OFFSET_X;
-            let world_z = local_z as f64 + WORLD_OFFSET_Z;
+            let world_x = local_x as f64 + LOCAL_OFFSET_X;
+            let world_z = local_z as f64 + LOCAL_OFFSET_Z;
 
             let height = generator.get_height(world_x, world_z) - base_offset;
             heights.push(height);
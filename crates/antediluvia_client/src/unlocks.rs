@@ -0,0 +1,243 @@
+//! Job unlock detection.
+//!
+//! Gameplay systems write telemetry messages; this module feeds them to the core
+//! tracker and reveals jobs on the player when a Feat of Legend is performed.
+//! Online the feats go to the server, which checks them and reveals the job.
+
+use bevy::prelude::*;
+use bevy_renet::RenetClient;
+use std::collections::HashMap;
+use antediluvia_core::entity::{JobTelemetry, JobUnlockTracker, LyreSong};
+use antediluvia_core::network::NetworkMessage;
+use crate::combat::{Mob, PlayerCombat};
+use crate::inventory::Satchel;
+use crate::mob_ai::{MobBrain, MobState};
+use crate::network::send_message;
+use crate::npc::NPCEntity;
+use crate::player::PlayerCamera;
+
+/// Message carrying job-unlock telemetry from gameplay systems.
+#[derive(Message, Clone, Copy)]
+pub struct JobTelemetryEvent(pub JobTelemetry);
+
+/// The player's unlock progress.
+#[derive(Resource, Default)]
+pub struct JobUnlocks(pub JobUnlockTracker);
+
+/// Range within which the player counts as guarding an NPC.
+const DEFEND_RANGE: f32 = 40.0;
+
+/// Range within which a hunting mob counts as threatening an NPC.
+const THREAT_RANGE: f32 = 60.0;
+
+/// Range within which a hunting mob mauls an NPC it passes.
+const MAUL_RANGE: f32 = 4.0;
+
+/// Range within which the player can tend a stranger.
+const TEND_RANGE: f32 = 6.0;
+
+/// Health a herb and bandage mend.
+const TEND_HEALING: f32 = 25.0;
+
+/// The song the player is playing on the Lyre, if any.
+#[derive(Resource, Default)]
+pub struct Lyre(pub Option<LyreSong>);
+
+/// Seconds of defence gathered before they are reported to the server.
+const DEFEND_REPORT_SECONDS: f32 = 1.0;
+
+/// Feed telemetry to the tracker and reveal jobs. Online, the feats are
+/// reported to the server instead; kills and crafts it sees for itself.
+pub fn job_unlock_system(
+    mut telemetry: MessageReader<JobTelemetryEvent>,
+    mut unlocks: ResMut<JobUnlocks>,
+    mut player_q: Query<&mut PlayerCombat, With<PlayerCamera>>,
+    client: Option<ResMut<RenetClient>>,
+    mut defended: Local<HashMap<u64, f32>>,
+) {
+    if let Some(mut client) = client.filter(|c| c.is_connected()) {
+        for JobTelemetryEvent(event) in telemetry.read() {
+            let feat = match *event {
+                JobTelemetry::MobDamaged { .. } | JobTelemetry::MobKilled { .. } | JobTelemetry::ItemCrafted { .. } => continue,
+                // A frame's defence is too little to send on its own
+                JobTelemetry::DefendedNpc { npc_id, seconds } => {
+                    let total = defended.entry(npc_id).or_insert(0.0);
+                    *total += seconds;
+                    if *total < DEFEND_REPORT_SECONDS {
+                        continue;
+                    }
+                    let seconds = std::mem::take(total);
+                    JobTelemetry::DefendedNpc { npc_id, seconds }
+                }
+                JobTelemetry::NpcDied { npc_id } => {
+                    defended.remove(&npc_id);
+                    JobTelemetry::NpcDied { npc_id }
+                }
+                feat => feat,
+            };
+            send_message(&mut client, &NetworkMessage::Feat { feat });
+        }
+        return;
+    }
+
+    let Ok(mut combat) = player_q.single_mut() else {
+        return;
+    };
+
+    for JobTelemetryEvent(event) in telemetry.read() {
+        if let Some(job) = unlocks.0.observe(event, &mut combat.mastery) {
+            println!("A Hidden Master has taken notice of you...");
+            println!("Job revealed: {:?} ({})", job, job.core_mechanic());
+        }
+    }
+}
+
+/// Report time spent guarding a non-hostile NPC from hunting predators.
+pub fn defend_telemetry_system(
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
+    player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    npc_q: Query<(&NPCEntity, &Transform)>,
+    mob_q: Query<(&Mob, &MobBrain, &Transform), Without<PlayerCamera>>,
+    time: Res<Time>,
+) {
+    let Ok((combat, player_transform)) = player_q.single() else {
+        return;
+    };
+    if combat.is_dead {
+        return;
+    }
+
+    let player_pos = player_transform.translation;
+    for (npc, npc_transform) in npc_q.iter() {
+        if npc.npc.is_hostile() || player_pos.distance(npc_transform.translation) > DEFEND_RANGE {
+            continue;
+        }
+
+        let threatened = mob_q.iter().any(|(mob, brain, mob_transform)| {
+            mob.is_alive()
+                && matches!(brain.state, MobState::Aggro | MobState::Attacking)
+                && mob_transform.translation.distance(npc_transform.translation) < THREAT_RANGE
+        });
+
+        if threatened {
+            telemetry.write(JobTelemetryEvent(JobTelemetry::DefendedNpc {
+                npc_id: npc.npc.entity.id.0,
+                seconds: time.delta_secs(),
+            }));
+        }
+    }
+}
+
+/// Let hunting predators maul the NPCs they pass. An NPC that dies is gone,
+/// and so is any defence of it.
+pub fn npc_peril_system(
+    mut commands: Commands,
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
+    mut npc_q: Query<(Entity, &mut NPCEntity, &Transform)>,
+    mob_q: Query<(&Mob, &MobBrain, &Transform), Without<PlayerCamera>>,
+    time: Res<Time>,
+) {
+    for (entity, mut npc, npc_transform) in npc_q.iter_mut() {
+        if npc.npc.is_hostile() {
+            continue;
+        }
+
+        let mauling: f32 = mob_q
+            .iter()
+            .filter(|(mob, brain, mob_transform)| {
+                mob.is_alive()
+                    && matches!(brain.state, MobState::Aggro | MobState::Attacking)
+                    && mob_transform.translation.distance(npc_transform.translation) < MAUL_RANGE
+            })
+            .map(|(mob, brain, _)| mob.damage_per_hit / brain.attack_cooldown.max(0.1))
+            .sum();
+        if mauling <= 0.0 {
+            continue;
+        }
+
+        if let Some(died) = npc.npc.wound(mauling * time.delta_secs()) {
+            println!("{} has been slain by beasts!", npc.npc.name);
+            telemetry.write(JobTelemetryEvent(died));
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Tend the nearest wounded stranger with H, spending a Healing Herb.
+pub fn tend_stranger_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
+    player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    mut npc_q: Query<(&mut NPCEntity, &Transform)>,
+    mut satchel_q: Query<&mut Satchel>,
+) {
+    if !keys.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+    let Ok((combat, player_transform)) = player_q.single() else {
+        return;
+    };
+    if combat.is_dead {
+        return;
+    }
+
+    let player_pos = player_transform.translation;
+    let Some((mut npc, _)) = npc_q
+        .iter_mut()
+        .filter(|(npc, t)| npc.npc.is_wounded() && !npc.npc.is_hostile() && t.translation.distance(player_pos) <= TEND_RANGE)
+        .min_by(|(_, a), (_, b)| a.translation.distance(player_pos).total_cmp(&b.translation.distance(player_pos)))
+    else {
+        println!("There is no one hurt nearby to tend.");
+        return;
+    };
+    let Ok(mut satchel) = satchel_q.single_mut() else {
+        return;
+    };
+    if !satchel.remove_item("Healing Herb", 1) {
+        println!("You need a Healing Herb to tend {}.", npc.npc.name);
+        return;
+    }
+
+    if let Some(tended) = npc.npc.tend(TEND_HEALING) {
+        let entity = &npc.npc.entity;
+        println!("You bind {}'s wounds. HP: {:.0}/{:.0}", npc.npc.name, entity.health, entity.max_health);
+        telemetry.write(JobTelemetryEvent(tended));
+    }
+}
+
+/// Play the Lyre with J: the first press starts a song, and each press after
+/// strums it on the beat. The song is judged once its last beat has passed.
+pub fn lyre_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut lyre: ResMut<Lyre>,
+    mut telemetry: MessageWriter<JobTelemetryEvent>,
+    player_q: Query<(&PlayerCombat, &Transform), With<PlayerCamera>>,
+    time: Res<Time>,
+) {
+    let Ok((combat, player_transform)) = player_q.single() else {
+        return;
+    };
+    if combat.is_dead {
+        lyre.0 = None;
+        return;
+    }
+
+    let Some(song) = lyre.0.as_mut() else {
+        if keys.just_pressed(KeyCode::KeyJ) {
+            println!("You take up the Lyre...");
+            lyre.0 = Some(LyreSong::new());
+        }
+        return;
+    };
+
+    song.update(time.delta_secs());
+    if keys.just_pressed(KeyCode::KeyJ) {
+        song.strum();
+    }
+    if song.is_finished() {
+        let played = song.finish(player_transform.translation);
+        println!("Your song ends. Accuracy: {:.0}%", song.accuracy() * 100.0);
+        telemetry.write(JobTelemetryEvent(played));
+        lyre.0 = None;
+    }
+}
@@ -0,0 +1,327 @@
+//! Per-job skill trees and the ability loadout.
+//!
+//! Each job has a tree of nodes gated by mastery. Nodes unlock new actions,
+//! passives (which apply to every action of the job) and modifiers (which apply
+//! to one action). The player slots unlocked actions into a loadout bound to
+//! keys 1-4; the server validates it against the player's mastery.
+
+use serde::{Deserialize, Serialize};
+use crate::combat::CombatAction;
+use crate::entity::{Job, JobMastery};
+use crate::error::{AntediluviaError, Result};
+
+/// Number of action slots (keys 1-4).
+pub const LOADOUT_SLOTS: usize = 4;
+
+/// Mastery gained each time an unlocked job's action is used.
+pub const MASTERY_PER_USE: f32 = 0.1;
+
+/// A change to an action's numbers, as a fraction (0.1 = 10%).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Modifier {
+    /// More damage and healing.
+    Damage(f32),
+    /// Shorter cooldown.
+    Cooldown(f32),
+    /// Cheaper in Breath.
+    Stamina(f32),
+}
+
+/// What a skill tree node grants.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AbilityEffect {
+    /// A new action that can be slotted.
+    Action(CombatAction),
+    /// A bonus to every action of the job.
+    Passive(Modifier),
+    /// A bonus to a single action.
+    Modifier { action: CombatAction, modifier: Modifier },
+}
+
+/// A node in a job's skill tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbilityNode {
+    pub name: String,
+    pub job: Job,
+    pub mastery_required: f32, // 0 means always available
+    pub effect: AbilityEffect,
+}
+
+impl AbilityNode {
+    fn new(name: &str, job: Job, mastery_required: f32, effect: AbilityEffect) -> Self {
+        Self {
+            name: name.to_string(),
+            job,
+            mastery_required,
+            effect,
+        }
+    }
+
+    /// Check if this node is unlocked at the given mastery.
+    pub fn is_unlocked(&self, mastery: &JobMastery) -> bool {
+        self.mastery_required <= 0.0 || mastery.get_level(self.job) >= self.mastery_required
+    }
+}
+
+/// A job's skill tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkillTree {
+    pub job: Job,
+    pub nodes: Vec<AbilityNode>,
+}
+
+impl SkillTree {
+    /// Get the skill tree for a job.
+    pub fn for_job(job: Job) -> Self {
+        use AbilityEffect::{Action, Modifier as Mod, Passive};
+        use CombatAction as A;
+        use Modifier::{Cooldown, Damage, Stamina};
+
+        let node = |name, mastery, effect| AbilityNode::new(name, job, mastery, effect);
+        let nodes = match job {
+            Job::Shepherd => vec![
+                node("Rebuke", 0.0, Action(A::ShepherdRebuke)),
+                node("Block", 0.0, Action(A::ShepherdBlock)),
+                node("Steady Hands", 10.0, Passive(Stamina(0.1))),
+                node("Sling", 25.0, Action(A::ShepherdSling)),
+                node("Thunderous Rebuke", 40.0, Mod { action: A::ShepherdRebuke, modifier: Damage(0.5) }),
+                node("Bulwark", 60.0, Action(A::ShepherdBulwark)),
+                node("Rod and Staff", 80.0, Passive(Cooldown(0.15))),
+            ],
+            Job::Levite => vec![
+                node("Prayer", 0.0, Action(A::LevitePrayer)),
+                node("Heal", 0.0, Action(A::LeviteHeal)),
+                node("Sweet Savour", 10.0, Passive(Damage(0.1))),
+                node("Censer", 25.0, Action(A::LeviteCenser)),
+                node("Quick Hands", 40.0, Mod { action: A::LeviteHeal, modifier: Cooldown(0.25) }),
+                node("Atonement", 60.0, Action(A::LeviteAtonement)),
+                node("Holy of Holies", 80.0, Passive(Damage(0.2))),
+            ],
+            Job::Hunter => vec![
+                node("Thrust", 0.0, Action(A::HunterThrust)),
+                node("Slash", 0.0, Action(A::HunterSlash)),
+                node("Tireless", 10.0, Passive(Stamina(0.15))),
+                node("Lunge", 25.0, Action(A::HunterLunge)),
+                node("Piercing Thrust", 40.0, Mod { action: A::HunterThrust, modifier: Damage(0.3) }),
+                node("Giant's Bane", 60.0, Action(A::HunterGiantsBane)),
+                node("Momentum", 80.0, Passive(Cooldown(0.2))),
+            ],
+            Job::Forge => vec![
+                node("Smash", 0.0, Action(A::ForgeSmash)),
+                node("Fire", 0.0, Action(A::ForgeFire)),
+                node("Tempered", 10.0, Passive(Damage(0.1))),
+                node("Quench", 25.0, Action(A::ForgeQuench)),
+                node("Heavy Hammer", 40.0, Mod { action: A::ForgeSmash, modifier: Damage(0.4) }),
+                node("Molten Strike", 60.0, Action(A::ForgeMoltenStrike)),
+                node("Bellows", 80.0, Passive(Stamina(0.25))),
+            ],
+            Job::Psalmist => vec![
+                node("Song", 0.0, Action(A::PsalmistSong)),
+                node("Buff", 0.0, Action(A::PsalmistBuff)),
+                node("Deep Breath", 10.0, Passive(Stamina(0.2))),
+                node("Lament", 25.0, Action(A::PsalmistLament)),
+                node("Refrain", 40.0, Mod { action: A::PsalmistSong, modifier: Cooldown(0.3) }),
+                node("Song of Ascent", 60.0, Action(A::PsalmistAscent)),
+                node("Selah", 80.0, Passive(Cooldown(0.15))),
+            ],
+        };
+
+        Self { job, nodes }
+    }
+
+    /// Nodes unlocked at the given mastery.
+    pub fn unlocked<'a>(&'a self, mastery: &'a JobMastery) -> impl Iterator<Item = &'a AbilityNode> {
+        self.nodes.iter().filter(move |n| n.is_unlocked(mastery))
+    }
+
+    /// Check if an action is unlocked at the given mastery.
+    pub fn action_unlocked(&self, action: CombatAction, mastery: &JobMastery) -> bool {
+        self.unlocked(mastery)
+            .any(|n| n.effect == AbilityEffect::Action(action))
+    }
+}
+
+/// Combined multipliers for an action after passives and modifiers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActionModifiers {
+    pub damage: f32,
+    pub cooldown: f32,
+    pub stamina: f32,
+}
+
+impl ActionModifiers {
+    /// Compute the multipliers for an action at the given mastery.
+    pub fn for_action(action: CombatAction, mastery: &JobMastery) -> Self {
+        let mut result = Self::default();
+        let tree = SkillTree::for_job(action.job());
+        for node in tree.unlocked(mastery) {
+            match node.effect {
+                AbilityEffect::Passive(modifier) => result.apply(modifier),
+                AbilityEffect::Modifier { action: target, modifier } if target == action => result.apply(modifier),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    fn apply(&mut self, modifier: Modifier) {
+        match modifier {
+            Modifier::Damage(f) => self.damage += f,
+            Modifier::Cooldown(f) => self.cooldown = (self.cooldown - f).max(0.25),
+            Modifier::Stamina(f) => self.stamina = (self.stamina - f).max(0.0),
+        }
+    }
+}
+
+impl Default for ActionModifiers {
+    fn default() -> Self {
+        Self {
+            damage: 1.0,
+            cooldown: 1.0,
+            stamina: 1.0,
+        }
+    }
+}
+
+/// The actions bound to keys 1-4.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Loadout {
+    pub slots: [Option<CombatAction>; LOADOUT_SLOTS],
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self {
+            slots: [
+                Some(CombatAction::HunterThrust),
+                Some(CombatAction::HunterSlash),
+                Some(CombatAction::LeviteHeal),
+                Some(CombatAction::ForgeSmash),
+            ],
+        }
+    }
+}
+
+impl Loadout {
+    /// Build a loadout from wire names, validating it against mastery.
+    pub fn from_names(names: &[Option<String>], mastery: &JobMastery) -> Result<Self> {
+        let loadout = Self::parse(names)?;
+        loadout.validate(mastery)?;
+        Ok(loadout)
+    }
+
+    /// Build a loadout from wire names without checking it against mastery,
+    /// as when taking the server's word for it.
+    pub fn parse(names: &[Option<String>]) -> Result<Self> {
+        if names.len() != LOADOUT_SLOTS {
+            return Err(AntediluviaError::InvalidLoadout(format!(
+                "expected {} slots, got {}",
+                LOADOUT_SLOTS,
+                names.len()
+            )));
+        }
+
+        let mut loadout = Self { slots: [None; LOADOUT_SLOTS] };
+        for (slot, name) in names.iter().enumerate() {
+            if let Some(name) = name {
+                let action = CombatAction::from_name(name)
+                    .ok_or_else(|| AntediluviaError::InvalidLoadout(format!("unknown action {}", name)))?;
+                loadout.slots[slot] = Some(action);
+            }
+        }
+        Ok(loadout)
+    }
+
+    /// Wire names of the slotted actions.
+    pub fn names(&self) -> Vec<Option<String>> {
+        self.slots.iter().map(|s| s.map(|a| format!("{:?}", a))).collect()
+    }
+
+    /// Get the action in a slot (0-based).
+    pub fn get(&self, slot: usize) -> Option<CombatAction> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Put an action in a slot if it is unlocked and not already slotted.
+    pub fn set(&mut self, slot: usize, action: Option<CombatAction>, mastery: &JobMastery) -> Result<()> {
+        if slot >= LOADOUT_SLOTS {
+            return Err(AntediluviaError::InvalidLoadout(format!("no slot {}", slot + 1)));
+        }
+        let mut candidate = *self;
+        candidate.slots[slot] = action;
+        candidate.validate(mastery)?;
+        *self = candidate;
+        Ok(())
+    }
+
+    /// Check if an action is slotted.
+    pub fn contains(&self, action: CombatAction) -> bool {
+        self.slots.contains(&Some(action))
+    }
+
+    /// Check every slotted action is unlocked and appears only once.
+    pub fn validate(&self, mastery: &JobMastery) -> Result<()> {
+        for (i, action) in self.slots.iter().enumerate() {
+            let Some(action) = action else { continue; };
+            if self.slots[..i].contains(&Some(*action)) {
+                return Err(AntediluviaError::InvalidLoadout(format!("{:?} is slotted twice", action)));
+            }
+            if !SkillTree::for_job(action.job()).action_unlocked(*action, mastery) {
+                return Err(AntediluviaError::InvalidLoadout(format!("{:?} is not unlocked", action)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mastery_unlocks_actions() {
+        let mut mastery = JobMastery::default();
+        let tree = SkillTree::for_job(Job::Hunter);
+        assert!(tree.action_unlocked(CombatAction::HunterThrust, &mastery));
+        assert!(!tree.action_unlocked(CombatAction::HunterLunge, &mastery));
+
+        mastery.increase(Job::Hunter, 25.0);
+        assert!(tree.action_unlocked(CombatAction::HunterLunge, &mastery));
+        assert!(!tree.action_unlocked(CombatAction::HunterGiantsBane, &mastery));
+    }
+
+    #[test]
+    fn test_passives_and_modifiers_stack() {
+        let mut mastery = JobMastery::default();
+        assert_eq!(ActionModifiers::for_action(CombatAction::HunterThrust, &mastery), ActionModifiers::default());
+
+        mastery.increase(Job::Hunter, 40.0);
+        let thrust = ActionModifiers::for_action(CombatAction::HunterThrust, &mastery);
+        assert!((thrust.damage - 1.3).abs() < 0.001);
+        assert!((thrust.stamina - 0.85).abs() < 0.001);
+
+        // The modifier is specific to Thrust
+        let slash = ActionModifiers::for_action(CombatAction::HunterSlash, &mastery);
+        assert_eq!(slash.damage, 1.0);
+    }
+
+    #[test]
+    fn test_loadout_validation() {
+        let mut mastery = JobMastery::default();
+        let mut loadout = Loadout::default();
+        assert!(loadout.validate(&mastery).is_ok());
+
+        // Locked and duplicate actions are rejected
+        assert!(loadout.set(0, Some(CombatAction::ForgeQuench), &mastery).is_err());
+        assert!(loadout.set(0, Some(CombatAction::HunterSlash), &mastery).is_err());
+        assert_eq!(loadout, Loadout::default());
+
+        mastery.increase(Job::Forge, 30.0);
+        assert!(loadout.set(0, Some(CombatAction::ForgeQuench), &mastery).is_ok());
+
+        let names = loadout.names();
+        assert_eq!(Loadout::from_names(&names, &mastery).unwrap(), loadout);
+        assert!(Loadout::from_names(&names, &JobMastery::default()).is_err());
+        assert_eq!(Loadout::parse(&names).unwrap(), loadout); // The server's word needs no mastery
+    }
+}
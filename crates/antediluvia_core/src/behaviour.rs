@@ -0,0 +1,391 @@
+//! Behaviour trees: how mobs, villagers and giants make up their minds.
+//!
+//! A tree is built from composable nodes: sequences, selectors, utility
+//! choices, conditions on facts, and actions. The trees that ship with the
+//! game are defined in `data/behaviours.json`. Each tick the host fills a
+//! [`Blackboard`] with what the creature knows, and the tree picks at most one
+//! [`Action`] for the host to carry out. The [`Decision`] keeps a trace of the
+//! nodes visited, for the debugger.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::error::{AntediluviaError, Result};
+
+/// The behaviour trees that ship with the game.
+const BEHAVIOUR_DATA: &str = include_str!("../data/behaviours.json");
+
+/// Something a creature knows when deciding. Flags are 1.0 when set.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Fact {
+    Provoked, // Angry with someone still about
+    InReach, // Its target is close enough to strike
+    TargetDistance,
+    HomeDistance,
+    Leashed, // Led past its leash
+    Health, // Share of its health left
+    PackAttack, // Its pack sends it in
+    PackFlank, // Its pack has it circle the prey
+    PackRetreat, // Its pack is broken and fleeing
+    Talking,
+    Corruption, // The world's, 0 to 100
+    Night,
+}
+
+/// Something a creature can do. The host carries it out.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Idle,
+    Wander,
+    Chase,
+    Strike,
+    Flank,
+    Retreat,
+    ReturnHome,
+    Talk,
+    Work,
+    Flee,
+}
+
+/// What a creature knows this tick. Unknown facts read as zero.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Blackboard {
+    facts: HashMap<Fact, f32>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a fact.
+    pub fn with(mut self, fact: Fact, value: f32) -> Self {
+        self.facts.insert(fact, value);
+        self
+    }
+
+    /// Record a flag.
+    pub fn with_flag(self, fact: Fact, set: bool) -> Self {
+        self.with(fact, if set { 1.0 } else { 0.0 })
+    }
+
+    /// Get a fact, or zero if it is unknown.
+    pub fn get(&self, fact: Fact) -> f32 {
+        self.facts.get(&fact).copied().unwrap_or(0.0)
+    }
+}
+
+/// A test of one fact. With no bounds it checks the fact is set.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Condition {
+    pub fact: Fact,
+    #[serde(default)]
+    pub above: Option<f32>,
+    #[serde(default)]
+    pub below: Option<f32>,
+}
+
+impl Condition {
+    /// Check the condition against what the creature knows.
+    pub fn holds(&self, board: &Blackboard) -> bool {
+        let value = board.get(self.fact);
+        match (self.above, self.below) {
+            (None, None) => value > 0.0,
+            (above, below) => above.is_none_or(|a| value > a) && below.is_none_or(|b| value < b),
+        }
+    }
+
+    fn label(&self) -> String {
+        let mut label = format!("{:?}", self.fact);
+        if let Some(above) = self.above {
+            label.push_str(&format!(" > {}", above));
+        }
+        if let Some(below) = self.below {
+            label.push_str(&format!(" < {}", below));
+        }
+        label + "?"
+    }
+}
+
+/// How much one fact counts toward a utility option's score.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Consideration {
+    pub fact: Fact,
+    pub weight: f32,
+}
+
+/// One choice of a utility node, scored from what the creature knows.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UtilityOption {
+    #[serde(default)]
+    pub base: f32,
+    #[serde(default)]
+    pub considerations: Vec<Consideration>,
+    pub node: BehaviourNode,
+}
+
+impl UtilityOption {
+    /// Score the option: its base plus each weighted fact.
+    pub fn score(&self, board: &Blackboard) -> f32 {
+        self.base + self.considerations.iter().map(|c| c.weight * board.get(c.fact)).sum::<f32>()
+    }
+}
+
+/// A node of a behaviour tree.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviourNode {
+    Sequence(Vec<BehaviourNode>), // Runs children in turn until one does not succeed
+    Selector(Vec<BehaviourNode>), // Tries children in turn until one does not fail
+    Utility(Vec<UtilityOption>), // Tries children best-scoring first
+    Not(Box<BehaviourNode>), // Turns success to failure and back
+    Condition(Condition),
+    Action(Action),
+}
+
+/// How a node came out.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NodeStatus {
+    Success,
+    Failure,
+    Running, // An action was chosen
+}
+
+impl BehaviourNode {
+    /// Check the node and everything under it is well formed.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        match self {
+            BehaviourNode::Sequence(children) | BehaviourNode::Selector(children) => {
+                if children.is_empty() {
+                    return Err("sequences and selectors need children".to_string());
+                }
+                children.iter().try_for_each(|c| c.validate())
+            }
+            BehaviourNode::Utility(options) => {
+                if options.is_empty() {
+                    return Err("utility nodes need options".to_string());
+                }
+                options.iter().try_for_each(|o| o.node.validate())
+            }
+            BehaviourNode::Not(child) => child.validate(),
+            BehaviourNode::Condition(Condition { fact, above: Some(above), below: Some(below) }) if above >= below => {
+                Err(format!("condition on {:?} can never hold", fact))
+            }
+            BehaviourNode::Condition(_) | BehaviourNode::Action(_) => Ok(()),
+        }
+    }
+
+    /// Tick the node, recording what it did in `decision`.
+    fn tick(&self, board: &Blackboard, depth: usize, decision: &mut Decision) -> NodeStatus {
+        let step = decision.trace.len();
+        decision.trace.push(TraceStep {
+            depth,
+            label: String::new(),
+            status: NodeStatus::Failure,
+        });
+
+        let (label, status) = match self {
+            BehaviourNode::Sequence(children) => {
+                let status = children
+                    .iter()
+                    .map(|c| c.tick(board, depth + 1, decision))
+                    .find(|s| *s != NodeStatus::Success)
+                    .unwrap_or(NodeStatus::Success);
+                ("sequence".to_string(), status)
+            }
+            BehaviourNode::Selector(children) => {
+                let status = children
+                    .iter()
+                    .map(|c| c.tick(board, depth + 1, decision))
+                    .find(|s| *s != NodeStatus::Failure)
+                    .unwrap_or(NodeStatus::Failure);
+                ("selector".to_string(), status)
+            }
+            BehaviourNode::Utility(options) => {
+                let mut scored: Vec<(f32, &UtilityOption)> = options.iter().map(|o| (o.score(board), o)).collect();
+                scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+                let scores: Vec<String> = scored.iter().map(|(s, _)| format!("{:.2}", s)).collect();
+                let status = scored
+                    .iter()
+                    .map(|(_, o)| o.node.tick(board, depth + 1, decision))
+                    .find(|s| *s != NodeStatus::Failure)
+                    .unwrap_or(NodeStatus::Failure);
+                (format!("utility [{}]", scores.join(", ")), status)
+            }
+            BehaviourNode::Not(child) => {
+                let status = match child.tick(board, depth + 1, decision) {
+                    NodeStatus::Success => NodeStatus::Failure,
+                    NodeStatus::Failure => NodeStatus::Success,
+                    NodeStatus::Running => NodeStatus::Running,
+                };
+                ("not".to_string(), status)
+            }
+            BehaviourNode::Condition(condition) => {
+                let status = if condition.holds(board) { NodeStatus::Success } else { NodeStatus::Failure };
+                (condition.label(), status)
+            }
+            BehaviourNode::Action(action) => {
+                decision.action = Some(*action);
+                (format!("{:?}", action), NodeStatus::Running)
+            }
+        };
+
+        decision.trace[step].label = label;
+        decision.trace[step].status = status;
+        status
+    }
+}
+
+/// A named behaviour tree.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BehaviourTree {
+    pub name: String,
+    pub root: BehaviourNode,
+}
+
+impl BehaviourTree {
+    /// Decide what to do, stopping at the first action reached.
+    pub fn decide(&self, board: &Blackboard) -> Decision {
+        let mut decision = Decision::default();
+        self.root.tick(board, 0, &mut decision);
+        decision
+    }
+}
+
+/// One node visited while deciding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    pub depth: usize,
+    pub label: String,
+    pub status: NodeStatus,
+}
+
+/// What a tree decided, and how.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Decision {
+    pub action: Option<Action>, // None if no action was reached
+    pub trace: Vec<TraceStep>,
+}
+
+impl Decision {
+    /// Describe the nodes visited, one indented line each, for the debugger.
+    pub fn describe(&self) -> Vec<String> {
+        self.trace
+            .iter()
+            .map(|step| format!("{}{} — {:?}", "  ".repeat(step.depth), step.label, step.status))
+            .collect()
+    }
+}
+
+/// Every behaviour tree, by name.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BehaviourLibrary {
+    trees: Vec<BehaviourTree>,
+}
+
+impl BehaviourLibrary {
+    /// Build a library, checking tree names are unique and every tree is
+    /// well formed.
+    pub fn new(trees: Vec<BehaviourTree>) -> Result<Self> {
+        for (i, tree) in trees.iter().enumerate() {
+            if trees[..i].iter().any(|t| t.name == tree.name) {
+                return Err(AntediluviaError::DataError(format!("behaviour tree {} is defined more than once", tree.name)));
+            }
+            tree.root
+                .validate()
+                .map_err(|reason| AntediluviaError::DataError(format!("behaviour tree {}: {}", tree.name, reason)))?;
+        }
+        Ok(Self { trees })
+    }
+
+    /// Load a library from JSON: the trees.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let data: Self = serde_json::from_str(json)?;
+        Self::new(data.trees)
+    }
+
+    /// Get a tree by name.
+    pub fn get(&self, name: &str) -> Option<&BehaviourTree> {
+        self.trees.iter().find(|t| t.name == name)
+    }
+
+    /// Get every tree.
+    pub fn trees(&self) -> &[BehaviourTree] {
+        &self.trees
+    }
+}
+
+/// The behaviour trees that ship with the game, loaded on first use.
+pub fn behaviour_library() -> &'static BehaviourLibrary {
+    static LIBRARY: OnceLock<BehaviourLibrary> = OnceLock::new();
+    LIBRARY.get_or_init(|| BehaviourLibrary::from_json(BEHAVIOUR_DATA).expect("shipped behaviour trees are valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mob::MobType;
+
+    #[test]
+    fn test_mob_tree_decisions() {
+        let tree = behaviour_library().get("mob").unwrap();
+        assert_eq!(tree.decide(&Blackboard::new()).action, Some(Action::Wander));
+
+        let provoked = Blackboard::new().with_flag(Fact::Provoked, true);
+        assert_eq!(tree.decide(&provoked).action, Some(Action::Chase));
+        let in_reach = provoked.clone().with_flag(Fact::InReach, true);
+        assert_eq!(tree.decide(&in_reach).action, Some(Action::Strike));
+
+        // The pack's word and the leash come before the mob's own anger
+        let flanking = in_reach.clone().with_flag(Fact::PackFlank, true);
+        assert_eq!(tree.decide(&flanking).action, Some(Action::Flank));
+        let leashed = in_reach.with_flag(Fact::Leashed, true);
+        assert_eq!(tree.decide(&leashed).action, Some(Action::ReturnHome));
+
+        // Giants stand guard rather than wander
+        let giant = behaviour_library().get(&MobType::Nephilim.definition().behaviour.tree).unwrap();
+        assert_eq!(giant.decide(&Blackboard::new()).action, Some(Action::Idle));
+    }
+
+    #[test]
+    fn test_utility_and_trace() {
+        let json = r#"{"trees": [{"name": "test", "root": {"utility": [
+            {"base": 0.5, "node": {"action": "idle"}},
+            {"considerations": [{"fact": "corruption", "weight": 0.01}],
+             "node": {"sequence": [{"not": {"condition": {"fact": "night"}}}, {"action": "work"}]}}
+        ]}}]}"#;
+        let library = BehaviourLibrary::from_json(json).unwrap();
+        let tree = library.get("test").unwrap();
+        assert_eq!(tree.decide(&Blackboard::new().with(Fact::Corruption, 20.0)).action, Some(Action::Idle));
+
+        // Work scores higher, but not at night: the next best is tried
+        let board = Blackboard::new().with(Fact::Corruption, 70.0);
+        assert_eq!(tree.decide(&board).action, Some(Action::Work));
+        let decision = tree.decide(&board.with_flag(Fact::Night, true));
+        assert_eq!(decision.action, Some(Action::Idle));
+        let lines = decision.describe();
+        assert_eq!(lines[0], "utility [0.70, 0.50] — Running");
+        assert!(lines.contains(&"      Night? — Success".to_string()));
+        assert_eq!(lines.last().unwrap(), "  Idle — Running");
+    }
+
+    #[test]
+    fn test_invalid_behaviour_data() {
+        let mut data: serde_json::Value = serde_json::from_str(BEHAVIOUR_DATA).unwrap();
+        assert!(BehaviourLibrary::from_json(&data.to_string()).is_ok());
+
+        let mut twice = data.clone();
+        let first = twice["trees"][0].clone();
+        twice["trees"].as_array_mut().unwrap().push(first);
+        assert!(BehaviourLibrary::from_json(&twice.to_string()).is_err());
+
+        data["trees"][0]["root"] = serde_json::json!({"selector": []});
+        assert!(BehaviourLibrary::from_json(&data.to_string()).is_err());
+        data["trees"][0]["root"] = serde_json::json!({"condition": {"fact": "health", "above": 0.5, "below": 0.2}});
+        assert!(BehaviourLibrary::from_json(&data.to_string()).is_err());
+        data["trees"][0]["root"] = serde_json::json!({"action": "dance"});
+        assert!(BehaviourLibrary::from_json(&data.to_string()).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Characters and the per-account roster.
+//!
+//! An account may hold several characters. Names are unique across the whole
+//! server (case-insensitive). Each character starts in a lineage of the
+//! player's choosing and carries its own appearance.
+
+use serde::{Deserialize, Serialize};
+use crate::entity::Lineage;
+use crate::error::{AntediluviaError, Result};
+use crate::mob::LootDrop;
+
+/// Most characters an account may hold.
+pub const MAX_CHARACTERS: usize = 6;
+
+/// Shortest allowed character name.
+pub const NAME_MIN_LEN: usize = 3;
+
+/// Longest allowed character name.
+pub const NAME_MAX_LEN: usize = 16;
+
+/// Skin tones (linear RGB).
+pub const SKIN_TONES: [[f32; 3]; 5] = [
+    [0.85, 0.68, 0.55],
+    [0.72, 0.56, 0.42],
+    [0.62, 0.45, 0.32],
+    [0.48, 0.33, 0.22],
+    [0.35, 0.24, 0.16],
+];
+
+/// Hair colors (linear RGB).
+pub const HAIR_COLORS: [[f32; 3]; 5] = [
+    [0.08, 0.06, 0.05],
+    [0.3, 0.18, 0.1],
+    [0.55, 0.35, 0.15],
+    [0.6, 0.25, 0.1],
+    [0.8, 0.8, 0.78], // The grey of the long-lived
+];
+
+/// Number of hair styles.
+pub const HAIR_STYLES: u8 = 4;
+
+/// Shortest and tallest allowed height scale.
+pub const HEIGHT_RANGE: (f32, f32) = (0.9, 1.1);
+
+/// A character's chosen look.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CharacterAppearance {
+    pub skin_tone: u8,  // Index into SKIN_TONES
+    pub hair_color: u8, // Index into HAIR_COLORS
+    pub hair_style: u8,
+    pub height: f32, // Scale, 1.0 is average
+}
+
+impl Default for CharacterAppearance {
+    fn default() -> Self {
+        Self {
+            skin_tone: 1,
+            hair_color: 0,
+            hair_style: 0,
+            height: 1.0,
+        }
+    }
+}
+
+impl CharacterAppearance {
+    /// Check every option is in range.
+    pub fn validate(&self) -> Result<()> {
+        let in_range = (self.skin_tone as usize) < SKIN_TONES.len()
+            && (self.hair_color as usize) < HAIR_COLORS.len()
+            && self.hair_style < HAIR_STYLES
+            && (HEIGHT_RANGE.0..=HEIGHT_RANGE.1).contains(&self.height);
+        if in_range {
+            Ok(())
+        } else {
+            Err(AntediluviaError::CharacterError("appearance out of range".to_string()))
+        }
+    }
+
+    /// Skin color (linear RGB).
+    pub fn skin_color(&self) -> [f32; 3] {
+        SKIN_TONES[(self.skin_tone as usize).min(SKIN_TONES.len() - 1)]
+    }
+
+    /// Hair color (linear RGB).
+    pub fn hair_rgb(&self) -> [f32; 3] {
+        HAIR_COLORS[(self.hair_color as usize).min(HAIR_COLORS.len() - 1)]
+    }
+}
+
+/// A playable character.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Character {
+    pub id: u64,
+    pub account_id: u64,
+    pub name: String,
+    pub lineage: Lineage, // Starting lineage; corruption moves it from here
+    pub appearance: CharacterAppearance,
+}
+
+/// Get what a new character carries: food, water, and the makings of a first weapon.
+pub fn starting_satchel() -> Vec<LootDrop> {
+    [("Gopher Wood", 5, 10.0), ("Bread", 10, 0.5), ("Waterskin", 1, 2.0), ("Bronze Ingot", 3, 4.0), ("Leather Grip", 2, 1.0)]
+        .into_iter()
+        .map(|(name, quantity, weight)| LootDrop { name: name.to_string(), quantity, weight })
+        .collect()
+}
+
+/// Check a character name is well-formed.
+pub fn validate_name(name: &str) -> Result<()> {
+    let len = name.chars().count();
+    if !(NAME_MIN_LEN..=NAME_MAX_LEN).contains(&len) {
+        return Err(AntediluviaError::CharacterError(format!(
+            "names must be {} to {} letters",
+            NAME_MIN_LEN, NAME_MAX_LEN
+        )));
+    }
+    if name.starts_with(' ') || name.ends_with(' ') {
+        return Err(AntediluviaError::CharacterError("names may not have leading or trailing spaces".to_string()));
+    }
+    if !name.starts_with(|c: char| c.is_alphabetic()) {
+        return Err(AntediluviaError::CharacterError("names must start with a letter".to_string()));
+    }
+    if !name.chars().all(|c| c.is_alphabetic() || c == ' ' || c == '-' || c == '\'') {
+        return Err(AntediluviaError::CharacterError("names may only contain letters, spaces, - and '".to_string()));
+    }
+    Ok(())
+}
+
+/// Every character on the server.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CharacterRegistry {
+    characters: Vec<Character>,
+    last_id: u64,
+}
+
+impl CharacterRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an existing character (e.g. loaded from storage).
+    pub fn insert(&mut self, character: Character) {
+        self.last_id = self.last_id.max(character.id);
+        self.characters.retain(|c| c.id != character.id);
+        self.characters.push(character);
+    }
+
+    /// Get a character by ID.
+    pub fn get(&self, character_id: u64) -> Option<&Character> {
+        self.characters.iter().find(|c| c.id == character_id)
+    }
+
+    /// Get an account's characters.
+    pub fn roster(&self, account_id: u64) -> Vec<Character> {
+        self.characters.iter().filter(|c| c.account_id == account_id).cloned().collect()
+    }
+
+    /// Check if a name is in use (case-insensitive).
+    pub fn name_taken(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        self.characters.iter().any(|c| c.name.to_lowercase() == name)
+    }
+
+    /// Create a character for an account.
+    pub fn create(
+        &mut self,
+        account_id: u64,
+        name: &str,
+        lineage: Lineage,
+        appearance: CharacterAppearance,
+    ) -> Result<Character> {
+        validate_name(name)?;
+        appearance.validate()?;
+        if self.name_taken(name) {
+            return Err(AntediluviaError::CharacterError(format!("the name {} is taken", name)));
+        }
+        if self.roster(account_id).len() >= MAX_CHARACTERS {
+            return Err(AntediluviaError::CharacterError(format!(
+                "an account may hold at most {} characters",
+                MAX_CHARACTERS
+            )));
+        }
+
+        self.last_id += 1;
+        let character = Character {
+            id: self.last_id,
+            account_id,
+            name: name.to_string(),
+            lineage,
+            appearance,
+        };
+        self.characters.push(character.clone());
+        Ok(character)
+    }
+
+    /// Select one of an account's characters to play.
+    pub fn select(&self, account_id: u64, character_id: u64) -> Result<&Character> {
+        self.get(character_id)
+            .filter(|c| c.account_id == account_id)
+            .ok_or_else(|| AntediluviaError::CharacterError(format!("no character {} on this account", character_id)))
+    }
+
+    /// Delete one of an account's characters, freeing its name.
+    pub fn delete(&mut self, account_id: u64, character_id: u64) -> Result<Character> {
+        self.select(account_id, character_id)?;
+        let index = self.characters.iter().position(|c| c.id == character_id).unwrap_or_default();
+        Ok(self.characters.remove(index))
+    }
+}
+
+/// Request an account's roster.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RosterRequest {
+    pub token: String,
+}
+
+/// Request a new character.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateCharacterRequest {
+    pub token: String,
+    pub name: String,
+    pub lineage: Lineage,
+    pub appearance: CharacterAppearance,
+}
+
+/// Request a character's deletion.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteCharacterRequest {
+    pub token: String,
+    pub character_id: u64,
+}
+
+/// The account's roster after a request, and the error if it failed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RosterResponse {
+    pub characters: Vec<Character>,
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_validation() {
+        assert!(validate_name("Enosh").is_ok());
+        assert!(validate_name("Mahalal-el").is_ok());
+        assert!(validate_name("Jo").is_err());
+        assert!(validate_name("Tubal Cain the Younger").is_err());
+        assert!(validate_name("x1337").is_err());
+        assert!(validate_name("-Seth").unwrap_err().to_string().contains("start with a letter"));
+
+        // Stray spaces are called out as such, whether checked alone or on creation
+        let mut registry = CharacterRegistry::new();
+        for spaced in [" Seth", "Seth "] {
+            assert!(validate_name(spaced).unwrap_err().to_string().contains("leading or trailing spaces"));
+            let created = registry.create(1, spaced, Lineage::Seth, CharacterAppearance::default());
+            assert!(created.unwrap_err().to_string().contains("leading or trailing spaces"));
+        }
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut registry = CharacterRegistry::new();
+        let a = registry.create(1, "Enosh", Lineage::Seth, CharacterAppearance::default()).unwrap();
+        assert!(registry.create(2, "enosh", Lineage::Cain, CharacterAppearance::default()).is_err());
+
+        // Deleting frees the name
+        assert!(registry.delete(2, a.id).is_err()); // Not their character
+        registry.delete(1, a.id).unwrap();
+        assert!(registry.create(2, "Enosh", Lineage::Cain, CharacterAppearance::default()).is_ok());
+    }
+
+    #[test]
+    fn test_roster_per_account() {
+        let mut registry = CharacterRegistry::new();
+        for name in ["Adah", "Zillah", "Naamah", "Jabal", "Jubal", "Tubal"] {
+            registry.create(7, name, Lineage::Cain, CharacterAppearance::default()).unwrap();
+        }
+        assert!(registry.create(7, "Lamech", Lineage::Cain, CharacterAppearance::default()).is_err());
+        assert_eq!(registry.roster(7).len(), MAX_CHARACTERS);
+        assert!(registry.roster(8).is_empty());
+
+        let bad = CharacterAppearance { height: 2.0, ..CharacterAppearance::default() };
+        assert!(registry.create(8, "Lamech", Lineage::Seth, bad).is_err());
+    }
+}
@@ -2,7 +2,83 @@
 //! 
 //! Inspired by FFXI. Mobs require parties. Skill chains provide massive damage bonuses.
 
+use serde::de::{value::Error as NameError, IntoDeserializer};
 use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use std::collections::HashMap;
+use crate::entity::Job;
+use crate::error::{AntediluviaError, Result};
+use crate::mob::MobType;
+use crate::variant::MobVariant;
+use crate::status::{DispelCategory, EffectKind};
+use crate::projectile::ProjectileKind;
+use crate::skill_chain::{skill_chains, SkillChain, MAX_CHAIN_STEPS};
+
+/// Reach of melee actions.
+pub const MELEE_RANGE: f32 = 30.0;
+
+/// Reach of spells and thrown or fired actions.
+pub const RANGED_RANGE: f32 = 150.0;
+
+/// Height above a combatant's feet that line of sight is traced from.
+pub const EYE_HEIGHT: f32 = 3.0;
+
+/// Distance between terrain samples along a line of sight.
+pub const LINE_OF_SIGHT_STEP: f32 = 5.0;
+
+/// Base chance for an action to land a critical hit.
+pub const CRIT_CHANCE: f32 = 0.05;
+
+/// Damage multiplier of a critical hit.
+pub const CRIT_MULTIPLIER: f32 = 1.5;
+
+/// Armor at which physical damage is halved.
+pub const ARMOR_SCALE: f32 = 100.0;
+
+/// Most physical damage armor can turn aside.
+pub const MAX_MITIGATION: f32 = 0.75;
+
+/// Threat per point of damage dealt by a Shepherd.
+pub const SHEPHERD_THREAT: f32 = 3.0;
+
+/// Flat threat of a Rebuke, on top of its damage.
+pub const REBUKE_THREAT: f32 = 100.0;
+
+/// Threat per point of healing done.
+pub const HEALING_THREAT: f32 = 0.5;
+
+/// Most incense a Levite's censer can hold.
+pub const MAX_INCENSE: f32 = 100.0;
+
+/// Incense that smoulders back each second.
+pub const INCENSE_REGEN: f32 = 2.0;
+
+/// Most momentum stacks a Hunter can build.
+pub const MAX_MOMENTUM: u32 = 5;
+
+/// Fraction taken off a Hunter's cooldowns per momentum stack.
+pub const MOMENTUM_HASTE: f32 = 0.08;
+
+/// Seconds without an attack before a Hunter's momentum is lost.
+pub const MOMENTUM_DECAY_SECONDS: f32 = 4.0;
+
+/// Most heat the forge can hold.
+pub const MAX_HEAT: f32 = 100.0;
+
+/// Damage bonus at full heat.
+pub const HEAT_DAMAGE_BONUS: f32 = 0.5;
+
+/// Heat lost each second.
+pub const HEAT_COOLING: f32 = 5.0;
+
+/// Weapon durability worn by a blow struck at full heat.
+pub const HEAT_WEAR: f32 = 2.0;
+
+/// How long a Psalmist channels a song.
+pub const SONG_CHANNEL_SECONDS: f32 = 12.0;
+
+/// Seconds between pulses of a channelled song.
+pub const SONG_PULSE_SECONDS: f32 = 3.0;
 
 /// A combat action (ability/spell).
 #[derive(Clone, Debug, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -10,25 +86,40 @@ pub enum CombatAction {
     // Shepherd (Tank)
     ShepherdRebuke,
     ShepherdBlock,
+    ShepherdSling,
+    ShepherdBulwark,
     
     // Levite (Healer)
     LevitePrayer,
     LeviteHeal,
+    LeviteCenser,
+    LeviteAtonement,
     
     // Hunter (Melee DPS)
     HunterThrust,
     HunterSlash,
+    HunterLunge,
+    HunterGiantsBane,
     
     // Forge (Burst DPS)
     ForgeSmash,
     ForgeFire,
+    ForgeQuench,
+    ForgeMoltenStrike,
     
     // Psalmist (Support)
     PsalmistSong,
     PsalmistBuff,
+    PsalmistLament,
+    PsalmistAscent,
 }
 
 impl CombatAction {
+    /// Parse an action from its wire name (the variant name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::deserialize(IntoDeserializer::<NameError>::into_deserializer(name)).ok()
+    }
+
     /// Get the damage dealt by this action.
     pub fn damage(&self) -> f32 {
         match self {
@@ -42,6 +133,25 @@ impl CombatAction {
             CombatAction::ForgeFire => 70.0,
             CombatAction::PsalmistSong => 0.0,
             CombatAction::PsalmistBuff => 0.0,
+            CombatAction::ShepherdSling => 30.0,
+            CombatAction::ShepherdBulwark => 0.0,
+            CombatAction::LeviteCenser => 25.0,
+            CombatAction::LeviteAtonement => 0.0,
+            CombatAction::HunterLunge => 55.0,
+            CombatAction::HunterGiantsBane => 120.0,
+            CombatAction::ForgeQuench => 45.0,
+            CombatAction::ForgeMoltenStrike => 110.0,
+            CombatAction::PsalmistLament => 20.0,
+            CombatAction::PsalmistAscent => 0.0,
+        }
+    }
+
+    /// Get the healing done by this action (to the caster).
+    pub fn healing(&self) -> f32 {
+        match self {
+            CombatAction::LeviteHeal => 50.0,
+            CombatAction::LeviteAtonement => 150.0,
+            _ => 0.0,
         }
     }
 
@@ -58,17 +168,177 @@ impl CombatAction {
             CombatAction::ForgeFire => 4.0,
             CombatAction::PsalmistSong => 6.0,
             CombatAction::PsalmistBuff => 5.0,
+            CombatAction::ShepherdSling => 2.0,
+            CombatAction::ShepherdBulwark => 20.0,
+            CombatAction::LeviteCenser => 3.0,
+            CombatAction::LeviteAtonement => 30.0,
+            CombatAction::HunterLunge => 3.0,
+            CombatAction::HunterGiantsBane => 12.0,
+            CombatAction::ForgeQuench => 3.0,
+            CombatAction::ForgeMoltenStrike => 10.0,
+            CombatAction::PsalmistLament => 4.0,
+            CombatAction::PsalmistAscent => 20.0,
+        }
+    }
+
+    /// Get the stamina (Breath) cost. Heavy weapons cost the most.
+    pub fn stamina_cost(&self) -> f32 {
+        match self {
+            CombatAction::ShepherdRebuke => 10.0,
+            CombatAction::ShepherdBlock => 15.0,
+            CombatAction::LevitePrayer => 0.0,
+            CombatAction::LeviteHeal => 0.0,
+            CombatAction::HunterThrust => 20.0,
+            CombatAction::HunterSlash => 15.0,
+            CombatAction::ForgeSmash => 30.0,
+            CombatAction::ForgeFire => 20.0,
+            CombatAction::PsalmistSong => 5.0,
+            CombatAction::PsalmistBuff => 5.0,
+            CombatAction::ShepherdSling => 10.0,
+            CombatAction::ShepherdBulwark => 25.0,
+            CombatAction::LeviteCenser => 5.0,
+            CombatAction::LeviteAtonement => 0.0,
+            CombatAction::HunterLunge => 20.0,
+            CombatAction::HunterGiantsBane => 35.0,
+            CombatAction::ForgeQuench => 15.0,
+            CombatAction::ForgeMoltenStrike => 35.0,
+            CombatAction::PsalmistLament => 5.0,
+            CombatAction::PsalmistAscent => 10.0,
+        }
+    }
+
+    /// Get the incense spent from the censer. Negative for actions that
+    /// burn incense into it.
+    pub fn incense_cost(&self) -> f32 {
+        match self {
+            CombatAction::LeviteCenser => -15.0,
+            CombatAction::LevitePrayer => 10.0,
+            CombatAction::LeviteHeal => 20.0,
+            CombatAction::LeviteAtonement => 60.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Get the heat this action builds at the forge.
+    pub fn heat(&self) -> f32 {
+        match self {
+            CombatAction::ForgeSmash => 20.0,
+            CombatAction::ForgeFire => 25.0,
+            CombatAction::ForgeMoltenStrike => 35.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Check if this action is a song the Psalmist channels.
+    pub fn is_channelled(&self) -> bool {
+        matches!(self, CombatAction::PsalmistSong | CombatAction::PsalmistBuff | CombatAction::PsalmistAscent)
+    }
+
+    /// Get the job this action belongs to.
+    pub fn job(&self) -> Job {
+        match self {
+            CombatAction::ShepherdRebuke
+            | CombatAction::ShepherdBlock
+            | CombatAction::ShepherdSling
+            | CombatAction::ShepherdBulwark => Job::Shepherd,
+            CombatAction::LevitePrayer
+            | CombatAction::LeviteHeal
+            | CombatAction::LeviteCenser
+            | CombatAction::LeviteAtonement => Job::Levite,
+            CombatAction::HunterThrust
+            | CombatAction::HunterSlash
+            | CombatAction::HunterLunge
+            | CombatAction::HunterGiantsBane => Job::Hunter,
+            CombatAction::ForgeSmash
+            | CombatAction::ForgeFire
+            | CombatAction::ForgeQuench
+            | CombatAction::ForgeMoltenStrike => Job::Forge,
+            CombatAction::PsalmistSong
+            | CombatAction::PsalmistBuff
+            | CombatAction::PsalmistLament
+            | CombatAction::PsalmistAscent => Job::Psalmist,
+        }
+    }
+
+    /// Get the reach of this action.
+    pub fn range(&self) -> f32 {
+        match self.action_type() {
+            _ if self.projectile().is_some() => RANGED_RANGE,
+            ActionType::Physical => MELEE_RANGE,
+            ActionType::Magic => RANGED_RANGE,
+        }
+    }
+
+    /// Check if this action strikes from afar: hurled, or cast.
+    pub fn is_ranged(&self) -> bool {
+        self.range() > MELEE_RANGE
+    }
+
+    /// Get what this action hurls, if it strikes from afar by projectile
+    /// rather than landing at once.
+    pub fn projectile(&self) -> Option<ProjectileKind> {
+        match self {
+            CombatAction::ShepherdSling => Some(ProjectileKind::SlingStone),
+            CombatAction::HunterGiantsBane => Some(ProjectileKind::Spear),
+            CombatAction::ForgeFire => Some(ProjectileKind::Fire),
+            _ => None,
+        }
+    }
+
+    /// Get the kind of damage this action deals.
+    pub fn damage_type(&self) -> DamageType {
+        match self {
+            CombatAction::HunterThrust
+            | CombatAction::HunterLunge
+            | CombatAction::HunterGiantsBane => DamageType::Pierce,
+            CombatAction::HunterSlash => DamageType::Slash,
+            CombatAction::ForgeFire | CombatAction::ForgeMoltenStrike => DamageType::Fire,
+            CombatAction::LeviteCenser
+            | CombatAction::LeviteAtonement
+            | CombatAction::LevitePrayer
+            | CombatAction::LeviteHeal
+            | CombatAction::PsalmistSong
+            | CombatAction::PsalmistBuff
+            | CombatAction::PsalmistLament
+            | CombatAction::PsalmistAscent => DamageType::Holy,
+            CombatAction::ShepherdRebuke
+            | CombatAction::ShepherdBlock
+            | CombatAction::ShepherdSling
+            | CombatAction::ShepherdBulwark
+            | CombatAction::ForgeSmash
+            | CombatAction::ForgeQuench => DamageType::Blunt,
+        }
+    }
+
+    /// Get the status effects this action applies. Harmful ones land on the
+    /// target, helpful ones on the caster (and, for songs, allies nearby).
+    pub fn status_effects(&self) -> Vec<EffectKind> {
+        match self {
+            CombatAction::ShepherdBulwark => vec![EffectKind::Bulwark],
+            CombatAction::HunterSlash | CombatAction::HunterLunge => vec![EffectKind::Bleed],
+            CombatAction::ForgeFire | CombatAction::ForgeMoltenStrike => vec![EffectKind::Burn],
+            CombatAction::PsalmistSong => vec![EffectKind::Psalm],
+            CombatAction::PsalmistBuff => vec![EffectKind::Hymn],
+            CombatAction::PsalmistAscent => vec![EffectKind::SongOfAscent],
+            CombatAction::PsalmistLament => vec![EffectKind::Lament],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the effect categories this action dispels from its target.
+    pub fn dispels(&self) -> &'static [DispelCategory] {
+        match self {
+            CombatAction::LevitePrayer => &[DispelCategory::Wound, DispelCategory::Curse],
+            CombatAction::LeviteAtonement => &[DispelCategory::Wound, DispelCategory::Burning, DispelCategory::Curse],
+            _ => &[],
         }
     }
 
     /// Get the action type for skill chain purposes.
     pub fn action_type(&self) -> ActionType {
-        match self {
-            CombatAction::ShepherdRebuke | CombatAction::ShepherdBlock => ActionType::Physical,
-            CombatAction::LevitePrayer | CombatAction::LeviteHeal => ActionType::Magic,
-            CombatAction::HunterThrust | CombatAction::HunterSlash => ActionType::Physical,
-            CombatAction::ForgeSmash | CombatAction::ForgeFire => ActionType::Physical,
-            CombatAction::PsalmistSong | CombatAction::PsalmistBuff => ActionType::Magic,
+        match self.job() {
+            Job::Shepherd | Job::Hunter | Job::Forge => ActionType::Physical,
+            Job::Levite | Job::Psalmist => ActionType::Magic,
         }
     }
 }
@@ -80,54 +350,136 @@ pub enum ActionType {
     Magic,
 }
 
-/// A skill chain (combo).
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct SkillChain {
-    pub first_action: CombatAction,
-    pub second_action: CombatAction,
-    pub damage_multiplier: f32,
-    pub name: String,
+/// The kinds of damage.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Pierce,
+    Slash,
+    Blunt,
+    Fire,
+    Holy,
 }
 
-impl SkillChain {
-    /// Create a skill chain.
-    pub fn new(first: CombatAction, second: CombatAction, multiplier: f32, name: String) -> Self {
-        Self {
-            first_action: first,
-            second_action: second,
-            damage_multiplier: multiplier,
-            name,
-        }
-    }
-
-    /// Check if a sequence of actions matches this chain.
-    pub fn matches(&self, first: CombatAction, second: CombatAction) -> bool {
-        self.first_action == first && self.second_action == second
-    }
-}
-
-/// The skill chain registry.
-pub fn get_skill_chains() -> Vec<SkillChain> {
-    vec![
-        SkillChain::new(
-            CombatAction::HunterThrust,
-            CombatAction::ForgeSmash,
-            2.5,
-            "Shatter".to_string(),
-        ),
-        SkillChain::new(
-            CombatAction::HunterSlash,
-            CombatAction::ForgeFire,
-            2.0,
-            "Inferno".to_string(),
-        ),
-        SkillChain::new(
-            CombatAction::ShepherdRebuke,
-            CombatAction::HunterThrust,
-            1.8,
-            "Righteous Fury".to_string(),
-        ),
-    ]
+impl DamageType {
+    /// Check if armor mitigates this damage. Fire and holy pass through it.
+    pub fn is_physical(&self) -> bool {
+        matches!(self, DamageType::Pierce | DamageType::Slash | DamageType::Blunt)
+    }
+}
+
+/// Multipliers on damage taken by type: below 1.0 resists, above 1.0 is a weakness.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Resistances {
+    pub pierce: f32,
+    pub slash: f32,
+    pub blunt: f32,
+    pub fire: f32,
+    pub holy: f32,
+}
+
+impl Resistances {
+    /// No resistances or weaknesses.
+    pub const NEUTRAL: Resistances = Resistances { pierce: 1.0, slash: 1.0, blunt: 1.0, fire: 1.0, holy: 1.0 };
+
+    /// Resistances of a mob type, as its definition sets them.
+    pub fn for_mob(mob_type: MobType) -> Self {
+        mob_type.definition().stats.resistances
+    }
+
+    /// Multiplier on damage of a type.
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Pierce => self.pierce,
+            DamageType::Slash => self.slash,
+            DamageType::Blunt => self.blunt,
+            DamageType::Fire => self.fire,
+            DamageType::Holy => self.holy,
+        }
+    }
+}
+
+/// Share of physical damage turned aside by `armor`.
+pub fn armor_mitigation(armor: f32) -> f32 {
+    let armor = armor.max(0.0);
+    (armor / (armor + ARMOR_SCALE)).min(MAX_MITIGATION)
+}
+
+/// Armor granted by an equipped item.
+pub fn item_armor(name: &str) -> f32 {
+    match name {
+        "Linen Tunic" => 10.0,
+        _ => 0.0,
+    }
+}
+
+/// Check if an item is a weapon, wielded rather than worn.
+pub fn is_weapon(name: &str) -> bool {
+    matches!(name, "Bronze Sword" | "Iron Sword")
+}
+
+/// How well a combatant withstands damage.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Defense {
+    pub armor: f32,
+    pub resistances: Resistances,
+}
+
+impl Defense {
+    /// Create a defense.
+    pub fn new(armor: f32, resistances: Resistances) -> Self {
+        Self { armor, resistances }
+    }
+
+    /// Defense of a player wearing the given items.
+    pub fn from_equipment<'a>(items: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::new(items.into_iter().map(item_armor).sum(), Resistances::NEUTRAL)
+    }
+
+    /// Defense of a mob of `mob_type` at `level`.
+    pub fn for_mob(mob_type: MobType, level: u32) -> Self {
+        let stats = &mob_type.definition().stats;
+        Self::new(stats.armor + level as f32 * 2.0, stats.resistances)
+    }
+
+    /// Defense of a mob of `mob_type` at `level`, hardened by its variant.
+    pub fn for_variant(mob_type: MobType, level: u32, variant: &MobVariant) -> Self {
+        let base = Self::for_mob(mob_type, level);
+        Self::new(base.armor * variant.armor_multiplier(), base.resistances)
+    }
+
+    /// Damage left after resistances and, for physical damage, armor.
+    pub fn mitigate(&self, damage: f32, damage_type: DamageType) -> f32 {
+        let mut damage = damage * self.resistances.multiplier(damage_type);
+        if damage_type.is_physical() {
+            damage *= 1.0 - armor_mitigation(self.armor);
+        }
+        damage.max(0.0)
+    }
+}
+
+/// The outcome of one blow.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Hit {
+    pub damage: f32,
+    pub damage_type: DamageType,
+    pub critical: bool,
+    pub resisted: f32, // Turned aside by the defense
+}
+
+/// Resolve a blow of `base` damage against a defense.
+///
+/// `roll` is a uniform random number in [0, 1); the blow is critical when it
+/// falls under `crit_chance`.
+pub fn resolve_hit(base: f32, damage_type: DamageType, defense: &Defense, crit_chance: f32, roll: f32) -> Hit {
+    let critical = roll < crit_chance;
+    let damage = if critical { base * CRIT_MULTIPLIER } else { base };
+    let mitigated = defense.mitigate(damage, damage_type);
+    Hit {
+        damage: mitigated,
+        damage_type,
+        critical,
+        resisted: damage - mitigated,
+    }
 }
 
 /// Combat state for a single entity.
@@ -136,8 +488,11 @@ pub struct CombatState {
     pub in_combat: bool,
     pub current_target: Option<u64>, // Entity ID
     pub last_action: Option<CombatAction>,
-    pub action_cooldown: f32,
+    #[serde(default)]
+    pub cooldowns: HashMap<CombatAction, f32>, // Seconds until each action is ready again
     pub combo_window: f32, // Time remaining to complete a skill chain
+    #[serde(default)]
+    pub chain_history: Vec<CombatAction>, // Recent actions a skill chain may build on
 }
 
 impl CombatState {
@@ -146,41 +501,58 @@ impl CombatState {
             in_combat: false,
             current_target: None,
             last_action: None,
-            action_cooldown: 0.0,
+            cooldowns: HashMap::new(),
             combo_window: 0.0,
+            chain_history: Vec::new(),
         }
     }
 
     /// Attempt to perform an action.
     pub fn perform_action(&mut self, action: CombatAction) -> bool {
-        if self.action_cooldown > 0.0 {
+        self.perform_scaled(action, 1.0)
+    }
+
+    /// Check if an action's cooldown has run out. Each action keeps its own.
+    pub fn is_ready(&self, action: CombatAction) -> bool {
+        !self.cooldowns.contains_key(&action)
+    }
+
+    /// Attempt to perform an action with its cooldown scaled (e.g. by ability modifiers).
+    pub fn perform_scaled(&mut self, action: CombatAction, cooldown_multiplier: f32) -> bool {
+        if !self.is_ready(action) {
             return false; // Still on cooldown
         }
 
+        if self.combo_window <= 0.0 {
+            self.chain_history.clear();
+        }
+        self.chain_history.push(action);
+        if self.chain_history.len() > MAX_CHAIN_STEPS {
+            self.chain_history.remove(0);
+        }
         self.last_action = Some(action);
-        self.action_cooldown = action.cooldown();
+        self.cooldowns.insert(action, action.cooldown() * cooldown_multiplier);
         self.combo_window = 3.0; // 3 seconds to chain
 
         true
     }
 
-    /// Check if a skill chain can be triggered.
-    pub fn check_skill_chain(&self, second_action: CombatAction) -> Option<SkillChain> {
-        if let Some(first_action) = self.last_action {
-            if self.combo_window > 0.0 {
-                for chain in get_skill_chains() {
-                    if chain.matches(first_action, second_action) {
-                        return Some(chain);
-                    }
-                }
-            }
+    /// Check if an action would close a skill chain, returning the longest.
+    pub fn check_skill_chain(&self, action: CombatAction) -> Option<&'static SkillChain> {
+        if self.combo_window <= 0.0 {
+            return None;
         }
-        None
+        let mut history = self.chain_history.clone();
+        history.push(action);
+        skill_chains().closing(&history).first().copied()
     }
 
     /// Update timers.
     pub fn update(&mut self, delta: f32) {
-        self.action_cooldown = (self.action_cooldown - delta).max(0.0);
+        self.cooldowns.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
         self.combo_window = (self.combo_window - delta).max(0.0);
     }
 }
@@ -191,30 +563,286 @@ impl Default for CombatState {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// How a job's resource changes one action.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActionBoost {
+    pub damage: f32,   // Damage multiplier
+    pub cooldown: f32, // Cooldown multiplier
+    pub wear: f32,     // Weapon durability lost
+}
 
-    #[test]
-    fn test_skill_chain_matching() {
-        let chain = SkillChain::new(
-            CombatAction::HunterThrust,
-            CombatAction::ForgeSmash,
-            2.5,
-            "Shatter".to_string(),
-        );
+impl Default for ActionBoost {
+    fn default() -> Self {
+        Self { damage: 1.0, cooldown: 1.0, wear: 0.0 }
+    }
+}
+
+/// A song being channelled by a Psalmist.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SongChannel {
+    pub song: CombatAction,
+    pub remaining: f32,
+    pulse_timer: f32,
+}
+
+/// Each job's core mechanic, kept as a resource.
+///
+/// - Levite: incense, spent by prayers and heals and burned back by the censer.
+/// - Hunter: momentum, stacked by each attack to quicken the next.
+/// - Forge: heat, which strengthens blows but wears the weapon, spent by quenching.
+/// - Psalmist: a channelled song that pulses its aura until interrupted.
+///
+/// The Shepherd's threat is generated per action; see [`threat_for`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct JobResources {
+    pub incense: f32,
+    pub momentum: u32,
+    pub heat: f32,
+    pub channel: Option<SongChannel>,
+    momentum_timer: f32,
+}
+
+impl JobResources {
+    pub fn new() -> Self {
+        Self {
+            incense: MAX_INCENSE,
+            momentum: 0,
+            heat: 0.0,
+            channel: None,
+            momentum_timer: 0.0,
+        }
+    }
+
+    /// Check the resources allow an action.
+    pub fn check(&self, action: CombatAction) -> Result<()> {
+        if action.incense_cost() > self.incense {
+            return Err(AntediluviaError::CombatError("not enough incense".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Spend and build resources for an action, returning how they change it.
+    /// Any action breaks a channelled song; a new song starts another.
+    pub fn commit(&mut self, action: CombatAction) -> ActionBoost {
+        let mut boost = ActionBoost::default();
+        self.channel = None;
+        match action.job() {
+            Job::Levite => {
+                self.incense = (self.incense - action.incense_cost()).clamp(0.0, MAX_INCENSE);
+            }
+            Job::Hunter => {
+                boost.cooldown = 1.0 - self.momentum as f32 * MOMENTUM_HASTE;
+                self.momentum = (self.momentum + 1).min(MAX_MOMENTUM);
+                self.momentum_timer = MOMENTUM_DECAY_SECONDS;
+            }
+            Job::Forge => {
+                let heat = self.heat / MAX_HEAT;
+                if action == CombatAction::ForgeQuench {
+                    // Quenching spends all the heat in one blow
+                    boost.damage = 1.0 + heat;
+                    self.heat = 0.0;
+                } else {
+                    boost.damage = 1.0 + heat * HEAT_DAMAGE_BONUS;
+                    boost.wear = heat * HEAT_WEAR;
+                    self.heat = (self.heat + action.heat()).min(MAX_HEAT);
+                }
+            }
+            Job::Psalmist if action.is_channelled() => {
+                self.channel = Some(SongChannel {
+                    song: action,
+                    remaining: SONG_CHANNEL_SECONDS,
+                    pulse_timer: SONG_PULSE_SECONDS,
+                });
+            }
+            _ => {}
+        }
+        boost
+    }
+
+    /// Advance timers. Returns the channelled song if it pulsed.
+    pub fn update(&mut self, delta: f32) -> Option<CombatAction> {
+        self.incense = (self.incense + INCENSE_REGEN * delta).min(MAX_INCENSE);
+        self.heat = (self.heat - HEAT_COOLING * delta).max(0.0);
+        self.momentum_timer = (self.momentum_timer - delta).max(0.0);
+        if self.momentum_timer <= 0.0 {
+            self.momentum = 0;
+        }
 
-        assert!(chain.matches(CombatAction::HunterThrust, CombatAction::ForgeSmash));
-        assert!(!chain.matches(CombatAction::HunterSlash, CombatAction::ForgeSmash));
+        let channel = self.channel.as_mut()?;
+        let elapsed = delta.min(channel.remaining);
+        channel.remaining -= elapsed;
+        channel.pulse_timer -= elapsed;
+        let pulse = if channel.pulse_timer <= 0.0 {
+            channel.pulse_timer += SONG_PULSE_SECONDS;
+            Some(channel.song)
+        } else {
+            None
+        };
+        if channel.remaining <= 0.0 {
+            self.channel = None;
+        }
+        pulse
     }
+}
+
+impl Default for JobResources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the threat an action generates against the mob it strikes. Shepherds
+/// draw far more for the same damage, and Rebuke draws it outright.
+pub fn threat_for(action: CombatAction, damage: f32, healing: f32) -> f32 {
+    let scale = if action.job() == Job::Shepherd { SHEPHERD_THREAT } else { 1.0 };
+    let rebuke = if action == CombatAction::ShepherdRebuke { REBUKE_THREAT } else { 0.0 };
+    damage * scale + healing * HEALING_THREAT + rebuke
+}
+
+/// Check nothing in the terrain blocks the line between two combatants.
+///
+/// `ground` gives the terrain height at an (x, z) coordinate.
+pub fn has_line_of_sight(from: Vec3, to: Vec3, ground: impl Fn(f32, f32) -> f32) -> bool {
+    let eye = Vec3::new(0.0, EYE_HEIGHT, 0.0);
+    let (from, to) = (from + eye, to + eye);
+    let steps = (from.distance(to) / LINE_OF_SIGHT_STEP).ceil().max(1.0) as usize;
+    (1..steps).all(|i| {
+        let point = from.lerp(to, i as f32 / steps as f32);
+        ground(point.x, point.z) <= point.y
+    })
+}
+
+/// Check a target at `to` is within reach of `action` from `from`, with a clear line of sight.
+pub fn check_reach(action: CombatAction, from: Vec3, to: Vec3, ground: impl Fn(f32, f32) -> f32) -> Result<()> {
+    if from.distance(to) > action.range() {
+        return Err(AntediluviaError::CombatError("target out of range".to_string()));
+    }
+    if !has_line_of_sight(from, to, ground) {
+        return Err(AntediluviaError::CombatError("no line of sight".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_combat_state() {
         let mut state = CombatState::new();
         assert!(state.perform_action(CombatAction::HunterThrust));
-        assert!(!state.perform_action(CombatAction::ForgeSmash)); // Still on cooldown
+        assert!(!state.perform_action(CombatAction::HunterThrust)); // Still on cooldown
+        assert!(state.is_ready(CombatAction::ForgeSmash)); // Each action keeps its own
 
-        state.update(3.0); // Wait for cooldown
+        state.update(2.5); // Wait for cooldown
+        assert!(state.is_ready(CombatAction::HunterThrust));
+        assert_eq!(state.check_skill_chain(CombatAction::ForgeSmash).map(|c| c.name.as_str()), Some("Shatter"));
         assert!(state.perform_action(CombatAction::ForgeSmash));
+
+        // Too late to carry Shatter on into Magma
+        state.update(3.5);
+        assert!(state.check_skill_chain(CombatAction::ForgeFire).is_none());
+    }
+
+    #[test]
+    fn test_action_wire_names() {
+        for action in [CombatAction::ShepherdBulwark, CombatAction::HunterGiantsBane, CombatAction::PsalmistAscent] {
+            assert_eq!(CombatAction::from_name(&format!("{:?}", action)), Some(action));
+        }
+        assert_eq!(CombatAction::from_name("Smite"), None);
+    }
+
+    #[test]
+    fn test_reach_and_line_of_sight() {
+        let flat = |_: f32, _: f32| 0.0;
+        let origin = Vec3::ZERO;
+
+        assert!(check_reach(CombatAction::HunterThrust, origin, Vec3::new(20.0, 0.0, 0.0), flat).is_ok());
+        assert!(check_reach(CombatAction::HunterThrust, origin, Vec3::new(100.0, 0.0, 0.0), flat).is_err());
+        assert!(check_reach(CombatAction::ShepherdSling, origin, Vec3::new(100.0, 0.0, 0.0), flat).is_ok());
+
+        // A ridge between the two blocks the shot
+        let ridge = |x: f32, _: f32| if (40.0..60.0).contains(&x) { 20.0 } else { 0.0 };
+        assert!(check_reach(CombatAction::ShepherdSling, origin, Vec3::new(100.0, 0.0, 0.0), ridge).is_err());
+    }
+
+    #[test]
+    fn test_armor_mitigation() {
+        assert_eq!(armor_mitigation(0.0), 0.0);
+        assert_eq!(armor_mitigation(ARMOR_SCALE), 0.5);
+        assert_eq!(armor_mitigation(10_000.0), MAX_MITIGATION);
+        assert_eq!(armor_mitigation(-50.0), 0.0);
+
+        // Armor stops blows but not fire
+        let tunic = Defense::from_equipment(["Linen Tunic", "Bronze Sword"]);
+        assert_eq!(tunic.armor, 10.0);
+        let plate = Defense::new(ARMOR_SCALE, Resistances::NEUTRAL);
+        assert_eq!(plate.mitigate(40.0, DamageType::Slash), 20.0);
+        assert_eq!(plate.mitigate(40.0, DamageType::Fire), 40.0);
+    }
+
+    #[test]
+    fn test_resistances_and_crits() {
+        let corrupted = Defense::new(0.0, Resistances::for_mob(MobType::Corrupted));
+        assert_eq!(corrupted.mitigate(50.0, DamageType::Holy), 100.0);
+        assert_eq!(corrupted.mitigate(40.0, DamageType::Fire), 30.0);
+
+        // Giant's Bane pierces where a smash glances off
+        let nephilim = Defense::for_mob(MobType::Nephilim, 0);
+        let bane = nephilim.mitigate(100.0, CombatAction::HunterGiantsBane.damage_type());
+        let smash = nephilim.mitigate(100.0, CombatAction::ForgeSmash.damage_type());
+        assert!(bane > smash);
+
+        let defense = Defense::new(0.0, Resistances::NEUTRAL);
+        let normal = resolve_hit(40.0, DamageType::Pierce, &defense, CRIT_CHANCE, 0.5);
+        assert!(!normal.critical);
+        assert_eq!(normal.damage, 40.0);
+        let critical = resolve_hit(40.0, DamageType::Pierce, &defense, CRIT_CHANCE, 0.01);
+        assert!(critical.critical);
+        assert_eq!(critical.damage, 40.0 * CRIT_MULTIPLIER);
+    }
+
+    #[test]
+    fn test_job_resources() {
+        let mut res = JobResources::new();
+
+        // Incense is spent by heals and burned back by the censer
+        for _ in 0..5 {
+            res.commit(CombatAction::LeviteHeal);
+        }
+        assert!(res.check(CombatAction::LeviteAtonement).is_err());
+        res.commit(CombatAction::LeviteCenser);
+        assert_eq!(res.incense, 15.0);
+
+        // Momentum quickens each attack until it lapses
+        assert_eq!(res.commit(CombatAction::HunterThrust).cooldown, 1.0);
+        res.commit(CombatAction::HunterSlash);
+        assert!((res.commit(CombatAction::HunterLunge).cooldown - 0.84).abs() < 1e-5);
+        res.update(MOMENTUM_DECAY_SECONDS);
+        assert_eq!(res.momentum, 0);
+
+        // Heat strengthens blows and wears the weapon until quenched
+        res.heat = MAX_HEAT;
+        let hot = res.commit(CombatAction::ForgeSmash);
+        assert_eq!(hot.damage, 1.0 + HEAT_DAMAGE_BONUS);
+        assert_eq!(hot.wear, HEAT_WEAR);
+        assert_eq!(res.commit(CombatAction::ForgeQuench).damage, 2.0);
+        assert_eq!(res.heat, 0.0);
+
+        // A channelled song pulses until another action breaks it
+        res.commit(CombatAction::PsalmistSong);
+        assert_eq!(res.update(SONG_PULSE_SECONDS), Some(CombatAction::PsalmistSong));
+        assert_eq!(res.update(1.0), None);
+        res.commit(CombatAction::PsalmistLament);
+        assert!(res.channel.is_none());
+    }
+
+    #[test]
+    fn test_threat() {
+        let rebuke = threat_for(CombatAction::ShepherdRebuke, 20.0, 0.0);
+        let thrust = threat_for(CombatAction::HunterThrust, 50.0, 0.0);
+        assert_eq!(rebuke, 20.0 * SHEPHERD_THREAT + REBUKE_THREAT);
+        assert!(rebuke > thrust);
+        assert_eq!(threat_for(CombatAction::LeviteHeal, 0.0, 50.0), 25.0);
     }
 }
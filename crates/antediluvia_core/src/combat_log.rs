@@ -0,0 +1,238 @@
+//! The combat log: a structured record of every blow and heal.
+//!
+//! Each resolved hit or heal becomes a [`CombatEvent`]. The server writes the
+//! stream to disk and tells clients of each event, clients show the recent
+//! ones in a panel, and a stream can be replayed into damage meters.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use crate::combat::{CombatAction, Hit};
+use crate::skill_chain::SkillChain;
+
+/// Events a client keeps for its combat log panel.
+pub const COMBAT_LOG_CAPACITY: usize = 200;
+
+/// One blow or heal.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CombatEvent {
+    pub timestamp: f32, // Seconds since the world began
+    pub attacker: u64,
+    pub target: u64,
+    pub action: Option<CombatAction>, // None for damage over time
+    pub damage: f32,
+    pub healing: f32,
+    pub chain: Option<String>, // The skill chain the blow closed
+    pub critical: bool,
+    pub resisted: f32, // Damage the target's defenses turned aside
+    pub killing_blow: bool,
+}
+
+impl CombatEvent {
+    /// A blow that landed, as resolved against the target's defenses.
+    pub fn hit(timestamp: f32, attacker: u64, target: u64, action: CombatAction, hit: &Hit, chain: Option<&SkillChain>) -> Self {
+        Self {
+            timestamp,
+            attacker,
+            target,
+            action: Some(action),
+            damage: hit.damage,
+            healing: 0.0,
+            chain: chain.map(|c| c.name.clone()),
+            critical: hit.critical,
+            resisted: hit.resisted,
+            killing_blow: false,
+        }
+    }
+
+    /// A blow that bypasses defenses: the Leviathan's hide, or a status
+    /// effect's damage over time (with no action).
+    pub fn blow(timestamp: f32, attacker: u64, target: u64, action: Option<CombatAction>, damage: f32) -> Self {
+        Self {
+            timestamp,
+            attacker,
+            target,
+            action,
+            damage,
+            healing: 0.0,
+            chain: None,
+            critical: false,
+            resisted: 0.0,
+            killing_blow: false,
+        }
+    }
+
+    /// A heal.
+    pub fn heal(timestamp: f32, healer: u64, target: u64, action: CombatAction, healing: f32) -> Self {
+        Self {
+            healing,
+            ..Self::blow(timestamp, healer, target, Some(action), 0.0)
+        }
+    }
+
+    /// Describe the event in a line, naming entities with `name`.
+    pub fn describe(&self, name: impl Fn(u64) -> String) -> String {
+        let action = self.action.map_or("wounds".to_string(), |a| format!("{:?}", a));
+        let mut line = if self.healing > 0.0 {
+            format!("{} heals {} with {} for {:.0}", name(self.attacker), name(self.target), action, self.healing)
+        } else {
+            format!("{} hits {} with {} for {:.0}", name(self.attacker), name(self.target), action, self.damage)
+        };
+        if self.critical {
+            line.push_str(" (critical)");
+        }
+        if self.resisted > 0.0 {
+            line.push_str(&format!(" ({:.0} resisted)", self.resisted));
+        }
+        if let Some(chain) = &self.chain {
+            line.push_str(&format!(" [{}]", chain));
+        }
+        if self.killing_blow {
+            line.push_str(" — killing blow");
+        }
+        line
+    }
+}
+
+/// The most recent combat events, oldest first.
+#[derive(Clone, Debug)]
+pub struct CombatLog {
+    events: VecDeque<CombatEvent>,
+    capacity: usize,
+}
+
+impl CombatLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record an event, forgetting the oldest once full.
+    pub fn record(&mut self, event: CombatEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Iterate over the events, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CombatEvent> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl Default for CombatLog {
+    fn default() -> Self {
+        Self::new(COMBAT_LOG_CAPACITY)
+    }
+}
+
+/// One combatant's totals over a stretch of combat.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageMeter {
+    pub damage: f32,
+    pub healing: f32,
+    pub hits: u32,
+    pub criticals: u32,
+    pub first: f32, // Timestamp of the first event
+    pub last: f32, // Timestamp of the last event
+}
+
+impl DamageMeter {
+    /// Add an event to the totals.
+    pub fn add(&mut self, event: &CombatEvent) {
+        if self.hits == 0 {
+            self.first = event.timestamp;
+        }
+        self.damage += event.damage;
+        self.healing += event.healing;
+        self.hits += 1;
+        self.criticals += event.critical as u32;
+        self.last = event.timestamp;
+    }
+
+    /// Get the seconds from the first event to the last, at least one.
+    pub fn duration(&self) -> f32 {
+        (self.last - self.first).max(1.0)
+    }
+
+    /// Get the damage dealt per second.
+    pub fn dps(&self) -> f32 {
+        self.damage / self.duration()
+    }
+
+    /// Get the healing done per second.
+    pub fn hps(&self) -> f32 {
+        self.healing / self.duration()
+    }
+}
+
+/// Replay a stream of events into a damage meter for each attacker.
+pub fn replay<'a>(events: impl IntoIterator<Item = &'a CombatEvent>) -> HashMap<u64, DamageMeter> {
+    let mut meters: HashMap<u64, DamageMeter> = HashMap::new();
+    for event in events {
+        meters.entry(event.attacker).or_default().add(event);
+    }
+    meters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{resolve_hit, Defense, CRIT_CHANCE};
+    use crate::mob::MobType;
+
+    #[test]
+    fn test_events_from_resolved_hits() {
+        let defense = Defense::for_mob(MobType::Lion, 5);
+        let action = CombatAction::HunterThrust;
+        let hit = resolve_hit(40.0, action.damage_type(), &defense, CRIT_CHANCE, 0.99);
+        let event = CombatEvent::hit(1.0, 7, 1001, action, &hit, None);
+        assert!(!event.critical);
+        assert!(event.resisted > 0.0); // A lion's hide turns some of it
+        assert!((event.damage + event.resisted - 40.0).abs() < 0.01);
+
+        let line = event.describe(|id| format!("#{}", id));
+        assert!(line.starts_with("#7 hits #1001 with HunterThrust"));
+        assert!(line.contains("resisted"));
+    }
+
+    #[test]
+    fn test_replay_dps_meters() {
+        // A hunter thrusts every two seconds for ten seconds; a Levite heals once
+        let mut log = CombatLog::new(50);
+        for i in 0..=5 {
+            log.record(CombatEvent::blow(i as f32 * 2.0, 1, 1001, Some(CombatAction::HunterThrust), 30.0));
+        }
+        log.record(CombatEvent::heal(4.0, 2, 1, CombatAction::LeviteHeal, 25.0));
+        log.record(CombatEvent::blow(10.0, 1, 1001, None, 5.0)); // A bleed ticking
+
+        let meters = replay(log.iter());
+        let hunter = &meters[&1];
+        assert_eq!(hunter.hits, 7);
+        assert_eq!(hunter.damage, 185.0);
+        assert_eq!(hunter.dps(), 18.5);
+        assert_eq!(meters[&2].hps(), 25.0); // A single heal counts over one second
+
+        // The log keeps only the most recent events
+        let mut short = CombatLog::new(3);
+        for event in log.iter().cloned() {
+            short.record(event);
+        }
+        assert_eq!(short.len(), 3);
+        assert_eq!(short.iter().map(|e| e.timestamp).collect::<Vec<_>>(), vec![10.0, 4.0, 10.0]);
+    }
+}
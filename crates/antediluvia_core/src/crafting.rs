@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::entity::Lineage;
 
 /// A craftable item.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -12,6 +13,17 @@ pub struct CraftableItem {
     pub recipe: Recipe,
     pub quality: ItemQuality,
     pub corruption_cost: f32, // Crafting this increases corruption
+    #[serde(default)]
+    pub weight: f32, // Of the item made
+    #[serde(default)]
+    pub lineage: Option<Lineage>, // Only this house knows the recipe
+}
+
+impl CraftableItem {
+    /// Check if a lineage knows this recipe.
+    pub fn available_to(&self, lineage: Lineage) -> bool {
+        self.lineage.is_none_or(|l| l == lineage)
+    }
 }
 
 /// A recipe for crafting.
@@ -34,6 +46,18 @@ pub enum ItemQuality {
 }
 
 impl ItemQuality {
+    /// Parse a quality from its wire name (the variant name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Slag" => Some(ItemQuality::Slag),
+            "Standard" => Some(ItemQuality::Standard),
+            "Fine" => Some(ItemQuality::Fine),
+            "Masterwork" => Some(ItemQuality::Masterwork),
+            "Legendary" => Some(ItemQuality::Legendary),
+            _ => None,
+        }
+    }
+
     /// Get the damage multiplier for this quality.
     pub fn damage_multiplier(&self) -> f32 {
         match self {
@@ -94,6 +118,11 @@ impl CraftedItem {
     }
 }
 
+/// A crafter's skill at a character level.
+pub fn crafting_skill(level: u32) -> u32 {
+    level * 5
+}
+
 /// The crafting system.
 pub struct CraftingSystem {
     recipes: HashMap<String, CraftableItem>,
@@ -122,6 +151,8 @@ impl CraftingSystem {
                 },
                 quality: ItemQuality::Standard,
                 corruption_cost: 0.0,
+                weight: 8.0,
+                lineage: None,
             },
         );
 
@@ -143,6 +174,31 @@ impl CraftingSystem {
                 },
                 quality: ItemQuality::Fine,
                 corruption_cost: 5.0, // Crafting iron increases corruption
+                weight: 10.0,
+                lineage: Some(Lineage::Cain),
+            },
+        );
+
+        // Sorcerer's Charm (Forbidden Arts)
+        recipes.insert(
+            "Sorcerer's Charm".to_string(),
+            CraftableItem {
+                name: "Sorcerer's Charm".to_string(),
+                recipe: Recipe {
+                    ingredients: {
+                        let mut m = HashMap::new();
+                        m.insert("Wolf Fang".to_string(), 2);
+                        m.insert("Thread".to_string(), 1);
+                        m
+                    },
+                    time_seconds: 25.0,
+                    skill_required: "Sorcery".to_string(),
+                    skill_level_required: 15,
+                },
+                quality: ItemQuality::Fine,
+                corruption_cost: 8.0,
+                weight: 1.0,
+                lineage: Some(Lineage::Cain),
             },
         );
 
@@ -164,6 +220,8 @@ impl CraftingSystem {
                 },
                 quality: ItemQuality::Standard,
                 corruption_cost: 0.0,
+                weight: 3.0,
+                lineage: None,
             },
         );
 
@@ -213,6 +271,11 @@ impl CraftingSystem {
     pub fn get_all_recipes(&self) -> Vec<&CraftableItem> {
         self.recipes.values().collect()
     }
+
+    /// Get the recipes a lineage knows.
+    pub fn recipes_for(&self, lineage: Lineage) -> Vec<&CraftableItem> {
+        self.recipes.values().filter(|r| r.available_to(lineage)).collect()
+    }
 }
 
 impl Default for CraftingSystem {
@@ -237,11 +300,22 @@ mod tests {
         assert_eq!(item.unwrap().quality, ItemQuality::Fine);
     }
 
+    #[test]
+    fn test_forbidden_recipes_are_cainite() {
+        let system = CraftingSystem::new();
+        let seth: Vec<_> = system.recipes_for(Lineage::Seth).iter().map(|r| r.name.clone()).collect();
+        assert!(seth.contains(&"Bronze Sword".to_string()));
+        assert!(!seth.contains(&"Iron Sword".to_string()));
+        assert!(!seth.contains(&"Sorcerer's Charm".to_string()));
+        assert_eq!(system.recipes_for(Lineage::Cain).len(), system.get_all_recipes().len());
+    }
+
     #[test]
     fn test_item_durability() {
         let mut item = CraftedItem::new("Bronze Sword".to_string(), ItemQuality::Masterwork);
         assert_eq!(item.max_durability, 200.0);
         item.degrade(50.0);
         assert_eq!(item.durability, 150.0);
+        assert_eq!(ItemQuality::from_name(&format!("{:?}", item.quality)), Some(ItemQuality::Masterwork));
     }
 }
@@ -0,0 +1,267 @@
+//! Death and its price.
+//!
+//! A fallen player leaves a corpse holding part of their satchel, takes on an
+//! experience debt, and their weapon is worn. They rise again at the altar or
+//! campfire their soul is bound to, and must run back to their corpse to
+//! recover what it holds. Once the Flood has begun, death is final for the
+//! rest of the season.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use crate::endgame::FloodPhase;
+use crate::entity::{Lineage, Zone};
+use crate::mob::LootDrop;
+
+/// Seconds the fallen wait before rising.
+pub const RESPAWN_SECONDS: f32 = 5.0;
+
+/// Share of each satchel stack left on the corpse (rounded up).
+pub const CORPSE_SHARE: f32 = 0.5;
+
+/// Seconds a corpse lies before it crumbles, along with what it holds.
+pub const CORPSE_DECAY_SECONDS: f32 = 1800.0;
+
+/// How close the owner must come to recover a corpse.
+pub const CORPSE_REACH: f32 = 10.0;
+
+/// How close a player must stand to bind their soul to a point.
+pub const BIND_REACH: f32 = 15.0;
+
+/// Share of the experience to the next level owed after a death.
+pub const XP_DEBT_SHARE: f32 = 0.1;
+
+/// Weapon durability lost on death.
+pub const DEATH_WEAR: f32 = 10.0;
+
+/// A place a soul can be bound to, and rise at.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum BindPoint {
+    /// The altar at the foot of the Eden Pillar, where every soul starts.
+    #[default]
+    EdenPillar,
+
+    /// The campfire among Havilah's elders.
+    HavilahCampfire,
+
+    /// The altar at Bethel, Seth's sanctuary.
+    BethelAltar,
+
+    /// The altar in the City of Enoch, Cain's city.
+    EnochAltar,
+}
+
+impl BindPoint {
+    /// Every bind point.
+    pub const ALL: [BindPoint; 4] = [
+        BindPoint::EdenPillar,
+        BindPoint::HavilahCampfire,
+        BindPoint::BethelAltar,
+        BindPoint::EnochAltar,
+    ];
+
+    /// Parse a bind point from its wire name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| format!("{:?}", p) == name)
+    }
+
+    /// Get the name shown to players.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BindPoint::EdenPillar => "the Eden Pillar",
+            BindPoint::HavilahCampfire => "the Havilah campfire",
+            BindPoint::BethelAltar => "the altar at Bethel",
+            BindPoint::EnochAltar => "the altar of Enoch",
+        }
+    }
+
+    /// Get where the point stands.
+    pub fn position(&self) -> Vec3 {
+        match self {
+            BindPoint::EdenPillar => Vec3::new(0.0, 5.0, 100.0),
+            BindPoint::HavilahCampfire => Vec3::new(5.0, 5.0, 15.0),
+            BindPoint::BethelAltar => Zone::Bethel.center() + Vec3::Y * 5.0,
+            BindPoint::EnochAltar => Zone::CityOfEnoch.center() + Vec3::Y * 5.0,
+        }
+    }
+
+    /// Check if a house may rise here. Neither city takes in the other house's dead.
+    pub fn admits(&self, lineage: Lineage) -> bool {
+        Zone::at(self.position()).is_none_or(|zone| lineage.can_enter(zone))
+    }
+
+    /// Find the point a player standing at `position` can bind to. Only the
+    /// distance across the ground counts, whatever the terrain's height.
+    pub fn near(position: Vec3) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| {
+            let offset = p.position() - position;
+            Vec3::new(offset.x, 0.0, offset.z).length() <= BIND_REACH
+        })
+    }
+
+    /// Get where a soul bound here rises. A house barred from the point
+    /// (its lineage having turned since binding) rises at the Eden Pillar.
+    pub fn respawn_point(&self, lineage: Lineage) -> Vec3 {
+        if self.admits(lineage) { self.position() } else { BindPoint::EdenPillar.position() }
+    }
+}
+
+/// What a fallen player leaves behind.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Corpse {
+    pub owner: u64,
+    pub position: Vec3,
+    pub items: Vec<LootDrop>,
+    pub decay: f32, // Seconds until it crumbles
+}
+
+impl Corpse {
+    pub fn new(owner: u64, position: Vec3, items: Vec<LootDrop>) -> Self {
+        Self {
+            owner,
+            position,
+            items,
+            decay: CORPSE_DECAY_SECONDS,
+        }
+    }
+
+    /// Let the corpse lie. Returns false once it has crumbled.
+    pub fn update(&mut self, delta_seconds: f32) -> bool {
+        self.decay -= delta_seconds;
+        self.decay > 0.0
+    }
+
+    /// Check if a player at `position` can recover the corpse. Only its owner can.
+    pub fn can_recover(&self, player: u64, position: Vec3) -> bool {
+        player == self.owner && self.position.distance(position) <= CORPSE_REACH
+    }
+}
+
+/// Get how much of a stack is left on the corpse.
+pub fn corpse_share(quantity: u32) -> u32 {
+    (quantity as f32 * CORPSE_SHARE).ceil() as u32
+}
+
+/// Lay a fallen player's corpse at `position`, taking its share of every
+/// stack out of their satchel.
+pub fn lay_corpse(owner: u64, position: Vec3, satchel: &mut Vec<LootDrop>) -> Corpse {
+    let mut items = Vec::new();
+    for stack in satchel.iter_mut() {
+        let share = corpse_share(stack.quantity).min(stack.quantity);
+        if share > 0 {
+            stack.quantity -= share;
+            items.push(LootDrop { quantity: share, ..stack.clone() });
+        }
+    }
+    satchel.retain(|stack| stack.quantity > 0);
+    Corpse::new(owner, position, items)
+}
+
+/// Get the experience the next level takes from `level`.
+pub fn xp_to_next_level(level: u32) -> f32 {
+    if level <= 1 { 100.0 } else { 100.0 * (1.0 + level as f32 * 0.5) }
+}
+
+/// A player's progress towards their next level, and what they owe for dying.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Experience {
+    pub level: u32,
+    pub earned: f32, // Towards the next level
+    pub debt: f32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, earned: 0.0, debt: 0.0 }
+    }
+}
+
+impl Experience {
+    /// Take on the debt for dying, and get the debt now owed.
+    pub fn die(&mut self) -> f32 {
+        self.debt += xp_debt(xp_to_next_level(self.level));
+        self.debt
+    }
+
+    /// Earn experience, repaying any debt first and rising as many levels as
+    /// it reaches.
+    pub fn gain(&mut self, xp: f32) {
+        self.earned += repay_xp_debt(&mut self.debt, xp);
+        while self.earned >= xp_to_next_level(self.level) {
+            self.earned -= xp_to_next_level(self.level);
+            self.level += 1;
+        }
+    }
+}
+
+/// Get the experience owed for dying, given what the next level takes.
+pub fn xp_debt(xp_to_next_level: f32) -> f32 {
+    xp_to_next_level * XP_DEBT_SHARE
+}
+
+/// Pay experience towards a debt, returning what is left over to keep.
+pub fn repay_xp_debt(debt: &mut f32, xp: f32) -> f32 {
+    let paid = xp.min(*debt);
+    *debt -= paid;
+    xp - paid
+}
+
+/// Check if death is final: once the Flood has begun, the fallen do not rise
+/// again this season.
+pub fn death_is_final(phase: &FloodPhase) -> bool {
+    *phase != FloodPhase::PreFlood
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_points() {
+        assert_eq!(BindPoint::near(Vec3::new(8.0, 5.0, 18.0)), Some(BindPoint::HavilahCampfire));
+        assert_eq!(BindPoint::near(Vec3::new(500.0, 5.0, 500.0)), None);
+        assert_eq!(BindPoint::from_name("BethelAltar"), Some(BindPoint::BethelAltar));
+
+        // A soul bound at Bethel that has since turned to Cain rises at the Pillar
+        assert!(BindPoint::BethelAltar.admits(Lineage::Seth));
+        assert_eq!(BindPoint::BethelAltar.respawn_point(Lineage::Cain), BindPoint::EdenPillar.position());
+        assert_eq!(BindPoint::HavilahCampfire.respawn_point(Lineage::Cain), BindPoint::HavilahCampfire.position());
+    }
+
+    #[test]
+    fn test_corpse_and_debt() {
+        assert_eq!(corpse_share(5), 3);
+        assert_eq!(corpse_share(1), 1);
+
+        let wood = LootDrop { name: "Cypress Wood".to_string(), quantity: 3, weight: 2.0 };
+        let mut corpse = Corpse::new(1, Vec3::ZERO, vec![wood]);
+        assert!(corpse.can_recover(1, Vec3::new(5.0, 0.0, 0.0)));
+        assert!(!corpse.can_recover(2, Vec3::ZERO)); // Not theirs
+        assert!(!corpse.can_recover(1, Vec3::new(50.0, 0.0, 0.0)));
+        assert!(corpse.update(60.0));
+        assert!(!corpse.update(CORPSE_DECAY_SECONDS));
+
+        let mut debt = xp_debt(200.0);
+        assert_eq!(repay_xp_debt(&mut debt, 15.0), 0.0);
+        assert_eq!(repay_xp_debt(&mut debt, 15.0), 10.0);
+        assert_eq!(debt, 0.0);
+
+        // Half of each stack goes to the corpse, and the satchel keeps the rest
+        let mut satchel = vec![
+            LootDrop { name: "Iron Ingot".to_string(), quantity: 5, weight: 3.0 },
+            LootDrop { name: "Healing Herb".to_string(), quantity: 1, weight: 0.5 },
+        ];
+        let corpse = lay_corpse(1, Vec3::ZERO, &mut satchel);
+        assert_eq!(corpse.items.iter().map(|i| i.quantity).collect::<Vec<_>>(), vec![3, 1]);
+        assert_eq!(satchel.len(), 1);
+        assert_eq!((satchel[0].name.as_str(), satchel[0].quantity), ("Iron Ingot", 2));
+
+        // Debt is owed against the next level, and paid before anything counts towards it
+        let mut experience = Experience::default();
+        assert_eq!(experience.die(), xp_debt(100.0));
+        experience.gain(10.0 + 100.0);
+        assert_eq!((experience.level, experience.debt, experience.earned), (2, 0.0, 0.0));
+
+        assert!(!death_is_final(&FloodPhase::PreFlood));
+        assert!(death_is_final(&FloodPhase::Rising));
+    }
+}
@@ -0,0 +1,175 @@
+//! The two houses of humanity and what belonging to one means.
+//!
+//! Lineage follows corruption, but with a hysteresis band around the threshold
+//! so a player hovering at 50.0 does not flicker between houses.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+
+/// Corruption at which lineage turns.
+pub const LINEAGE_THRESHOLD: f32 = 50.0;
+
+/// Half-width of the band around the threshold in which lineage holds.
+pub const LINEAGE_HYSTERESIS: f32 = 5.0;
+
+/// Reputation an NPC of the player's own house starts with.
+pub const KIN_REPUTATION: f32 = 10.0;
+
+/// Reputation an NPC of the other house starts with.
+pub const RIVAL_REPUTATION: f32 = -25.0;
+
+/// The two houses of humanity.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Lineage {
+    /// The House of Seth. Faith, Discipline, Integrity.
+    #[default]
+    Seth,
+
+    /// The House of Cain. Technology, Ambition, Corruption.
+    Cain,
+}
+
+impl Lineage {
+    /// Parse a lineage from its stored name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Seth" => Some(Lineage::Seth),
+            "Cain" => Some(Lineage::Cain),
+            _ => None,
+        }
+    }
+
+    /// Corruption a new character of this lineage starts with.
+    pub fn starting_corruption(&self) -> f32 {
+        match self {
+            Lineage::Seth => 0.0,
+            Lineage::Cain => LINEAGE_THRESHOLD + LINEAGE_HYSTERESIS * 2.0,
+        }
+    }
+
+    /// The lineage after corruption changes to `corruption`.
+    ///
+    /// Turning to Cain needs corruption above the band; returning to Seth
+    /// needs it below the band. Inside the band the current lineage holds.
+    pub fn resolve(self, corruption: f32) -> Lineage {
+        match self {
+            Lineage::Seth if corruption > LINEAGE_THRESHOLD + LINEAGE_HYSTERESIS => Lineage::Cain,
+            Lineage::Cain if corruption < LINEAGE_THRESHOLD - LINEAGE_HYSTERESIS => Lineage::Seth,
+            _ => self,
+        }
+    }
+
+    /// Check if this lineage may enter a zone.
+    pub fn can_enter(&self, zone: Zone) -> bool {
+        match zone {
+            Zone::CityOfEnoch => *self == Lineage::Cain,
+            Zone::Bethel => *self == Lineage::Seth,
+        }
+    }
+
+    /// Starting reputation with an NPC of the given lineage.
+    pub fn reputation_with(&self, npc_lineage: Option<Lineage>) -> f32 {
+        match npc_lineage {
+            Some(l) if l == *self => KIN_REPUTATION,
+            Some(_) => RIVAL_REPUTATION,
+            None => 0.0,
+        }
+    }
+
+    /// Cosmetic appearance for this lineage.
+    pub fn appearance(&self) -> LineageAppearance {
+        match self {
+            Lineage::Seth => LineageAppearance {
+                robe_color: [0.3, 0.25, 0.55],
+                skin_tint: [0.72, 0.56, 0.42],
+                marked: false,
+            },
+            Lineage::Cain => LineageAppearance {
+                robe_color: [0.45, 0.12, 0.1],
+                skin_tint: [0.62, 0.5, 0.4],
+                marked: true,
+            },
+        }
+    }
+}
+
+/// How a lineage looks (linear RGB).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LineageAppearance {
+    pub robe_color: [f32; 3],
+    pub skin_tint: [f32; 3],
+    pub marked: bool, // The Mark of Cain
+}
+
+/// A zone closed to one of the houses.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Zone {
+    /// The City of Enoch in the land of Nod. Cain's city.
+    CityOfEnoch,
+
+    /// Bethel, where the ladder reaches heaven. Seth's sanctuary.
+    Bethel,
+}
+
+impl Zone {
+    /// Center of the zone.
+    pub fn center(&self) -> Vec3 {
+        match self {
+            Zone::CityOfEnoch => Vec3::new(8000.0, 0.0, 0.0),
+            Zone::Bethel => Vec3::new(10000.0, 0.0, 10000.0),
+        }
+    }
+
+    /// Radius of the zone.
+    pub fn radius(&self) -> f32 {
+        match self {
+            Zone::CityOfEnoch => 800.0,
+            Zone::Bethel => 600.0,
+        }
+    }
+
+    /// Get the restricted zone containing a position, if any.
+    pub fn at(position: Vec3) -> Option<Zone> {
+        [Zone::CityOfEnoch, Zone::Bethel].into_iter().find(|zone| {
+            let center = zone.center();
+            Vec3::new(position.x - center.x, 0.0, position.z - center.z).length() < zone.radius()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hysteresis_band() {
+        let mut lineage = Lineage::Seth;
+        for corruption in [49.0, 51.0, 50.5, 54.9] {
+            lineage = lineage.resolve(corruption);
+            assert_eq!(lineage, Lineage::Seth);
+        }
+
+        lineage = lineage.resolve(55.1);
+        assert_eq!(lineage, Lineage::Cain);
+
+        // Dipping back under 50 is not enough to return
+        lineage = lineage.resolve(48.0);
+        assert_eq!(lineage, Lineage::Cain);
+        lineage = lineage.resolve(44.9);
+        assert_eq!(lineage, Lineage::Seth);
+
+        // A born Cainite stays one
+        assert_eq!(Lineage::Cain.resolve(Lineage::Cain.starting_corruption()), Lineage::Cain);
+    }
+
+    #[test]
+    fn test_zone_access() {
+        let enoch = Zone::CityOfEnoch.center();
+        assert_eq!(Zone::at(enoch), Some(Zone::CityOfEnoch));
+        assert_eq!(Zone::at(Vec3::ZERO), None);
+
+        assert!(Lineage::Cain.can_enter(Zone::CityOfEnoch));
+        assert!(!Lineage::Seth.can_enter(Zone::CityOfEnoch));
+        assert!(Lineage::Seth.can_enter(Zone::Bethel));
+    }
+}
@@ -3,10 +3,18 @@
 pub mod player;
 pub mod npc;
 pub mod job;
+pub mod stats;
+pub mod unlock;
+pub mod lineage;
+pub mod reputation;
 
 pub use player::*;
 pub use npc::*;
 pub use job::*;
+pub use stats::*;
+pub use unlock::*;
+pub use lineage::*;
+pub use reputation::*;
 
 use serde::{Deserialize, Serialize};
 use glam::Vec3;
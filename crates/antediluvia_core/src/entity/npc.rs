@@ -1,7 +1,7 @@
 //! NPC (Non-Player Character) definitions.
 
 use serde::{Deserialize, Serialize};
-use super::{Entity, EntityId};
+use super::{Entity, EntityId, Faction, JobTelemetry, Lineage};
 use glam::Vec3;
 
 /// An NPC in the world.
@@ -11,6 +11,10 @@ pub struct NPC {
     pub name: String,
     pub npc_type: NPCType,
     pub dialogue_state: String,
+    #[serde(default)]
+    pub lineage: Option<Lineage>, // None for the unaligned
+    #[serde(default)]
+    pub faction: Option<Faction>,
 }
 
 /// The type of NPC.
@@ -52,9 +56,23 @@ impl NPC {
             name,
             npc_type,
             dialogue_state: String::new(),
+            lineage: None,
+            faction: None,
         }
     }
 
+    /// Set the NPC's house.
+    pub fn with_lineage(mut self, lineage: Lineage) -> Self {
+        self.lineage = Some(lineage);
+        self
+    }
+
+    /// Set the NPC's faction.
+    pub fn with_faction(mut self, faction: Faction) -> Self {
+        self.faction = Some(faction);
+        self
+    }
+
     /// Check if this NPC is interactive (can be talked to).
     pub fn is_interactive(&self) -> bool {
         matches!(
@@ -67,4 +85,31 @@ impl NPC {
     pub fn is_hostile(&self) -> bool {
         matches!(self.npc_type, NPCType::Creature)
     }
+
+    /// Check if this NPC is hurt but still alive.
+    pub fn is_wounded(&self) -> bool {
+        self.entity.is_alive() && self.entity.health < self.entity.max_health
+    }
+
+    /// Wound the NPC. Reports its death if this blow killed it.
+    pub fn wound(&mut self, damage: f32) -> Option<JobTelemetry> {
+        if !self.entity.is_alive() {
+            return None;
+        }
+        self.entity.take_damage(damage);
+        (!self.entity.is_alive()).then_some(JobTelemetry::NpcDied { npc_id: self.entity.id.0 })
+    }
+
+    /// Tend a wounded stranger with herbs and bandages, mending `amount` health.
+    /// Reports the tending, and whether the stranger recovered.
+    pub fn tend(&mut self, amount: f32) -> Option<JobTelemetry> {
+        if self.is_hostile() || !self.is_wounded() {
+            return None;
+        }
+        self.entity.heal(amount);
+        Some(JobTelemetry::HealedStranger {
+            npc_id: self.entity.id.0,
+            recovered: self.entity.health >= self.entity.max_health,
+        })
+    }
 }
@@ -1,7 +1,7 @@
 //! Player character data structures.
 
 use serde::{Deserialize, Serialize};
-use super::{Entity, EntityId, JobMastery};
+use super::{Entity, EntityId, JobMastery, Lineage, Stats, Breath, Reputation, ReputationEvent};
 use glam::Vec3;
 
 /// A player character.
@@ -12,16 +12,12 @@ pub struct Player {
     pub lineage: Lineage,
     pub corruption: f32, // 0.0 (Pure) to 100.0 (Fallen)
     pub job_mastery: JobMastery,
-}
-
-/// The two houses of humanity.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Lineage {
-    /// The House of Seth. Faith, Discipline, Integrity.
-    Seth,
-    
-    /// The House of Cain. Technology, Ambition, Corruption.
-    Cain,
+    #[serde(default)]
+    pub stats: Stats,
+    #[serde(default)]
+    pub breath: Breath,
+    #[serde(default)]
+    pub reputation: Reputation,
 }
 
 impl Player {
@@ -33,6 +29,9 @@ impl Player {
             lineage: Lineage::Seth,
             corruption: 0.0,
             job_mastery: JobMastery::default(),
+            stats: Stats::default(),
+            breath: Breath::default(),
+            reputation: Reputation::for_lineage(Lineage::Seth),
         }
     }
 
@@ -41,19 +40,20 @@ impl Player {
         self.corruption > 50.0
     }
 
-    /// Increase the player's corruption.
+    /// Increase the player's corruption. Reputation moves only by what was
+    /// actually gained, once corruption is capped.
     pub fn corrupt(&mut self, amount: f32) {
+        let before = self.corruption;
         self.corruption = (self.corruption + amount).min(100.0);
-        if self.corruption > 50.0 {
-            self.lineage = Lineage::Cain;
-        }
+        self.lineage = self.lineage.resolve(self.corruption);
+        self.reputation.apply(&ReputationEvent::Corrupted { amount: self.corruption - before });
     }
 
-    /// Decrease the player's corruption (Redemption).
+    /// Decrease the player's corruption (Redemption), by no more than it has.
     pub fn redeem(&mut self, amount: f32) {
+        let before = self.corruption;
         self.corruption = (self.corruption - amount).max(0.0);
-        if self.corruption < 50.0 {
-            self.lineage = Lineage::Seth;
-        }
+        self.lineage = self.lineage.resolve(self.corruption);
+        self.reputation.apply(&ReputationEvent::Redeemed { amount: before - self.corruption });
     }
 }
@@ -0,0 +1,308 @@
+//! Faction reputation.
+//!
+//! Each player has a standing with every faction, from -100 (Hated) to 100
+//! (Exalted). Quests, kills and corruption move it; standing gates merchant
+//! prices, trainer access and how NPCs speak to the player.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::Lineage;
+use crate::mob::MobType;
+
+/// Lowest and highest reputation.
+pub const REPUTATION_RANGE: (f32, f32) = (-100.0, 100.0);
+
+/// Reputation gained with the victim's enemies per kill.
+pub const KILL_REPUTATION: f32 = 1.0;
+
+/// Share of a gain that is lost with the faction's rival.
+pub const RIVAL_SHARE: f32 = 0.5;
+
+/// The factions of the world.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Faction {
+    /// The villages of the House of Seth.
+    SethiteVillages,
+
+    /// The City of Enoch, built by Cain.
+    CityOfEnoch,
+
+    /// Noah and his household, builders of the Ark.
+    NoahsHousehold,
+
+    /// The cult of the fallen Watchers.
+    WatchersCult,
+}
+
+impl Faction {
+    /// All factions.
+    pub const ALL: [Faction; 4] = [
+        Faction::SethiteVillages,
+        Faction::CityOfEnoch,
+        Faction::NoahsHousehold,
+        Faction::WatchersCult,
+    ];
+
+    /// Parse a faction from its wire name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| format!("{:?}", f) == name)
+    }
+
+    /// The house this faction belongs to.
+    pub fn lineage(&self) -> Lineage {
+        match self {
+            Faction::SethiteVillages | Faction::NoahsHousehold => Lineage::Seth,
+            Faction::CityOfEnoch | Faction::WatchersCult => Lineage::Cain,
+        }
+    }
+
+    /// The faction a member of a house answers to.
+    pub fn home_of(lineage: Lineage) -> Faction {
+        match lineage {
+            Lineage::Seth => Faction::SethiteVillages,
+            Lineage::Cain => Faction::CityOfEnoch,
+        }
+    }
+
+    /// The faction that loses standing when this one gains.
+    pub fn rival(&self) -> Faction {
+        match self {
+            Faction::SethiteVillages => Faction::CityOfEnoch,
+            Faction::CityOfEnoch => Faction::SethiteVillages,
+            Faction::NoahsHousehold => Faction::WatchersCult,
+            Faction::WatchersCult => Faction::NoahsHousehold,
+        }
+    }
+}
+
+/// A named band of reputation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Standing {
+    Hated,
+    Hostile,
+    Unfriendly,
+    Neutral,
+    Friendly,
+    Honored,
+    Exalted,
+}
+
+impl Standing {
+    /// The band a reputation value falls in.
+    pub fn from_value(value: f32) -> Self {
+        match value {
+            v if v <= -60.0 => Standing::Hated,
+            v if v <= -30.0 => Standing::Hostile,
+            v if v < 0.0 => Standing::Unfriendly,
+            v if v < 20.0 => Standing::Neutral,
+            v if v < 50.0 => Standing::Friendly,
+            v if v < 80.0 => Standing::Honored,
+            _ => Standing::Exalted,
+        }
+    }
+
+    /// Merchant price multiplier. None if they refuse to trade.
+    pub fn price_multiplier(&self) -> Option<f32> {
+        match self {
+            Standing::Hated | Standing::Hostile => None,
+            Standing::Unfriendly => Some(1.25),
+            Standing::Neutral => Some(1.0),
+            Standing::Friendly => Some(0.95),
+            Standing::Honored => Some(0.9),
+            Standing::Exalted => Some(0.8),
+        }
+    }
+
+    /// Check if the faction's trainers will teach the player.
+    pub fn can_train(&self) -> bool {
+        *self >= Standing::Friendly
+    }
+}
+
+/// Something that changes reputation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ReputationEvent {
+    /// A quest for a faction was completed.
+    QuestCompleted { faction: Faction, amount: f32 },
+
+    /// The player killed a mob.
+    MobKilled { mob_type: MobType },
+
+    /// The player killed a member of a faction.
+    MemberKilled { faction: Faction },
+
+    /// The player gained corruption.
+    Corrupted { amount: f32 },
+
+    /// The player shed corruption.
+    Redeemed { amount: f32 },
+}
+
+/// Goods a faction asks the player to bring, and the standing bringing them earns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Errand {
+    pub faction: Faction,
+    pub item: &'static str,
+    pub quantity: u32,
+    pub reward: f32,
+}
+
+impl Errand {
+    /// The errand a faction's people set. The Watchers' cult asks nothing it will say aloud.
+    pub fn for_faction(faction: Faction) -> Option<Self> {
+        let (item, quantity, reward) = match faction {
+            Faction::NoahsHousehold => ("Gopher Wood", 10, 15.0),
+            Faction::SethiteVillages => ("Healing Herb", 5, 10.0),
+            Faction::CityOfEnoch => ("Iron Ingot", 3, 10.0),
+            Faction::WatchersCult => return None,
+        };
+        Some(Self { faction, item, quantity, reward })
+    }
+
+    /// Hand over `carried` of the item. Completes the errand if it is enough.
+    pub fn complete(&self, carried: u32) -> Option<ReputationEvent> {
+        (carried >= self.quantity).then_some(ReputationEvent::QuestCompleted { faction: self.faction, amount: self.reward })
+    }
+}
+
+/// A player's reputation with every faction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Reputation {
+    values: HashMap<Faction, f32>,
+}
+
+impl Reputation {
+    /// Starting reputation for a lineage: kin factions are warm, rivals cold.
+    pub fn for_lineage(lineage: Lineage) -> Self {
+        let values = Faction::ALL
+            .iter()
+            .map(|f| (*f, lineage.reputation_with(Some(f.lineage()))))
+            .collect();
+        Self { values }
+    }
+
+    /// Reputation with a faction.
+    pub fn get(&self, faction: Faction) -> f32 {
+        self.values.get(&faction).copied().unwrap_or(0.0)
+    }
+
+    /// Standing with a faction.
+    pub fn standing(&self, faction: Faction) -> Standing {
+        Standing::from_value(self.get(faction))
+    }
+
+    /// Change reputation with a faction.
+    pub fn adjust(&mut self, faction: Faction, amount: f32) {
+        let value = self.values.entry(faction).or_insert(0.0);
+        *value = (*value + amount).clamp(REPUTATION_RANGE.0, REPUTATION_RANGE.1);
+    }
+
+    /// Apply a reputation event.
+    pub fn apply(&mut self, event: &ReputationEvent) {
+        match *event {
+            ReputationEvent::QuestCompleted { faction, amount } => {
+                self.adjust(faction, amount);
+                self.adjust(faction.rival(), -amount * RIVAL_SHARE);
+            }
+            ReputationEvent::MobKilled { mob_type } => match mob_type {
+                // The Watchers' offspring and their corruption threaten the faithful
+                MobType::Nephilim => {
+                    self.adjust(Faction::NoahsHousehold, KILL_REPUTATION * 3.0);
+                    self.adjust(Faction::SethiteVillages, KILL_REPUTATION * 2.0);
+                    self.adjust(Faction::WatchersCult, -KILL_REPUTATION * 3.0);
+                }
+                MobType::Corrupted => {
+                    self.adjust(Faction::SethiteVillages, KILL_REPUTATION);
+                    self.adjust(Faction::WatchersCult, -KILL_REPUTATION);
+                }
+                // Predators trouble every village alike
+                _ => self.adjust(Faction::SethiteVillages, KILL_REPUTATION * 0.2),
+            },
+            ReputationEvent::MemberKilled { faction } => {
+                self.adjust(faction, -KILL_REPUTATION * 10.0);
+                self.adjust(faction.rival(), KILL_REPUTATION * 2.0);
+            }
+            ReputationEvent::Corrupted { amount } => {
+                self.adjust(Faction::WatchersCult, amount);
+                self.adjust(Faction::CityOfEnoch, amount * 0.5);
+                self.adjust(Faction::NoahsHousehold, -amount);
+                self.adjust(Faction::SethiteVillages, -amount * 0.5);
+            }
+            ReputationEvent::Redeemed { amount } => {
+                self.adjust(Faction::NoahsHousehold, amount * 0.5);
+                self.adjust(Faction::SethiteVillages, amount * 0.5);
+                self.adjust(Faction::WatchersCult, -amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{EntityId, Player};
+    use glam::Vec3;
+
+    #[test]
+    fn test_starting_reputation_follows_lineage() {
+        let seth = Reputation::for_lineage(Lineage::Seth);
+        assert_eq!(seth.standing(Faction::SethiteVillages), Standing::Neutral);
+        assert_eq!(seth.standing(Faction::CityOfEnoch), Standing::Unfriendly);
+
+        let cain = Reputation::for_lineage(Lineage::Cain);
+        assert!(cain.get(Faction::WatchersCult) > cain.get(Faction::NoahsHousehold));
+    }
+
+    #[test]
+    fn test_events_move_reputation() {
+        let mut rep = Reputation::default();
+        rep.apply(&ReputationEvent::QuestCompleted { faction: Faction::NoahsHousehold, amount: 40.0 });
+        assert_eq!(rep.standing(Faction::NoahsHousehold), Standing::Friendly);
+        assert_eq!(rep.get(Faction::WatchersCult), -20.0);
+
+        rep.apply(&ReputationEvent::Corrupted { amount: 30.0 });
+        assert_eq!(rep.get(Faction::NoahsHousehold), 10.0);
+
+        // Clamped to the range
+        rep.adjust(Faction::CityOfEnoch, -500.0);
+        assert_eq!(rep.get(Faction::CityOfEnoch), REPUTATION_RANGE.0);
+    }
+
+    #[test]
+    fn test_errands_and_kills() {
+        let mut rep = Reputation::for_lineage(Lineage::Seth);
+        let errand = Errand::for_faction(Faction::NoahsHousehold).unwrap();
+        assert_eq!(errand.complete(errand.quantity - 1), None);
+        let before = rep.get(Faction::NoahsHousehold);
+        rep.apply(&errand.complete(errand.quantity).unwrap());
+        assert_eq!(rep.get(Faction::NoahsHousehold), before + errand.reward);
+        assert_eq!(Errand::for_faction(Faction::WatchersCult), None);
+
+        // Slaying one of Enoch's people sours Enoch and pleases the villages
+        let (enoch, villages) = (rep.get(Faction::CityOfEnoch), rep.get(Faction::SethiteVillages));
+        rep.apply(&ReputationEvent::MemberKilled { faction: Faction::home_of(Lineage::Cain) });
+        assert!(rep.get(Faction::CityOfEnoch) < enoch && rep.get(Faction::SethiteVillages) > villages);
+    }
+
+    #[test]
+    fn test_capped_corruption_moves_reputation_no_further() {
+        let mut player = Player::new(EntityId(1), "Lamech".to_string(), Vec3::ZERO);
+        player.corrupt(90.0);
+        let noah = player.reputation.get(Faction::NoahsHousehold);
+
+        // Only 10 more corruption fits; the other 40 changes nothing
+        player.corrupt(50.0);
+        assert_eq!(player.corruption, 100.0);
+        assert_eq!(player.reputation.get(Faction::NoahsHousehold), noah - 10.0);
+        player.redeem(150.0);
+        assert_eq!(player.reputation.get(Faction::WatchersCult), Reputation::for_lineage(Lineage::Seth).get(Faction::WatchersCult));
+    }
+
+    #[test]
+    fn test_standing_gates_trade_and_training() {
+        assert_eq!(Standing::Hostile.price_multiplier(), None);
+        assert!(Standing::Exalted.price_multiplier().unwrap() < Standing::Neutral.price_multiplier().unwrap());
+        assert!(!Standing::Neutral.can_train());
+        assert!(Standing::Friendly.can_train());
+    }
+}
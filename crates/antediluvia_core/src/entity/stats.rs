@@ -0,0 +1,166 @@
+//! Character attributes and stamina ("The Breath").
+
+use serde::{Deserialize, Serialize};
+
+/// Walking speed in meters per second.
+pub const BASE_MOVE_SPEED: f32 = 50.0;
+
+/// Speed multiplier while sprinting.
+pub const SPRINT_MULTIPLIER: f32 = 3.0;
+
+/// Stamina drained per second of sprinting.
+pub const SPRINT_STAMINA_PER_SECOND: f32 = 20.0;
+
+/// Seconds without exertion before the Breath starts to return.
+pub const BREATH_REGEN_DELAY: f32 = 1.5;
+
+/// A character's core attributes.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Stats {
+    pub strength: f32,
+    pub endurance: f32,
+    pub faith: f32,
+    pub cunning: f32,
+    pub max_stamina: f32,
+    pub stamina_regen: f32, // per second while idle
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            strength: 10.0,
+            endurance: 10.0,
+            faith: 10.0,
+            cunning: 10.0,
+            max_stamina: 100.0,
+            stamina_regen: 15.0,
+        }
+    }
+}
+
+impl Stats {
+    /// Effective max stamina (endurance adds 2 per point above 10).
+    pub fn effective_max_stamina(&self) -> f32 {
+        self.max_stamina + (self.endurance - 10.0).max(0.0) * 2.0
+    }
+
+    /// Effective stamina regen (endurance adds 0.5/s per point above 10).
+    pub fn effective_stamina_regen(&self) -> f32 {
+        self.stamina_regen + (self.endurance - 10.0).max(0.0) * 0.5
+    }
+}
+
+/// The Breath. Consumed by sprinting and abilities, regained when idle.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Breath {
+    pub current: f32,
+    pub max: f32,
+    pub regen_per_second: f32,
+    pub idle_timer: f32, // seconds since the last exertion
+}
+
+impl Breath {
+    /// Create a full Breath pool.
+    pub fn new(max: f32, regen_per_second: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            regen_per_second,
+            idle_timer: BREATH_REGEN_DELAY,
+        }
+    }
+
+    /// Create a Breath pool from a stats block.
+    pub fn from_stats(stats: &Stats) -> Self {
+        Self::new(stats.effective_max_stamina(), stats.effective_stamina_regen())
+    }
+
+    /// Spend stamina. Returns false (and spends nothing) if there is not enough.
+    pub fn drain(&mut self, amount: f32) -> bool {
+        if amount <= 0.0 {
+            return true;
+        }
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        self.idle_timer = 0.0;
+        true
+    }
+
+    /// Give back stamina spent on something that did not happen.
+    pub fn refund(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    /// Spend stamina for `delta_seconds` of sprinting.
+    pub fn sprint(&mut self, delta_seconds: f32) -> bool {
+        self.drain(SPRINT_STAMINA_PER_SECOND * delta_seconds)
+    }
+
+    /// Regenerate after the idle delay.
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.idle_timer += delta_seconds;
+        if self.idle_timer >= BREATH_REGEN_DELAY {
+            self.current = (self.current + self.regen_per_second * delta_seconds).min(self.max);
+        }
+    }
+
+    /// Check if the Breath is spent.
+    pub fn is_exhausted(&self) -> bool {
+        self.current < 1.0
+    }
+
+    /// Fraction of the pool remaining (0.0 to 1.0).
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0.0 {
+            0.0
+        } else {
+            self.current / self.max
+        }
+    }
+}
+
+impl Default for Breath {
+    fn default() -> Self {
+        Self::from_stats(&Stats::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breath_drain_and_regen() {
+        let mut breath = Breath::default();
+        assert!(breath.drain(60.0));
+        assert!(!breath.drain(60.0)); // Not enough left
+        assert_eq!(breath.current, 40.0);
+
+        // No regen until idle
+        breath.update(1.0);
+        assert_eq!(breath.current, 40.0);
+        breath.update(1.0);
+        assert!(breath.current > 40.0);
+    }
+
+    #[test]
+    fn test_sprint_exhausts() {
+        let mut breath = Breath::default();
+        let mut seconds = 0;
+        while breath.sprint(1.0) {
+            seconds += 1;
+        }
+        assert_eq!(seconds, 5);
+        assert!(breath.is_exhausted());
+    }
+
+    #[test]
+    fn test_endurance_scales_breath() {
+        let stats = Stats { endurance: 20.0, ..Stats::default() };
+        let breath = Breath::from_stats(&stats);
+        assert_eq!(breath.max, 120.0);
+        assert_eq!(breath.regen_per_second, 20.0);
+    }
+}
@@ -0,0 +1,271 @@
+//! Hidden job unlocks ("Feats of Legend").
+//!
+//! Jobs are revealed, not chosen. The tracker observes gameplay telemetry and
+//! unlocks a job, seeding its mastery, once its unlock condition is met.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use std::collections::HashMap;
+use super::{Job, JobMastery};
+use crate::crafting::ItemQuality;
+use crate::death::BindPoint;
+use crate::mob::MobType;
+
+/// Mastery granted when a job is first revealed.
+pub const UNLOCK_MASTERY_SEED: f32 = 5.0;
+
+/// Seconds an NPC must be defended to reveal the Shepherd.
+pub const SHEPHERD_DEFEND_SECONDS: f32 = 300.0;
+
+/// Share of a Giant's health the player must deal to reveal the Hunter.
+pub const HUNTER_DAMAGE_SHARE: f32 = 0.5;
+
+/// Lyre accuracy that counts as a perfect rhythm.
+pub const PERFECT_RHYTHM_ACCURACY: f32 = 0.98;
+
+/// Beats in a song on the Lyre.
+pub const LYRE_BEATS: u32 = 8;
+
+/// Seconds between the beats of a song on the Lyre.
+pub const LYRE_BEAT_SECONDS: f32 = 0.75;
+
+/// A piece of gameplay telemetry relevant to job unlocks.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum JobTelemetry {
+    /// Time spent defending an NPC from predators.
+    DefendedNpc { npc_id: u64, seconds: f32 },
+
+    /// A defended NPC died. Its defence starts over.
+    NpcDied { npc_id: u64 },
+
+    /// A wounded stranger was tended with herbs and bandages.
+    HealedStranger { npc_id: u64, recovered: bool },
+
+    /// Damage the player dealt to a mob.
+    MobDamaged { mob_id: u64, mob_type: MobType, damage: f32 },
+
+    /// A mob died. `max_health` is its full health pool.
+    MobKilled { mob_id: u64, mob_type: MobType, max_health: f32 },
+
+    /// The player crafted an item.
+    ItemCrafted { quality: ItemQuality, is_weapon: bool },
+
+    /// The player finished a song on the Lyre.
+    LyrePlayed { accuracy: f32, at_campfire: bool },
+}
+
+/// Tracks progress toward each job's unlock condition.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JobUnlockTracker {
+    defend_seconds: HashMap<u64, f32>, // NPC ID -> seconds defended
+    giant_damage: HashMap<u64, f32>,   // Mob ID -> damage dealt by the player
+}
+
+impl JobUnlockTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe telemetry. Returns the job if this event revealed it.
+    pub fn observe(&mut self, event: &JobTelemetry, mastery: &mut JobMastery) -> Option<Job> {
+        let job = self.check(event)?;
+        if mastery.is_unlocked(job) {
+            return None;
+        }
+        mastery.increase(job, UNLOCK_MASTERY_SEED);
+        Some(job)
+    }
+
+    /// Seconds the player has defended an NPC.
+    pub fn defend_progress(&self, npc_id: u64) -> f32 {
+        self.defend_seconds.get(&npc_id).copied().unwrap_or(0.0)
+    }
+
+    /// Update progress and report which job's condition (if any) was just met.
+    fn check(&mut self, event: &JobTelemetry) -> Option<Job> {
+        match *event {
+            JobTelemetry::DefendedNpc { npc_id, seconds } => {
+                let total = self.defend_seconds.entry(npc_id).or_insert(0.0);
+                *total += seconds;
+                if *total >= SHEPHERD_DEFEND_SECONDS {
+                    self.defend_seconds.remove(&npc_id);
+                    return Some(Job::Shepherd);
+                }
+                None
+            }
+            JobTelemetry::NpcDied { npc_id } => {
+                self.defend_seconds.remove(&npc_id);
+                None
+            }
+            JobTelemetry::HealedStranger { recovered, .. } => recovered.then_some(Job::Levite),
+            JobTelemetry::MobDamaged { mob_id, mob_type, damage } => {
+                if mob_type.is_giant() {
+                    *self.giant_damage.entry(mob_id).or_insert(0.0) += damage;
+                }
+                None
+            }
+            JobTelemetry::MobKilled { mob_id, mob_type, max_health } => {
+                let dealt = self.giant_damage.remove(&mob_id).unwrap_or(0.0);
+                let share = if max_health > 0.0 { dealt / max_health } else { 0.0 };
+                (mob_type.is_giant() && share > HUNTER_DAMAGE_SHARE).then_some(Job::Hunter)
+            }
+            JobTelemetry::ItemCrafted { quality, is_weapon } => {
+                (is_weapon && quality >= ItemQuality::Masterwork).then_some(Job::Forge)
+            }
+            JobTelemetry::LyrePlayed { accuracy, at_campfire } => {
+                (at_campfire && accuracy >= PERFECT_RHYTHM_ACCURACY).then_some(Job::Psalmist)
+            }
+        }
+    }
+}
+
+/// A song being played on the Lyre. The player strums once on every beat;
+/// the closer each strum falls to its beat, the truer the song.
+#[derive(Clone, Debug, Default)]
+pub struct LyreSong {
+    elapsed: f32,
+    strums: Vec<f32>, // Each strum's distance from its beat, in seconds
+}
+
+impl LyreSong {
+    /// Start a song. The first beat falls one beat in.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the song.
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.elapsed += delta_seconds;
+    }
+
+    /// Strum the Lyre, against the nearest beat.
+    pub fn strum(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        let beat = (self.elapsed / LYRE_BEAT_SECONDS).round().clamp(1.0, LYRE_BEATS as f32);
+        self.strums.push((self.elapsed - beat * LYRE_BEAT_SECONDS).abs());
+    }
+
+    /// Check if the last beat has passed.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= (LYRE_BEATS as f32 + 0.5) * LYRE_BEAT_SECONDS
+    }
+
+    /// How true the song was, from 0 to 1. Missed beats count for nothing,
+    /// and strums beyond one a beat dilute the rest.
+    pub fn accuracy(&self) -> f32 {
+        let half_beat = LYRE_BEAT_SECONDS / 2.0;
+        let score: f32 = self.strums.iter().map(|off| (1.0 - off / half_beat).max(0.0)).sum();
+        score / self.strums.len().max(LYRE_BEATS as usize) as f32
+    }
+
+    /// Finish the song where the player stands.
+    pub fn finish(&self, position: Vec3) -> JobTelemetry {
+        JobTelemetry::LyrePlayed {
+            accuracy: self.accuracy(),
+            at_campfire: BindPoint::near(position) == Some(BindPoint::HavilahCampfire),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{EntityId, NPCType, NPC};
+
+    #[test]
+    fn test_shepherd_unlock_after_defence() {
+        let mut tracker = JobUnlockTracker::new();
+        let mut mastery = JobMastery::default();
+
+        let tick = JobTelemetry::DefendedNpc { npc_id: 9, seconds: 150.0 };
+        assert_eq!(tracker.observe(&tick, &mut mastery), None);
+
+        // The NPC died; the feat starts over
+        tracker.observe(&JobTelemetry::NpcDied { npc_id: 9 }, &mut mastery);
+        assert_eq!(tracker.observe(&tick, &mut mastery), None);
+        assert_eq!(tracker.observe(&tick, &mut mastery), Some(Job::Shepherd));
+        assert_eq!(mastery.get_level(Job::Shepherd), UNLOCK_MASTERY_SEED);
+    }
+
+    #[test]
+    fn test_hunter_requires_damage_share_on_giant() {
+        let mut tracker = JobUnlockTracker::new();
+        let mut mastery = JobMastery::default();
+
+        let hit = JobTelemetry::MobDamaged { mob_id: 1, mob_type: MobType::Nephilim, damage: 200.0 };
+        tracker.observe(&hit, &mut mastery);
+        let kill = JobTelemetry::MobKilled { mob_id: 1, mob_type: MobType::Nephilim, max_health: 500.0 };
+        assert_eq!(tracker.observe(&kill, &mut mastery), None); // Only 40%
+
+        tracker.observe(&JobTelemetry::MobDamaged { mob_id: 2, mob_type: MobType::Nephilim, damage: 300.0 }, &mut mastery);
+        let kill = JobTelemetry::MobKilled { mob_id: 2, mob_type: MobType::Nephilim, max_health: 500.0 };
+        assert_eq!(tracker.observe(&kill, &mut mastery), Some(Job::Hunter));
+    }
+
+    #[test]
+    fn test_craft_and_song_unlocks() {
+        let mut tracker = JobUnlockTracker::new();
+        let mut mastery = JobMastery::default();
+
+        let fine = JobTelemetry::ItemCrafted { quality: ItemQuality::Fine, is_weapon: true };
+        assert_eq!(tracker.observe(&fine, &mut mastery), None);
+        let masterwork = JobTelemetry::ItemCrafted { quality: ItemQuality::Masterwork, is_weapon: true };
+        assert_eq!(tracker.observe(&masterwork, &mut mastery), Some(Job::Forge));
+        assert_eq!(tracker.observe(&masterwork, &mut mastery), None); // Already revealed
+
+        let song = JobTelemetry::LyrePlayed { accuracy: 1.0, at_campfire: true };
+        assert_eq!(tracker.observe(&song, &mut mastery), Some(Job::Psalmist));
+    }
+
+    #[test]
+    fn test_feats_through_their_deeds() {
+        let mut tracker = JobUnlockTracker::new();
+        let mut mastery = JobMastery::default();
+        let mut observe = |event: Option<JobTelemetry>| event.and_then(|e| tracker.observe(&e, &mut mastery));
+
+        // A villager mauled to death takes the defence with it
+        let mut villager = NPC::new(EntityId(7), "Ada".to_string(), NPCType::Villager, Vec3::ZERO);
+        observe(Some(JobTelemetry::DefendedNpc { npc_id: 7, seconds: SHEPHERD_DEFEND_SECONDS - 1.0 }));
+        assert_eq!(villager.wound(10.0), None);
+        assert_eq!(observe(villager.wound(1000.0)), None);
+        assert_eq!(villager.wound(10.0), None); // Only dies once
+        assert_eq!(observe(Some(JobTelemetry::DefendedNpc { npc_id: 7, seconds: 1.0 })), None);
+
+        // Tending a stranger reveals the Levite only once they recover
+        let mut stranger = NPC::new(EntityId(8), "Irad".to_string(), NPCType::Villager, Vec3::ZERO);
+        assert_eq!(stranger.tend(10.0), None); // Not hurt
+        stranger.wound(30.0);
+        assert_eq!(observe(stranger.tend(10.0)), None);
+        assert_eq!(observe(stranger.tend(20.0)), Some(Job::Levite));
+        assert_eq!(NPC::new(EntityId(9), "Beast".to_string(), NPCType::Creature, Vec3::ZERO).tend(10.0), None);
+
+        // A true song reveals the Psalmist, but only by the campfire
+        let play = |offset: f32| {
+            let mut song = LyreSong::new();
+            for _ in 0..LYRE_BEATS {
+                song.update(LYRE_BEAT_SECONDS + offset);
+                song.strum();
+                song.update(-offset);
+            }
+            song.update(LYRE_BEAT_SECONDS);
+            assert!(song.is_finished());
+            song
+        };
+        let campfire = BindPoint::HavilahCampfire.position();
+        assert!(play(0.2).accuracy() < PERFECT_RHYTHM_ACCURACY);
+        assert_eq!(observe(Some(play(0.2).finish(campfire))), None);
+        assert_eq!(observe(Some(play(0.0).finish(Vec3::new(500.0, 0.0, 500.0)))), None);
+        assert_eq!(observe(Some(play(0.0).finish(campfire))), Some(Job::Psalmist));
+
+        // Strumming every frame is no song at all
+        let mut noise = LyreSong::new();
+        while !noise.is_finished() {
+            noise.update(LYRE_BEAT_SECONDS / 8.0);
+            noise.strum();
+        }
+        assert!(noise.accuracy() < 0.5);
+    }
+}
@@ -14,6 +14,39 @@ pub enum AntediluviaError {
     #[error("Invalid coordinates: {0}")]
     InvalidCoordinates(String),
 
+    #[error("Invalid loadout: {0}")]
+    InvalidLoadout(String),
+
+    #[error("Character error: {0}")]
+    CharacterError(String),
+
+    #[error("Combat action refused: {0}")]
+    CombatError(String),
+
+    #[error("Party error: {0}")]
+    PartyError(String),
+
+    #[error("Redemption refused: {0}")]
+    RedemptionError(String),
+
+    #[error("Gathering failed: {0}")]
+    GatheringError(String),
+
+    #[error("Feat not recognised: {0}")]
+    FeatError(String),
+
+    #[error("Errand refused: {0}")]
+    ErrandError(String),
+
+    #[error("Crafting failed: {0}")]
+    CraftingError(String),
+
+    #[error("Equipment refused: {0}")]
+    EquipmentError(String),
+
+    #[error("Invalid game data: {0}")]
+    DataError(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
@@ -1,8 +1,9 @@
 //! World events and spiritual visions.
-//! Implements Nephilim raids and Jacob's Ladder vision triggers.
+//! Implements Nephilim raids, Jacob's Ladder vision triggers and the Leviathan.
 
 use serde::{Deserialize, Serialize};
 use glam::Vec3;
+use crate::leviathan::LeviathanConfig;
 
 /// Types of world events.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -11,6 +12,8 @@ pub enum WorldEventType {
     NephilimRaid,
     /// A prophetic vision (Jacob's Ladder).
     VisionJacobLadder,
+    /// The Leviathan rises in the Great Moat.
+    Leviathan,
 }
 
 /// A scheduled world event.
@@ -21,6 +24,8 @@ pub struct WorldEvent {
     pub trigger_time: f32,   // seconds since server start
     pub duration: f32,       // seconds
     pub active: bool,
+    #[serde(default)]
+    pub repeat_interval: Option<f32>, // seconds between recurrences
 }
 
 impl WorldEvent {
@@ -31,6 +36,15 @@ impl WorldEvent {
             trigger_time,
             duration,
             active: false,
+            repeat_interval: None,
+        }
+    }
+
+    /// Create an event that recurs every `interval` seconds.
+    pub fn recurring(event_type: WorldEventType, location: Vec3, trigger_time: f32, duration: f32, interval: f32) -> Self {
+        Self {
+            repeat_interval: Some(interval),
+            ..Self::new(event_type, location, trigger_time, duration)
         }
     }
 }
@@ -77,9 +91,27 @@ impl EventManager {
             600.0,
             120.0,
         ));
+        manager.schedule_leviathan(&LeviathanConfig::default());
         manager
     }
 
+    /// Schedule the recurring Leviathan appearance in the Great Moat.
+    pub fn schedule_leviathan(&mut self, config: &LeviathanConfig) {
+        self.events.retain(|evt| evt.event_type != WorldEventType::Leviathan);
+        self.events.push(WorldEvent::recurring(
+            WorldEventType::Leviathan,
+            Vec3::ZERO,
+            config.first_appearance,
+            config.duration,
+            config.interval,
+        ));
+    }
+
+    /// Check if an event of the given type is currently active.
+    pub fn is_active(&self, event_type: &WorldEventType) -> bool {
+        self.events.iter().any(|evt| evt.active && evt.event_type == *event_type)
+    }
+
     /// Advance time and activate/deactivate events.
     pub fn update(&mut self, delta_seconds: f32) -> Vec<WorldEventType> {
         self.time_seconds += delta_seconds;
@@ -92,6 +124,9 @@ impl EventManager {
             }
             if evt.active && self.time_seconds >= evt.trigger_time + evt.duration {
                 evt.active = false;
+                if let Some(interval) = evt.repeat_interval {
+                    evt.trigger_time += interval;
+                }
             }
         }
         triggered
@@ -108,4 +143,21 @@ mod tests {
         let fired = mgr.update(301.0);
         assert!(fired.contains(&WorldEventType::VisionJacobLadder));
     }
+
+    #[test]
+    fn test_leviathan_recurs() {
+        let mut mgr = EventManager::default();
+        let config = LeviathanConfig {
+            first_appearance: 10.0,
+            duration: 5.0,
+            interval: 100.0,
+            ..LeviathanConfig::default()
+        };
+        mgr.schedule_leviathan(&config);
+
+        assert!(mgr.update(10.0).contains(&WorldEventType::Leviathan));
+        mgr.update(5.0);
+        assert!(!mgr.is_active(&WorldEventType::Leviathan));
+        assert!(mgr.update(95.0).contains(&WorldEventType::Leviathan));
+    }
 }
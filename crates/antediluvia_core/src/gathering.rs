@@ -0,0 +1,207 @@
+//! Gathering sites, where the land yields wood, ore, herbs and cloth.
+//!
+//! The sites are defined in `data/gathering.json`, and stand in the same
+//! places in every world. Each yields a few gatherings before it is spent,
+//! and grows back in time. Online the server keeps what is left at each, so
+//! nothing is gathered that the land did not give.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use std::sync::OnceLock;
+use crate::error::{AntediluviaError, Result};
+use crate::mob::LootDrop;
+
+/// The gathering sites that ship with the game.
+const GATHERING_DATA: &str = include_str!("../data/gathering.json");
+
+/// How near a site a player must stand to gather from it.
+pub const GATHER_REACH: f32 = 20.0;
+
+/// A kind of site, and everywhere one stands.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SiteKind {
+    name: String,
+    resource: String,
+    per_gather: u32,
+    weight: f32, // Of one unit of the resource
+    capacity: u32,
+    regrow_seconds: f32,
+    positions: Vec<[f32; 2]>, // Across the ground; the terrain gives the height
+}
+
+/// One gathering site.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GatheringSite {
+    pub name: String,
+    pub resource: String,
+    pub position: Vec3, // On the ground plane
+    pub per_gather: u32,
+    pub weight: f32,
+    pub capacity: u32,
+    pub regrow_seconds: f32,
+}
+
+impl GatheringSite {
+    /// Check if a player standing at `position` can reach the site. Only the
+    /// distance across the ground counts, whatever the terrain's height.
+    pub fn in_reach(&self, position: Vec3) -> bool {
+        Vec3::new(self.position.x - position.x, 0.0, self.position.z - position.z).length() <= GATHER_REACH
+    }
+}
+
+/// Build the sites from JSON: a list of kinds, each with its positions,
+/// checking every kind yields something.
+pub fn sites_from_json(json: &str) -> Result<Vec<GatheringSite>> {
+    #[derive(Deserialize)]
+    struct GatheringData {
+        sites: Vec<SiteKind>,
+    }
+    let data: GatheringData = serde_json::from_str(json)?;
+    let mut sites = Vec::new();
+    for kind in data.sites {
+        if kind.per_gather == 0 || kind.capacity < kind.per_gather {
+            return Err(AntediluviaError::DataError(format!("gathering site {} yields nothing", kind.name)));
+        }
+        for [x, z] in &kind.positions {
+            sites.push(GatheringSite {
+                name: kind.name.clone(),
+                resource: kind.resource.clone(),
+                position: Vec3::new(*x, 0.0, *z),
+                per_gather: kind.per_gather,
+                weight: kind.weight,
+                capacity: kind.capacity,
+                regrow_seconds: kind.regrow_seconds,
+            });
+        }
+    }
+    Ok(sites)
+}
+
+/// Every gathering site, loaded from the game's data on first use. A site's
+/// index is its ID.
+pub fn gathering_sites() -> &'static [GatheringSite] {
+    static SITES: OnceLock<Vec<GatheringSite>> = OnceLock::new();
+    SITES.get_or_init(|| sites_from_json(GATHERING_DATA).expect("gathering data is invalid"))
+}
+
+/// What is left at each gathering site, and how long the spent ones have
+/// until they grow back.
+#[derive(Clone, Debug)]
+pub struct Gathering {
+    remaining: Vec<u32>,
+    regrowth: Vec<f32>,
+}
+
+impl Gathering {
+    /// Create the sites as the world begins, every one of them full.
+    pub fn new() -> Self {
+        Self {
+            remaining: gathering_sites().iter().map(|s| s.capacity).collect(),
+            regrowth: vec![0.0; gathering_sites().len()],
+        }
+    }
+
+    /// Get how much a site has left to give.
+    pub fn remaining(&self, site: usize) -> u32 {
+        self.remaining.get(site).copied().unwrap_or(0)
+    }
+
+    /// Gather from a site by a player standing at `position`, and get what it
+    /// yields. A site that runs dry starts to grow back.
+    pub fn gather(&mut self, site: usize, position: Vec3) -> Result<LootDrop> {
+        let refuse = |reason: &str| Err(AntediluviaError::GatheringError(reason.to_string()));
+        let Some(found) = gathering_sites().get(site) else {
+            return refuse("there is no such place to gather from");
+        };
+        if !found.in_reach(position) {
+            return refuse("that is too far away to gather from");
+        }
+        let quantity = found.per_gather.min(self.remaining[site]);
+        if quantity == 0 {
+            return refuse("nothing is left here; it will grow back");
+        }
+        self.remaining[site] -= quantity;
+        if self.remaining[site] == 0 {
+            self.regrowth[site] = found.regrow_seconds;
+        }
+        Ok(LootDrop { name: found.resource.clone(), quantity, weight: found.weight })
+    }
+
+    /// Take the server's word on what a site has left.
+    pub fn set_remaining(&mut self, site: usize, remaining: u32) {
+        if let (Some(left), Some(found)) = (self.remaining.get_mut(site), gathering_sites().get(site)) {
+            *left = remaining.min(found.capacity);
+        }
+    }
+
+    /// Give back a gathering that could not be carried.
+    pub fn put_back(&mut self, site: usize, quantity: u32) {
+        if let (Some(remaining), Some(found)) = (self.remaining.get_mut(site), gathering_sites().get(site)) {
+            *remaining = (*remaining + quantity).min(found.capacity);
+        }
+    }
+
+    /// Grow spent sites back, and get the ones that are full again.
+    pub fn update(&mut self, delta_seconds: f32) -> Vec<usize> {
+        let mut regrown = Vec::new();
+        for (site, found) in gathering_sites().iter().enumerate() {
+            if self.remaining[site] > 0 {
+                continue;
+            }
+            self.regrowth[site] -= delta_seconds;
+            if self.regrowth[site] <= 0.0 {
+                self.remaining[site] = found.capacity;
+                regrown.push(site);
+            }
+        }
+        regrown
+    }
+}
+
+impl Default for Gathering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sites_run_dry_and_grow_back() {
+        let site = gathering_sites().iter().position(|s| s.name == "Wood Pile").unwrap();
+        let found = &gathering_sites()[site];
+        let mut gathering = Gathering::new();
+        assert_eq!(gathering.remaining(site), found.capacity);
+
+        // Only from close by
+        assert!(gathering.gather(site, found.position + Vec3::X * (GATHER_REACH + 1.0)).is_err());
+        assert!(gathering.gather(gathering_sites().len(), found.position).is_err());
+
+        // Each gathering takes its share, the last whatever is left
+        let mut gathered = 0;
+        while let Ok(drop) = gathering.gather(site, found.position + Vec3::Y * 30.0) {
+            assert_eq!(drop.name, found.resource);
+            gathered += drop.quantity;
+        }
+        assert_eq!(gathered, found.capacity);
+
+        // Spent, it grows back in time
+        assert!(gathering.update(found.regrow_seconds / 2.0).is_empty());
+        assert_eq!(gathering.update(found.regrow_seconds / 2.0), vec![site]);
+        assert_eq!(gathering.remaining(site), found.capacity);
+
+        // A gathering that could not be carried goes back
+        gathering.gather(site, found.position).unwrap();
+        gathering.put_back(site, found.per_gather);
+        assert_eq!(gathering.remaining(site), found.capacity);
+    }
+
+    #[test]
+    fn test_invalid_gathering_data() {
+        let barren = r#"{"sites": [{"name": "Rock", "resource": "Stone", "per_gather": 0, "weight": 1.0, "capacity": 3, "regrow_seconds": 10.0, "positions": [[0.0, 0.0]]}]}"#;
+        assert!(sites_from_json(barren).is_err());
+        assert!(sites_from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! The Leviathan world event.
+//!
+//! A sea serpent rises in the Great Moat (Job 41). While it hunts, the sea crossing
+//! is closed, rafts and coastal settlements are attacked, and only coordinated
+//! ranged fire from many hands can drive it back into the deep.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use crate::combat::CombatAction;
+use crate::world::{in_great_moat, HAVILAH_RADIUS, GREAT_MOAT_RADIUS};
+
+/// Entity ID reserved for the Leviathan in network messages.
+pub const LEVIATHAN_ENTITY_ID: u64 = u64::MAX - 1;
+
+/// How far from its centre the Leviathan can be struck by a projectile.
+pub const LEVIATHAN_HIT_RADIUS: f32 = 30.0;
+
+/// Settlements on the Havilah shore that the Leviathan can reach.
+pub const COASTAL_SETTLEMENTS: [Vec3; 3] = [
+    Vec3::new(1950.0, 20.0, 0.0),
+    Vec3::new(-1400.0, 20.0, 1350.0),
+    Vec3::new(0.0, 20.0, -1950.0),
+];
+
+/// Schedule and tuning for the Leviathan event.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeviathanConfig {
+    pub first_appearance: f32,    // seconds since server start
+    pub interval: f32,            // seconds between appearances
+    pub duration: f32,            // seconds before it submerges on its own
+    pub max_health: f32,
+    pub swim_speed: f32,          // meters per second
+    pub attack_range: f32,
+    pub attack_damage: f32,
+    pub attack_cooldown: f32,
+    pub min_ranged_distance: f32, // hits from closer than this are shrugged off
+    pub coordination_window: f32, // seconds a hit counts toward coordination
+    pub min_attackers: usize,     // distinct attackers needed for full damage
+    pub solo_damage_factor: f32,  // damage fraction applied when uncoordinated
+}
+
+impl Default for LeviathanConfig {
+    fn default() -> Self {
+        Self {
+            first_appearance: 900.0,
+            interval: 3600.0,
+            duration: 600.0,
+            max_health: 20000.0,
+            swim_speed: 40.0,
+            attack_range: 150.0,
+            attack_damage: 80.0,
+            attack_cooldown: 6.0,
+            min_ranged_distance: 40.0,
+            coordination_window: 10.0,
+            min_attackers: 3,
+            solo_damage_factor: 0.1,
+        }
+    }
+}
+
+/// The Leviathan's behaviour state.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LeviathanState {
+    /// Resting in the deep. The sea crossing is open.
+    Submerged,
+
+    /// Circling the moat and attacking anything in reach.
+    Hunting,
+
+    /// Wounded by coordinated fire. Retreats until its next appearance.
+    DrivenOff,
+}
+
+/// An attack made by the Leviathan this tick.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum LeviathanAttack {
+    /// A player on a raft or boat in the moat.
+    Vessel { target_id: u64, damage: f32 },
+
+    /// A settlement on the shore.
+    Settlement { position: Vec3, damage: f32 },
+}
+
+/// A ranged hit, remembered for the coordination check.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct RangedHit {
+    attacker_id: u64,
+    time: f32,
+}
+
+/// The Leviathan.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Leviathan {
+    pub config: LeviathanConfig,
+    pub state: LeviathanState,
+    pub position: Vec3,
+    pub health: f32,
+    pub time_surfaced: f32,
+    pub attack_timer: f32,
+    recent_hits: Vec<RangedHit>,
+}
+
+impl Leviathan {
+    /// Create a submerged Leviathan.
+    pub fn new(config: LeviathanConfig) -> Self {
+        let health = config.max_health;
+        Self {
+            config,
+            state: LeviathanState::Submerged,
+            position: Vec3::ZERO,
+            health,
+            time_surfaced: 0.0,
+            attack_timer: 0.0,
+            recent_hits: Vec::new(),
+        }
+    }
+
+    /// The radius of the moat's centre line.
+    pub fn moat_radius() -> f32 {
+        ((HAVILAH_RADIUS + GREAT_MOAT_RADIUS) / 2.0) as f32
+    }
+
+    /// Rise from the deep at an angle (radians) around the moat.
+    pub fn surface(&mut self, angle: f32) {
+        let radius = Self::moat_radius();
+        self.state = LeviathanState::Hunting;
+        self.position = Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+        self.health = self.config.max_health;
+        self.time_surfaced = 0.0;
+        self.attack_timer = self.config.attack_cooldown;
+        self.recent_hits.clear();
+    }
+
+    /// Return to the deep.
+    pub fn submerge(&mut self) {
+        self.state = LeviathanState::Submerged;
+        self.recent_hits.clear();
+    }
+
+    /// Check if the Leviathan is hunting.
+    pub fn is_active(&self) -> bool {
+        self.state == LeviathanState::Hunting
+    }
+
+    /// Check if a position is cut off from the sea crossing.
+    pub fn blocks_crossing(&self, position: Vec3) -> bool {
+        self.is_active() && in_great_moat(position.x as f64, position.z as f64)
+    }
+
+    /// Apply a ranged hit. Returns the damage actually dealt.
+    ///
+    /// Melee blows and close hits are shrugged off. Uncoordinated fire only
+    /// scratches the hide; full damage needs several attackers within the
+    /// coordination window.
+    pub fn apply_ranged_damage(&mut self, attacker_id: u64, action: CombatAction, attacker_pos: Vec3, damage: f32) -> f32 {
+        if !self.is_active() || !action.is_ranged() {
+            return 0.0;
+        }
+        if attacker_pos.distance(self.position) < self.config.min_ranged_distance {
+            return 0.0;
+        }
+
+        self.recent_hits.push(RangedHit {
+            attacker_id,
+            time: self.time_surfaced,
+        });
+
+        let factor = if self.coordinated_attackers() >= self.config.min_attackers {
+            1.0
+        } else {
+            self.config.solo_damage_factor
+        };
+        let dealt = damage * factor;
+        self.health = (self.health - dealt).max(0.0);
+
+        if self.health <= 0.0 {
+            self.state = LeviathanState::DrivenOff;
+            self.recent_hits.clear();
+        }
+
+        dealt
+    }
+
+    /// Count the distinct attackers inside the coordination window.
+    pub fn coordinated_attackers(&self) -> usize {
+        let mut attackers: Vec<u64> = Vec::new();
+        for hit in &self.recent_hits {
+            if self.time_surfaced - hit.time <= self.config.coordination_window
+                && !attackers.contains(&hit.attacker_id)
+            {
+                attackers.push(hit.attacker_id);
+            }
+        }
+        attackers.len()
+    }
+
+    /// Advance the hunt. `vessels` are players afloat in the moat.
+    pub fn update(&mut self, delta_seconds: f32, vessels: &[(u64, Vec3)], settlements: &[Vec3]) -> Vec<LeviathanAttack> {
+        let mut attacks = Vec::new();
+        if !self.is_active() {
+            return attacks;
+        }
+
+        self.time_surfaced += delta_seconds;
+        if self.time_surfaced >= self.config.duration {
+            self.submerge();
+            return attacks;
+        }
+
+        let window = self.config.coordination_window;
+        let now = self.time_surfaced;
+        self.recent_hits.retain(|hit| now - hit.time <= window);
+
+        // Hunt the nearest vessel, otherwise circle the moat
+        let prey = vessels
+            .iter()
+            .filter(|(_, pos)| in_great_moat(pos.x as f64, pos.z as f64))
+            .min_by(|a, b| {
+                a.1.distance(self.position)
+                    .partial_cmp(&b.1.distance(self.position))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .copied();
+
+        let step = self.config.swim_speed * delta_seconds;
+        if let Some((_, prey_pos)) = prey {
+            let to_prey = Vec3::new(prey_pos.x - self.position.x, 0.0, prey_pos.z - self.position.z);
+            self.position += to_prey.clamp_length_max(step);
+        } else {
+            let radius = Vec3::new(self.position.x, 0.0, self.position.z).length().max(1.0);
+            let angle = self.position.z.atan2(self.position.x) + step / radius;
+            self.position = Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+        }
+        self.clamp_to_moat();
+
+        self.attack_timer -= delta_seconds;
+        if self.attack_timer > 0.0 {
+            return attacks;
+        }
+
+        let damage = self.config.attack_damage;
+        if let Some((target_id, prey_pos)) = prey {
+            if prey_pos.distance(self.position) <= self.config.attack_range {
+                attacks.push(LeviathanAttack::Vessel { target_id, damage });
+            }
+        }
+        for &settlement in settlements {
+            let flat = Vec3::new(settlement.x, 0.0, settlement.z);
+            if flat.distance(self.position) <= self.config.attack_range {
+                attacks.push(LeviathanAttack::Settlement { position: settlement, damage });
+            }
+        }
+
+        if !attacks.is_empty() {
+            self.attack_timer = self.config.attack_cooldown;
+        }
+        attacks
+    }
+
+    /// Keep the Leviathan inside the moat ring.
+    fn clamp_to_moat(&mut self) {
+        let flat = Vec3::new(self.position.x, 0.0, self.position.z);
+        let radius = flat.length();
+        if radius <= 0.0 {
+            return;
+        }
+        let clamped = radius.clamp(HAVILAH_RADIUS as f32, GREAT_MOAT_RADIUS as f32 - 1.0);
+        self.position = flat * (clamped / radius);
+    }
+}
+
+impl Default for Leviathan {
+    fn default() -> Self {
+        Self::new(LeviathanConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surfaced() -> Leviathan {
+        let mut leviathan = Leviathan::default();
+        leviathan.surface(0.0);
+        leviathan
+    }
+
+    #[test]
+    fn test_blocks_crossing_only_while_hunting() {
+        let mut leviathan = Leviathan::default();
+        let raft = Vec3::new(3000.0, 0.0, 0.0);
+        assert!(!leviathan.blocks_crossing(raft));
+
+        leviathan.surface(0.0);
+        assert!(leviathan.blocks_crossing(raft));
+        assert!(!leviathan.blocks_crossing(Vec3::new(1000.0, 20.0, 0.0)));
+    }
+
+    #[test]
+    fn test_requires_coordinated_ranged_fire() {
+        let mut leviathan = surfaced();
+        let archer = leviathan.position + Vec3::new(-100.0, 0.0, 0.0);
+        let sling = CombatAction::ShepherdSling;
+
+        // Melee range is shrugged off, and so are melee blows from afar
+        assert_eq!(leviathan.apply_ranged_damage(1, sling, leviathan.position, 100.0), 0.0);
+        assert_eq!(leviathan.apply_ranged_damage(1, CombatAction::HunterSlash, archer, 100.0), 0.0);
+
+        // A lone archer barely scratches the hide
+        let solo = leviathan.apply_ranged_damage(1, sling, archer, 100.0);
+        assert_eq!(solo, 10.0);
+
+        leviathan.apply_ranged_damage(2, sling, archer, 100.0);
+        let coordinated = leviathan.apply_ranged_damage(3, sling, archer, 100.0);
+        assert_eq!(coordinated, 100.0);
+
+        for _ in 0..300 {
+            leviathan.apply_ranged_damage(1, sling, archer, 100.0);
+        }
+        assert_eq!(leviathan.state, LeviathanState::DrivenOff);
+        assert!(!leviathan.blocks_crossing(Vec3::new(3000.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_attacks_vessels_and_settlements() {
+        let mut leviathan = surfaced();
+        leviathan.attack_timer = 0.0;
+        let raft = (7, leviathan.position + Vec3::new(0.0, 0.0, 50.0));
+        let attacks = leviathan.update(0.1, &[raft], &COASTAL_SETTLEMENTS);
+        assert!(attacks.contains(&LeviathanAttack::Vessel { target_id: 7, damage: 80.0 }));
+
+        let mut leviathan = Leviathan::default();
+        leviathan.surface(0.0);
+        leviathan.position = Vec3::new(2000.0, 0.0, 0.0);
+        leviathan.attack_timer = 0.0;
+        let attacks = leviathan.update(0.1, &[], &COASTAL_SETTLEMENTS);
+        assert!(matches!(attacks[0], LeviathanAttack::Settlement { .. }));
+    }
+
+    #[test]
+    fn test_submerges_after_duration() {
+        let mut leviathan = surfaced();
+        leviathan.update(601.0, &[], &[]);
+        assert_eq!(leviathan.state, LeviathanState::Submerged);
+    }
+}
@@ -12,6 +12,23 @@ pub mod crafting;
 pub mod endgame;
 pub mod network;
 pub mod events;
+pub mod leviathan;
+pub mod abilities;
+pub mod character;
+pub mod party;
+pub mod status;
+pub mod threat;
+pub mod targeting;
+pub mod skill_chain;
+pub mod projectile;
+pub mod death;
+pub mod pvp;
+pub mod combat_log;
+pub mod spawner;
+pub mod behaviour;
+pub mod variant;
+pub mod mutation;
+pub mod gathering;
 
 pub use world::*;
 pub use entity::*;
@@ -22,3 +39,20 @@ pub use crafting::*;
 pub use endgame::*;
 pub use network::*;
 pub use events::*;
+pub use leviathan::*;
+pub use abilities::*;
+pub use character::*;
+pub use party::*;
+pub use status::*;
+pub use threat::*;
+pub use targeting::*;
+pub use skill_chain::*;
+pub use projectile::*;
+pub use death::*;
+pub use pvp::*;
+pub use combat_log::*;
+pub use spawner::*;
+pub use behaviour::*;
+pub use variant::*;
+pub use mutation::*;
+pub use gathering::*;
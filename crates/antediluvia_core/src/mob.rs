@@ -1,9 +1,58 @@
 //! Mob AI system with Pack Tactics.
 //! 
 //! Mobs hunt in coordinated groups. Solo players cannot defeat them.
+//!
+//! A pack's leader picks the prey. A couple of its members go in while the
+//! rest circle it, taking turns at the front; a howl calls idle kin to the
+//! hunt, and a pack that loses its leader or most of its blood breaks and
+//! runs for home before regrouping.
+//!
+//! Every mob type is defined in `data/mobs.json`: its stats, loot, behaviour
+//! and model. The server and the client both spawn from these definitions,
+//! so balancing is done there.
 
 use serde::{Deserialize, Serialize};
 use glam::Vec3;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::OnceLock;
+use crate::behaviour::{behaviour_library, Action, BehaviourTree, Blackboard, Decision, Fact};
+use crate::combat::{DamageType, Defense, Resistances};
+use crate::error::{AntediluviaError, Result};
+use crate::mutation::{mutant_name, Mutation};
+use crate::status::StatusEffects;
+use crate::threat::{ThreatTable, AGGRO_THREAT};
+use crate::variant::{MobRank, MobVariant};
+
+/// First mob ID, keeping mob IDs clear of player IDs.
+pub const MOB_ID_BASE: u64 = 1 << 32;
+
+/// Distance at which a chasing mob stops to strike.
+pub const MOB_ATTACK_RANGE: f32 = 15.0;
+
+/// Mobs a pack sends in at once; the rest circle the prey.
+pub const PACK_ATTACKERS: usize = 2;
+
+/// Seconds before a pack's attackers fall back and flankers take their turn.
+pub const PACK_ROTATION_SECONDS: f32 = 4.0;
+
+/// Distance a pack's flankers circle their prey at, just out of reach.
+pub const FLANK_RADIUS: f32 = MOB_ATTACK_RANGE * 1.75;
+
+/// Distance within which a lone pack hunter joins a pack of its kind.
+pub const PACK_RANGE: f32 = 60.0;
+
+/// How far a pack's howl carries.
+pub const HOWL_RANGE: f32 = 150.0;
+
+/// Share of the health it went in with that a pack can lose before it breaks.
+pub const RETREAT_HEALTH: f32 = 0.3;
+
+/// Seconds a broken pack flees before regrouping.
+pub const RETREAT_SECONDS: f32 = 8.0;
+
+/// The mobs that ship with the game.
+const MOB_DATA: &str = include_str!("../data/mobs.json");
 
 /// A mob (hostile creature).
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -18,10 +67,32 @@ pub struct Mob {
     pub pack_id: Option<u32>, // Which pack this mob belongs to
     pub aggro_range: f32,
     pub is_aggressive: bool,
+    #[serde(default)]
+    pub home: Vec3, // Where it spawned and returns to
+    #[serde(default)]
+    pub target: Option<u64>, // Player it is chasing
+    #[serde(default)]
+    pub effects: StatusEffects,
+    #[serde(default)]
+    pub threat: ThreatTable,
+    #[serde(default)]
+    pub variant: MobVariant, // Elite or rare, and its affixes
+    #[serde(default)]
+    pub mutation: Mutation, // How far tainted land has turned it
+    #[serde(default)]
+    pub attack_timer: f32, // Seconds until it can strike again
+}
+
+/// An item dropped by a slain mob.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LootDrop {
+    pub name: String,
+    pub quantity: u32,
+    pub weight: f32,
 }
 
 /// Types of mobs.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MobType {
     Wolf,
     Lion,
@@ -31,40 +102,209 @@ pub enum MobType {
 }
 
 impl MobType {
+    /// Every mob type.
+    pub const ALL: [MobType; 5] = [MobType::Wolf, MobType::Lion, MobType::Nephilim, MobType::Chimera, MobType::Corrupted];
+
+    /// Find a mob type by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| format!("{:?}", t) == name)
+    }
+
+    /// Get this mob type's definition from the game's data.
+    pub fn definition(&self) -> &'static MobDefinition {
+        mob_definitions().get(*self)
+    }
+
     /// Get the base HP for this mob type.
     pub fn base_hp(&self) -> f32 {
-        match self {
-            MobType::Wolf => 50.0,
-            MobType::Lion => 100.0,
-            MobType::Nephilim => 500.0,
-            MobType::Chimera => 200.0,
-            MobType::Corrupted => 150.0,
-        }
+        self.definition().stats.health
     }
 
     /// Get the base damage for this mob type.
     pub fn base_damage(&self) -> f32 {
-        match self {
-            MobType::Wolf => 10.0,
-            MobType::Lion => 20.0,
-            MobType::Nephilim => 50.0,
-            MobType::Chimera => 30.0,
-            MobType::Corrupted => 25.0,
-        }
+        self.definition().stats.damage
+    }
+
+    /// Get the movement speed for this mob type.
+    pub fn move_speed(&self) -> f32 {
+        self.definition().behaviour.move_speed
+    }
+
+    /// Get the seconds between this mob type's blows.
+    pub fn attack_cooldown(&self) -> f32 {
+        self.definition().behaviour.attack_cooldown
+    }
+
+    /// Get the experience for slaying a mob of this type at `level`.
+    pub fn xp_reward(&self, level: u32) -> f32 {
+        let stats = &self.definition().stats;
+        stats.xp + level as f32 * stats.xp_per_level
+    }
+
+    /// Get the loot dropped by a mob of this type at `level`.
+    pub fn loot(&self, level: u32) -> Vec<LootDrop> {
+        self.loot_for(level, MobRank::Normal)
+    }
+
+    /// Get the loot dropped by a mob of this type at `level` and `rank`.
+    pub fn loot_for(&self, level: u32, rank: MobRank) -> Vec<LootDrop> {
+        self.definition()
+            .loot
+            .iter()
+            .filter(|entry| level >= entry.min_level && rank >= entry.rank)
+            .map(|entry| LootDrop {
+                name: entry.name.clone(),
+                quantity: entry.quantity,
+                weight: entry.weight,
+            })
+            .collect()
+    }
+
+    /// Get the kind of damage this mob's attacks deal.
+    pub fn attack_type(&self) -> DamageType {
+        self.definition().stats.attack_type
+    }
+
+    /// Check if this is a Giant-class enemy.
+    pub fn is_giant(&self) -> bool {
+        self.definition().giant
     }
 
     /// Get the aggro range for this mob type.
     pub fn aggro_range(&self) -> f32 {
-        match self {
-            MobType::Wolf => 50.0,
-            MobType::Lion => 75.0,
-            MobType::Nephilim => 200.0,
-            MobType::Chimera => 100.0,
-            MobType::Corrupted => 80.0,
+        self.definition().behaviour.aggro_range
+    }
+
+    /// Get how far from its centre this mob can be struck by a projectile.
+    pub fn hit_radius(&self) -> f32 {
+        self.definition().model.radius
+    }
+
+    /// Get how dangerous this mob type is.
+    pub fn tier(&self) -> MobTier {
+        self.definition().tier
+    }
+}
+
+/// How dangerous a mob is, which sets how it is drawn and marked.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MobTier {
+    Common,
+    Elite,
+    Boss,
+}
+
+/// A mob type's fighting numbers at level zero.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MobStats {
+    pub health: f32,
+    pub damage: f32, // Per blow
+    pub armor: f32,
+    pub resistances: Resistances,
+    pub attack_type: DamageType,
+    pub xp: f32,
+    pub xp_per_level: f32,
+}
+
+/// An item a mob type may drop.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LootEntry {
+    pub name: String,
+    pub quantity: u32,
+    pub weight: f32,
+    #[serde(default)]
+    pub min_level: u32, // Only mobs this level or higher drop it
+    #[serde(default)]
+    pub rank: MobRank, // Only mobs of this rank or higher drop it
+}
+
+/// How a mob type hunts.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MobBehaviour {
+    pub aggro_range: f32,
+    pub move_speed: f32,
+    pub attack_cooldown: f32, // Seconds between blows
+    #[serde(default)]
+    pub pack: bool, // Hunts in packs
+    pub tree: String, // The behaviour tree it decides with
+}
+
+/// How a mob type looks.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MobModel {
+    pub color: [f32; 3], // sRGB
+    pub radius: f32, // Body size, and how wide it stands to a projectile
+}
+
+/// Everything about a mob type: its stats, loot, behaviour and model.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MobDefinition {
+    pub mob_type: MobType,
+    pub name: String,
+    pub tier: MobTier,
+    #[serde(default)]
+    pub giant: bool,
+    #[serde(default)]
+    pub mutates: bool, // Turns in tainted land
+    pub stats: MobStats,
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+    pub behaviour: MobBehaviour,
+    pub model: MobModel,
+}
+
+/// Every mob type's definition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MobRegistry {
+    mobs: Vec<MobDefinition>,
+}
+
+impl MobRegistry {
+    /// Build a registry, checking every mob type is defined exactly once.
+    pub fn new(mobs: Vec<MobDefinition>) -> Result<Self> {
+        let invalid = |reason: String| Err(AntediluviaError::DataError(reason));
+        for mob_type in MobType::ALL {
+            match mobs.iter().filter(|d| d.mob_type == mob_type).count() {
+                0 => return invalid(format!("mob {:?} has no definition", mob_type)),
+                1 => {}
+                _ => return invalid(format!("mob {:?} is defined more than once", mob_type)),
+            }
         }
+        if let Some(mob) = mobs.iter().find(|d| d.stats.health <= 0.0 || d.behaviour.attack_cooldown <= 0.0) {
+            return invalid(format!("mob {}: health and attack cooldown must be positive", mob.name));
+        }
+        if let Some(mob) = mobs.iter().find(|d| behaviour_library().get(&d.behaviour.tree).is_none()) {
+            return invalid(format!("mob {}: no behaviour tree {}", mob.name, mob.behaviour.tree));
+        }
+        Ok(Self { mobs })
+    }
+
+    /// Load a registry from JSON: the mob definitions.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let data: Self = serde_json::from_str(json)?;
+        Self::new(data.mobs)
+    }
+
+    /// Get a mob type's definition.
+    pub fn get(&self, mob_type: MobType) -> &MobDefinition {
+        self.mobs
+            .iter()
+            .find(|d| d.mob_type == mob_type)
+            .expect("every mob type is defined")
+    }
+
+    /// Get every definition.
+    pub fn definitions(&self) -> &[MobDefinition] {
+        &self.mobs
     }
 }
 
+/// The mob registry, loaded from the game's data on first use.
+pub fn mob_definitions() -> &'static MobRegistry {
+    static REGISTRY: OnceLock<MobRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| MobRegistry::from_json(MOB_DATA).expect("mob data is invalid"))
+}
+
 impl Mob {
     /// Create a new mob.
     pub fn new(id: u64, name: String, mob_type: MobType, position: Vec3, level: u32) -> Self {
@@ -80,9 +320,24 @@ impl Mob {
             pack_id: None,
             aggro_range: mob_type.aggro_range(),
             is_aggressive: false,
+            home: position,
+            target: None,
+            effects: StatusEffects::new(),
+            threat: ThreatTable::new(),
+            variant: MobVariant::default(),
+            mutation: Mutation::default(),
+            attack_timer: 0.0,
         }
     }
 
+    /// Make the mob an elite or rare, at full health for its rank.
+    pub fn with_variant(mut self, variant: MobVariant) -> Self {
+        self.max_health *= variant.health_multiplier();
+        self.health = self.max_health;
+        self.variant = variant;
+        self
+    }
+
     /// Check if the mob is alive.
     pub fn is_alive(&self) -> bool {
         self.health > 0.0
@@ -95,7 +350,72 @@ impl Mob {
 
     /// Get the damage this mob deals.
     pub fn get_damage(&self) -> f32 {
-        self.mob_type.base_damage() * (1.0 + self.level as f32 * 0.05)
+        self.mob_type.base_damage() * (1.0 + self.level as f32 * 0.05) * self.variant.damage_multiplier()
+    }
+
+    /// Get how fast the mob moves.
+    pub fn move_speed(&self) -> f32 {
+        self.mob_type.move_speed() * self.variant.speed_multiplier()
+    }
+
+    /// Get the experience for slaying the mob.
+    pub fn xp_reward(&self) -> f32 {
+        self.mob_type.xp_reward(self.level) * self.variant.rank.xp_multiplier()
+    }
+
+    /// Get the loot the mob drops.
+    pub fn loot(&self) -> Vec<LootDrop> {
+        self.variant.loot(self.mob_type, self.level)
+    }
+
+    /// Get the mob's armor and resistances.
+    pub fn defense(&self) -> Defense {
+        Defense::for_variant(self.mob_type, self.level, &self.variant)
+    }
+
+    /// Heal as a Regenerating mob does over `delta_seconds`. The dead stay dead.
+    pub fn regenerate(&mut self, delta_seconds: f32) {
+        if self.is_alive() {
+            self.health = (self.health + self.variant.regeneration(self.max_health) * delta_seconds).min(self.max_health);
+        }
+    }
+
+    /// Soak the mob in the corruption of the land it stands in for
+    /// `delta_seconds`. Returns true if it turned.
+    pub fn expose(&mut self, corruption: f32, delta_seconds: f32) -> bool {
+        match self.mutation.expose(self.mob_type, corruption, delta_seconds) {
+            Some(into) => {
+                self.mutate(into);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turn the mob into a mutant of `into`, remembering what it was.
+    pub fn mutate(&mut self, into: MobType) {
+        self.mutation.turn(self.mob_type, &self.name);
+        self.name = mutant_name(into, &self.name);
+        self.reshape(into);
+    }
+
+    /// Return a mutant to what it was. Returns false if it never turned.
+    pub fn redeem(&mut self) -> bool {
+        let Some((mob_type, name)) = self.mutation.redeem() else {
+            return false;
+        };
+        self.name = name;
+        self.reshape(mob_type);
+        true
+    }
+
+    /// Take on the stats of another mob type, keeping its share of health.
+    fn reshape(&mut self, mob_type: MobType) {
+        let share = self.health / self.max_health;
+        self.mob_type = mob_type;
+        self.max_health = mob_type.base_hp() * (1.0 + self.level as f32 * 0.1) * self.variant.health_multiplier();
+        self.health = self.max_health * share;
+        self.aggro_range = mob_type.aggro_range();
     }
 
     /// Check if a target is in aggro range.
@@ -103,6 +423,195 @@ impl Mob {
         let distance = self.position.distance(target_pos);
         distance <= self.aggro_range
     }
+
+    /// Take notice of players who wander into aggro range, holding a little
+    /// threat against each so the mob turns on them unprovoked.
+    pub fn notice(&mut self, players: &[(u64, Vec3)]) {
+        for (id, pos) in players {
+            if self.is_in_range(*pos) && !self.threat.contains(*id) {
+                self.threat.add(*id, AGGRO_THREAT);
+            }
+        }
+    }
+
+    /// How far the mob will chase from home before giving up.
+    pub fn leash_range(&self) -> f32 {
+        self.aggro_range * 3.0
+    }
+
+    /// Chase the target at `target_pos`, or walk home if there is none or it
+    /// has led the mob past its leash. A leashed mob forgets all threat.
+    pub fn chase(&mut self, target_pos: Option<Vec3>, delta_seconds: f32) {
+        let destination = match target_pos {
+            Some(pos) if self.home.distance(pos) <= self.leash_range() => pos,
+            Some(_) => {
+                self.target = None;
+                self.threat.wipe();
+                self.home
+            }
+            None => {
+                self.target = None;
+                self.home
+            }
+        };
+
+        let stop = if self.target.is_some() { MOB_ATTACK_RANGE } else { 0.0 };
+        self.walk_to(destination, stop, delta_seconds);
+    }
+
+    /// Carry out a pack's order. Prey is looked up in `players`; a flanker
+    /// whose prey has strayed past its leash gives up as a chaser would.
+    pub fn follow(&mut self, order: PackOrder, players: &[(u64, Vec3)], delta_seconds: f32) {
+        let prey_pos = |id: u64| players.iter().find(|(p, _)| *p == id).map(|(_, pos)| *pos);
+        match order {
+            PackOrder::Attack { target } => {
+                self.target = Some(target);
+                self.chase(prey_pos(target), delta_seconds);
+            }
+            PackOrder::Flank { target, position } => {
+                self.target = Some(target);
+                match prey_pos(target) {
+                    Some(pos) if self.home.distance(pos) <= self.leash_range() => {
+                        self.walk_to(position, 0.0, delta_seconds);
+                    }
+                    pos => self.chase(pos, delta_seconds),
+                }
+            }
+            PackOrder::Retreat { home } => {
+                self.target = None;
+                self.threat.wipe();
+                self.walk_to(home, 0.0, delta_seconds);
+            }
+        }
+    }
+
+    /// Get the behaviour tree the mob decides with.
+    pub fn tree(&self) -> &'static BehaviourTree {
+        behaviour_library().get(&self.mob_type.definition().behaviour.tree).expect("mob trees are checked when mobs load")
+    }
+
+    /// Fill a blackboard with what the mob knows: where its prey is, how far
+    /// it has strayed from home, its blood, and its pack's word.
+    pub fn blackboard(&self, prey_pos: Option<Vec3>, order: Option<PackOrder>) -> Blackboard {
+        let prey_distance = prey_pos.map(|pos| self.position.distance(pos));
+        let home_distance = self.position.distance(self.home);
+        Blackboard::new()
+            .with_flag(Fact::Provoked, prey_pos.is_some())
+            .with_flag(Fact::InReach, prey_distance.is_some_and(|d| d < MOB_ATTACK_RANGE * 1.5))
+            .with(Fact::TargetDistance, prey_distance.unwrap_or(0.0))
+            .with(Fact::HomeDistance, home_distance)
+            .with_flag(Fact::Leashed, home_distance > self.leash_range())
+            .with(Fact::Health, self.health / self.max_health)
+            .with_flag(Fact::PackAttack, matches!(order, Some(PackOrder::Attack { .. })))
+            .with_flag(Fact::PackFlank, matches!(order, Some(PackOrder::Flank { .. })))
+            .with_flag(Fact::PackRetreat, matches!(order, Some(PackOrder::Retreat { .. })))
+    }
+
+    /// Decide with the mob's behaviour tree what to do about its target and
+    /// its pack's order, and do it. Prey is looked up in `players`; the pack
+    /// picks it for mobs it leads. Returns the decision, for the debugger.
+    pub fn think(&mut self, order: Option<PackOrder>, players: &[(u64, Vec3)], delta_seconds: f32) -> Decision {
+        self.attack_timer = (self.attack_timer - delta_seconds).max(0.0);
+        let prey_pos = self.prey(order, players).map(|(_, pos)| pos);
+        let decision = self.tree().decide(&self.blackboard(prey_pos, order));
+
+        match (decision.action, order) {
+            (Some(Action::Retreat | Action::Flank), Some(order)) | (Some(Action::Chase | Action::Strike), Some(order @ PackOrder::Attack { .. })) => {
+                self.follow(order, players, delta_seconds);
+            }
+            (Some(Action::Chase | Action::Strike | Action::Flank), _) => self.chase(prey_pos, delta_seconds),
+            (Some(Action::ReturnHome | Action::Retreat), _) => {
+                // Turned for home, it forgets the fight
+                self.target = None;
+                self.threat.wipe();
+                self.walk_to(self.home, 0.0, delta_seconds);
+            }
+            (Some(Action::Wander), _) => self.chase(None, delta_seconds),
+            _ => self.target = None,
+        }
+        decision
+    }
+
+    /// Land a blow on the mob's prey, if it is in reach and the mob has caught
+    /// its breath since its last. Returns who was struck. Called when `think`
+    /// decides to strike.
+    pub fn swing(&mut self, order: Option<PackOrder>, players: &[(u64, Vec3)]) -> Option<u64> {
+        let (prey, prey_pos) = self.prey(order, players)?;
+        if self.attack_timer > 0.0 || self.position.distance(prey_pos) >= MOB_ATTACK_RANGE * 1.5 {
+            return None;
+        }
+        self.attack_timer = self.mob_type.attack_cooldown();
+        Some(prey)
+    }
+
+    /// Find the player the mob is after, and where they stand: its pack's
+    /// choice if it has one, otherwise its own.
+    fn prey(&self, order: Option<PackOrder>, players: &[(u64, Vec3)]) -> Option<(u64, Vec3)> {
+        let prey = match order {
+            Some(PackOrder::Attack { target } | PackOrder::Flank { target, .. }) => Some(target),
+            _ => self.target,
+        }?;
+        players.iter().find(|(p, _)| *p == prey).copied()
+    }
+
+    /// Walk toward `destination`, stopping `stop` short of it.
+    fn walk_to(&mut self, destination: Vec3, stop: f32, delta_seconds: f32) {
+        let offset = destination - self.position;
+        let distance = offset.length();
+        if distance > stop {
+            let step = (self.move_speed() * delta_seconds).min(distance - stop);
+            self.position += offset / distance * step;
+        }
+    }
+}
+
+/// What pack tactics needs to know of a mob, so the server's mobs and the
+/// client's can both be led.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackMember {
+    pub id: u64,
+    pub mob_type: MobType,
+    pub position: Vec3,
+    pub home: Vec3,
+    pub health: f32,
+    pub max_health: f32,
+    pub target: Option<u64>, // Whom the mob itself is angriest with
+}
+
+impl From<&Mob> for PackMember {
+    fn from(mob: &Mob) -> Self {
+        Self {
+            id: mob.id,
+            mob_type: mob.mob_type,
+            position: mob.position,
+            home: mob.home,
+            health: mob.health,
+            max_health: mob.max_health,
+            target: mob.target,
+        }
+    }
+}
+
+/// What a pack tells one of its members to do.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PackOrder {
+    Attack { target: u64 }, // Close in and strike
+    Flank { target: u64, position: Vec3 }, // Hold this place around the prey
+    Retreat { home: Vec3 }, // Flee home, forgetting the fight
+}
+
+/// The orders from one round of pack tactics.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PackOrders {
+    pub orders: HashMap<u64, PackOrder>, // Mob ID -> order; mobs without one act alone
+    pub howls: Vec<u64>, // Leaders who howled for kin this round
+}
+
+impl PackOrders {
+    /// Get the order for a mob, if its pack gave one.
+    pub fn get(&self, mob_id: u64) -> Option<PackOrder> {
+        self.orders.get(&mob_id).copied()
+    }
 }
 
 /// A pack of mobs that hunt together.
@@ -113,6 +622,14 @@ pub struct MobPack {
     pub leader_id: u64,
     pub target: Option<u64>, // Current target (player ID)
     pub coordination_level: f32, // 0.0 (chaotic) to 1.0 (perfect)
+    #[serde(default)]
+    pub rotation: usize, // Turns taken at the front so far
+    #[serde(default)]
+    pub rotation_timer: f32,
+    #[serde(default)]
+    pub retreat_timer: f32, // Seconds left fleeing; zero while hunting
+    #[serde(default)]
+    pub engaged_health: f32, // The pack's health when the hunt began
 }
 
 impl MobPack {
@@ -124,6 +641,10 @@ impl MobPack {
             leader_id,
             target: None,
             coordination_level: 0.8, // Packs are well-coordinated
+            rotation: 0,
+            rotation_timer: PACK_ROTATION_SECONDS,
+            retreat_timer: 0.0,
+            engaged_health: 0.0,
         }
     }
 
@@ -148,6 +669,50 @@ impl MobPack {
     pub fn should_attack(&self) -> bool {
         self.size() >= 2 || self.coordination_level > 0.9
     }
+
+    /// Check if the pack is broken and fleeing.
+    pub fn is_retreating(&self) -> bool {
+        self.retreat_timer > 0.0
+    }
+
+    /// Get the members whose turn it is at the front.
+    pub fn attackers(&self) -> Vec<u64> {
+        let n = self.size();
+        (0..PACK_ATTACKERS.min(n)).map(|i| self.mobs[(self.rotation + i) % n]).collect()
+    }
+
+    /// Break off the hunt and flee.
+    fn retreat(&mut self) {
+        self.target = None;
+        self.retreat_timer = RETREAT_SECONDS;
+    }
+
+    /// Get where each flanker should stand around prey at `prey_pos`: spread
+    /// evenly round from the side the leader comes at, as precisely as the
+    /// pack's coordination allows.
+    fn flank_positions(&self, flankers: &[u64], prey_pos: Vec3, leader_pos: Vec3) -> Vec<Vec3> {
+        let approach = leader_pos - prey_pos;
+        let base = if approach.x == 0.0 && approach.z == 0.0 { 0.0 } else { approach.z.atan2(approach.x) };
+        let sloppiness = 1.0 - self.coordination_level.clamp(0.0, 1.0);
+        let slots = flankers.len() as f32 + 1.0;
+        flankers
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let stray = scatter(id, self.rotation);
+                let angle = base + TAU * (i as f32 + 1.0) / slots + sloppiness * stray * FRAC_PI_2;
+                let radius = FLANK_RADIUS * (1.0 + sloppiness * stray);
+                prey_pos + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius
+            })
+            .collect()
+    }
+}
+
+/// A mob's fixed stray from its place in the pack, in [-0.5, 0.5); it changes
+/// with each rotation so sloppy packs do not shuffle every frame.
+fn scatter(mob_id: u64, rotation: usize) -> f32 {
+    let hash = (mob_id ^ rotation as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    (hash >> 40) as f32 / (1u64 << 24) as f32 - 0.5
 }
 
 /// Pack Tactics AI behavior.
@@ -203,6 +768,153 @@ impl PackTacticsAI {
             0.0
         }
     }
+
+    /// Get the pack a mob belongs to.
+    pub fn pack_of(&self, mob_id: u64) -> Option<&MobPack> {
+        self.packs.iter().find(|p| p.mobs.contains(&mob_id))
+    }
+
+    /// Lead every pack for a round: gather lone pack hunters into packs, pick
+    /// each pack's prey and tell its members how to take it. `members` is
+    /// every mob in the world; `players` the living prey and where they are.
+    pub fn update(&mut self, members: &[PackMember], players: &[(u64, Vec3)], delta_seconds: f32) -> PackOrders {
+        let living: HashMap<u64, &PackMember> = members.iter().filter(|m| m.health > 0.0).map(|m| (m.id, m)).collect();
+        for pack in &mut self.packs {
+            pack.mobs.retain(|id| living.contains_key(id));
+        }
+        self.packs.retain(|p| !p.mobs.is_empty());
+        self.gather(members);
+
+        let prey_pos = |id: u64| players.iter().find(|(p, _)| *p == id).map(|(_, pos)| *pos);
+        let mut orders = PackOrders::default();
+        let mut howling = Vec::new();
+        for pack in &mut self.packs {
+            let health: f32 = pack.mobs.iter().map(|id| living[id].health).sum();
+
+            // A fallen leader breaks the hunt; the hardiest survivor leads after
+            if !pack.mobs.contains(&pack.leader_id) {
+                pack.leader_id = *pack
+                    .mobs
+                    .iter()
+                    .min_by(|a, b| living[*b].health.total_cmp(&living[*a].health))
+                    .expect("empty packs are disbanded");
+                if pack.target.is_some() {
+                    pack.retreat();
+                }
+            }
+            if pack.target.is_some() && health < pack.engaged_health * RETREAT_HEALTH {
+                pack.retreat();
+            }
+
+            if pack.is_retreating() {
+                pack.retreat_timer = (pack.retreat_timer - delta_seconds).max(0.0);
+                for id in &pack.mobs {
+                    orders.orders.insert(*id, PackOrder::Retreat { home: living[id].home });
+                }
+                continue;
+            }
+
+            // The leader picks the prey, answering for any member set upon
+            let leader = living[&pack.leader_id];
+            let target = leader
+                .target
+                .into_iter()
+                .chain(pack.mobs.iter().filter_map(|id| living[id].target))
+                .find(|t| prey_pos(*t).is_some());
+            if target.is_some() && pack.target.is_none() {
+                pack.engaged_health = health;
+                howling.push(pack.id);
+            }
+            pack.target = target;
+            let (Some(target), Some(prey)) = (target, target.and_then(prey_pos)) else {
+                continue;
+            };
+            if !pack.should_attack() {
+                continue; // A pack of one fights as a loner
+            }
+
+            pack.rotation_timer -= delta_seconds;
+            if pack.rotation_timer <= 0.0 {
+                pack.rotation += PACK_ATTACKERS;
+                pack.rotation_timer = PACK_ROTATION_SECONDS;
+            }
+            let attackers = pack.attackers();
+            let flankers: Vec<u64> = pack.mobs.iter().copied().filter(|id| !attackers.contains(id)).collect();
+            for id in attackers {
+                orders.orders.insert(id, PackOrder::Attack { target });
+            }
+            for (id, position) in flankers.iter().zip(pack.flank_positions(&flankers, prey, leader.position)) {
+                orders.orders.insert(*id, PackOrder::Flank { target, position });
+            }
+        }
+
+        for pack_id in howling {
+            if let Some(leader) = self.howl(pack_id, members) {
+                orders.howls.push(leader);
+            }
+        }
+        orders
+    }
+
+    /// Join each lone pack hunter to the nearest pack of its kind in range,
+    /// or have it found one.
+    fn gather(&mut self, members: &[PackMember]) {
+        let packed: HashSet<u64> = self.packs.iter().flat_map(|p| p.mobs.iter().copied()).collect();
+        for member in members.iter().filter(|m| m.health > 0.0 && m.mob_type.definition().behaviour.pack) {
+            if packed.contains(&member.id) {
+                continue;
+            }
+            let nearest = self
+                .packs
+                .iter_mut()
+                .filter(|p| p.target.is_none() && !p.is_retreating())
+                .filter_map(|p| {
+                    let leader = members.iter().find(|m| m.id == p.leader_id)?;
+                    let distance = leader.position.distance(member.position);
+                    (leader.mob_type == member.mob_type && distance <= PACK_RANGE).then_some((p, distance))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match nearest {
+                Some((pack, _)) => pack.add_mob(member.id),
+                None => {
+                    self.create_pack(member.id);
+                }
+            }
+        }
+    }
+
+    /// Howl for kin: idle packs of the leader's kind within earshot join the
+    /// hunt. Returns the leader who howled.
+    fn howl(&mut self, pack_id: u32, members: &[PackMember]) -> Option<u64> {
+        let pack = self.packs.iter().find(|p| p.id == pack_id)?;
+        let leader = members.iter().find(|m| m.id == pack.leader_id)?;
+        let heard: Vec<u32> = self
+            .packs
+            .iter()
+            .filter(|p| p.id != pack_id && p.target.is_none() && !p.is_retreating())
+            .filter(|p| {
+                members
+                    .iter()
+                    .find(|m| m.id == p.leader_id)
+                    .is_some_and(|m| m.mob_type == leader.mob_type && m.position.distance(leader.position) <= HOWL_RANGE)
+            })
+            .map(|p| p.id)
+            .collect();
+
+        let recruits: Vec<u64> = self
+            .packs
+            .iter()
+            .filter(|p| heard.contains(&p.id))
+            .flat_map(|p| p.mobs.iter().copied())
+            .collect();
+        self.packs.retain(|p| !heard.contains(&p.id));
+        let pack = self.packs.iter_mut().find(|p| p.id == pack_id)?;
+        for id in recruits {
+            pack.engaged_health += members.iter().find(|m| m.id == id).map_or(0.0, |m| m.health);
+            pack.add_mob(id);
+        }
+        Some(leader.id)
+    }
 }
 
 impl Default for PackTacticsAI {
@@ -243,4 +955,215 @@ mod tests {
         ai.recruit_nearby(pack_id, &mobs, 50.0);
         assert_eq!(ai.packs[0].size(), 2);
     }
+
+    #[test]
+    fn test_mobs_notice_the_near() {
+        let mut mob = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 1);
+        let near = Vec3::X * (mob.aggro_range - 1.0);
+        let players = [(7, near), (8, Vec3::X * (mob.aggro_range + 1.0))];
+        mob.notice(&players);
+        assert_eq!(mob.threat.select_target(mob.position, &players), Some(7));
+        assert!(!mob.threat.contains(8));
+
+        // Noticing again adds nothing to threat already held
+        mob.notice(&players);
+        assert_eq!(mob.threat.threat(7), AGGRO_THREAT);
+    }
+
+    #[test]
+    fn test_chase_respects_leash() {
+        let mut mob = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 1);
+        mob.target = Some(7);
+        mob.threat.add(7, 50.0);
+
+        // Closes on the target but stops within striking distance
+        let player = Vec3::new(40.0, 0.0, 0.0);
+        for _ in 0..10 {
+            mob.chase(Some(player), 0.5);
+        }
+        assert!((mob.position.distance(player) - MOB_ATTACK_RANGE).abs() < 0.01);
+
+        // A target past the leash is abandoned
+        mob.chase(Some(Vec3::new(1000.0, 0.0, 0.0)), 0.5);
+        assert_eq!(mob.target, None);
+        assert!(mob.threat.is_empty());
+        for _ in 0..10 {
+            mob.chase(None, 0.5);
+        }
+        assert_eq!(mob.position, mob.home);
+    }
+
+    fn wolf(id: u64, x: f32) -> PackMember {
+        PackMember::from(&Mob::new(id, "Wolf".to_string(), MobType::Wolf, Vec3::new(x, 0.0, 0.0), 1))
+    }
+
+    #[test]
+    fn test_pack_surrounds_and_rotates() {
+        let mut ai = PackTacticsAI::new();
+        let lion = PackMember::from(&Mob::new(9, "Lion".to_string(), MobType::Lion, Vec3::ZERO, 1));
+        let nephilim = PackMember::from(&Mob::new(10, "Nephilim".to_string(), MobType::Nephilim, Vec3::ZERO, 1));
+        let mut members = vec![wolf(1, 0.0), wolf(2, 10.0), wolf(3, 20.0), lion, nephilim];
+        let players = [(7, Vec3::new(60.0, 0.0, 0.0))];
+
+        // Wolves run together; a lone lion is a pack of one; giants hunt alone
+        assert!(ai.update(&members, &players, 0.1).orders.is_empty());
+        assert_eq!(ai.packs.len(), 2);
+        assert_eq!(ai.pack_of(3).map(|p| p.size()), Some(3));
+        assert!(ai.pack_of(10).is_none());
+        ai.packs[0].coordination_level = 1.0;
+
+        // The leader spots prey: two go in, the third circles, and the pack howls
+        members[0].target = Some(7);
+        let orders = ai.update(&members, &players, 0.1);
+        assert_eq!(orders.howls, vec![1]);
+        assert_eq!(orders.get(1), Some(PackOrder::Attack { target: 7 }));
+        assert_eq!(orders.get(2), Some(PackOrder::Attack { target: 7 }));
+        let Some(PackOrder::Flank { position, .. }) = orders.get(3) else { panic!("wolf 3 should flank") };
+        assert!((position.distance(players[0].1) - FLANK_RADIUS).abs() < 0.01);
+        assert!(orders.get(9).is_none()); // The lion is not part of this
+
+        // In time the flanker takes its turn at the front
+        let orders = ai.update(&members, &players, PACK_ROTATION_SECONDS);
+        assert_eq!(orders.get(3), Some(PackOrder::Attack { target: 7 }));
+        assert!(orders.howls.is_empty());
+    }
+
+    #[test]
+    fn test_pack_howls_and_retreats() {
+        let mut ai = PackTacticsAI::new();
+        let mut members = vec![wolf(1, 0.0), wolf(2, 10.0), wolf(3, 100.0), wolf(4, 110.0)];
+        let players = [(7, Vec3::new(-30.0, 0.0, 0.0))];
+        ai.update(&members, &players, 0.1);
+        assert_eq!(ai.packs.len(), 2);
+
+        // A howl carries further than packs gather from
+        members[1].target = Some(7);
+        let orders = ai.update(&members, &players, 0.1);
+        assert_eq!(orders.howls, vec![1]);
+        assert_eq!(ai.packs.len(), 1);
+        assert_eq!(ai.packs[0].size(), 4);
+
+        // Losing the leader breaks the pack; the hardiest survivor leads after
+        members[0].health = 0.0;
+        members[2].health -= 5.0;
+        let orders = ai.update(&members, &players, 0.1);
+        assert_eq!(orders.get(3), Some(PackOrder::Retreat { home: members[2].home }));
+        assert_eq!(ai.packs[0].leader_id, 2);
+        assert!(ai.packs[0].is_retreating());
+
+        // Regrouped, it hunts again until it has bled too much
+        members[1].target = None;
+        ai.update(&members, &players, RETREAT_SECONDS);
+        assert!(!ai.packs[0].is_retreating());
+        members[3].target = Some(7);
+        assert_eq!(ai.update(&members, &players, 0.1).get(2), Some(PackOrder::Attack { target: 7 }));
+        for m in &mut members[1..] {
+            m.health *= RETREAT_HEALTH * 0.9;
+        }
+        assert!(matches!(ai.update(&members, &players, 0.1).get(4), Some(PackOrder::Retreat { .. })));
+    }
+
+    #[test]
+    fn test_mob_follows_orders() {
+        let mut mob = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 1);
+        let players = [(7, Vec3::new(40.0, 0.0, 0.0))];
+        let position = Vec3::new(40.0, 0.0, 25.0);
+        for _ in 0..10 {
+            mob.follow(PackOrder::Flank { target: 7, position }, &players, 0.5);
+        }
+        assert_eq!(mob.target, Some(7));
+        assert!(mob.position.distance(position) < 0.01);
+
+        mob.threat.add(7, 10.0);
+        for _ in 0..10 {
+            mob.follow(PackOrder::Retreat { home: mob.home }, &players, 0.5);
+        }
+        assert_eq!((mob.target, mob.position), (None, Vec3::ZERO));
+        assert!(mob.threat.is_empty());
+    }
+
+    #[test]
+    fn test_mobs_act_on_their_trees() {
+        let players = [(7, Vec3::new(40.0, 0.0, 0.0))];
+
+        // Unprovoked, a wolf wanders about home; angered, it closes on its prey
+        let mut wolf = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 1);
+        assert_eq!(wolf.think(None, &players, 0.5).action, Some(Action::Wander));
+        wolf.target = Some(7);
+        assert_eq!(wolf.think(None, &players, 0.5).action, Some(Action::Chase));
+        assert!(wolf.position.x > 0.0);
+        for _ in 0..10 {
+            wolf.think(None, &players, 0.5);
+        }
+        assert_eq!(wolf.think(None, &players, 0.5).action, Some(Action::Strike));
+
+        // A blow lands, and the next must wait for its breath
+        assert_eq!(wolf.swing(None, &players), Some(7));
+        assert_eq!(wolf.swing(None, &players), None);
+        wolf.think(None, &players, MobType::Wolf.attack_cooldown());
+        assert_eq!(wolf.swing(None, &players), Some(7));
+
+        // Its pack's word comes first, and it does not need to be angry to heed it
+        let mut packed = Mob::new(2, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 1);
+        let position = Vec3::new(40.0, 0.0, 25.0);
+        let decision = packed.think(Some(PackOrder::Flank { target: 7, position }), &players, 0.5);
+        assert_eq!((decision.action, packed.target), (Some(Action::Flank), Some(7)));
+
+        // Led past its leash, it turns for home and forgets the fight
+        wolf.position = Vec3::X * (wolf.leash_range() + 1.0);
+        assert_eq!(wolf.think(None, &players, 0.5).action, Some(Action::ReturnHome));
+        assert!(wolf.target.is_none() && wolf.threat.is_empty());
+
+        // A Nephilim stands guard: it walks back to its post and waits there
+        let mut giant = Mob::new(3, "Nephilim".to_string(), MobType::Nephilim, Vec3::ZERO, 5);
+        giant.position = Vec3::new(0.0, 0.0, 10.0);
+        assert_eq!(giant.think(None, &players, 0.5).action, Some(Action::ReturnHome));
+        for _ in 0..10 {
+            giant.think(None, &players, 0.5);
+        }
+        let post = giant.position;
+        assert_eq!(giant.think(None, &players, 0.5).action, Some(Action::Idle));
+        assert_eq!(giant.position, post);
+        giant.target = Some(7);
+        assert_eq!(giant.think(None, &players, 0.5).action, Some(Action::Chase));
+    }
+
+    #[test]
+    fn test_mobs_from_definitions() {
+        let wolf = MobType::Wolf.definition();
+        assert_eq!((wolf.name.as_str(), wolf.tier), ("Wolf", MobTier::Common));
+        assert!(MobType::Nephilim.is_giant() && !MobType::Wolf.is_giant());
+        assert_eq!(MobType::from_name("Chimera"), Some(MobType::Chimera));
+
+        // Fangs only come from older wolves
+        assert_eq!(MobType::Wolf.loot(1).len(), 1);
+        assert_eq!(MobType::Wolf.loot(2)[1].name, "Wolf Fang");
+
+        // Mobs take their numbers from the definitions
+        let mob = Mob::new(1, wolf.name.clone(), MobType::Wolf, Vec3::ZERO, 2);
+        assert_eq!(mob.aggro_range, wolf.behaviour.aggro_range);
+        assert!(mob.max_health > wolf.stats.health && mob.get_damage() > wolf.stats.damage);
+    }
+
+    #[test]
+    fn test_invalid_mob_data() {
+        let mut data: serde_json::Value = serde_json::from_str(MOB_DATA).unwrap();
+        let wolf = data["mobs"][0].clone();
+        let lion = data["mobs"].as_array_mut().unwrap().remove(1);
+        assert!(MobRegistry::from_json(&data.to_string()).is_err()); // Lions undefined
+
+        data["mobs"].as_array_mut().unwrap().push(lion);
+        assert!(MobRegistry::from_json(&data.to_string()).is_ok());
+
+        let mut twice = data.clone();
+        twice["mobs"].as_array_mut().unwrap().push(wolf);
+        assert!(MobRegistry::from_json(&twice.to_string()).is_err());
+
+        let mut untaught = data.clone();
+        untaught["mobs"][0]["behaviour"]["tree"] = "dancing".into();
+        assert!(MobRegistry::from_json(&untaught.to_string()).is_err());
+
+        data["mobs"][0]["stats"]["health"] = 0.0.into();
+        assert!(MobRegistry::from_json(&data.to_string()).is_err());
+    }
 }
@@ -0,0 +1,354 @@
+//! Corruption-driven mutation.
+//!
+//! Each spawn zone is a region of the world, and holds corruption of its own
+//! on top of the world's: the taint its land was born with in
+//! `data/spawns.json`, and whatever deeds done there have added or washed
+//! away. Beasts left long in deeply tainted land turn into Corrupted, or into
+//! Chimeras where the taint runs deepest. A sacrifice burnt before a region's
+//! idol, or at the altar nearest it, or the idol's destruction, redeems a
+//! region, and its mutants return to what they were.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use std::collections::HashMap;
+use crate::death::BindPoint;
+use crate::error::{AntediluviaError, Result};
+use crate::mob::{LootDrop, MobType};
+use crate::spawner::{spawn_data, SpawnZone};
+use crate::world::CorruptionEvent;
+
+/// Regional corruption at which beasts begin to turn.
+pub const MUTATION_CORRUPTION: f32 = 60.0;
+
+/// Regional corruption at which beasts turn into Chimeras rather than Corrupted.
+pub const CHIMERA_CORRUPTION: f32 = 85.0;
+
+/// Seconds a beast must spend at the threshold to turn; deeper taint turns it sooner.
+pub const MUTATION_SECONDS: f32 = 90.0;
+
+/// How much more a deed corrupts the region it is done in than the world.
+pub const REGIONAL_TAINT: f32 = 10.0;
+
+/// The most corruption deeds can add to, or wash from, a region.
+pub const MAX_REGIONAL_TAINT: f32 = 50.0;
+
+/// How far a region reaches, in radii of its spawn zone.
+pub const REGION_SPREAD: f32 = 2.0;
+
+/// Seconds a redeemed region waits before it accepts another offering.
+pub const HALLOW_SECONDS: f32 = 300.0;
+
+/// How near an altar or idol an offering must be made.
+pub const SHRINE_REACH: f32 = 15.0;
+
+/// What a sacrifice burns: the flesh of a beast.
+pub const OFFERING: &str = "Raw Meat";
+
+/// Check if an event redeems the region it is done in.
+pub fn redeems(event: CorruptionEvent) -> bool {
+    matches!(event, CorruptionEvent::Sacrifice | CorruptionEvent::IdolDestruction)
+}
+
+/// Find the region a position lies in: the nearest spawn zone within reach.
+/// Only the distance across the ground counts.
+pub fn region_of(position: Vec3) -> Option<&'static SpawnZone> {
+    let distance = |zone: &SpawnZone| Vec3::new(zone.center.x - position.x, 0.0, zone.center.z - position.z).length();
+    spawn_data()
+        .zones()
+        .iter()
+        .filter(|z| distance(z) <= z.radius * REGION_SPREAD)
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+/// Find the region whose idol stands near a position. Every region's idol
+/// stands at the heart of its spawn zone.
+pub fn idol_near(position: Vec3) -> Option<&'static SpawnZone> {
+    spawn_data()
+        .zones()
+        .iter()
+        .find(|z| Vec3::new(z.center.x - position.x, 0.0, z.center.z - position.z).length() <= SHRINE_REACH)
+}
+
+/// Find the region nearest a position, however far off it lies.
+pub fn nearest_region(position: Vec3) -> Option<&'static SpawnZone> {
+    let distance = |zone: &SpawnZone| Vec3::new(zone.center.x - position.x, 0.0, zone.center.z - position.z).length();
+    spawn_data().zones().iter().min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+/// Find the altar near a position, if there is one. The Havilah campfire is
+/// no altar.
+pub fn altar_near(position: Vec3) -> Option<BindPoint> {
+    BindPoint::ALL.into_iter().filter(|p| *p != BindPoint::HavilahCampfire).find(|p| {
+        let offset = p.position() - position;
+        Vec3::new(offset.x, 0.0, offset.z).length() <= SHRINE_REACH
+    })
+}
+
+/// Burn the offering a sacrifice needs from what a player carries. Other
+/// redemptions cost nothing.
+pub fn make_offering(event: CorruptionEvent, items: &mut Vec<LootDrop>) -> Result<()> {
+    if event != CorruptionEvent::Sacrifice {
+        return Ok(());
+    }
+    let Some(index) = items.iter().position(|item| item.name == OFFERING && item.quantity > 0) else {
+        return Err(AntediluviaError::RedemptionError(format!("you carry no {} to offer", OFFERING)));
+    };
+    items[index].quantity -= 1;
+    if items[index].quantity == 0 {
+        items.remove(index);
+    }
+    Ok(())
+}
+
+/// Name a mutant after what it turned into ("Corrupted Wolf").
+pub fn mutant_name(into: MobType, name: &str) -> String {
+    format!("{} {}", into.definition().name, name)
+}
+
+/// How far a mob has gone toward turning, and what it was before it did.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Mutation {
+    pub exposure: f32, // Seconds in tainted land, quickened by how deep the taint runs
+    pub from: Option<(MobType, String)>, // Its type and name before it turned
+}
+
+impl Mutation {
+    /// Check if the mob has turned.
+    pub fn is_mutated(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// Soak a mob of `mob_type` in `corruption` for `delta_seconds`, and get
+    /// what it turns into once it has soaked long enough. Only beasts whose
+    /// definition says they mutate ever turn, and only once; out of tainted
+    /// land the exposure wears off.
+    pub fn expose(&mut self, mob_type: MobType, corruption: f32, delta_seconds: f32) -> Option<MobType> {
+        if self.is_mutated() || !mob_type.definition().mutates {
+            return None;
+        }
+        if corruption < MUTATION_CORRUPTION {
+            self.exposure = (self.exposure - delta_seconds).max(0.0);
+            return None;
+        }
+        self.exposure += delta_seconds * corruption / MUTATION_CORRUPTION;
+        if self.exposure < MUTATION_SECONDS {
+            return None;
+        }
+        Some(if corruption >= CHIMERA_CORRUPTION { MobType::Chimera } else { MobType::Corrupted })
+    }
+
+    /// Remember what a mob was as it turns.
+    pub fn turn(&mut self, mob_type: MobType, name: &str) {
+        self.from = Some((mob_type, name.to_string()));
+    }
+
+    /// Take back what a mutant was, clearing its exposure.
+    pub fn redeem(&mut self) -> Option<(MobType, String)> {
+        self.exposure = 0.0;
+        self.from.take()
+    }
+}
+
+/// The corruption deeds have left in each region.
+#[derive(Clone, Debug, Default)]
+pub struct RegionalCorruption {
+    taint: HashMap<String, f32>, // Zone name -> corruption added, or washed away if negative
+    hallowed: HashMap<String, f32>, // Zone name -> seconds until it accepts another offering
+}
+
+impl RegionalCorruption {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let recently redeemed regions accept offerings again in time.
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.hallowed.retain(|_, timer| {
+            *timer -= delta_seconds;
+            *timer > 0.0
+        });
+    }
+
+    /// Get the corruption at a position: the world's, plus its region's own.
+    pub fn corruption_at(&self, world: f32, position: Vec3) -> f32 {
+        let Some(zone) = region_of(position) else {
+            return world;
+        };
+        let deeds = self.taint.get(&zone.name).copied().unwrap_or(0.0);
+        (world + zone.taint + deeds).clamp(0.0, 100.0)
+    }
+
+    /// Record an event done at a position against its region, if it is in one.
+    pub fn record(&mut self, event: CorruptionEvent, position: Vec3) -> Option<&'static SpawnZone> {
+        let zone = region_of(position)?;
+        self.mark(event, zone);
+        Some(zone)
+    }
+
+    /// Add an event's taint to a region, or wash it away.
+    fn mark(&mut self, event: CorruptionEvent, zone: &SpawnZone) {
+        let taint = self.taint.entry(zone.name.clone()).or_insert(0.0);
+        *taint = (*taint + event.delta() * REGIONAL_TAINT).clamp(-MAX_REGIONAL_TAINT, MAX_REGIONAL_TAINT);
+    }
+
+    /// Check if a sacrifice or idol destruction may be done at a position, and
+    /// get the region it would redeem. A sacrifice is burnt before an idol,
+    /// redeeming the idol's region, or at an altar, redeeming the region
+    /// nearest the altar; only an idol can be destroyed.
+    pub fn accepts(&self, event: CorruptionEvent, position: Vec3) -> Result<&'static SpawnZone> {
+        let refuse = |reason: &str| Err(AntediluviaError::RedemptionError(reason.to_string()));
+        let shrine = match event {
+            CorruptionEvent::Sacrifice => idol_near(position)
+                .or_else(|| altar_near(position).and_then(|altar| nearest_region(altar.position()))),
+            CorruptionEvent::IdolDestruction => idol_near(position),
+            _ => return refuse("only a sacrifice or a destroyed idol redeems the land"),
+        };
+        let Some(zone) = shrine else {
+            return refuse(match event {
+                CorruptionEvent::Sacrifice => "there is no altar or idol here to make an offering at",
+                _ => "there is no idol here to destroy",
+            });
+        };
+        if self.hallowed.contains_key(&zone.name) {
+            return refuse("this land has been redeemed too recently");
+        }
+        Ok(zone)
+    }
+
+    /// Redeem the region a sacrifice or idol destruction is made for, and get
+    /// the region, whose mutants then return to what they were. A region
+    /// accepts one offering in `HALLOW_SECONDS`. Whatever the offering costs
+    /// is the caller's to take once `accepts` allows it.
+    pub fn redeem(&mut self, event: CorruptionEvent, position: Vec3) -> Result<&'static SpawnZone> {
+        let zone = self.accepts(event, position)?;
+        self.mark(event, zone);
+        self.hallowed.insert(zone.name.clone(), HALLOW_SECONDS);
+        Ok(zone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mob::Mob;
+
+    fn zone(name: &str) -> &'static SpawnZone {
+        spawn_data().zones().iter().find(|z| z.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_beasts_turn_in_tainted_land() {
+        let mut wolf = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 2);
+        let plain = wolf.clone();
+        wolf.take_damage(wolf.max_health / 2.0);
+
+        // Exposure builds only in tainted land, and wears off out of it
+        assert!(!wolf.expose(MUTATION_CORRUPTION - 1.0, MUTATION_SECONDS * 2.0));
+        assert!(!wolf.expose(MUTATION_CORRUPTION, MUTATION_SECONDS / 2.0));
+        assert!(!wolf.expose(0.0, MUTATION_SECONDS));
+        assert_eq!(wolf.mutation.exposure, 0.0);
+        assert!(wolf.expose(MUTATION_CORRUPTION, MUTATION_SECONDS));
+
+        assert_eq!(wolf.mob_type, MobType::Corrupted);
+        assert_eq!(wolf.name, "Corrupted Wolf");
+        assert!((wolf.health / wolf.max_health - 0.5).abs() < 0.01);
+        assert!(wolf.max_health > plain.max_health && wolf.get_damage() > plain.get_damage());
+
+        // Deeper taint turns a beast sooner, and into a Chimera; a mutant never turns twice
+        let mut lion = Mob::new(2, "Lion".to_string(), MobType::Lion, Vec3::ZERO, 2);
+        assert!(lion.expose(CHIMERA_CORRUPTION, MUTATION_SECONDS * 0.75));
+        assert_eq!(lion.mob_type, MobType::Chimera);
+        assert!(!lion.expose(100.0, MUTATION_SECONDS * 10.0));
+
+        // Giants and the already corrupted are what they are
+        let mut giant = Mob::new(3, "Nephilim".to_string(), MobType::Nephilim, Vec3::ZERO, 5);
+        assert!(!giant.expose(100.0, MUTATION_SECONDS * 10.0));
+
+        // Redeemed, the wolf is itself again
+        assert!(wolf.redeem());
+        assert_eq!((wolf.mob_type, wolf.name.as_str()), (MobType::Wolf, "Wolf"));
+        assert_eq!(wolf.max_health, plain.max_health);
+        assert!(!wolf.redeem());
+    }
+
+    #[test]
+    fn test_regions_corrupt_and_are_redeemed() {
+        let mut regions = RegionalCorruption::new();
+        let blight = zone("Western Blight");
+        let meadows = zone("Pillar Meadows");
+        assert!(blight.taint > 0.0);
+        assert_eq!(regions.corruption_at(40.0, blight.center), 40.0 + blight.taint);
+        assert_eq!(regions.corruption_at(40.0, Vec3::new(0.0, 0.0, 500.0)), 40.0);
+        assert_eq!(region_of(blight.center + Vec3::X * blight.radius * 1.5).map(|z| &z.name), Some(&blight.name));
+
+        // Murder darkens the land it is done in, and only there
+        regions.record(CorruptionEvent::PlayerKill, meadows.center);
+        let darkened = regions.corruption_at(40.0, meadows.center);
+        assert_eq!(darkened, 40.0 + meadows.taint + CorruptionEvent::PlayerKill.delta() * REGIONAL_TAINT);
+        assert_eq!(regions.corruption_at(40.0, blight.center), 40.0 + blight.taint);
+
+        // A sacrifice redeems it, once in a while
+        assert_eq!(regions.redeem(CorruptionEvent::Sacrifice, meadows.center).unwrap().name, meadows.name);
+        assert!(regions.corruption_at(40.0, meadows.center) < darkened);
+        assert!(regions.redeem(CorruptionEvent::IdolDestruction, meadows.center).is_err());
+        regions.update(HALLOW_SECONDS);
+        assert!(regions.redeem(CorruptionEvent::IdolDestruction, meadows.center).is_ok());
+
+        // Only redemptions redeem, and only where there is land to redeem
+        assert!(regions.redeem(CorruptionEvent::PlayerKill, blight.center).is_err());
+        assert!(regions.redeem(CorruptionEvent::Sacrifice, Vec3::new(0.0, 0.0, 500.0)).is_err());
+
+        // Offerings are made at an altar or before an idol, and nowhere else in the land
+        let wilds = blight.center + Vec3::X * blight.radius;
+        assert_eq!(region_of(wilds).map(|z| &z.name), Some(&blight.name));
+        assert!(regions.accepts(CorruptionEvent::Sacrifice, wilds).is_err());
+        assert!(regions.redeem(CorruptionEvent::IdolDestruction, wilds).is_err());
+        assert_eq!(idol_near(blight.center + Vec3::X * 10.0).map(|z| &z.name), Some(&blight.name));
+        assert_eq!(altar_near(BindPoint::BethelAltar.position()), Some(BindPoint::BethelAltar));
+        assert_eq!(altar_near(BindPoint::HavilahCampfire.position()), None);
+        assert!(regions.accepts(CorruptionEvent::Sacrifice, BindPoint::HavilahCampfire.position()).is_err());
+
+        // Washed land never drops below clean
+        for _ in 0..20 {
+            regions.record(CorruptionEvent::Sacrifice, meadows.center);
+        }
+        assert_eq!(regions.corruption_at(0.0, meadows.center), 0.0);
+    }
+
+    #[test]
+    fn test_an_altar_redeems_the_nearest_region() {
+        let mut regions = RegionalCorruption::new();
+        let altar = BindPoint::EdenPillar.position();
+        let meadows = zone("Pillar Meadows");
+
+        // The Eden Pillar stands outside every region, yet an offering there
+        // redeems the region nearest it
+        assert!(region_of(altar).is_none());
+        assert_eq!(nearest_region(altar).map(|z| &z.name), Some(&meadows.name));
+        let before = regions.corruption_at(40.0, meadows.center);
+        assert_eq!(regions.redeem(CorruptionEvent::Sacrifice, altar).unwrap().name, meadows.name);
+        assert!(regions.corruption_at(40.0, meadows.center) < before);
+        assert!(regions.accepts(CorruptionEvent::Sacrifice, altar).is_err());
+
+        // An idol cannot be torn down at an altar
+        regions.update(HALLOW_SECONDS);
+        assert!(regions.accepts(CorruptionEvent::IdolDestruction, altar).is_err());
+    }
+
+    #[test]
+    fn test_a_sacrifice_burns_an_offering() {
+        let meat = |quantity| LootDrop { name: OFFERING.to_string(), quantity, weight: 1.0 };
+        let pelt = LootDrop { name: "Wolf Pelt".to_string(), quantity: 1, weight: 3.0 };
+
+        let mut items = vec![pelt.clone(), meat(2)];
+        assert!(make_offering(CorruptionEvent::Sacrifice, &mut items).is_ok());
+        assert_eq!(items, vec![pelt.clone(), meat(1)]);
+        assert!(make_offering(CorruptionEvent::Sacrifice, &mut items).is_ok());
+        assert_eq!(items, vec![pelt.clone()]);
+
+        // No flesh, no sacrifice; tearing down an idol costs nothing
+        assert!(make_offering(CorruptionEvent::Sacrifice, &mut items).is_err());
+        assert!(make_offering(CorruptionEvent::IdolDestruction, &mut items).is_ok());
+        assert_eq!(items, vec![pelt]);
+    }
+}
@@ -4,6 +4,32 @@
 
 use serde::{Deserialize, Serialize};
 use bevy::prelude::Vec3;
+use crate::abilities::Loadout;
+use crate::combat::{item_armor, CombatState, Defense, JobResources};
+use crate::combat_log::CombatEvent;
+use crate::crafting::{crafting_skill, CraftedItem, CraftingSystem};
+use crate::error::{AntediluviaError, Result};
+use crate::death::{BindPoint, Experience};
+use crate::mob::{LootDrop, Mob};
+use crate::pvp::PvpStatus;
+use crate::status::StatusEffects;
+use crate::entity::{
+    Breath, Errand, Faction, Job, JobMastery, JobTelemetry, JobUnlockTracker, Lineage, Reputation, ReputationEvent, Standing, Zone,
+    BASE_MOVE_SPEED, SPRINT_MULTIPLIER,
+};
+
+/// Slack allowed on server-side speed checks for network jitter.
+pub const MOVE_SPEED_TOLERANCE: f32 = 1.25;
+
+/// The most seconds of movement a player can bank between moves, so bunched
+/// packets are not mistaken for teleports.
+pub const MOVE_BUDGET_SECONDS: f32 = 1.0;
+
+/// A player's full health on the server.
+pub const MAX_PLAYER_HEALTH: f32 = 100.0;
+
+/// The most weight a player's satchel holds.
+pub const SATCHEL_MAX_WEIGHT: f32 = 100.0;
 
 /// A network message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -12,11 +38,48 @@ pub enum NetworkMessage {
     PlayerMove { position: Vec3, rotation: f32 },
     PlayerAction { action: String, target: Option<u64> },
     PlayerChat { message: String },
+    EquipArmor { armor: Option<String> }, // The armor the player now wears, if any, from their satchel
+    ArmorRefused { reason: String },
     
     // Combat
     CombatAction { action_type: String, target_id: u64 },
+    SetTarget { target_id: Option<u64> },
+    TargetChanged { entity_id: u64, target_id: Option<u64> }, // What a player or mob is targeting
     SkillChain { first_action: String, second_action: String },
-    
+    SetLoadout { slots: Vec<Option<String>> },
+    LoadoutUpdate { slots: Vec<Option<String>> },
+    CombatResult {
+        attacker_id: u64,
+        target_id: u64,
+        action_type: String,
+        damage: f32,
+        healing: f32,
+        target_health: f32,
+        skill_chain: Option<String>,
+        critical: bool,
+        resisted: f32,
+    },
+    CombatRejected { action_type: String, reason: String },
+    ProjectileLaunched { projectile_id: u64, owner_id: u64, action_type: String, position: Vec3, velocity: Vec3 },
+    ProjectileLanded { projectile_id: u64, position: Vec3, target_id: Option<u64> }, // No target: struck the ground
+    StatusUpdate { target_id: u64, effects: Vec<(String, u32, f32)> }, // Name, stacks, seconds left
+    ExperienceGained { amount: f32 },
+    LootAwarded { item: String, quantity: u32, weight: f32 },
+
+    // Party
+    PartyInvite { player_id: u64 },
+    PartyInvited { from: u64 },
+    PartyAccept,
+    PartyDecline,
+    PartyLeave,
+    PartyPromote { player_id: u64 },
+    PartySetLootRule { rule: String },
+    PartyUpdate { leader: u64, members: Vec<u64>, loot_rule: String }, // No members: not in a party
+    PartyRefused { reason: String },
+    LootRollStart { roll_id: u64, item: String, quantity: u32 },
+    LootRollChoice { roll_id: u64, choice: String },
+    LootRollResult { roll_id: u64, item: String, winner: Option<u64> },
+
     // NPC interaction
     NPCInteract { npc_id: u64, query: String },
     
@@ -26,12 +89,138 @@ pub enum NetworkMessage {
     // Server state
     WorldStateUpdate { corruption: f32, flood_phase: String },
     PlayerStateUpdate { player_id: u64, health: f32, position: Vec3 },
+
+    // Death
+    BindSoul, // Bind to the altar or campfire the player stands by
+    SoulBound { point: String },
+    PlayerDied { player_id: u64, position: Vec3, permanent: bool }, // Permanent: fallen in the Flood
+    PlayerRespawned { player_id: u64, position: Vec3 },
+    CorpseLaid { position: Vec3, items: Vec<LootDrop>, wear: f32 }, // To the fallen: what their corpse holds, and their weapon's wear
+    CorpseRecovered, // To its owner; what it held follows as loot
+    XpDebt { debt: f32 }, // To a player: the experience they owe
+    SatchelUpdate { items: Vec<LootDrop> }, // To a player: everything the server holds they carry
+    ItemUsed { item: String, quantity: u32 }, // From a player: goods used up, taken from what they carry
+
+    // Player against player
+    SetPvpFlag { flagged: bool },
+    PvpStatusUpdate { player_id: u64, flagged: bool, murderer: bool },
+    PvpRefused { reason: String },
+    PlayerKilled { killer_id: u64, victim_id: u64, murder: bool },
+    PostBounty { target_id: u64 }, // A victim's contract on their murderer
+    BountyPosted { target_id: u64, reward: f32 }, // The total now on their head
+    BountyClaimed { hunter_id: u64, target_id: u64, reward: f32 },
+
+    // Jobs
+    Feat { feat: JobTelemetry }, // A deed only the player saw, for the server to check
+    JobUnlocked { job: String },
+    MasteryUpdate { mastery: JobMastery }, // To a player: their mastery of every job
+
+    // Reputation
+    RunErrand { faction: String }, // Hand over the goods a faction asked for
+    ReputationUpdate { reputation: Reputation }, // To a player: their standing with every faction
+
+    // Gathering
+    Gather { site: usize },
+    SiteUpdate { site: usize, remaining: u32 }, // What a gathering site has left
+    GatherRefused { reason: String },
+
+    // Crafting
+    Craft { item: String },
+    Crafted { item: String, quality: String }, // To the crafter; the satchel and corruption follow
+    CraftRefused { reason: String },
+    CorruptionUpdate { corruption: f32, lineage: String }, // To a player: their own corruption, and the house it leaves them in
+
+    // Mobs
+    MobSpawned { mob_id: u64, mob_type: String, name: String, level: u32, rank: String, affixes: Vec<String>, position: Vec3 },
+    MobMutated { mob_id: u64, mob_type: String, name: String, health: f32, max_health: f32 }, // Turned, or redeemed
+    MobPositions { mobs: Vec<MobSnapshot> }, // Every living mob, a few times a second
+    LandRedeemed { region: String },
+    RedemptionRefused { reason: String },
+    OfferingBurnt { item: String }, // To the one who made it
+
+    // World events
+    WorldEvent { event_type: String, location: Vec3, active: bool },
+    LeviathanStrike { position: Vec3, target_id: Option<u64>, damage: f32 },
     
     // Connection
     Ping,
     Pong,
 }
 
+impl NetworkMessage {
+    /// A combat event, as sent to clients. Damage over time goes by the name "StatusEffect".
+    pub fn combat_result(event: &CombatEvent, target_health: f32) -> Self {
+        NetworkMessage::CombatResult {
+            attacker_id: event.attacker,
+            target_id: event.target,
+            action_type: event.action.map_or("StatusEffect".to_string(), |a| format!("{:?}", a)),
+            damage: event.damage,
+            healing: event.healing,
+            target_health,
+            skill_chain: event.chain.clone(),
+            critical: event.critical,
+            resisted: event.resisted,
+        }
+    }
+
+    /// A mob entering the world, as sent to clients.
+    pub fn mob_spawned(mob: &Mob) -> Self {
+        NetworkMessage::MobSpawned {
+            mob_id: mob.id,
+            mob_type: format!("{:?}", mob.mob_type),
+            name: mob.name.clone(),
+            level: mob.level,
+            rank: format!("{:?}", mob.variant.rank),
+            affixes: mob.variant.affixes.iter().map(|a| format!("{:?}", a)).collect(),
+            position: mob.position,
+        }
+    }
+
+    /// A mob that has turned or been redeemed, as sent to clients.
+    pub fn mob_mutated(mob: &Mob) -> Self {
+        NetworkMessage::MobMutated {
+            mob_id: mob.id,
+            mob_type: format!("{:?}", mob.mob_type),
+            name: mob.name.clone(),
+            health: mob.health,
+            max_health: mob.max_health,
+        }
+    }
+
+    /// Where every living mob stands and whom it is after, as sent to clients.
+    pub fn mob_positions<'a>(mobs: impl IntoIterator<Item = &'a Mob>) -> Self {
+        let mobs = mobs
+            .into_iter()
+            .filter(|m| m.is_alive())
+            .map(|m| MobSnapshot { mob_id: m.id, position: m.position, target_id: m.target })
+            .collect();
+        NetworkMessage::MobPositions { mobs }
+    }
+
+    /// A player's standing in the fighting between players, as sent to clients.
+    pub fn pvp_update(player_id: u64, pvp: &PvpStatus) -> Self {
+        NetworkMessage::PvpStatusUpdate { player_id, flagged: pvp.flagged, murderer: pvp.is_murderer() }
+    }
+
+    /// A player's own corruption and house, as sent to them.
+    pub fn corruption_update(state: &PlayerNetworkState) -> Self {
+        NetworkMessage::CorruptionUpdate { corruption: state.corruption, lineage: format!("{:?}", state.lineage) }
+    }
+
+    /// The status effects on an entity, as sent to clients.
+    pub fn status_update(target_id: u64, effects: &StatusEffects) -> Self {
+        NetworkMessage::StatusUpdate { target_id, effects: effects.snapshot() }
+    }
+}
+
+/// Where a mob stands and whom it is after, as the server last saw it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MobSnapshot {
+    pub mob_id: u64,
+    pub position: Vec3,
+    pub target_id: Option<u64>,
+}
+
 /// A player's network state.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerNetworkState {
@@ -40,6 +229,40 @@ pub struct PlayerNetworkState {
     pub rotation: f32,
     pub health: f32,
     pub last_update: f32, // Timestamp
+    #[serde(default)]
+    pub move_budget: f32, // Seconds of movement banked since the last move
+    #[serde(default)]
+    pub breath: Breath,
+    #[serde(default)]
+    pub mastery: JobMastery,
+    #[serde(default)]
+    pub loadout: Loadout,
+    #[serde(default)]
+    pub lineage: Lineage,
+    #[serde(default)]
+    pub corruption: f32,
+    #[serde(default)]
+    pub combat: CombatState,
+    #[serde(default)]
+    pub effects: StatusEffects,
+    #[serde(default)]
+    pub resources: JobResources,
+    #[serde(default)]
+    pub bind_point: BindPoint,
+    #[serde(default)]
+    pub pvp: PvpStatus,
+    #[serde(default)]
+    pub armor: Option<LootDrop>, // The armor worn, taken out of the satchel
+    #[serde(default)]
+    pub satchel: Vec<LootDrop>, // What they carry; the server's word, not the client's
+    #[serde(default)]
+    pub experience: Experience,
+    #[serde(default)]
+    pub reputation: Reputation,
+    #[serde(default)]
+    pub unlocks: JobUnlockTracker, // Progress toward revealing each job
+    #[serde(default)]
+    pub defended_at: f32, // When the player last reported defending an NPC
 }
 
 impl PlayerNetworkState {
@@ -49,8 +272,25 @@ impl PlayerNetworkState {
             player_id,
             position,
             rotation: 0.0,
-            health: 100.0,
+            health: MAX_PLAYER_HEALTH,
             last_update: 0.0,
+            move_budget: 0.0,
+            breath: Breath::default(),
+            mastery: JobMastery::default(),
+            loadout: Loadout::default(),
+            lineage: Lineage::Seth,
+            corruption: 0.0,
+            combat: CombatState::new(),
+            effects: StatusEffects::new(),
+            resources: JobResources::new(),
+            bind_point: BindPoint::default(),
+            reputation: Reputation::for_lineage(Lineage::Seth),
+            unlocks: JobUnlockTracker::new(),
+            defended_at: 0.0,
+            pvp: PvpStatus::new(),
+            armor: None,
+            satchel: Vec::new(),
+            experience: Experience::default(),
         }
     }
 
@@ -60,6 +300,205 @@ impl PlayerNetworkState {
         self.rotation = rotation;
     }
 
+    /// Check if moving to `position` would enter a zone closed to the player's house.
+    pub fn is_barred_from(&self, position: Vec3) -> bool {
+        match Zone::at(position) {
+            Some(zone) => !self.lineage.can_enter(zone) && Zone::at(self.position) != Some(zone),
+            None => false,
+        }
+    }
+
+    /// Validate and apply a movement update at server time `now`.
+    ///
+    /// Time since the last move is banked, up to `MOVE_BUDGET_SECONDS`, and
+    /// each move spends the time it takes. A walk spends only what it needs;
+    /// anything faster is a sprint, which spends the time it takes at a
+    /// sprint and drains the Breath for only that. Moving faster than a
+    /// sprint, or sprinting while out of breath, is rejected.
+    pub fn try_move(&mut self, position: Vec3, rotation: f32, now: f32) -> bool {
+        let budget = (self.move_budget + (now - self.last_update).max(0.0)).min(MOVE_BUDGET_SECONDS);
+        self.last_update = now;
+        self.move_budget = budget;
+
+        let distance = Vec3::new(position.x - self.position.x, 0.0, position.z - self.position.z).length();
+        let walk = BASE_MOVE_SPEED * MOVE_SPEED_TOLERANCE;
+        if distance <= walk * budget {
+            self.move_budget -= distance / walk;
+        } else {
+            let sprinted = distance / (walk * SPRINT_MULTIPLIER);
+            if sprinted > budget || !self.breath.sprint(sprinted) {
+                return false;
+            }
+            self.move_budget -= sprinted;
+        }
+
+        self.update_position(position, rotation);
+        true
+    }
+
+    /// Put loot in the player's satchel, unless it would be too heavy to carry.
+    pub fn stow(&mut self, drop: LootDrop) -> bool {
+        let carried: f32 = self.satchel.iter().map(|item| item.weight * item.quantity as f32).sum();
+        if carried + drop.weight * drop.quantity as f32 > SATCHEL_MAX_WEIGHT {
+            return false;
+        }
+        match self.satchel.iter_mut().find(|item| item.name == drop.name) {
+            Some(item) => item.quantity += drop.quantity,
+            None => self.satchel.push(drop),
+        }
+        true
+    }
+
+    /// Take goods out of the player's satchel, if they carry that many.
+    pub fn take(&mut self, name: &str, quantity: u32) -> bool {
+        let Some(index) = self.satchel.iter().position(|item| item.name == name && item.quantity >= quantity) else {
+            return false;
+        };
+        self.satchel[index].quantity -= quantity;
+        if self.satchel[index].quantity == 0 {
+            self.satchel.remove(index);
+        }
+        true
+    }
+
+    /// Put on armor from the satchel, or take off what is worn if `armor` is
+    /// `None`. Whatever comes off goes back in the satchel, and nothing comes
+    /// off if there is no room for it.
+    pub fn wear(&mut self, armor: Option<&str>) -> Result<()> {
+        if self.armor.as_ref().map(|worn| worn.name.as_str()) == armor {
+            return Ok(());
+        }
+        let refuse = |reason: &str| Err(AntediluviaError::EquipmentError(reason.to_string()));
+        let put_on = match armor {
+            Some(name) if item_armor(name) <= 0.0 => return refuse("that is not armor"),
+            Some(name) => {
+                let Some(weight) = self.satchel.iter().find(|item| item.name == name).map(|item| item.weight) else {
+                    return refuse("you do not carry that armor");
+                };
+                self.take(name, 1);
+                Some(LootDrop { name: name.to_string(), quantity: 1, weight })
+            }
+            None => None,
+        };
+        if let Some(worn) = self.armor.clone() {
+            if !self.stow(worn) {
+                if let Some(put_on) = put_on {
+                    self.stow(put_on);
+                }
+                return refuse("your satchel is too heavy to take that armor off");
+            }
+        }
+        self.armor = put_on;
+        Ok(())
+    }
+
+    /// Everything the player has: what they carry, and the armor they wear.
+    pub fn belongings(&self) -> Vec<LootDrop> {
+        self.satchel.iter().cloned().chain(self.armor.clone()).collect()
+    }
+
+    /// Craft an item from what the player carries, at the skill their level
+    /// gives them, corrupting them by what the recipe costs. Only recipes
+    /// their house knows can be made, and only if the item can be carried.
+    pub fn craft(&mut self, crafting: &CraftingSystem, item: &str) -> Result<CraftedItem> {
+        let refuse = |reason: &str| Err(AntediluviaError::CraftingError(reason.to_string()));
+        let Some(recipe) = crafting.get_recipe(item).filter(|r| r.available_to(self.lineage)) else {
+            return refuse("you know no such recipe");
+        };
+        let owned = self.satchel.iter().map(|i| (i.name.clone(), i.quantity)).collect();
+        let Some(crafted) = crafting.craft(item, crafting_skill(self.experience.level), &owned) else {
+            return refuse("you lack the skill or the materials");
+        };
+        let carried = self.satchel.clone();
+        for (ingredient, quantity) in &recipe.recipe.ingredients {
+            self.take(ingredient, *quantity);
+        }
+        if !self.stow(LootDrop { name: item.to_string(), quantity: 1, weight: recipe.weight }) {
+            self.satchel = carried;
+            return refuse("your satchel is too heavy to carry it");
+        }
+        self.corrupt(recipe.corruption_cost);
+        Ok(crafted)
+    }
+
+    /// Observe a deed, and get the job it revealed, if any.
+    pub fn observe(&mut self, deed: &JobTelemetry) -> Option<Job> {
+        self.unlocks.observe(deed, &mut self.mastery)
+    }
+
+    /// Check a feat the player reports at `now` against what the server knows
+    /// of them, and observe it. Blows, kills and crafting the server sees for
+    /// itself, and never takes on the player's word. Tending a stranger burns
+    /// a Healing Herb from the satchel, a song counts as played at the
+    /// campfire only if the player stands there, and no more time is spent
+    /// defending than has passed since the last report.
+    pub fn report_feat(&mut self, feat: JobTelemetry, now: f32) -> Result<Option<Job>> {
+        let refuse = |reason: &str| Err(AntediluviaError::FeatError(reason.to_string()));
+        let feat = match feat {
+            JobTelemetry::MobDamaged { .. } | JobTelemetry::MobKilled { .. } | JobTelemetry::ItemCrafted { .. } => {
+                return refuse("the server sees that for itself");
+            }
+            JobTelemetry::HealedStranger { .. } if !self.take("Healing Herb", 1) => {
+                return refuse("you have no Healing Herb to tend them with");
+            }
+            JobTelemetry::DefendedNpc { npc_id, seconds } => {
+                let elapsed = (now - self.defended_at).max(0.0);
+                self.defended_at = now;
+                JobTelemetry::DefendedNpc { npc_id, seconds: seconds.min(elapsed) }
+            }
+            JobTelemetry::LyrePlayed { accuracy, at_campfire } => JobTelemetry::LyrePlayed {
+                accuracy,
+                at_campfire: at_campfire && BindPoint::near(self.position) == Some(BindPoint::HavilahCampfire),
+            },
+            feat => feat,
+        };
+        Ok(self.observe(&feat))
+    }
+
+    /// Heal, up to full health.
+    pub fn heal(&mut self, amount: f32) {
+        self.health = (self.health + amount).min(MAX_PLAYER_HEALTH);
+    }
+
+    /// Corrupt the player, turning their house if they cross the line.
+    /// Reputation moves only by the corruption that fit under the cap.
+    pub fn corrupt(&mut self, amount: f32) {
+        let before = self.corruption;
+        self.corruption = (self.corruption + amount).clamp(0.0, 100.0);
+        self.lineage = self.lineage.resolve(self.corruption);
+        let applied = self.corruption - before;
+        self.reputation.apply(&if applied >= 0.0 {
+            ReputationEvent::Corrupted { amount: applied }
+        } else {
+            ReputationEvent::Redeemed { amount: -applied }
+        });
+    }
+
+    /// Hand over the goods a faction asked for from the player's satchel, and
+    /// earn the standing it brings. Nothing is asked of those the faction shuns.
+    pub fn run_errand(&mut self, faction: Faction) -> Result<Errand> {
+        let refuse = |reason: &str| Err(AntediluviaError::ErrandError(reason.to_string()));
+        if self.reputation.standing(faction) <= Standing::Hostile {
+            return refuse("they will ask nothing of you");
+        }
+        let Some(errand) = Errand::for_faction(faction) else {
+            return refuse("they have no errand for you");
+        };
+        let carried = self.satchel.iter().find(|i| i.name == errand.item).map_or(0, |i| i.quantity);
+        let Some(completed) = errand.complete(carried) else {
+            return refuse("you do not carry what they asked for");
+        };
+        self.take(errand.item, errand.quantity);
+        self.reputation.apply(&completed);
+        Ok(errand)
+    }
+
+    /// The player's defense: their armor, and whatever effects harden it.
+    pub fn defense(&self) -> Defense {
+        let worn = Defense::from_equipment(self.armor.as_ref().map(|armor| armor.name.as_str()));
+        Defense::new(worn.armor + self.effects.armor_bonus(), worn.resistances)
+    }
+
     /// Apply damage.
     pub fn take_damage(&mut self, damage: f32) {
         self.health = (self.health - damage).max(0.0);
@@ -69,6 +508,15 @@ impl PlayerNetworkState {
     pub fn is_alive(&self) -> bool {
         self.health > 0.0
     }
+
+    /// Rise again at `position`, whole and rid of every effect.
+    pub fn respawn(&mut self, position: Vec3) {
+        self.health = MAX_PLAYER_HEALTH;
+        self.position = position;
+        self.breath = Breath::default();
+        self.effects = StatusEffects::new();
+        self.resources = JobResources::new();
+    }
 }
 
 /// Network configuration.
@@ -138,6 +586,7 @@ impl Default for RollbackState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entity::{SHEPHERD_DEFEND_SECONDS, SPRINT_STAMINA_PER_SECOND};
 
     #[test]
     fn test_player_network_state() {
@@ -145,6 +594,202 @@ mod tests {
         state.take_damage(25.0);
         assert_eq!(state.health, 75.0);
         assert!(state.is_alive());
+
+        // Worn armor blunts blows, as on the client
+        assert_eq!(state.defense().armor, 0.0);
+        state.armor = Some(LootDrop { name: "Linen Tunic".to_string(), quantity: 1, weight: 3.0 });
+        assert_eq!(state.defense(), Defense::from_equipment(["Linen Tunic"]));
+    }
+
+    #[test]
+    fn test_crafting_corrupts_the_crafter() {
+        let crafting = CraftingSystem::new();
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        state.experience.level = 5;
+        let goods = |name: &str, quantity| LootDrop { name: name.to_string(), quantity, weight: 1.0 };
+        state.satchel = vec![goods("Iron Ingot", 3), goods("Leather Grip", 1)];
+
+        // Iron is Cain's craft, beyond the House of Seth
+        assert!(state.craft(&crafting, "Iron Sword").is_err());
+        state.lineage = Lineage::Cain;
+        let sword = state.craft(&crafting, "Iron Sword").unwrap();
+        assert_eq!(sword.name, "Iron Sword");
+        assert_eq!(state.satchel.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), vec!["Iron Sword"]);
+        assert_eq!(state.corruption, crafting.get_recipe("Iron Sword").unwrap().corruption_cost);
+
+        // Nothing is made from nothing
+        assert!(state.craft(&crafting, "Iron Sword").is_err());
+        assert!(state.craft(&crafting, "Golden Calf").is_err());
+    }
+
+    #[test]
+    fn test_errands_are_run_from_the_satchel() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let errand = Errand::for_faction(Faction::NoahsHousehold).unwrap();
+        let before = state.reputation.get(Faction::NoahsHousehold);
+        assert!(state.run_errand(Faction::NoahsHousehold).is_err());
+
+        state.satchel = vec![LootDrop { name: errand.item.to_string(), quantity: errand.quantity, weight: 1.0 }];
+        state.run_errand(Faction::NoahsHousehold).unwrap();
+        assert!(state.satchel.is_empty());
+        assert_eq!(state.reputation.get(Faction::NoahsHousehold), before + errand.reward);
+
+        // Corruption sours the faithful
+        state.corrupt(10.0);
+        assert_eq!(state.reputation.get(Faction::NoahsHousehold), before + errand.reward - 10.0);
+    }
+
+    #[test]
+    fn test_feats_are_checked_by_the_server() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+
+        // What the server sees for itself is not taken on the player's word
+        let kill = JobTelemetry::MobKilled { mob_id: 1, mob_type: crate::mob::MobType::Nephilim, max_health: 1.0 };
+        assert!(state.report_feat(kill, 0.0).is_err());
+
+        // Tending a stranger costs a herb
+        let tended = JobTelemetry::HealedStranger { npc_id: 2, recovered: true };
+        assert!(state.report_feat(tended, 0.0).is_err());
+        state.satchel = vec![LootDrop { name: "Healing Herb".to_string(), quantity: 1, weight: 0.3 }];
+        assert_eq!(state.report_feat(tended, 0.0).unwrap(), Some(Job::Levite));
+        assert!(state.satchel.is_empty());
+
+        // A song is played where the player stands, not where they say
+        let song = JobTelemetry::LyrePlayed { accuracy: 1.0, at_campfire: true };
+        assert_eq!(state.report_feat(song, 0.0).unwrap(), None);
+        state.position = BindPoint::HavilahCampfire.position();
+        assert_eq!(state.report_feat(song, 0.0).unwrap(), Some(Job::Psalmist));
+
+        // No more time is spent defending than has passed
+        let defended = JobTelemetry::DefendedNpc { npc_id: 3, seconds: SHEPHERD_DEFEND_SECONDS };
+        assert_eq!(state.report_feat(defended, 10.0).unwrap(), None);
+        assert_eq!(state.unlocks.defend_progress(3), 10.0);
+        assert_eq!(state.report_feat(defended, SHEPHERD_DEFEND_SECONDS + 10.0).unwrap(), Some(Job::Shepherd));
+    }
+
+    #[test]
+    fn test_mob_positions_leave_out_the_dead() {
+        let mut wolf = Mob::new(1, "Wolf".to_string(), crate::mob::MobType::Wolf, Vec3::X, 1);
+        wolf.target = Some(7);
+        let mut slain = Mob::new(2, "Wolf".to_string(), crate::mob::MobType::Wolf, Vec3::Z, 1);
+        slain.take_damage(slain.max_health);
+
+        let NetworkMessage::MobPositions { mobs } = NetworkMessage::mob_positions([&wolf, &slain]) else { panic!() };
+        assert_eq!(mobs, vec![MobSnapshot { mob_id: 1, position: Vec3::X, target_id: Some(7) }]);
+    }
+
+    #[test]
+    fn test_only_carried_armor_is_worn() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let tunic = LootDrop { name: "Linen Tunic".to_string(), quantity: 1, weight: 3.0 };
+        assert!(state.wear(Some("Linen Tunic")).is_err());
+        assert!(state.stow(LootDrop { name: "Bread".to_string(), quantity: 1, weight: 0.5 }));
+        assert!(state.wear(Some("Bread")).is_err());
+        assert_eq!(state.armor, None);
+
+        // Put on, it leaves the satchel; taken off, it goes back
+        assert!(state.stow(tunic.clone()));
+        state.wear(Some("Linen Tunic")).unwrap();
+        assert_eq!(state.armor, Some(tunic.clone()));
+        assert!(!state.satchel.contains(&tunic));
+        assert!(state.belongings().contains(&tunic));
+
+        // With no room for it in the satchel, it stays on
+        let boulder = LootDrop { name: "Boulder".to_string(), quantity: 1, weight: SATCHEL_MAX_WEIGHT - 0.5 };
+        assert!(state.stow(boulder));
+        assert!(state.wear(None).is_err());
+        assert_eq!(state.armor, Some(tunic.clone()));
+        state.take("Boulder", 1);
+        state.wear(None).unwrap();
+        assert_eq!(state.armor, None);
+        assert!(state.satchel.contains(&tunic));
+    }
+
+    #[test]
+    fn test_satchel_is_kept_by_the_server() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let pelt = |quantity| LootDrop { name: "Wolf Pelt".to_string(), quantity, weight: 3.0 };
+        assert!(state.stow(pelt(1)));
+        assert!(state.stow(pelt(2)));
+        assert_eq!(state.satchel, vec![pelt(3)]);
+
+        // Nothing past what the satchel holds, and nothing taken that is not there
+        assert!(!state.stow(LootDrop { name: "Boulder".to_string(), quantity: 1, weight: SATCHEL_MAX_WEIGHT }));
+        assert!(!state.take("Wolf Pelt", 4));
+        assert!(!state.take("Bread", 1));
+        assert!(state.take("Wolf Pelt", 3));
+        assert!(state.satchel.is_empty());
+    }
+
+    #[test]
+    fn test_server_enforces_breath() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+
+        // Walking is free
+        assert!(state.try_move(Vec3::new(50.0, 0.0, 0.0), 0.0, 1.0));
+        assert_eq!(state.breath.current, state.breath.max);
+
+        // Sprinting drains the Breath until it is spent
+        let mut now = 1.0;
+        let mut sprinted = 0;
+        loop {
+            now += 1.0;
+            let target = state.position + Vec3::new(150.0, 0.0, 0.0);
+            if !state.try_move(target, 0.0, now) {
+                break;
+            }
+            sprinted += 1;
+        }
+        assert_eq!(sprinted, 6);
+
+        // A short dash drains the Breath for the dash alone, and banks the rest
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let walk = BASE_MOVE_SPEED * MOVE_SPEED_TOLERANCE;
+        state.last_update = 1000.0;
+        assert!(state.try_move(Vec3::new(walk * SPRINT_MULTIPLIER * 0.05, 0.0, 0.0), 0.0, 1000.1));
+        assert!((state.move_budget - 0.05).abs() < 0.001);
+        assert!((state.breath.max - state.breath.current - SPRINT_STAMINA_PER_SECOND * 0.05).abs() < 0.001);
+
+        // Teleporting is never allowed
+        assert!(!state.try_move(Vec3::new(10000.0, 0.0, 0.0), 0.0, now + 1.0));
+    }
+
+    #[test]
+    fn test_movement_budget() {
+        // Joining late in the server's life banks no more than a moment
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let walk = BASE_MOVE_SPEED * MOVE_SPEED_TOLERANCE;
+        let sprint = walk * SPRINT_MULTIPLIER * MOVE_BUDGET_SECONDS;
+        assert!(!state.try_move(Vec3::new(sprint + 1.0, 0.0, 0.0), 0.0, 1000.0));
+
+        // Bunched packets share the time that passed, rather than each earning their own
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        state.last_update = 1000.0;
+        let step = walk * 0.08;
+        let mut moved = 0;
+        for _ in 0..10 {
+            if state.try_move(state.position + Vec3::new(step, 0.0, 0.0), 0.0, 1000.1) {
+                moved += 1;
+            }
+        }
+        assert_eq!(moved, 1);
+        assert_eq!(state.breath.current, state.breath.max);
+
+        // Walking at a steady rate is always fine
+        for tick in 1..=50 {
+            assert!(state.try_move(state.position + Vec3::new(step, 0.0, 0.0), 0.0, 1000.1 + tick as f32 * 0.1));
+        }
+    }
+
+    #[test]
+    fn test_zone_barred_by_lineage() {
+        let mut state = PlayerNetworkState::new(1, Vec3::ZERO);
+        let enoch = Zone::CityOfEnoch.center();
+        assert!(state.is_barred_from(enoch));
+        assert!(!state.is_barred_from(Zone::Bethel.center()));
+
+        state.lineage = Lineage::Cain;
+        assert!(!state.is_barred_from(enoch));
     }
 
     #[test]
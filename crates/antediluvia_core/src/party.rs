@@ -0,0 +1,449 @@
+//! Parties of up to six players who hunt together.
+//!
+//! Actions from different members on the same target chain into skill chains,
+//! experience from a kill is split by each player's contribution, and loot is
+//! shared out by the party's loot rule.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::combat::CombatAction;
+use crate::skill_chain::{skill_chains, SkillChain, MAX_CHAIN_STEPS};
+use crate::error::{AntediluviaError, Result};
+use crate::mob::LootDrop;
+
+/// Most players in a party.
+pub const MAX_PARTY_SIZE: usize = 6;
+
+/// Seconds after one member's action in which another's can chain from it.
+pub const PARTY_COMBO_WINDOW: f32 = 3.0;
+
+/// Seconds members have to choose need, greed or pass.
+pub const LOOT_ROLL_SECONDS: f32 = 30.0;
+
+/// How a party shares loot.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LootRule {
+    /// Each drop goes to the next member in turn.
+    #[default]
+    RoundRobin,
+
+    /// Members roll for each drop; need beats greed.
+    NeedGreed,
+}
+
+impl LootRule {
+    /// Parse a loot rule from its wire name (the variant name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "RoundRobin" => Some(LootRule::RoundRobin),
+            "NeedGreed" => Some(LootRule::NeedGreed),
+            _ => None,
+        }
+    }
+}
+
+/// A member's choice in a need/greed roll.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LootChoice {
+    Pass,
+    Greed,
+    Need,
+}
+
+impl LootChoice {
+    /// Parse a choice from its wire name (the variant name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Need" => Some(LootChoice::Need),
+            "Greed" => Some(LootChoice::Greed),
+            "Pass" => Some(LootChoice::Pass),
+            _ => None,
+        }
+    }
+}
+
+/// An action a member landed on a target.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct ChainLink {
+    member: u64,
+    action: CombatAction,
+    time: f32,
+}
+
+/// A party.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Party {
+    pub id: u32,
+    pub leader: u64,
+    pub members: Vec<u64>,
+    pub loot_rule: LootRule,
+    next_looter: usize,
+    chains: HashMap<u64, Vec<ChainLink>>, // Recent actions by target
+}
+
+impl Party {
+    /// Create a party led by `leader`.
+    pub fn new(id: u32, leader: u64) -> Self {
+        Self {
+            id,
+            leader,
+            members: vec![leader],
+            loot_rule: LootRule::default(),
+            next_looter: 0,
+            chains: HashMap::new(),
+        }
+    }
+
+    /// Check if a player is in the party.
+    pub fn is_member(&self, player_id: u64) -> bool {
+        self.members.contains(&player_id)
+    }
+
+    /// Check if the party has room for no one else.
+    pub fn is_full(&self) -> bool {
+        self.members.len() >= MAX_PARTY_SIZE
+    }
+
+    /// Record a member's action on a target at time `now`.
+    ///
+    /// Returns the longest skill chain it closes on that target, if any. Each
+    /// step must follow the last within the combo window, and a party chain
+    /// needs more than one member's hand in it.
+    pub fn record_action(&mut self, member: u64, target: u64, action: CombatAction, now: f32) -> Option<&'static SkillChain> {
+        let links = self.chains.entry(target).or_default();
+        if links.last().is_some_and(|link| now - link.time > PARTY_COMBO_WINDOW) {
+            links.clear();
+        }
+        links.push(ChainLink { member, action, time: now });
+        if links.len() > MAX_CHAIN_STEPS {
+            links.remove(0);
+        }
+
+        let history: Vec<CombatAction> = links.iter().map(|link| link.action).collect();
+        skill_chains().closing(&history).into_iter().find(|chain| {
+            let steps = &links[links.len().saturating_sub(chain.steps())..];
+            steps.iter().any(|link| link.member != member)
+        })
+    }
+
+    /// Forget chains on a target (e.g. once it is slain).
+    pub fn forget_target(&mut self, target: u64) {
+        self.chains.remove(&target);
+    }
+
+    /// Who takes a drop that is not rolled for: the member whose turn it is,
+    /// or the killer if no member is eligible.
+    pub fn looter(&mut self, killer: u64, eligible: &[u64]) -> u64 {
+        self.next_looter(eligible).unwrap_or(killer)
+    }
+
+    /// The member whose turn it is for a round-robin drop, among those eligible.
+    pub fn next_looter(&mut self, eligible: &[u64]) -> Option<u64> {
+        let count = self.members.len();
+        for offset in 0..count {
+            let index = (self.next_looter + offset) % count;
+            if eligible.contains(&self.members[index]) {
+                self.next_looter = (index + 1) % count;
+                return Some(self.members[index]);
+            }
+        }
+        None
+    }
+}
+
+/// Split experience between players by their contribution (e.g. damage dealt).
+pub fn split_xp(xp: f32, contributions: &HashMap<u64, f32>) -> Vec<(u64, f32)> {
+    let total: f32 = contributions.values().filter(|c| **c > 0.0).sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut shares: Vec<(u64, f32)> = contributions
+        .iter()
+        .filter(|(_, c)| **c > 0.0)
+        .map(|(id, c)| (*id, xp * c / total))
+        .collect();
+    shares.sort_by_key(|(id, _)| *id);
+    shares
+}
+
+/// A need/greed roll over one drop.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LootRoll {
+    pub id: u64,
+    pub drop: LootDrop,
+    pub eligible: Vec<u64>,
+    pub choices: HashMap<u64, LootChoice>,
+    pub time_left: f32,
+}
+
+impl LootRoll {
+    /// Open a roll among the eligible members.
+    pub fn new(id: u64, drop: LootDrop, eligible: Vec<u64>) -> Self {
+        Self {
+            id,
+            drop,
+            eligible,
+            choices: HashMap::new(),
+            time_left: LOOT_ROLL_SECONDS,
+        }
+    }
+
+    /// Record a member's choice.
+    pub fn choose(&mut self, player_id: u64, choice: LootChoice) -> Result<()> {
+        if !self.eligible.contains(&player_id) {
+            return Err(AntediluviaError::PartyError("not eligible for this roll".to_string()));
+        }
+        if self.choices.contains_key(&player_id) {
+            return Err(AntediluviaError::PartyError("already chosen".to_string()));
+        }
+        self.choices.insert(player_id, choice);
+        Ok(())
+    }
+
+    /// Advance the timer.
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.time_left = (self.time_left - delta_seconds).max(0.0);
+    }
+
+    /// Check if everyone has chosen or time is up.
+    pub fn is_complete(&self) -> bool {
+        self.choices.len() >= self.eligible.len() || self.time_left <= 0.0
+    }
+
+    /// The winner: need beats greed, and the highest roll wins within a choice.
+    /// Members who did not choose in time pass. `roll` gives each player's roll.
+    pub fn winner(&self, mut roll: impl FnMut(u64) -> u32) -> Option<u64> {
+        let mut best: Option<(LootChoice, u32, u64)> = None;
+        for (&player_id, &choice) in &self.choices {
+            if choice == LootChoice::Pass {
+                continue;
+            }
+            let entry = (choice, roll(player_id), player_id);
+            if best.is_none_or(|b| (entry.0, entry.1) > (b.0, b.1)) {
+                best = Some(entry);
+            }
+        }
+        best.map(|(_, _, player_id)| player_id)
+    }
+}
+
+/// Every party on the server, and invitations waiting on an answer.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PartyManager {
+    parties: HashMap<u32, Party>,
+    membership: HashMap<u64, u32>, // Player -> party
+    invites: HashMap<u64, u64>,    // Invitee -> inviter
+    last_id: u32,
+}
+
+impl PartyManager {
+    /// Create an empty manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a player's party.
+    pub fn party_of(&self, player_id: u64) -> Option<&Party> {
+        self.membership.get(&player_id).and_then(|id| self.parties.get(id))
+    }
+
+    /// Get a player's party, mutably.
+    pub fn party_of_mut(&mut self, player_id: u64) -> Option<&mut Party> {
+        let id = self.membership.get(&player_id)?;
+        self.parties.get_mut(id)
+    }
+
+    /// Invite a player. Only a party's leader may invite to it.
+    pub fn invite(&mut self, inviter: u64, invitee: u64) -> Result<()> {
+        let refuse = |reason: &str| Err(AntediluviaError::PartyError(reason.to_string()));
+        if inviter == invitee {
+            return refuse("you cannot invite yourself");
+        }
+        if self.membership.contains_key(&invitee) {
+            return refuse("they are already in a party");
+        }
+        if let Some(party) = self.party_of(inviter) {
+            if party.leader != inviter {
+                return refuse("only the leader may invite");
+            }
+            if party.is_full() {
+                return refuse("the party is full");
+            }
+        }
+        self.invites.insert(invitee, inviter);
+        Ok(())
+    }
+
+    /// Accept a pending invitation. Returns the party joined.
+    pub fn accept(&mut self, invitee: u64) -> Result<u32> {
+        let inviter = self
+            .invites
+            .remove(&invitee)
+            .ok_or_else(|| AntediluviaError::PartyError("no pending invitation".to_string()))?;
+        if self.membership.contains_key(&invitee) {
+            return Err(AntediluviaError::PartyError("already in a party".to_string()));
+        }
+        // The inviter may have handed over the lead, or joined another's party, since
+        if self.party_of(inviter).is_some_and(|party| party.leader != inviter) {
+            return Err(AntediluviaError::PartyError("they no longer lead a party".to_string()));
+        }
+
+        // The inviter founds a party on first acceptance
+        let party_id = match self.membership.get(&inviter) {
+            Some(id) => *id,
+            None => {
+                self.last_id += 1;
+                self.parties.insert(self.last_id, Party::new(self.last_id, inviter));
+                self.membership.insert(inviter, self.last_id);
+                self.last_id
+            }
+        };
+        let party = self
+            .parties
+            .get_mut(&party_id)
+            .ok_or_else(|| AntediluviaError::PartyError("the party has disbanded".to_string()))?;
+        if party.is_full() {
+            return Err(AntediluviaError::PartyError("the party is full".to_string()));
+        }
+        party.members.push(invitee);
+        self.membership.insert(invitee, party_id);
+        Ok(party_id)
+    }
+
+    /// Decline a pending invitation. Returns who sent it.
+    pub fn decline(&mut self, invitee: u64) -> Option<u64> {
+        self.invites.remove(&invitee)
+    }
+
+    /// Leave a party. A departing leader hands over to the next member, and a
+    /// party left with one member disbands. Returns the party left.
+    pub fn leave(&mut self, player_id: u64) -> Option<Party> {
+        self.invites.retain(|invitee, inviter| *invitee != player_id && *inviter != player_id);
+        let party_id = self.membership.remove(&player_id)?;
+        let party = self.parties.get_mut(&party_id)?;
+        party.members.retain(|m| *m != player_id);
+        if party.leader == player_id {
+            if let Some(next) = party.members.first() {
+                party.leader = *next;
+            }
+        }
+
+        let party = party.clone();
+        if party.members.len() < 2 {
+            for member in &party.members {
+                self.membership.remove(member);
+            }
+            self.parties.remove(&party_id);
+        }
+        Some(party)
+    }
+
+    /// Hand leadership to another member.
+    pub fn promote(&mut self, leader: u64, new_leader: u64) -> Result<()> {
+        let party = self
+            .party_of_mut(leader)
+            .filter(|p| p.leader == leader)
+            .ok_or_else(|| AntediluviaError::PartyError("only the leader may promote".to_string()))?;
+        if !party.is_member(new_leader) {
+            return Err(AntediluviaError::PartyError("they are not in the party".to_string()));
+        }
+        party.leader = new_leader;
+        Ok(())
+    }
+
+    /// Change the party's loot rule.
+    pub fn set_loot_rule(&mut self, leader: u64, rule: LootRule) -> Result<()> {
+        let party = self
+            .party_of_mut(leader)
+            .filter(|p| p.leader == leader)
+            .ok_or_else(|| AntediluviaError::PartyError("only the leader may change the loot rule".to_string()))?;
+        party.loot_rule = rule;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mob::MobType;
+
+    #[test]
+    fn test_invite_leave_and_leader() {
+        let mut parties = PartyManager::new();
+        parties.invite(1, 2).unwrap();
+        let party_id = parties.accept(2).unwrap();
+        assert_eq!(parties.party_of(2).unwrap().leader, 1);
+
+        // Only the leader invites, and the party caps at six
+        assert!(parties.invite(2, 3).is_err());
+        for invitee in 3..=6 {
+            parties.invite(1, invitee).unwrap();
+            assert_eq!(parties.accept(invitee).unwrap(), party_id);
+        }
+        assert!(parties.invite(1, 7).is_err());
+
+        // An invitation lapses once its sender no longer leads
+        parties.leave(6);
+        parties.invite(1, 7).unwrap();
+        parties.promote(1, 2).unwrap();
+        assert!(parties.accept(7).is_err());
+        assert!(parties.party_of(7).is_none());
+        parties.promote(2, 1).unwrap();
+
+        // The leader leaving hands over to the next member
+        parties.leave(1);
+        assert_eq!(parties.party_of(2).unwrap().leader, 2);
+        assert!(parties.party_of(1).is_none());
+
+        // Down to one member, the party disbands
+        for member in 3..=6 {
+            parties.leave(member);
+        }
+        assert!(parties.party_of(2).is_none());
+    }
+
+    #[test]
+    fn test_chains_across_members() {
+        let mut party = Party::new(1, 10);
+        party.members.push(11);
+
+        // The same member cannot chain with themselves here
+        assert!(party.record_action(10, 99, CombatAction::HunterThrust, 0.0).is_none());
+        assert!(party.record_action(10, 99, CombatAction::ForgeSmash, 1.0).is_none());
+
+        // Another member on the same target within the window
+        party.record_action(10, 99, CombatAction::HunterThrust, 2.0);
+        let chain = party.record_action(11, 99, CombatAction::ForgeSmash, 3.5).unwrap();
+        assert_eq!(chain.name, "Shatter");
+
+        // A different target, or too late, does not chain
+        party.record_action(10, 99, CombatAction::HunterThrust, 4.0);
+        assert!(party.record_action(11, 98, CombatAction::ForgeSmash, 4.5).is_none());
+        assert!(party.record_action(11, 99, CombatAction::ForgeSmash, 8.0).is_none());
+    }
+
+    #[test]
+    fn test_xp_and_loot() {
+        let contributions = HashMap::from([(1, 300.0), (2, 100.0), (3, 0.0)]);
+        assert_eq!(split_xp(100.0, &contributions), vec![(1, 75.0), (2, 25.0)]);
+
+        let mut party = Party::new(1, 1);
+        party.members.extend([2, 3]);
+        let looters: Vec<u64> = (0..4).filter_map(|_| party.next_looter(&[1, 3])).collect();
+        assert_eq!(looters, vec![1, 3, 1, 3]);
+        assert_eq!(party.looter(2, &[]), 2); // No one fought beside the killer
+
+        let mut roll = LootRoll::new(1, MobType::Wolf.loot(2)[1].clone(), vec![1, 2, 3]);
+        assert_eq!(roll.drop.name, "Wolf Fang");
+        roll.choose(1, LootChoice::Greed).unwrap();
+        roll.choose(2, LootChoice::Need).unwrap();
+        assert!(roll.choose(2, LootChoice::Greed).is_err());
+        assert!(!roll.is_complete());
+        roll.choose(3, LootChoice::Pass).unwrap();
+        assert!(roll.is_complete());
+
+        // Need beats a higher greed roll
+        assert_eq!(roll.winner(|id| if id == 1 { 100 } else { 1 }), Some(2));
+    }
+}
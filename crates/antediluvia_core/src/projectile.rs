@@ -0,0 +1,185 @@
+//! Projectiles: sling stones, thrown spears and hurled fire.
+//!
+//! A projectile is aimed along an arc that reaches its target, then flies
+//! under gravity. It strikes the first collider it passes through, or the
+//! ground, and whatever it strikes is what takes the blow: a foe stepping
+//! into the path of a stone takes it instead. The server simulates every
+//! flight; clients fly their own copy from the launch they are sent.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use crate::combat::CombatAction;
+
+/// Seconds a projectile flies before it falls spent.
+pub const PROJECTILE_LIFETIME: f32 = 5.0;
+
+/// Shortest flight, so point-blank shots still travel.
+pub const MIN_FLIGHT_SECONDS: f32 = 0.1;
+
+/// What is flying.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ProjectileKind {
+    SlingStone,
+    Spear,
+    Fire,
+}
+
+impl ProjectileKind {
+    /// Get the launch speed along the ground.
+    pub fn speed(&self) -> f32 {
+        match self {
+            ProjectileKind::SlingStone => 120.0,
+            ProjectileKind::Spear => 90.0,
+            ProjectileKind::Fire => 70.0,
+        }
+    }
+
+    /// Get the pull of gravity. Heavier projectiles arc higher to reach as far.
+    pub fn gravity(&self) -> f32 {
+        match self {
+            ProjectileKind::SlingStone => 40.0,
+            ProjectileKind::Spear => 60.0,
+            ProjectileKind::Fire => 5.0,
+        }
+    }
+
+    /// Get the projectile's own radius.
+    pub fn radius(&self) -> f32 {
+        match self {
+            ProjectileKind::SlingStone => 0.5,
+            ProjectileKind::Spear => 1.0,
+            ProjectileKind::Fire => 2.0,
+        }
+    }
+}
+
+/// What ended a flight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Impact {
+    /// Struck a collider.
+    Hit { id: u64, position: Vec3 },
+
+    /// Struck the ground.
+    Ground(Vec3),
+
+    /// Flew its lifetime without striking anything.
+    Spent(Vec3),
+}
+
+/// A projectile in flight.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Projectile {
+    pub id: u64,
+    pub owner: u64,
+    pub action: CombatAction,
+    pub kind: ProjectileKind,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,
+}
+
+impl Projectile {
+    /// Launch a projectile from `from` along the arc that reaches `to`.
+    pub fn launch(id: u64, owner: u64, action: CombatAction, kind: ProjectileKind, from: Vec3, to: Vec3) -> Self {
+        let offset = to - from;
+        let across = Vec3::new(offset.x, 0.0, offset.z);
+        let flight = (across.length() / kind.speed()).max(MIN_FLIGHT_SECONDS);
+        let rise = (offset.y + 0.5 * kind.gravity() * flight * flight) / flight;
+        Self {
+            id,
+            owner,
+            action,
+            kind,
+            position: from,
+            velocity: across / flight + Vec3::Y * rise,
+            age: 0.0,
+        }
+    }
+
+    /// Resume a flight from a launch position and velocity, e.g. as sent by the server.
+    pub fn resume(id: u64, owner: u64, action: CombatAction, kind: ProjectileKind, position: Vec3, velocity: Vec3) -> Self {
+        Self { id, owner, action, kind, position, velocity, age: 0.0 }
+    }
+
+    /// Fly for `delta_seconds`, returning what the projectile struck, if anything.
+    ///
+    /// `ground` gives the terrain height at an (x, z) coordinate, and
+    /// `colliders` lists what can be struck as (ID, centre, radius). The
+    /// nearest collider along the path this step is struck first.
+    pub fn update(&mut self, delta_seconds: f32, ground: impl Fn(f32, f32) -> f32, colliders: &[(u64, Vec3, f32)]) -> Option<Impact> {
+        let start = self.position;
+        self.velocity.y -= self.kind.gravity() * delta_seconds;
+        self.position += self.velocity * delta_seconds;
+        self.age += delta_seconds;
+
+        let path = self.position - start;
+        let struck = colliders
+            .iter()
+            .filter_map(|(id, centre, radius)| {
+                let along = if path == Vec3::ZERO { 0.0 } else { ((*centre - start).dot(path) / path.length_squared()).clamp(0.0, 1.0) };
+                let closest = start + path * along;
+                (closest.distance(*centre) <= radius + self.kind.radius()).then_some((*id, closest, along))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        if let Some((id, position, _)) = struck {
+            self.position = position;
+            return Some(Impact::Hit { id, position });
+        }
+
+        let floor = ground(self.position.x, self.position.z);
+        if self.position.y <= floor {
+            self.position.y = floor;
+            return Some(Impact::Ground(self.position));
+        }
+        (self.age >= PROJECTILE_LIFETIME).then_some(Impact::Spent(self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fly until the projectile strikes something.
+    fn fly(projectile: &mut Projectile, ground: impl Fn(f32, f32) -> f32, colliders: &[(u64, Vec3, f32)]) -> Impact {
+        loop {
+            if let Some(impact) = projectile.update(1.0 / 30.0, &ground, colliders) {
+                return impact;
+            }
+        }
+    }
+
+    #[test]
+    fn test_arc_reaches_target() {
+        let flat = |_: f32, _: f32| 0.0;
+        let target = Vec3::new(100.0, 3.0, 0.0);
+        let mut stone = Projectile::launch(1, 7, CombatAction::ShepherdSling, ProjectileKind::SlingStone, Vec3::new(0.0, 3.0, 0.0), target);
+        assert!(stone.velocity.y > 0.0); // Thrown upward to arc over
+
+        let impact = fly(&mut stone, flat, &[(42, target, 3.0)]);
+        assert!(matches!(impact, Impact::Hit { id: 42, .. }));
+        assert!(stone.age > 0.5); // It took time to get there
+    }
+
+    #[test]
+    fn test_first_collider_in_the_path_is_struck() {
+        let flat = |_: f32, _: f32| 0.0;
+        let target = Vec3::new(100.0, 3.0, 0.0);
+        let mut fire = Projectile::launch(1, 7, CombatAction::ForgeFire, ProjectileKind::Fire, Vec3::new(0.0, 3.0, 0.0), target);
+        let colliders = [(42, target, 3.0), (43, Vec3::new(50.0, 3.0, 0.0), 3.0)];
+        assert!(matches!(fly(&mut fire, flat, &colliders), Impact::Hit { id: 43, .. }));
+    }
+
+    #[test]
+    fn test_terrain_stops_projectiles() {
+        let ridge = |x: f32, _: f32| if (40.0..60.0).contains(&x) { 200.0 } else { 0.0 };
+        let mut spear = Projectile::launch(1, 7, CombatAction::HunterGiantsBane, ProjectileKind::Spear, Vec3::new(0.0, 3.0, 0.0), Vec3::new(100.0, 3.0, 0.0));
+        match fly(&mut spear, ridge, &[]) {
+            Impact::Ground(position) => assert!((40.0..60.0).contains(&position.x)),
+            other => panic!("expected the ridge to stop the spear, got {:?}", other),
+        }
+
+        // Thrown into the open sky, a projectile eventually falls spent
+        let mut stone = Projectile::resume(2, 7, CombatAction::ShepherdSling, ProjectileKind::SlingStone, Vec3::ZERO, Vec3::new(10.0, 500.0, 0.0));
+        assert!(matches!(fly(&mut stone, |_, _| -1000.0, &[]), Impact::Spent(_)));
+    }
+}
@@ -0,0 +1,293 @@
+//! Player against player.
+//!
+//! Players fight each other only where and when the world allows it. In the
+//! Age of Innocence only those who have flagged themselves for battle fight,
+//! and only each other; from the Age of Violence the wilds are open to all.
+//! Havilah's village shelters everyone until the Age of Judgment, the Eden
+//! Pillar until the Flood, and once the Flood comes nowhere is safe.
+//!
+//! Killing a player who had not taken up arms is murder. A murderer is marked
+//! for a time, may be struck by anyone anywhere, has a bounty set on their
+//! head, and every murder corrupts them more than the last.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use crate::error::{AntediluviaError, Result};
+use crate::world::{CorruptionEvent, FloodStage};
+
+/// Seconds after taking up arms (or last fighting) before the flag can be lowered.
+pub const PVP_FLAG_SECONDS: f32 = 60.0;
+
+/// Seconds a murderer stays marked, for each murder they have committed.
+pub const MURDERER_SECONDS: f32 = 600.0;
+
+/// Experience the elders set on a murderer's head for each murder.
+pub const MURDER_BOUNTY: f32 = 100.0;
+
+/// Experience a victim's contract sets on their murderer's head.
+pub const CONTRACT_BOUNTY: f32 = 50.0;
+
+/// How wide a player stands, to a projectile.
+pub const PLAYER_HIT_RADIUS: f32 = 1.5;
+
+/// Ground where players may not fight each other.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SafeZone {
+    /// Havilah's village, among the elders and the merchant.
+    HavilahVillage,
+
+    /// The foot of the Eden Pillar, where every soul starts.
+    EdenPillar,
+}
+
+impl SafeZone {
+    /// Every safe zone.
+    pub const ALL: [SafeZone; 2] = [SafeZone::HavilahVillage, SafeZone::EdenPillar];
+
+    /// Center of the zone.
+    pub fn center(&self) -> Vec3 {
+        match self {
+            SafeZone::HavilahVillage => Vec3::new(0.0, 0.0, 10.0),
+            SafeZone::EdenPillar => Vec3::new(0.0, 0.0, 100.0),
+        }
+    }
+
+    /// Radius of the zone.
+    pub fn radius(&self) -> f32 {
+        match self {
+            SafeZone::HavilahVillage => 80.0,
+            SafeZone::EdenPillar => 40.0,
+        }
+    }
+
+    /// Check if the zone still shelters players in a flood stage.
+    pub fn shelters(&self, stage: FloodStage) -> bool {
+        match self {
+            SafeZone::HavilahVillage => stage < FloodStage::Judgment,
+            SafeZone::EdenPillar => stage < FloodStage::TheFlood,
+        }
+    }
+
+    /// Check if a position is on ground that shelters players in a flood stage.
+    pub fn is_safe(position: Vec3, stage: FloodStage) -> bool {
+        Self::ALL.into_iter().any(|zone| {
+            let center = zone.center();
+            zone.shelters(stage) && Vec3::new(position.x - center.x, 0.0, position.z - center.z).length() < zone.radius()
+        })
+    }
+}
+
+/// A player's standing in the fighting between players.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PvpStatus {
+    pub flagged: bool, // Has taken up arms against other players
+    pub flag_timer: f32, // Seconds until the flag can be lowered
+    pub murders: u32,
+    pub murderer_timer: f32, // Seconds the murderer's mark lasts
+}
+
+impl PvpStatus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the player bears the murderer's mark.
+    pub fn is_murderer(&self) -> bool {
+        self.murderer_timer > 0.0
+    }
+
+    /// Check if the player has taken up arms: flagged, or marked a murderer.
+    pub fn is_combatant(&self) -> bool {
+        self.flagged || self.is_murderer()
+    }
+
+    /// Raise or lower the flag. It cannot be lowered soon after raising it or fighting.
+    pub fn set_flag(&mut self, flagged: bool) -> Result<()> {
+        if !flagged && self.flagged && self.flag_timer > 0.0 {
+            return Err(AntediluviaError::CombatError(format!(
+                "the flag cannot be lowered for another {:.0}s",
+                self.flag_timer
+            )));
+        }
+        if flagged && !self.flagged {
+            self.flag_timer = PVP_FLAG_SECONDS;
+        }
+        self.flagged = flagged;
+        Ok(())
+    }
+
+    /// Note the player fought another, keeping their flag up.
+    pub fn engage(&mut self) {
+        if self.flagged {
+            self.flag_timer = PVP_FLAG_SECONDS;
+        }
+    }
+
+    /// Count down the flag and the murderer's mark. Returns true when the mark fades.
+    pub fn update(&mut self, delta_seconds: f32) -> bool {
+        let was_murderer = self.is_murderer();
+        self.flag_timer = (self.flag_timer - delta_seconds).max(0.0);
+        self.murderer_timer = (self.murderer_timer - delta_seconds).max(0.0);
+        was_murderer && !self.is_murderer()
+    }
+
+    /// Mark the player for a murder, returning the corruption it brings. Each
+    /// murder corrupts more than the last, and marks the murderer for longer.
+    pub fn commit_murder(&mut self) -> f32 {
+        self.murders += 1;
+        self.murderer_timer = MURDERER_SECONDS * self.murders as f32;
+        CorruptionEvent::PlayerKill.delta() * self.murders as f32
+    }
+}
+
+/// Check if one player may strike another, given where each stands and the
+/// flood stage.
+pub fn can_attack(attacker: &PvpStatus, attacker_pos: Vec3, victim: &PvpStatus, victim_pos: Vec3, stage: FloodStage) -> Result<()> {
+    let refuse = |reason: &str| Err(AntediluviaError::CombatError(reason.to_string()));
+
+    // Murderers are fair game anywhere
+    if victim.is_murderer() {
+        return Ok(());
+    }
+    if SafeZone::is_safe(attacker_pos, stage) || SafeZone::is_safe(victim_pos, stage) {
+        return refuse("no blood may be shed on safe ground");
+    }
+    if stage == FloodStage::Innocence && !(attacker.is_combatant() && victim.is_combatant()) {
+        return refuse("in the Age of Innocence only those flagged for battle fight each other");
+    }
+    Ok(())
+}
+
+/// Check if killing a player is murder: they had not taken up arms.
+pub fn is_murder(victim: &PvpStatus) -> bool {
+    !victim.is_combatant()
+}
+
+/// A reward set on a murderer's head.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Bounty {
+    pub target: u64,
+    pub issuer: Option<u64>, // None when set by the elders
+    pub reward: f32, // Experience
+}
+
+/// The bounties on murderers' heads, and the grievances their victims may
+/// turn into contracts.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BountyBoard {
+    pub bounties: Vec<Bounty>,
+    pub grievances: Vec<(u64, u64)>, // (victim, murderer) not yet set as contracts
+}
+
+impl BountyBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a murder: the elders set a bounty, and the victim may set a contract.
+    pub fn record_murder(&mut self, murderer: u64, victim: u64) {
+        self.bounties.push(Bounty { target: murderer, issuer: None, reward: MURDER_BOUNTY });
+        self.grievances.push((victim, murderer));
+    }
+
+    /// Set a contract on the player who murdered `issuer`.
+    pub fn post_contract(&mut self, issuer: u64, target: u64) -> Result<()> {
+        let Some(index) = self.grievances.iter().position(|g| *g == (issuer, target)) else {
+            return Err(AntediluviaError::CombatError("a contract can only be set on your own murderer".to_string()));
+        };
+        self.grievances.remove(index);
+        self.bounties.push(Bounty { target, issuer: Some(issuer), reward: CONTRACT_BOUNTY });
+        Ok(())
+    }
+
+    /// Get the total reward on a player's head.
+    pub fn reward_on(&self, target: u64) -> f32 {
+        self.bounties.iter().filter(|b| b.target == target).map(|b| b.reward).sum()
+    }
+
+    /// Collect the bounties on a slain player, returning the reward. Nobody
+    /// collects on themselves or on their own contracts.
+    pub fn claim(&mut self, hunter: u64, target: u64) -> f32 {
+        if hunter == target {
+            return 0.0;
+        }
+        let mut reward = 0.0;
+        self.bounties.retain(|b| {
+            let claimed = b.target == target && b.issuer != Some(hunter);
+            if claimed {
+                reward += b.reward;
+            }
+            !claimed
+        });
+        reward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protection_by_stage() {
+        let wilds = Vec3::new(500.0, 0.0, 500.0);
+        let village = SafeZone::HavilahVillage.center();
+        let (peaceful, mut flagged) = (PvpStatus::new(), PvpStatus::new());
+        flagged.set_flag(true).unwrap();
+
+        // Innocence: only the flagged fight, and only each other
+        assert!(can_attack(&flagged, wilds, &peaceful, wilds, FloodStage::Innocence).is_err());
+        assert!(can_attack(&flagged, wilds, &flagged, wilds, FloodStage::Innocence).is_ok());
+
+        // Violence opens the wilds, but the village still shelters
+        assert!(can_attack(&peaceful, wilds, &peaceful, wilds, FloodStage::Violence).is_ok());
+        assert!(can_attack(&flagged, wilds, &flagged, village, FloodStage::Violence).is_err());
+
+        // Judgment leaves only the Pillar, and the Flood nothing
+        assert!(can_attack(&peaceful, wilds, &peaceful, village, FloodStage::Judgment).is_ok());
+        assert!(!SafeZone::is_safe(SafeZone::EdenPillar.center(), FloodStage::TheFlood));
+    }
+
+    #[test]
+    fn test_murder_escalates() {
+        let mut status = PvpStatus::new();
+        let first = status.commit_murder();
+        let second = status.commit_murder();
+        assert!(second > first);
+        assert!(status.is_murderer() && status.is_combatant());
+
+        // A murderer may be struck even on safe ground in the Age of Innocence
+        let village = SafeZone::HavilahVillage.center();
+        assert!(can_attack(&PvpStatus::new(), village, &status, village, FloodStage::Innocence).is_ok());
+
+        assert!(!status.update(MURDERER_SECONDS));
+        assert!(status.update(MURDERER_SECONDS)); // Two murders, twice as long
+        assert!(!status.is_murderer());
+    }
+
+    #[test]
+    fn test_flag_cannot_be_dropped_mid_fight() {
+        let mut status = PvpStatus::new();
+        status.set_flag(true).unwrap();
+        assert!(status.set_flag(false).is_err());
+        status.update(PVP_FLAG_SECONDS);
+        status.engage();
+        assert!(status.set_flag(false).is_err());
+        status.update(PVP_FLAG_SECONDS);
+        assert!(status.set_flag(false).is_ok());
+    }
+
+    #[test]
+    fn test_bounties() {
+        let mut board = BountyBoard::new();
+        board.record_murder(7, 1);
+        assert!(board.post_contract(2, 7).is_err()); // Not their murderer
+        board.post_contract(1, 7).unwrap();
+        assert!(board.post_contract(1, 7).is_err()); // Only once per murder
+        assert_eq!(board.reward_on(7), MURDER_BOUNTY + CONTRACT_BOUNTY);
+
+        // The victim can't collect on their own contract
+        assert_eq!(board.claim(1, 7), MURDER_BOUNTY);
+        assert_eq!(board.claim(3, 7), CONTRACT_BOUNTY);
+        assert_eq!(board.reward_on(7), 0.0);
+    }
+}
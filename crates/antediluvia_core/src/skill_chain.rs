@@ -0,0 +1,343 @@
+//! Skill chains: sequences of two to four actions landed on one target in
+//! quick succession.
+//!
+//! Chains are defined in `data/skill_chains.json`. Each closes with an
+//! elemental property, and the property a chain closes with stays open for
+//! the next weapon skill: if that skill closes a chain of its own whose
+//! property fuses with the open one, the two form a chain of the higher tier,
+//! as Shatter's Earth and Kindle's Fire form Magma. Higher-tier chains may
+//! also be given sequences of their own, as the Fountains of the Deep are.
+//! Sequences are kept in a trie so the longest chain ending in the latest
+//! action is found in one walk per starting point.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::combat::CombatAction;
+use crate::error::{AntediluviaError, Result};
+use crate::status::EffectKind;
+
+/// Fewest actions in a chain.
+pub const MIN_CHAIN_STEPS: usize = 2;
+
+/// Most actions in a chain.
+pub const MAX_CHAIN_STEPS: usize = 4;
+
+/// The chains that ship with the game.
+const SKILL_CHAIN_DATA: &str = include_str!("../data/skill_chains.json");
+
+/// The elemental property a chain closes with.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ChainProperty {
+    // Tier 1: a single element
+    Earth,
+    Fire,
+    Holy,
+    Wind,
+
+    // Tier 2: two elements fused
+    Magma,
+    Radiance,
+    Tempest,
+
+    // Tier 3: the waters
+    Deluge,
+}
+
+impl ChainProperty {
+    /// Get the tier: one less than the steps a chain of this property takes.
+    pub fn tier(&self) -> usize {
+        match self {
+            ChainProperty::Earth | ChainProperty::Fire | ChainProperty::Holy | ChainProperty::Wind => 1,
+            ChainProperty::Magma | ChainProperty::Radiance | ChainProperty::Tempest => 2,
+            ChainProperty::Deluge => 3,
+        }
+    }
+
+    /// Get the single elements the property is made of.
+    pub fn elements(&self) -> &'static [ChainProperty] {
+        use ChainProperty::*;
+        match self {
+            Earth => &[Earth],
+            Fire => &[Fire],
+            Holy => &[Holy],
+            Wind => &[Wind],
+            Magma => &[Earth, Fire],
+            Radiance => &[Holy, Fire],
+            Tempest => &[Wind, Earth],
+            Deluge => &[Earth, Fire, Holy, Wind],
+        }
+    }
+
+    /// Fuse an open property with the single element of the chain that
+    /// follows it, getting the property of the higher tier they form. Two
+    /// elements fuse only in the pairs that make Magma, Radiance and Tempest;
+    /// a fused pair takes any element it lacks into the Deluge.
+    pub fn combine(self, next: ChainProperty) -> Option<ChainProperty> {
+        use ChainProperty::*;
+        match (self.tier(), next.tier()) {
+            (1, 1) => match (self, next) {
+                (Earth, Fire) | (Fire, Earth) => Some(Magma),
+                (Holy, Fire) | (Fire, Holy) => Some(Radiance),
+                (Wind, Earth) | (Earth, Wind) => Some(Tempest),
+                _ => None,
+            },
+            (2, 1) if !self.elements().contains(&next) => Some(Deluge),
+            _ => None,
+        }
+    }
+}
+
+/// What a chain does besides multiplying the damage of its closing action.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ChainEffect {
+    /// Lay a harmful effect on the target.
+    Inflict(EffectKind),
+
+    /// Grant a helpful effect to whoever closed the chain (songs reach their allies).
+    Bless(EffectKind),
+
+    /// Heal whoever closed the chain.
+    Heal(f32),
+}
+
+/// A skill chain (combo).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SkillChain {
+    pub name: String,
+    pub property: ChainProperty,
+    #[serde(default)]
+    pub sequence: Vec<CombatAction>, // Empty if the chain is only formed by fusion
+    pub damage_multiplier: f32,
+    #[serde(default)]
+    pub effects: Vec<ChainEffect>,
+}
+
+impl SkillChain {
+    /// Check if a sequence of actions is exactly this chain.
+    pub fn matches(&self, sequence: &[CombatAction]) -> bool {
+        self.sequence == sequence
+    }
+
+    /// Get the chain's tier.
+    pub fn tier(&self) -> usize {
+        self.property.tier()
+    }
+
+    /// Get how many actions close the chain, however it is formed.
+    pub fn steps(&self) -> usize {
+        self.tier() + 1
+    }
+
+    /// Get the harmful effects the chain lays on its target.
+    pub fn inflicted(&self) -> impl Iterator<Item = EffectKind> + '_ {
+        self.effects.iter().filter_map(|e| match e {
+            ChainEffect::Inflict(kind) => Some(*kind),
+            _ => None,
+        })
+    }
+
+    /// Get the helpful effects the chain grants whoever closed it.
+    pub fn blessings(&self) -> impl Iterator<Item = EffectKind> + '_ {
+        self.effects.iter().filter_map(|e| match e {
+            ChainEffect::Bless(kind) => Some(*kind),
+            _ => None,
+        })
+    }
+
+    /// Get the healing the chain does for whoever closed it.
+    pub fn healing(&self) -> f32 {
+        self.effects.iter().map(|e| if let ChainEffect::Heal(amount) = e { *amount } else { 0.0 }).sum()
+    }
+}
+
+/// A node of the chain trie.
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    children: HashMap<CombatAction, usize>,
+    chain: Option<usize>, // The chain this sequence completes
+}
+
+/// Every skill chain, indexed by sequence.
+#[derive(Clone, Debug)]
+pub struct SkillChainRegistry {
+    chains: Vec<SkillChain>,
+    nodes: Vec<TrieNode>, // Root first
+}
+
+impl SkillChainRegistry {
+    /// Build a registry from chains, checking each has a sensible length for
+    /// its property and that no two share a sequence.
+    pub fn new(chains: Vec<SkillChain>) -> Result<Self> {
+        let invalid = |chain: &SkillChain, reason: &str| AntediluviaError::DataError(format!("skill chain {}: {}", chain.name, reason));
+        let mut nodes = vec![TrieNode::default()];
+        for (index, chain) in chains.iter().enumerate() {
+            if chain.sequence.is_empty() {
+                if chain.tier() == 1 {
+                    return Err(invalid(chain, "a single element has no chains to be fused from"));
+                }
+                continue;
+            }
+            if !(MIN_CHAIN_STEPS..=MAX_CHAIN_STEPS).contains(&chain.sequence.len()) {
+                return Err(invalid(chain, "must take two to four actions"));
+            }
+            // Fusion finds where a chain opened by its steps, so its sequence must match them
+            if chain.sequence.len() != chain.steps() {
+                return Err(invalid(chain, "takes the wrong number of actions for its property"));
+            }
+
+            let mut node = 0;
+            for action in &chain.sequence {
+                node = match nodes[node].children.get(action) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(TrieNode::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(*action, child);
+                        child
+                    }
+                };
+            }
+            if nodes[node].chain.replace(index).is_some() {
+                return Err(invalid(chain, "repeats another chain's sequence"));
+            }
+        }
+        Ok(Self { chains, nodes })
+    }
+
+    /// Load a registry from JSON: a list of chains.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::new(serde_json::from_str(json)?)
+    }
+
+    /// Get every chain.
+    pub fn chains(&self) -> &[SkillChain] {
+        &self.chains
+    }
+
+    /// Find a chain by name.
+    pub fn get(&self, name: &str) -> Option<&SkillChain> {
+        self.chains.iter().find(|c| c.name == name)
+    }
+
+    /// Find the chain formed by fusing into a property: the first of that property.
+    pub fn of_property(&self, property: ChainProperty) -> Option<&SkillChain> {
+        self.chains.iter().find(|c| c.property == property)
+    }
+
+    /// Find the chain a sequence completes exactly.
+    pub fn find(&self, sequence: &[CombatAction]) -> Option<&SkillChain> {
+        let mut node = 0;
+        for action in sequence {
+            node = *self.nodes[node].children.get(action)?;
+        }
+        self.nodes[node].chain.map(|i| &self.chains[i])
+    }
+
+    /// Find the chains completed by the end of `history`, longest first.
+    ///
+    /// Besides the chains whose sequences end it, a chain closed by the
+    /// latest action fuses with the one left open where it began, so two
+    /// chains sharing an action can form one of the higher tier.
+    pub fn closing(&self, history: &[CombatAction]) -> Vec<&SkillChain> {
+        let start = history.len().saturating_sub(MAX_CHAIN_STEPS);
+        let end = history.len().saturating_sub(MIN_CHAIN_STEPS - 1);
+        let mut closed: Vec<&SkillChain> = (start..end).filter_map(|i| self.find(&history[i..])).collect();
+
+        for next in closed.clone() {
+            let opened = history.len() + 1 - next.steps();
+            for open in self.closing(&history[..opened]) {
+                let fused = open.property.combine(next.property).and_then(|p| self.of_property(p));
+                if let Some(chain) = fused.filter(|c| !closed.iter().any(|d| std::ptr::eq(*d, *c))) {
+                    closed.push(chain);
+                }
+            }
+        }
+        closed.sort_by_key(|c| Reverse(c.tier()));
+        closed
+    }
+}
+
+/// The skill chain registry, loaded from the game's data on first use.
+pub fn skill_chains() -> &'static SkillChainRegistry {
+    static REGISTRY: OnceLock<SkillChainRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| SkillChainRegistry::from_json(SKILL_CHAIN_DATA).expect("skill chain data is invalid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skill_chain_matching() {
+        let shatter = skill_chains().get("Shatter").unwrap();
+        assert!(shatter.matches(&[CombatAction::HunterThrust, CombatAction::ForgeSmash]));
+        assert!(!shatter.matches(&[CombatAction::HunterSlash, CombatAction::ForgeSmash]));
+        assert_eq!(skill_chains().find(&[CombatAction::HunterThrust, CombatAction::ForgeSmash]), Some(shatter));
+        assert_eq!(skill_chains().find(&[CombatAction::HunterThrust]), None);
+    }
+
+    #[test]
+    fn test_chains_climb_tiers() {
+        let mut history = vec![CombatAction::ShepherdBlock, CombatAction::HunterThrust, CombatAction::ForgeSmash];
+        assert_eq!(skill_chains().closing(&history)[0].name, "Shatter");
+
+        // Carrying on from Shatter reaches higher tiers, longest chain first
+        history.push(CombatAction::ForgeFire);
+        let magma = skill_chains().closing(&history)[0];
+        assert_eq!((magma.name.as_str(), magma.tier()), ("Magma", 2));
+        history.push(CombatAction::ForgeQuench);
+        let deluge = skill_chains().closing(&history)[0];
+        assert_eq!(deluge.property, ChainProperty::Deluge);
+        assert!(deluge.damage_multiplier > magma.damage_multiplier);
+        assert_eq!(deluge.inflicted().collect::<Vec<_>>(), vec![EffectKind::Lament]);
+        assert_eq!(deluge.blessings().collect::<Vec<_>>(), vec![EffectKind::SongOfAscent]);
+    }
+
+    #[test]
+    fn test_closing_properties_fuse() {
+        assert_eq!(ChainProperty::Earth.combine(ChainProperty::Fire), Some(ChainProperty::Magma));
+        assert_eq!(ChainProperty::Holy.combine(ChainProperty::Earth), None);
+        assert_eq!(ChainProperty::Magma.combine(ChainProperty::Holy), Some(ChainProperty::Deluge));
+        assert_eq!(ChainProperty::Magma.combine(ChainProperty::Fire), None);
+
+        // Gale leaves Wind open; Landslide, begun on Gale's last step, closes Earth into a Tempest
+        let gale = [CombatAction::ShepherdSling, CombatAction::HunterLunge];
+        let landslide = [CombatAction::HunterLunge, CombatAction::ForgeSmash];
+        assert_eq!(skill_chains().find(&gale).unwrap().property, ChainProperty::Wind);
+        assert_eq!(skill_chains().find(&landslide).unwrap().property, ChainProperty::Earth);
+        let history = [CombatAction::ShepherdSling, CombatAction::HunterLunge, CombatAction::ForgeSmash];
+        let tempest = skill_chains().closing(&history)[0];
+        assert_eq!((tempest.name.as_str(), tempest.steps()), ("Tempest", 3));
+        assert_eq!(skill_chains().find(&history), None);
+
+        // Righteous Fury's Holy and Brand's Fire dawn into Radiance
+        let history = [CombatAction::ShepherdRebuke, CombatAction::HunterThrust, CombatAction::ForgeMoltenStrike];
+        assert_eq!(skill_chains().closing(&history)[0].property, ChainProperty::Radiance);
+
+        // Holy left open does not fuse with Shatter's Earth: only Shatter closes
+        let history = [CombatAction::ShepherdRebuke, CombatAction::HunterThrust, CombatAction::ForgeSmash];
+        let closed: Vec<_> = skill_chains().closing(&history).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(closed, vec!["Shatter"]);
+    }
+
+    #[test]
+    fn test_invalid_chain_data() {
+        let too_short = r#"[{"name": "Tap", "property": "Earth", "sequence": ["HunterThrust"], "damage_multiplier": 1.5}]"#;
+        assert!(SkillChainRegistry::from_json(too_short).is_err());
+        // A chain takes exactly the steps its property's tier calls for, no fewer and no more
+        let wrong_tier = r#"[{"name": "Quake", "property": "Deluge", "sequence": ["HunterThrust", "ForgeSmash"], "damage_multiplier": 5.0}]"#;
+        assert!(SkillChainRegistry::from_json(wrong_tier).unwrap_err().to_string().contains("wrong number of actions"));
+        let overlong = r#"[{"name": "Ember", "property": "Fire", "sequence": ["HunterThrust", "ForgeSmash", "ForgeFire"], "damage_multiplier": 2.0}]"#;
+        assert!(SkillChainRegistry::from_json(overlong).unwrap_err().to_string().contains("wrong number of actions"));
+        let twice = r#"[
+            {"name": "A", "property": "Earth", "sequence": ["HunterThrust", "ForgeSmash"], "damage_multiplier": 2.0},
+            {"name": "B", "property": "Fire", "sequence": ["HunterThrust", "ForgeSmash"], "damage_multiplier": 2.0}
+        ]"#;
+        assert!(SkillChainRegistry::from_json(twice).is_err());
+        let unfused = r#"[{"name": "Rumble", "property": "Earth", "damage_multiplier": 2.0}]"#;
+        assert!(SkillChainRegistry::from_json(unfused).is_err());
+        assert!(SkillChainRegistry::from_json("not json").is_err());
+    }
+}
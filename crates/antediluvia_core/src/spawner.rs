@@ -0,0 +1,501 @@
+//! The mob spawner: keeps the wilds stocked from spawn tables.
+//!
+//! Spawn zones, the weighted spawn table of each biome and the level bands by
+//! distance from Eden are defined in `data/spawns.json`. Each zone holds up to
+//! its population cap, and a slain mob's place is filled after the zone's
+//! respawn time, by whatever its table rolls. Night and mist bring out the
+//! nocturnal hunters, storms swell the packs and rain thins them, and the
+//! world's corruption breeds the corrupted and makes every mob stronger.
+//!
+//! A few of the mobs rolled are elites. Named rares are listed there too: each
+//! haunts one zone and returns a while after it dies, on a timer rolled
+//! between its shortest and longest absence.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use std::sync::OnceLock;
+use crate::error::{AntediluviaError, Result};
+use crate::mob::{LootDrop, Mob, MobType, MOB_ID_BASE};
+use crate::variant::{Affix, MobVariant};
+use crate::world::{is_night, WeatherState};
+
+/// The spawn tables that ship with the game.
+const SPAWN_DATA: &str = include_str!("../data/spawns.json");
+
+/// How much likelier a nocturnal mob is in the dark.
+pub const NOCTURNAL_WEIGHT: f32 = 3.0;
+
+/// How much more crowded the wilds grow at night.
+pub const NIGHT_POPULATION: f32 = 1.5;
+
+/// Corruption that doubles the chance of a corrupted mob, and adds a level to every mob.
+pub const CORRUPTION_PER_LEVEL: f32 = 25.0;
+
+/// The lay of the land, which decides what lives there.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Meadow,
+    Savanna,
+    Blight,
+    Badlands,
+    Crags,
+}
+
+/// A mob type a biome's table may roll.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpawnEntry {
+    pub mob_type: MobType,
+    pub weight: f32,
+    #[serde(default)]
+    pub nocturnal: bool, // Likelier in the dark
+    #[serde(default)]
+    pub corrupted: bool, // Likelier as the world corrupts
+}
+
+/// What spawns in a biome, by weight.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpawnTable {
+    pub biome: Biome,
+    pub entries: Vec<SpawnEntry>,
+}
+
+/// The levels of mobs spawned at least `distance` from Eden.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LevelBand {
+    pub distance: f32,
+    pub min_level: u32,
+    pub max_level: u32,
+}
+
+/// A stretch of ground mobs spawn on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpawnZone {
+    pub name: String,
+    pub biome: Biome,
+    pub center: Vec3,
+    pub radius: f32,
+    pub cap: u32, // Mobs alive at once, in daylight and fair weather
+    pub respawn_seconds: f32,
+    #[serde(default)]
+    pub taint: f32, // Corruption the land holds above the world's
+}
+
+/// The time, weather and corruption mobs spawn under.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnConditions {
+    pub hour: f32,
+    pub weather: WeatherState,
+    pub corruption: f32,
+}
+
+impl SpawnConditions {
+    pub fn new(hour: f32, weather: WeatherState, corruption: f32) -> Self {
+        Self { hour, weather, corruption }
+    }
+
+    /// Check if it is dark enough for nocturnal hunters: night, mist or storm.
+    pub fn is_dark(&self) -> bool {
+        is_night(self.hour) || matches!(self.weather, WeatherState::Mist | WeatherState::Thunderstorm | WeatherState::TheDeluge)
+    }
+
+    /// Get the multiplier on every zone's population cap.
+    pub fn population(&self) -> f32 {
+        let weather = match self.weather {
+            WeatherState::Clear | WeatherState::Mist => 1.0,
+            WeatherState::LightRain => 0.9,
+            WeatherState::HeavyRain => 0.75, // Beasts keep to their dens
+            WeatherState::Thunderstorm => 1.25,
+            WeatherState::TheDeluge => 1.5,
+        };
+        let night = if is_night(self.hour) { NIGHT_POPULATION } else { 1.0 };
+        weather * night
+    }
+
+    /// Get the levels the world's corruption adds to every mob.
+    pub fn level_bonus(&self) -> u32 {
+        (self.corruption.max(0.0) / CORRUPTION_PER_LEVEL) as u32
+    }
+
+    /// Get an entry's weight under these conditions.
+    pub fn weight(&self, entry: &SpawnEntry) -> f32 {
+        let mut weight = entry.weight;
+        if entry.nocturnal && self.is_dark() {
+            weight *= NOCTURNAL_WEIGHT;
+        }
+        if entry.corrupted {
+            weight *= 1.0 + self.corruption.max(0.0) / CORRUPTION_PER_LEVEL;
+        }
+        weight
+    }
+}
+
+/// A named rare: where it haunts and how long it stays away.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RareSpawn {
+    pub name: String,
+    pub mob_type: MobType,
+    pub zone: String, // The spawn zone it haunts
+    pub level: u32,
+    pub affixes: Vec<Affix>,
+    pub respawn_min: f32, // Seconds, after its death, before it returns
+    pub respawn_max: f32,
+    pub trophy: LootDrop,
+}
+
+/// Every spawn zone, spawn table, level band and rare.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnData {
+    zones: Vec<SpawnZone>,
+    tables: Vec<SpawnTable>,
+    levels: Vec<LevelBand>, // Nearest Eden first
+    #[serde(default)]
+    rares: Vec<RareSpawn>,
+    #[serde(default)]
+    elite_chance: f32, // Chance a mob rolled from a table is an elite
+}
+
+impl SpawnData {
+    /// Build spawn data, checking every zone's biome has a table that can roll
+    /// something, the level bands climb outward from Eden and every rare
+    /// haunts a zone that exists.
+    pub fn new(
+        zones: Vec<SpawnZone>,
+        tables: Vec<SpawnTable>,
+        levels: Vec<LevelBand>,
+        rares: Vec<RareSpawn>,
+        elite_chance: f32,
+    ) -> Result<Self> {
+        let invalid = |reason: String| Err(AntediluviaError::DataError(reason));
+        for zone in &zones {
+            let Some(table) = tables.iter().find(|t| t.biome == zone.biome) else {
+                return invalid(format!("spawn zone {}: no spawn table for {:?}", zone.name, zone.biome));
+            };
+            if table.entries.iter().all(|e| e.weight <= 0.0) {
+                return invalid(format!("spawn table for {:?} can roll nothing", table.biome));
+            }
+            if zone.radius <= 0.0 || zone.respawn_seconds < 0.0 {
+                return invalid(format!("spawn zone {}: radius must be positive and respawn time not negative", zone.name));
+            }
+            if !(0.0..=100.0).contains(&zone.taint) {
+                return invalid(format!("spawn zone {}: taint must be between 0 and 100", zone.name));
+            }
+        }
+        if levels.first().is_none_or(|band| band.distance > 0.0) {
+            return invalid("level bands must start at Eden".to_string());
+        }
+        for pair in levels.windows(2) {
+            if pair[1].distance <= pair[0].distance {
+                return invalid("level bands must climb outward from Eden".to_string());
+            }
+        }
+        if let Some(band) = levels.iter().find(|b| b.min_level == 0 || b.min_level > b.max_level) {
+            return invalid(format!("level band at {} has no levels", band.distance));
+        }
+        for rare in &rares {
+            if !zones.iter().any(|z| z.name == rare.zone) {
+                return invalid(format!("rare {}: no spawn zone {}", rare.name, rare.zone));
+            }
+            if rare.level == 0 || rare.respawn_min <= 0.0 || rare.respawn_min > rare.respawn_max {
+                return invalid(format!("rare {}: needs a level and a respawn window", rare.name));
+            }
+        }
+        if !(0.0..=1.0).contains(&elite_chance) {
+            return invalid("elite chance must be between 0 and 1".to_string());
+        }
+        Ok(Self { zones, tables, levels, rares, elite_chance })
+    }
+
+    /// Load spawn data from JSON: the zones, tables, level bands and rares.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let data: Self = serde_json::from_str(json)?;
+        Self::new(data.zones, data.tables, data.levels, data.rares, data.elite_chance)
+    }
+
+    /// Get every rare.
+    pub fn rares(&self) -> &[RareSpawn] {
+        &self.rares
+    }
+
+    /// Get every spawn zone.
+    pub fn zones(&self) -> &[SpawnZone] {
+        &self.zones
+    }
+
+    /// Get a biome's spawn table.
+    pub fn table(&self, biome: Biome) -> Option<&SpawnTable> {
+        self.tables.iter().find(|t| t.biome == biome)
+    }
+
+    /// Get the level band for a position, by its distance from Eden.
+    pub fn level_band(&self, position: Vec3) -> LevelBand {
+        let distance = Vec3::new(position.x, 0.0, position.z).length();
+        *self.levels.iter().rev().find(|b| b.distance <= distance).unwrap_or(&self.levels[0])
+    }
+}
+
+/// The spawn data, loaded from the game's data on first use.
+pub fn spawn_data() -> &'static SpawnData {
+    static DATA: OnceLock<SpawnData> = OnceLock::new();
+    DATA.get_or_init(|| SpawnData::from_json(SPAWN_DATA).expect("spawn data is invalid"))
+}
+
+/// The mobs of one zone.
+#[derive(Clone, Debug, Default)]
+struct ZonePopulation {
+    alive: Vec<u64>,
+    respawns: Vec<f32>, // Seconds until each emptied place can be filled
+}
+
+/// A rare's comings and goings.
+#[derive(Clone, Debug)]
+struct RareHunt {
+    alive: Option<u64>,
+    timer: f32, // Seconds until it returns; rolled ahead while it lives
+}
+
+/// Spawns mobs into the zones, keeping each up to its cap, and brings the
+/// rares back on their timers.
+#[derive(Clone, Debug)]
+pub struct MobSpawner {
+    data: SpawnData,
+    zones: Vec<ZonePopulation>,
+    rares: Vec<RareHunt>,
+    next_id: u64,
+}
+
+impl MobSpawner {
+    pub fn new(data: SpawnData) -> Self {
+        Self {
+            zones: vec![ZonePopulation::default(); data.zones.len()],
+            rares: data.rares.iter().map(|r| RareHunt { alive: None, timer: r.respawn_min }).collect(),
+            data,
+            next_id: MOB_ID_BASE,
+        }
+    }
+
+    /// Get each rare with the seconds until it returns, or None while it is abroad.
+    pub fn rare_timers(&self) -> impl Iterator<Item = (&RareSpawn, Option<f32>)> {
+        self.data.rares.iter().zip(&self.rares).map(|(rare, hunt)| (rare, hunt.alive.is_none().then_some(hunt.timer)))
+    }
+
+    /// Get the number of living mobs spawned.
+    pub fn population(&self) -> usize {
+        self.zones.iter().map(|z| z.alive.len()).sum()
+    }
+
+    /// Count down respawns and fill every zone up to its cap, returning the
+    /// mobs spawned. `roll` gives numbers in `0.0..1.0`.
+    pub fn update(&mut self, delta_seconds: f32, conditions: &SpawnConditions, roll: &mut impl FnMut() -> f32) -> Vec<Mob> {
+        let mut spawned = Vec::new();
+        for (index, zone) in self.data.zones.iter().enumerate() {
+            let population = &mut self.zones[index];
+            population.respawns.retain_mut(|timer| {
+                *timer -= delta_seconds;
+                *timer > 0.0
+            });
+
+            let cap = (zone.cap as f32 * conditions.population()).round() as usize;
+            while population.alive.len() + population.respawns.len() < cap {
+                let Some(mob) = Self::roll_mob(&self.data, zone, self.next_id, conditions, roll) else { break; };
+                self.next_id += 1;
+                population.alive.push(mob.id);
+                spawned.push(mob);
+            }
+        }
+
+        for (rare, hunt) in self.data.rares.iter().zip(self.rares.iter_mut()) {
+            if hunt.alive.is_some() {
+                continue;
+            }
+            hunt.timer -= delta_seconds;
+            let Some(zone) = self.data.zones.iter().find(|z| z.name == rare.zone).filter(|_| hunt.timer <= 0.0) else {
+                continue;
+            };
+            let variant = MobVariant::rare(rare.affixes.clone(), rare.trophy.clone());
+            let mob = Mob::new(self.next_id, rare.name.clone(), rare.mob_type, Self::roll_position(zone, roll), rare.level)
+                .with_variant(variant);
+            self.next_id += 1;
+            hunt.alive = Some(mob.id);
+            hunt.timer = rare.respawn_min + roll() * (rare.respawn_max - rare.respawn_min);
+            spawned.push(mob);
+        }
+        spawned
+    }
+
+    /// Note a mob has died, so its zone refills its place after the respawn
+    /// time, or a rare's timer starts. Returns false if the spawner never
+    /// spawned it.
+    pub fn despawn(&mut self, mob_id: u64) -> bool {
+        if let Some(hunt) = self.rares.iter_mut().find(|h| h.alive == Some(mob_id)) {
+            hunt.alive = None;
+            return true;
+        }
+        for (zone, population) in self.data.zones.iter().zip(self.zones.iter_mut()) {
+            if let Some(index) = population.alive.iter().position(|id| *id == mob_id) {
+                population.alive.remove(index);
+                population.respawns.push(zone.respawn_seconds);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Roll a mob from a zone's table, somewhere in the zone.
+    fn roll_mob(data: &SpawnData, zone: &SpawnZone, id: u64, conditions: &SpawnConditions, roll: &mut impl FnMut() -> f32) -> Option<Mob> {
+        let entries = &data.table(zone.biome)?.entries;
+        let total: f32 = entries.iter().map(|e| conditions.weight(e)).sum();
+        let mut pick = roll() * total;
+        let entry = entries
+            .iter()
+            .find(|e| {
+                pick -= conditions.weight(e);
+                pick < 0.0
+            })
+            .or(entries.last())?;
+
+        let position = Self::roll_position(zone, roll);
+        let band = data.level_band(position);
+        let spread = band.max_level - band.min_level + 1;
+        let level = band.min_level + ((roll() * spread as f32) as u32).min(spread - 1) + conditions.level_bonus();
+
+        let name = &entry.mob_type.definition().name;
+        if roll() < data.elite_chance {
+            let affix = Affix::ALL[((roll() * Affix::ALL.len() as f32) as usize).min(Affix::ALL.len() - 1)];
+            let variant = MobVariant::elite(affix);
+            return Some(Mob::new(id, variant.title(name), entry.mob_type, position, level).with_variant(variant));
+        }
+        Some(Mob::new(id, name.clone(), entry.mob_type, position, level))
+    }
+
+    /// Roll a place evenly over a zone's disc.
+    fn roll_position(zone: &SpawnZone, roll: &mut impl FnMut() -> f32) -> Vec3 {
+        let angle = roll() * std::f32::consts::TAU;
+        let reach = zone.radius * roll().sqrt();
+        zone.center + Vec3::new(angle.cos() * reach, 0.0, angle.sin() * reach)
+    }
+}
+
+impl Default for MobSpawner {
+    fn default() -> Self {
+        Self::new(spawn_data().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::MobRank;
+
+    /// A cycle of rolls spread over `0.0..1.0`.
+    fn rolls() -> impl FnMut() -> f32 {
+        let mut n = 0u32;
+        move || {
+            n = n.wrapping_mul(1103515245).wrapping_add(12345);
+            (n >> 8) as f32 / (1u32 << 24) as f32
+        }
+    }
+
+    #[test]
+    fn test_zones_fill_to_cap_and_respawn() {
+        let noon = SpawnConditions::new(12.0, WeatherState::Clear, 0.0);
+        let mut roll = rolls();
+        let mut spawner = MobSpawner::default();
+        let mobs = spawner.update(0.0, &noon, &mut roll);
+        let capacity: u32 = spawn_data().zones().iter().map(|z| z.cap).sum();
+        assert_eq!(mobs.len(), capacity as usize);
+        assert!(spawner.update(1.0, &noon, &mut roll).is_empty());
+
+        // Each mob stands in a zone whose table can roll it, at its band's level
+        for mob in &mobs {
+            let zone = spawn_data().zones().iter().find(|z| z.center.distance(mob.position) <= z.radius + 0.01).unwrap();
+            assert!(spawn_data().table(zone.biome).unwrap().entries.iter().any(|e| e.mob_type == mob.mob_type));
+            let band = spawn_data().level_band(mob.position);
+            assert!((band.min_level..=band.max_level).contains(&mob.level));
+        }
+
+        // A slain mob's place stays empty until the zone's respawn time is up
+        let slain = &mobs[0];
+        let zone = spawn_data().zones().iter().find(|z| z.center.distance(slain.position) <= z.radius + 0.01).unwrap();
+        assert!(spawner.despawn(slain.id));
+        assert!(!spawner.despawn(slain.id));
+        assert!(spawner.update(zone.respawn_seconds - 1.0, &noon, &mut roll).is_empty());
+        let respawned = spawner.update(1.0, &noon, &mut roll);
+        assert_eq!(respawned.len(), 1);
+        assert!(mobs.iter().all(|m| m.id != respawned[0].id));
+    }
+
+    #[test]
+    fn test_night_and_corruption() {
+        let noon = SpawnConditions::new(12.0, WeatherState::Clear, 0.0);
+        let midnight = SpawnConditions { hour: 0.0, ..noon };
+        let corrupt = SpawnConditions { corruption: 60.0, ..noon };
+
+        // Night fills the wilds further
+        let mut roll = rolls();
+        let mut spawner = MobSpawner::default();
+        spawner.update(0.0, &noon, &mut roll);
+        assert!(!spawner.update(0.0, &midnight, &mut roll).is_empty());
+        let night_capacity: usize = spawn_data()
+            .zones()
+            .iter()
+            .map(|z| (z.cap as f32 * NIGHT_POPULATION).round() as usize)
+            .sum();
+        assert_eq!(spawner.population(), night_capacity);
+
+        // Nocturnal hunters and the corrupted are weighted up; corruption adds levels
+        let wolf = SpawnEntry { mob_type: MobType::Wolf, weight: 2.0, nocturnal: true, corrupted: false };
+        let blighted = SpawnEntry { mob_type: MobType::Corrupted, weight: 2.0, nocturnal: false, corrupted: true };
+        assert_eq!(midnight.weight(&wolf), 2.0 * NOCTURNAL_WEIGHT);
+        assert_eq!(SpawnConditions { weather: WeatherState::Mist, ..noon }.weight(&wolf), 2.0 * NOCTURNAL_WEIGHT);
+        assert!(corrupt.weight(&blighted) > noon.weight(&blighted));
+        assert_eq!(corrupt.level_bonus(), 2);
+        let mut roll = rolls();
+        let tainted = MobSpawner::default().update(0.0, &corrupt, &mut roll);
+        assert!(tainted.iter().all(|m| m.level >= spawn_data().level_band(m.position).min_level + 2));
+    }
+
+    #[test]
+    fn test_rares_and_elites() {
+        let noon = SpawnConditions::new(12.0, WeatherState::Clear, 0.0);
+        let mut roll = rolls();
+        let mut spawner = MobSpawner::default();
+        let ashfang = &spawn_data().rares()[0];
+        assert!(spawner.update(ashfang.respawn_min - 1.0, &noon, &mut roll).iter().all(|m| m.name != ashfang.name));
+        let rare = spawner.update(1.0, &noon, &mut roll).into_iter().find(|m| m.name == ashfang.name).unwrap();
+        assert_eq!((rare.variant.rank, rare.level), (MobRank::Rare, ashfang.level));
+        assert!(rare.loot().contains(&ashfang.trophy));
+        assert!(spawner.rare_timers().any(|(r, timer)| r.name == ashfang.name && timer.is_none()));
+
+        // Slain, it stays away for somewhere between its shortest and longest absence
+        assert!(spawner.despawn(rare.id));
+        let (_, timer) = spawner.rare_timers().find(|(r, _)| r.name == ashfang.name).unwrap();
+        assert!((ashfang.respawn_min..=ashfang.respawn_max).contains(&timer.unwrap()));
+        assert!(spawner.update(ashfang.respawn_min - 1.0, &noon, &mut roll).iter().all(|m| m.name != ashfang.name));
+        let returned = spawner.update(ashfang.respawn_max - ashfang.respawn_min + 1.0, &noon, &mut roll);
+        assert!(returned.iter().any(|m| m.name == ashfang.name && m.id != rare.id));
+
+        // Where elites are certain, every mob rolled is one, named for its affix
+        let mut data: serde_json::Value = serde_json::from_str(SPAWN_DATA).unwrap();
+        data["elite_chance"] = 1.0.into();
+        let mut spawner = MobSpawner::new(SpawnData::from_json(&data.to_string()).unwrap());
+        for mob in spawner.update(0.0, &noon, &mut roll) {
+            assert_eq!(mob.variant.rank, MobRank::Elite);
+            assert_eq!(mob.name, format!("{} {}", mob.variant.affixes[0].title(), mob.mob_type.definition().name));
+        }
+    }
+
+    #[test]
+    fn test_invalid_spawn_data() {
+        let mut data: serde_json::Value = serde_json::from_str(SPAWN_DATA).unwrap();
+        data["tables"].as_array_mut().unwrap().retain(|t| t["biome"] != "Crags");
+        assert!(SpawnData::from_json(&data.to_string()).is_err()); // Giants' ground has no table
+
+        let mut data: serde_json::Value = serde_json::from_str(SPAWN_DATA).unwrap();
+        data["levels"].as_array_mut().unwrap().reverse();
+        assert!(SpawnData::from_json(&data.to_string()).is_err());
+
+        let mut data: serde_json::Value = serde_json::from_str(SPAWN_DATA).unwrap();
+        data["rares"][0]["zone"] = "Nod".into();
+        assert!(SpawnData::from_json(&data.to_string()).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Status effects: songs, blessings, bleeds and burns.
+//!
+//! Effects are timed and may stack. Each stack can deal or heal damage every
+//! tick and modify its bearer's stats. Songs are auras that reach every ally
+//! within their radius of the singer.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+
+/// Seconds between damage and healing ticks.
+pub const STATUS_TICK_SECONDS: f32 = 1.0;
+
+/// What can strip an effect away.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DispelCategory {
+    /// Songs and blessings.
+    Magic,
+
+    /// Bleeding wounds.
+    Wound,
+
+    /// Burns.
+    Burning,
+
+    /// Laments and other curses.
+    Curse,
+}
+
+/// A stat an effect changes, per stack.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum StatModifier {
+    /// Fraction added to damage dealt.
+    Damage(f32),
+
+    /// Fraction added to damage taken (negative to reduce it).
+    DamageTaken(f32),
+
+    /// Fraction taken off cooldowns.
+    Cooldown(f32),
+
+    /// Armor added.
+    Armor(f32),
+}
+
+/// The status effects.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EffectKind {
+    /// A psalm that slowly mends the singer's allies.
+    Psalm,
+
+    /// A hymn that shields allies from harm.
+    Hymn,
+
+    /// The Songs of Ascent, lifting allies' strength and speed.
+    SongOfAscent,
+
+    /// A lament that saps the target's strength.
+    Lament,
+
+    /// A bleeding wound.
+    Bleed,
+
+    /// A burn.
+    Burn,
+
+    /// The Shepherd's stance behind the shield.
+    Bulwark,
+}
+
+impl EffectKind {
+    /// Parse an effect from its wire name (the variant name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Psalm" => Some(EffectKind::Psalm),
+            "Hymn" => Some(EffectKind::Hymn),
+            "SongOfAscent" => Some(EffectKind::SongOfAscent),
+            "Lament" => Some(EffectKind::Lament),
+            "Bleed" => Some(EffectKind::Bleed),
+            "Burn" => Some(EffectKind::Burn),
+            "Bulwark" => Some(EffectKind::Bulwark),
+            _ => None,
+        }
+    }
+
+    /// Short label for the HUD.
+    pub fn icon(&self) -> &'static str {
+        match self {
+            EffectKind::Psalm => "PSA",
+            EffectKind::Hymn => "HYM",
+            EffectKind::SongOfAscent => "ASC",
+            EffectKind::Lament => "LAM",
+            EffectKind::Bleed => "BLD",
+            EffectKind::Burn => "BRN",
+            EffectKind::Bulwark => "BUL",
+        }
+    }
+
+    /// Check if the effect harms its bearer. Harmful effects land on the
+    /// target of an action; helpful ones on the caster and their allies.
+    pub fn is_harmful(&self) -> bool {
+        matches!(self, EffectKind::Lament | EffectKind::Bleed | EffectKind::Burn)
+    }
+
+    /// Get the duration in seconds.
+    pub fn duration(&self) -> f32 {
+        match self {
+            EffectKind::Psalm => 12.0,
+            EffectKind::Hymn => 10.0,
+            EffectKind::SongOfAscent => 15.0,
+            EffectKind::Lament => 8.0,
+            EffectKind::Bleed => 8.0,
+            EffectKind::Burn => 6.0,
+            EffectKind::Bulwark => 10.0,
+        }
+    }
+
+    /// Get the most stacks the effect can build.
+    pub fn max_stacks(&self) -> u32 {
+        match self {
+            EffectKind::Bleed => 5,
+            EffectKind::Burn => 3,
+            _ => 1,
+        }
+    }
+
+    /// Get the damage dealt each tick, per stack.
+    pub fn tick_damage(&self) -> f32 {
+        match self {
+            EffectKind::Bleed => 2.0,
+            EffectKind::Burn => 4.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Get the healing done each tick, per stack.
+    pub fn tick_healing(&self) -> f32 {
+        match self {
+            EffectKind::Psalm => 3.0,
+            EffectKind::SongOfAscent => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Get the stat modifiers, per stack.
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            EffectKind::Hymn => vec![StatModifier::DamageTaken(-0.15)],
+            EffectKind::SongOfAscent => vec![StatModifier::Damage(0.2), StatModifier::Cooldown(0.2)],
+            EffectKind::Lament => vec![StatModifier::Damage(-0.2)],
+            EffectKind::Bulwark => vec![StatModifier::Armor(50.0)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the aura radius, for songs that reach every ally nearby.
+    pub fn aura_radius(&self) -> Option<f32> {
+        match self {
+            EffectKind::Psalm | EffectKind::Hymn => Some(50.0),
+            EffectKind::SongOfAscent => Some(60.0),
+            _ => None,
+        }
+    }
+
+    /// Get what can dispel the effect. None if it cannot be dispelled.
+    pub fn dispel(&self) -> Option<DispelCategory> {
+        match self {
+            EffectKind::Psalm | EffectKind::Hymn | EffectKind::SongOfAscent => Some(DispelCategory::Magic),
+            EffectKind::Lament => Some(DispelCategory::Curse),
+            EffectKind::Bleed => Some(DispelCategory::Wound),
+            EffectKind::Burn => Some(DispelCategory::Burning),
+            EffectKind::Bulwark => None,
+        }
+    }
+}
+
+/// An effect on one bearer.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    pub stacks: u32,
+    pub remaining: f32,
+    pub source: u64, // Who applied it
+    tick_timer: f32,
+}
+
+/// Damage and healing from one update of a bearer's effects.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EffectTick {
+    pub damage: f32,
+    pub healing: f32,
+}
+
+/// Every effect on one bearer.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply an effect: a fresh one starts at one stack, a present one gains
+    /// a stack (up to its cap) and its duration is refreshed.
+    pub fn apply(&mut self, kind: EffectKind, source: u64) {
+        match self.effects.iter_mut().find(|e| e.kind == kind) {
+            Some(effect) => {
+                effect.stacks = (effect.stacks + 1).min(kind.max_stacks());
+                effect.remaining = kind.duration();
+                effect.source = source;
+            }
+            None => self.effects.push(StatusEffect {
+                kind,
+                stacks: 1,
+                remaining: kind.duration(),
+                source,
+                tick_timer: STATUS_TICK_SECONDS,
+            }),
+        }
+    }
+
+    /// Rebuild effects from a snapshot sent by the server.
+    pub fn from_snapshot(snapshot: &[(String, u32, f32)]) -> Self {
+        let effects = snapshot
+            .iter()
+            .filter_map(|(name, stacks, remaining)| {
+                EffectKind::from_name(name).map(|kind| StatusEffect {
+                    kind,
+                    stacks: *stacks,
+                    remaining: *remaining,
+                    source: 0,
+                    tick_timer: STATUS_TICK_SECONDS,
+                })
+            })
+            .collect();
+        Self { effects }
+    }
+
+    /// Name, stacks and seconds left of each effect, for sending to clients.
+    pub fn snapshot(&self) -> Vec<(String, u32, f32)> {
+        self.effects.iter().map(|e| (format!("{:?}", e.kind), e.stacks, e.remaining)).collect()
+    }
+
+    /// Get an effect, if present.
+    pub fn get(&self, kind: EffectKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    /// Get the stacks of an effect (0 if absent).
+    pub fn stacks(&self, kind: EffectKind) -> u32 {
+        self.get(kind).map_or(0, |e| e.stacks)
+    }
+
+    /// Remove an effect, returning the stacks it had.
+    pub fn consume(&mut self, kind: EffectKind) -> u32 {
+        let stacks = self.stacks(kind);
+        self.effects.retain(|e| e.kind != kind);
+        stacks
+    }
+
+    /// Remove every effect of the given dispel categories. Returns how many were removed.
+    pub fn dispel(&mut self, categories: &[DispelCategory]) -> usize {
+        let before = self.effects.len();
+        self.effects.retain(|e| e.kind.dispel().is_none_or(|c| !categories.contains(&c)));
+        before - self.effects.len()
+    }
+
+    /// Advance timers, returning the damage and healing from ticks that fell
+    /// due. Expired effects are removed.
+    pub fn update(&mut self, delta_seconds: f32) -> EffectTick {
+        let mut tick = EffectTick::default();
+        for effect in &mut self.effects {
+            let elapsed = delta_seconds.min(effect.remaining);
+            effect.remaining -= elapsed;
+            effect.tick_timer -= elapsed;
+            while effect.tick_timer <= 0.0 {
+                effect.tick_timer += STATUS_TICK_SECONDS;
+                tick.damage += effect.kind.tick_damage() * effect.stacks as f32;
+                tick.healing += effect.kind.tick_healing() * effect.stacks as f32;
+            }
+        }
+        self.effects.retain(|e| e.remaining > 0.0);
+        tick
+    }
+
+    fn total(&self, pick: impl Fn(StatModifier) -> Option<f32>) -> f32 {
+        self.effects
+            .iter()
+            .flat_map(|e| e.kind.modifiers().into_iter().filter_map(&pick).map(move |v| v * e.stacks as f32))
+            .sum()
+    }
+
+    /// Multiplier on damage dealt.
+    pub fn damage_multiplier(&self) -> f32 {
+        (1.0 + self.total(|m| if let StatModifier::Damage(f) = m { Some(f) } else { None })).max(0.0)
+    }
+
+    /// Multiplier on damage taken.
+    pub fn damage_taken_multiplier(&self) -> f32 {
+        (1.0 + self.total(|m| if let StatModifier::DamageTaken(f) = m { Some(f) } else { None })).max(0.0)
+    }
+
+    /// Multiplier on cooldowns.
+    pub fn cooldown_multiplier(&self) -> f32 {
+        (1.0 - self.total(|m| if let StatModifier::Cooldown(f) = m { Some(f) } else { None })).max(0.1)
+    }
+
+    /// Armor added.
+    pub fn armor_bonus(&self) -> f32 {
+        self.total(|m| if let StatModifier::Armor(a) = m { Some(a) } else { None })
+    }
+}
+
+/// The allies within reach of an aura sung at `origin`.
+pub fn aura_recipients(kind: EffectKind, origin: Vec3, allies: &[(u64, Vec3)]) -> Vec<u64> {
+    let Some(radius) = kind.aura_radius() else { return Vec::new(); };
+    allies
+        .iter()
+        .filter(|(_, pos)| pos.distance(origin) <= radius)
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::CombatAction;
+
+    #[test]
+    fn test_stacks_and_ticks() {
+        let mut effects = StatusEffects::new();
+        for _ in 0..7 {
+            effects.apply(EffectKind::Bleed, 1);
+        }
+        assert_eq!(effects.stacks(EffectKind::Bleed), 5);
+
+        // Five stacks at two damage a second
+        let tick = effects.update(1.0);
+        assert_eq!(tick.damage, 10.0);
+        assert_eq!(effects.update(0.5), EffectTick::default());
+
+        // The bleed runs out
+        effects.update(EffectKind::Bleed.duration());
+        assert_eq!(effects.stacks(EffectKind::Bleed), 0);
+    }
+
+    #[test]
+    fn test_modifiers_and_dispel() {
+        let mut effects = StatusEffects::new();
+        effects.apply(EffectKind::SongOfAscent, 1);
+        effects.apply(EffectKind::Bulwark, 2);
+        effects.apply(EffectKind::Hymn, 1);
+        assert!((effects.damage_multiplier() - 1.2).abs() < 1e-5);
+        assert!((effects.cooldown_multiplier() - 0.8).abs() < 1e-5);
+        assert!((effects.damage_taken_multiplier() - 0.85).abs() < 1e-5);
+
+        // Songs can be stripped; the Shepherd's stance cannot
+        assert_eq!(effects.dispel(&[DispelCategory::Magic]), 2);
+        assert_eq!(effects.stacks(EffectKind::Bulwark), 1);
+        assert_eq!(effects.damage_multiplier(), 1.0);
+
+        // A prayer lifts a bleed but not a burn
+        effects.apply(EffectKind::Bleed, 3);
+        effects.apply(EffectKind::Burn, 3);
+        assert_eq!(effects.dispel(CombatAction::LevitePrayer.dispels()), 1);
+        assert_eq!(effects.stacks(EffectKind::Burn), 1);
+    }
+
+    #[test]
+    fn test_aura_reach() {
+        let allies = [(1, Vec3::ZERO), (2, Vec3::new(40.0, 0.0, 0.0)), (3, Vec3::new(200.0, 0.0, 0.0))];
+        assert_eq!(aura_recipients(EffectKind::Psalm, Vec3::ZERO, &allies), vec![1, 2]);
+        assert!(aura_recipients(EffectKind::Bleed, Vec3::ZERO, &allies).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut effects = StatusEffects::new();
+        effects.apply(EffectKind::Burn, 1);
+        effects.apply(EffectKind::Burn, 1);
+        let restored = StatusEffects::from_snapshot(&effects.snapshot());
+        assert_eq!(restored.stacks(EffectKind::Burn), 2);
+        assert_eq!(restored.get(EffectKind::Burn).unwrap().remaining, EffectKind::Burn.duration());
+    }
+}
@@ -0,0 +1,96 @@
+//! Choosing what to attack: tab cycling through targets in view, and picking
+//! a target under a ray cast from the camera.
+
+use glam::Vec3;
+
+/// Farthest a target can be selected from.
+pub const TARGET_RANGE: f32 = 200.0;
+
+/// Widest angle (radians, either side of facing) a tab target can be at.
+pub const TARGET_VIEW_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+
+/// Distance one radian off facing counts as, when ordering tab targets.
+pub const TARGET_ANGLE_WEIGHT: f32 = 60.0;
+
+/// How close a ray must pass to a target to pick it.
+pub const TARGET_PICK_RADIUS: f32 = 8.0;
+
+/// Angle on the ground between `facing` and the direction from `origin` to `pos`.
+fn view_angle(origin: Vec3, facing: Vec3, pos: Vec3) -> f32 {
+    let flat = |v: Vec3| Vec3::new(v.x, 0.0, v.z).normalize_or_zero();
+    let (facing, toward) = (flat(facing), flat(pos - origin));
+    if toward == Vec3::ZERO {
+        return 0.0;
+    }
+    facing.dot(toward).clamp(-1.0, 1.0).acos()
+}
+
+/// Order the candidates in view for tab cycling: nearest and most
+/// straight-ahead first. Candidates out of range or out of view are left out.
+pub fn tab_order(origin: Vec3, facing: Vec3, candidates: &[(u64, Vec3)]) -> Vec<u64> {
+    let mut scored: Vec<(u64, f32)> = candidates
+        .iter()
+        .filter(|(_, pos)| origin.distance(*pos) <= TARGET_RANGE)
+        .map(|(id, pos)| (*id, origin.distance(*pos), view_angle(origin, facing, *pos)))
+        .filter(|(_, _, angle)| *angle <= TARGET_VIEW_ANGLE)
+        .map(|(id, distance, angle)| (id, distance + angle * TARGET_ANGLE_WEIGHT))
+        .collect();
+    scored.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    scored.into_iter().map(|(id, _)| id).collect()
+}
+
+/// Get the target after `current` in tab order, wrapping around. Starts from
+/// the first if there is no current target or it has left the order.
+pub fn next_tab_target(current: Option<u64>, order: &[u64]) -> Option<u64> {
+    let next = current
+        .and_then(|c| order.iter().position(|id| *id == c))
+        .map_or(0, |i| (i + 1) % order.len().max(1));
+    order.get(next).copied()
+}
+
+/// Pick the nearest candidate the ray passes close by, within range.
+pub fn pick_target(ray_origin: Vec3, ray_direction: Vec3, candidates: &[(u64, Vec3)]) -> Option<u64> {
+    let direction = ray_direction.normalize_or_zero();
+    candidates
+        .iter()
+        .filter_map(|(id, pos)| {
+            let along = (*pos - ray_origin).dot(direction);
+            let closest = ray_origin + direction * along;
+            (along > 0.0 && along <= TARGET_RANGE && closest.distance(*pos) <= TARGET_PICK_RADIUS).then_some((*id, along))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_cycling() {
+        let facing = Vec3::NEG_Z;
+        let candidates = [
+            (1, Vec3::new(0.0, 0.0, -80.0)),  // Ahead, far
+            (2, Vec3::new(0.0, 0.0, -30.0)),  // Ahead, near
+            (3, Vec3::new(30.0, 0.0, -30.0)), // Off to the side
+            (4, Vec3::new(0.0, 0.0, 30.0)),   // Behind
+            (5, Vec3::new(0.0, 0.0, -500.0)), // Out of range
+        ];
+        let order = tab_order(Vec3::ZERO, facing, &candidates);
+        assert_eq!(order, vec![2, 1, 3]);
+
+        assert_eq!(next_tab_target(None, &order), Some(2));
+        assert_eq!(next_tab_target(Some(2), &order), Some(1));
+        assert_eq!(next_tab_target(Some(3), &order), Some(2));
+        assert_eq!(next_tab_target(Some(4), &order), Some(2));
+        assert_eq!(next_tab_target(None, &[]), None);
+    }
+
+    #[test]
+    fn test_pick_target() {
+        let candidates = [(1, Vec3::new(0.0, 0.0, -50.0)), (2, Vec3::new(0.0, 0.0, -100.0)), (3, Vec3::new(40.0, 0.0, -50.0))];
+        assert_eq!(pick_target(Vec3::ZERO, Vec3::NEG_Z, &candidates), Some(1));
+        assert_eq!(pick_target(Vec3::ZERO, Vec3::new(0.8, 0.0, -1.0), &candidates), Some(3));
+        assert_eq!(pick_target(Vec3::ZERO, Vec3::Z, &candidates), None);
+    }
+}
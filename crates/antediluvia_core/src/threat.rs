@@ -0,0 +1,182 @@
+//! Threat (aggro) tables: whom a mob is angriest with.
+//!
+//! Damage, healing and taunts build threat against a mob. It attacks whoever
+//! holds the most, but a new target must overtake the current one by a margin
+//! before the mob turns. Threat fades over time and is wiped when the mob is
+//! led past its leash.
+
+use serde::{Deserialize, Serialize};
+use glam::Vec3;
+use crate::combat::{threat_for, CombatAction, MELEE_RANGE};
+
+/// Threat a mob holds against a player who wanders into its aggro range.
+pub const AGGRO_THREAT: f32 = 1.0;
+
+/// How far a challenger in melee range must overtake the current target.
+pub const MELEE_OVERTAKE: f32 = 1.1;
+
+/// How far a challenger at range must overtake the current target.
+pub const RANGED_OVERTAKE: f32 = 1.3;
+
+/// Fraction of threat that fades each second.
+pub const THREAT_DECAY: f32 = 0.02;
+
+/// Threat below which a player is forgotten.
+pub const MIN_THREAT: f32 = 0.1;
+
+/// One mob's threat against each player.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ThreatTable {
+    entries: Vec<(u64, f32)>, // Player, threat
+    pub target: Option<u64>,
+}
+
+impl ThreatTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the threat against a player (0 if none).
+    pub fn threat(&self, player: u64) -> f32 {
+        self.entries.iter().find(|(id, _)| *id == player).map_or(0.0, |(_, t)| *t)
+    }
+
+    /// Check if a player is on the table.
+    pub fn contains(&self, player: u64) -> bool {
+        self.entries.iter().any(|(id, _)| *id == player)
+    }
+
+    /// Check if the mob holds threat against anyone.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add threat against a player.
+    pub fn add(&mut self, player: u64, amount: f32) {
+        match self.entries.iter_mut().find(|(id, _)| *id == player) {
+            Some((_, threat)) => *threat += amount,
+            None => self.entries.push((player, amount)),
+        }
+    }
+
+    /// Taunt the mob: the player's threat rises to the top of the table and
+    /// the mob turns on them at once.
+    pub fn taunt(&mut self, player: u64) {
+        let top = self.entries.iter().map(|(_, t)| *t).fold(0.0, f32::max);
+        let own = self.threat(player);
+        self.add(player, top - own);
+        self.target = Some(player);
+    }
+
+    /// Record an action against the mob. Rebuke taunts.
+    pub fn record(&mut self, player: u64, action: CombatAction, damage: f32, healing: f32) {
+        if action == CombatAction::ShepherdRebuke {
+            self.taunt(player);
+        }
+        self.add(player, threat_for(action, damage, healing));
+    }
+
+    /// Forget a player, e.g. when they leave.
+    pub fn remove(&mut self, player: u64) {
+        self.entries.retain(|(id, _)| *id != player);
+        if self.target == Some(player) {
+            self.target = None;
+        }
+    }
+
+    /// Forget everyone, e.g. when the mob is leashed.
+    pub fn wipe(&mut self) {
+        self.entries.clear();
+        self.target = None;
+    }
+
+    /// Let threat fade. Players whose threat falls too low are forgotten.
+    pub fn update(&mut self, delta_seconds: f32) {
+        let keep = (1.0 - THREAT_DECAY * delta_seconds).max(0.0);
+        for (_, threat) in &mut self.entries {
+            *threat *= keep;
+        }
+        self.entries.retain(|(_, t)| *t >= MIN_THREAT);
+        if self.target.is_some_and(|t| !self.contains(t)) {
+            self.target = None;
+        }
+    }
+
+    /// Choose whom to attack among the living `players`, returning the target.
+    ///
+    /// Players missing from `players` are forgotten. The highest threat takes
+    /// the mob from the current target only once it exceeds the target's
+    /// threat by the overtake margin, which is smaller in melee range.
+    pub fn select_target(&mut self, mob_pos: Vec3, players: &[(u64, Vec3)]) -> Option<u64> {
+        self.entries.retain(|(id, _)| players.iter().any(|(p, _)| p == id));
+        let Some((top, top_threat)) = self.entries.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1)) else {
+            self.target = None;
+            return None;
+        };
+
+        match self.target.filter(|t| self.contains(*t)) {
+            Some(current) if current != top => {
+                let top_pos = players.iter().find(|(p, _)| *p == top).map_or(mob_pos, |(_, pos)| *pos);
+                let margin = if mob_pos.distance(top_pos) <= MELEE_RANGE { MELEE_OVERTAKE } else { RANGED_OVERTAKE };
+                if top_threat > self.threat(current) * margin {
+                    self.target = Some(top);
+                }
+            }
+            Some(_) => {}
+            None => self.target = Some(top),
+        }
+        self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overtaking() {
+        let mut table = ThreatTable::new();
+        let near = Vec3::new(10.0, 0.0, 0.0);
+        let far = Vec3::new(100.0, 0.0, 0.0);
+        let players = [(1, near), (2, near), (3, far)];
+
+        table.add(1, 100.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(1));
+
+        // A little more threat is not enough to pull the mob away
+        table.add(2, 105.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(1));
+        table.add(2, 10.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(2));
+
+        // From range it takes more
+        table.add(3, 140.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(2));
+        table.add(3, 10.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(3));
+
+        // A player who leaves is forgotten
+        assert_eq!(table.select_target(Vec3::ZERO, &players[..2]), Some(2));
+        assert!(!table.contains(3));
+    }
+
+    #[test]
+    fn test_taunt_decay_and_wipe() {
+        let mut table = ThreatTable::new();
+        let players = [(1, Vec3::ZERO), (2, Vec3::ZERO)];
+        table.record(1, CombatAction::HunterGiantsBane, 120.0, 0.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(1));
+
+        // Rebuke snatches the mob from the Hunter
+        table.record(2, CombatAction::ShepherdRebuke, 20.0, 0.0);
+        assert_eq!(table.select_target(Vec3::ZERO, &players), Some(2));
+        assert!(table.threat(2) > table.threat(1));
+
+        table.update(10.0);
+        assert!((table.threat(1) - 120.0 * 0.8).abs() < 1e-3);
+
+        table.wipe();
+        assert!(table.is_empty());
+        assert_eq!(table.select_target(Vec3::ZERO, &players), None);
+    }
+}
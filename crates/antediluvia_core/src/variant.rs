@@ -0,0 +1,220 @@
+//! Elite and rare mobs.
+//!
+//! Now and then the spawner rolls an elite: a tougher mob with an affix that
+//! names it ("Swift Wolf"). Rares are named mobs defined in `data/spawns.json`
+//! that return to their haunts on long, uncertain timers. Both hit harder,
+//! give more experience and drop loot that common mobs never carry.
+
+use serde::{Deserialize, Serialize};
+use crate::mob::{LootDrop, MobType};
+
+/// How much faster a Fast mob moves.
+pub const FAST_SPEED: f32 = 1.4;
+
+/// How much more armor an Armored mob wears.
+pub const ARMORED_ARMOR: f32 = 2.0;
+
+/// How much more health and damage a Corrupted mob has.
+pub const CORRUPTED_MIGHT: f32 = 1.25;
+
+/// Share of its health a Regenerating mob heals each second.
+pub const REGENERATION_RATE: f32 = 0.02;
+
+/// How a mob stands among others of its type.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MobRank {
+    #[default]
+    Normal,
+    Elite,
+    Rare, // Named, on a timer
+}
+
+impl MobRank {
+    pub const ALL: [MobRank; 3] = [MobRank::Normal, MobRank::Elite, MobRank::Rare];
+
+    /// Find a rank by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| format!("{:?}", r) == name)
+    }
+
+    pub fn health_multiplier(&self) -> f32 {
+        match self {
+            MobRank::Normal => 1.0,
+            MobRank::Elite => 2.0,
+            MobRank::Rare => 3.5,
+        }
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            MobRank::Normal => 1.0,
+            MobRank::Elite => 1.3,
+            MobRank::Rare => 1.6,
+        }
+    }
+
+    pub fn xp_multiplier(&self) -> f32 {
+        match self {
+            MobRank::Normal => 1.0,
+            MobRank::Elite => 2.5,
+            MobRank::Rare => 6.0,
+        }
+    }
+
+    /// How much bigger the mob stands than others of its type.
+    pub fn scale(&self) -> f32 {
+        match self {
+            MobRank::Normal => 1.0,
+            MobRank::Elite => 1.2,
+            MobRank::Rare => 1.35,
+        }
+    }
+}
+
+/// A trait that sets an elite or rare apart.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Affix {
+    Fast,
+    Armored,
+    Corrupted,
+    Regenerating,
+}
+
+impl Affix {
+    pub const ALL: [Affix; 4] = [Affix::Fast, Affix::Armored, Affix::Corrupted, Affix::Regenerating];
+
+    /// Find an affix by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| format!("{:?}", a) == name)
+    }
+
+    /// The word an elite takes into its name.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Affix::Fast => "Swift",
+            Affix::Armored => "Ironhide",
+            Affix::Corrupted => "Blighted",
+            Affix::Regenerating => "Undying",
+        }
+    }
+}
+
+/// What sets one mob apart from the rest of its type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct MobVariant {
+    pub rank: MobRank,
+    pub affixes: Vec<Affix>,
+    #[serde(default)]
+    pub trophy: Option<LootDrop>, // A rare's own drop
+}
+
+impl MobVariant {
+    /// An elite with one affix.
+    pub fn elite(affix: Affix) -> Self {
+        Self {
+            rank: MobRank::Elite,
+            affixes: vec![affix],
+            trophy: None,
+        }
+    }
+
+    /// A rare with its affixes and trophy.
+    pub fn rare(affixes: Vec<Affix>, trophy: LootDrop) -> Self {
+        Self {
+            rank: MobRank::Rare,
+            affixes,
+            trophy: Some(trophy),
+        }
+    }
+
+    /// Check if the mob has an affix.
+    pub fn has(&self, affix: Affix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// Name an elite after its affixes ("Swift Wolf"); others keep `name`.
+    pub fn title(&self, name: &str) -> String {
+        if self.rank != MobRank::Elite {
+            return name.to_string();
+        }
+        let mut words: Vec<&str> = self.affixes.iter().map(|a| a.title()).collect();
+        words.push(name);
+        words.join(" ")
+    }
+
+    pub fn health_multiplier(&self) -> f32 {
+        self.rank.health_multiplier() * if self.has(Affix::Corrupted) { CORRUPTED_MIGHT } else { 1.0 }
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        self.rank.damage_multiplier() * if self.has(Affix::Corrupted) { CORRUPTED_MIGHT } else { 1.0 }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(Affix::Fast) { FAST_SPEED } else { 1.0 }
+    }
+
+    pub fn armor_multiplier(&self) -> f32 {
+        if self.has(Affix::Armored) { ARMORED_ARMOR } else { 1.0 }
+    }
+
+    /// Get the health healed each second by a mob of `max_health`.
+    pub fn regeneration(&self, max_health: f32) -> f32 {
+        if self.has(Affix::Regenerating) { max_health * REGENERATION_RATE } else { 0.0 }
+    }
+
+    /// Get the loot a mob of `mob_type` at `level` drops: what its rank
+    /// allows from its type's table, and a rare's trophy.
+    pub fn loot(&self, mob_type: MobType, level: u32) -> Vec<LootDrop> {
+        let mut loot = mob_type.loot_for(level, self.rank);
+        loot.extend(self.trophy.clone());
+        loot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::Defense;
+    use crate::mob::Mob;
+    use glam::Vec3;
+
+    #[test]
+    fn test_elites_outclass_their_kind() {
+        let wolf = Mob::new(1, "Wolf".to_string(), MobType::Wolf, Vec3::ZERO, 2);
+        let variant = MobVariant::elite(Affix::Armored);
+        let elite = Mob::new(2, variant.title("Wolf"), MobType::Wolf, Vec3::ZERO, 2).with_variant(variant);
+        assert_eq!(elite.name, "Ironhide Wolf");
+        assert_eq!(elite.health, wolf.health * 2.0);
+        assert!(elite.get_damage() > wolf.get_damage() && elite.xp_reward() > wolf.xp_reward());
+        assert_eq!(elite.defense().armor, Defense::for_mob(MobType::Wolf, 2).armor * ARMORED_ARMOR);
+        assert_eq!(elite.move_speed(), wolf.move_speed());
+
+        // Elites carry loot common wolves never do
+        assert!(wolf.loot().iter().all(|d| d.name != "Alpha Pelt"));
+        assert!(elite.loot().iter().any(|d| d.name == "Alpha Pelt"));
+    }
+
+    #[test]
+    fn test_rare_affixes() {
+        let trophy = LootDrop { name: "Ashfang's Collar".to_string(), quantity: 1, weight: 0.5 };
+        let variant = MobVariant::rare(vec![Affix::Fast, Affix::Regenerating, Affix::Corrupted], trophy.clone());
+        assert_eq!(variant.title("Ashfang"), "Ashfang");
+        let mut rare = Mob::new(1, "Ashfang".to_string(), MobType::Wolf, Vec3::ZERO, 4).with_variant(variant);
+        assert_eq!(rare.move_speed(), MobType::Wolf.move_speed() * FAST_SPEED);
+        assert!(rare.loot().contains(&trophy));
+
+        // It knits its wounds, but not past full health nor back from death
+        rare.take_damage(100.0);
+        let wounded = rare.health;
+        rare.regenerate(1.0);
+        assert!((rare.health - wounded - rare.max_health * REGENERATION_RATE).abs() < 0.01);
+        rare.regenerate(1000.0);
+        assert_eq!(rare.health, rare.max_health);
+        rare.take_damage(rare.max_health);
+        rare.regenerate(1.0);
+        assert!(!rare.is_alive());
+        assert_eq!(MobRank::from_name("Rare"), Some(MobRank::Rare));
+        assert_eq!(Affix::from_name("Fast"), Some(Affix::Fast));
+    }
+}
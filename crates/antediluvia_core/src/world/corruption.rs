@@ -27,6 +27,20 @@ pub enum CorruptionEvent {
 }
 
 impl CorruptionEvent {
+    pub const ALL: [CorruptionEvent; 6] = [
+        CorruptionEvent::PlayerKill,
+        CorruptionEvent::ForbiddenTech,
+        CorruptionEvent::IdolWorship,
+        CorruptionEvent::ArkConstruction,
+        CorruptionEvent::Sacrifice,
+        CorruptionEvent::IdolDestruction,
+    ];
+
+    /// Find an event by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| format!("{:?}", e) == name)
+    }
+
     /// Get the corruption delta for this event.
     /// Positive = increases corruption. Negative = decreases corruption.
     pub fn delta(&self) -> f32 {
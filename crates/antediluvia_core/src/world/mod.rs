@@ -11,6 +11,22 @@ pub use corruption::*;
 
 use serde::{Deserialize, Serialize};
 
+/// In-game hours that pass each real second.
+pub const HOURS_PER_SECOND: f32 = 0.1;
+
+/// The hour the world begins at.
+pub const FIRST_HOUR: f32 = 8.0;
+
+/// Get the hour of the day (0.0 to 24.0) `seconds` after the world began.
+pub fn hour_of_day(seconds: f32) -> f32 {
+    (FIRST_HOUR + seconds * HOURS_PER_SECOND).rem_euclid(24.0)
+}
+
+/// Check if an hour falls between dusk and dawn.
+pub fn is_night(hour: f32) -> bool {
+    !(6.0..18.0).contains(&hour)
+}
+
 /// The global world state.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WorldState {
@@ -39,6 +55,19 @@ pub enum WeatherState {
     TheDeluge, // The Flood begins
 }
 
+impl WeatherState {
+    /// Get the weather a level of corruption brings.
+    pub fn from_corruption(corruption: f32) -> Self {
+        match corruption {
+            c if c < 50.0 => WeatherState::Clear,
+            c if c < 80.0 => WeatherState::Mist,
+            c if c < 90.0 => WeatherState::HeavyRain,
+            c if c < 99.0 => WeatherState::Thunderstorm,
+            _ => WeatherState::TheDeluge,
+        }
+    }
+}
+
 impl WorldState {
     /// Create a new world state at the beginning of time.
     pub fn new() -> Self {
@@ -53,13 +82,7 @@ impl WorldState {
     /// Update weather based on corruption level.
     /// As corruption rises, the world darkens.
     pub fn update_weather(&mut self) {
-        self.weather = match self.corruption_level {
-            c if c < 50.0 => WeatherState::Clear,
-            c if c < 80.0 => WeatherState::Mist,
-            c if c < 90.0 => WeatherState::HeavyRain,
-            c if c < 99.0 => WeatherState::Thunderstorm,
-            _ => WeatherState::TheDeluge,
-        };
+        self.weather = WeatherState::from_corruption(self.corruption_level);
     }
 
     /// Check if the world has ended (Flood has begun).
@@ -6,6 +6,26 @@
 
 use noise::{NoiseFn, Simplex};
 
+/// Radius of the Garden Plateau around Eden (meters).
+pub const EDEN_RADIUS: f64 = 500.0;
+
+/// Outer radius of Havilah, where the Great Moat begins (meters).
+pub const HAVILAH_RADIUS: f64 = 2000.0;
+
+/// Outer radius of the Great Moat ocean ring (meters).
+pub const GREAT_MOAT_RADIUS: f64 = 5000.0;
+
+/// Offset applied to game coordinates to place the starting area within Havilah.
+/// Havilah spans distances 500-2000 from world origin.
+pub const LOCAL_OFFSET_X: f64 = 1000.0;
+pub const LOCAL_OFFSET_Z: f64 = 0.0;
+
+/// Check if an (x, z) coordinate lies in the Great Moat.
+pub fn in_great_moat(x: f64, z: f64) -> bool {
+    let dist = (x * x + z * z).sqrt();
+    (HAVILAH_RADIUS..GREAT_MOAT_RADIUS).contains(&dist)
+}
+
 /// The Pangea terrain generator.
 pub struct PangeaGenerator {
     noise: Simplex,
@@ -45,13 +65,13 @@ impl PangeaGenerator {
         let base_noise = self.noise.get([x * self.scale, z * self.scale]);
 
         // The "C-Shape" Mask: Radial gradient forcing continent shape
-        let height = if dist < 500.0 {
+        let height = if dist < EDEN_RADIUS {
             // The Garden Plateau (Inaccessible)
             2000.0
-        } else if dist < 2000.0 {
+        } else if dist < HAVILAH_RADIUS {
             // Havilah (Starting zone) - Lush, safe
             20.0 + (base_noise as f32 * 10.0)
-        } else if dist < 5000.0 {
+        } else if dist < GREAT_MOAT_RADIUS {
             // The Great Moat (Ocean barrier)
             -50.0
         } else if dist < 30000.0 {
@@ -72,6 +92,11 @@ impl PangeaGenerator {
         height
     }
 
+    /// Get the height at a game coordinate (offset into Havilah).
+    pub fn local_height(&self, local_x: f32, local_z: f32) -> f32 {
+        self.get_height(local_x as f64 + LOCAL_OFFSET_X, local_z as f64 + LOCAL_OFFSET_Z)
+    }
+
     /// Generate a heightmap for a region.
     /// 
     /// Returns a Vec of heights for a grid of (width x height) points.
@@ -123,5 +148,7 @@ mod tests {
         let gen = PangeaGenerator::new();
         let height = gen.get_height(3000.0, 0.0);
         assert!(height < 0.0, "The moat should be ocean");
+        assert!(in_great_moat(3000.0, 0.0));
+        assert!(!in_great_moat(1000.0, 0.0));
     }
 }
@@ -1,15 +1,20 @@
 //! Simple in-memory auth/token service (placeholder).
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use rand::Rng;
 use axum::{Router, routing::post, Json, extract::State};
 use serde::{Serialize, Deserialize};
 use anyhow::Result;
+use tracing::info;
+use antediluvia_core::{
+    CharacterRegistry, CreateCharacterRequest, DeleteCharacterRequest, RosterRequest, RosterResponse,
+};
+use crate::db::{CharacterRecord, DbPool};
 
 #[derive(Clone, Default)]
 pub struct AuthService {
-    tokens: Arc<Mutex<HashSet<String>>>,
+    tokens: Arc<Mutex<HashMap<String, u64>>>, // Token -> account ID
 }
 
 impl AuthService {
@@ -17,13 +22,13 @@ impl AuthService {
         Self::default()
     }
 
-    /// Issue a new token for a player_id (placeholder, no password validation).
-    pub fn issue_token(&self, _player_id: u64) -> String {
+    /// Issue a new token for an account (placeholder, no password validation).
+    pub fn issue_token(&self, account_id: u64) -> String {
         let mut bytes = [0u8; 16];
         rand::rng().fill(&mut bytes);
         let token = hex::encode(bytes);
-        if let Ok(mut set) = self.tokens.lock() {
-            set.insert(token.clone());
+        if let Ok(mut map) = self.tokens.lock() {
+            map.insert(token.clone(), account_id);
         }
         token
     }
@@ -31,13 +36,26 @@ impl AuthService {
     /// Validate a token.
     #[allow(dead_code)]
     pub fn validate(&self, token: &str) -> bool {
+        self.account_for(token).is_some()
+    }
+
+    /// Get the account a token was issued to.
+    pub fn account_for(&self, token: &str) -> Option<u64> {
         self.tokens
             .lock()
-            .map(|set| set.contains(token))
-            .unwrap_or(false)
+            .ok()
+            .and_then(|map| map.get(token).copied())
     }
 }
 
+/// Shared state for the auth and character endpoints.
+#[derive(Clone)]
+pub struct AuthState {
+    pub auth: AuthService,
+    pub characters: Arc<Mutex<CharacterRegistry>>,
+    pub db: Option<Arc<DbPool>>,
+}
+
 #[derive(Deserialize)]
 struct LoginRequest {
     username: String,
@@ -48,29 +66,117 @@ struct LoginRequest {
 #[derive(Serialize)]
 struct LoginResponse {
     token: String,
-    player_id: u64,
+    account_id: u64,
 }
 
 /// Start a minimal auth HTTP server on the given port.
-pub async fn start_auth_server(auth: AuthService, port: u16) -> Result<()> {
+pub async fn start_auth_server(state: AuthState, port: u16) -> Result<()> {
     async fn login_handler(
-        State(auth): State<AuthService>,
+        State(state): State<AuthState>,
         Json(payload): Json<LoginRequest>,
     ) -> Json<LoginResponse> {
-        let player_id = hash_username(&payload.username);
-        let token = auth.issue_token(player_id);
-        Json(LoginResponse { token, player_id })
+        let account_id = hash_username(&payload.username);
+        let token = state.auth.issue_token(account_id);
+        Json(LoginResponse { token, account_id })
+    }
+
+    async fn roster_handler(
+        State(state): State<AuthState>,
+        Json(payload): Json<RosterRequest>,
+    ) -> Json<RosterResponse> {
+        let Some(account_id) = state.auth.account_for(&payload.token) else {
+            return Json(rejected("invalid token"));
+        };
+        Json(roster(&state, account_id, None))
+    }
+
+    async fn create_handler(
+        State(state): State<AuthState>,
+        Json(payload): Json<CreateCharacterRequest>,
+    ) -> Json<RosterResponse> {
+        let Some(account_id) = state.auth.account_for(&payload.token) else {
+            return Json(rejected("invalid token"));
+        };
+        let created = state
+            .characters
+            .lock()
+            .map_err(|_| "character registry unavailable".to_string())
+            .and_then(|mut registry| {
+                registry
+                    .create(account_id, &payload.name, payload.lineage, payload.appearance)
+                    .map_err(|e| e.to_string())
+            });
+
+        match created {
+            Ok(character) => {
+                info!("Account {} created character {} ({})", account_id, character.name, character.id);
+                if let Some(db) = state.db.as_ref() {
+                    if let Err(e) = db.save_character(&CharacterRecord::from(&character)).await {
+                        info!("Failed to save character {}: {}", character.id, e);
+                    }
+                }
+                Json(roster(&state, account_id, None))
+            }
+            Err(e) => Json(roster(&state, account_id, Some(e))),
+        }
+    }
+
+    async fn delete_handler(
+        State(state): State<AuthState>,
+        Json(payload): Json<DeleteCharacterRequest>,
+    ) -> Json<RosterResponse> {
+        let Some(account_id) = state.auth.account_for(&payload.token) else {
+            return Json(rejected("invalid token"));
+        };
+        let deleted = state
+            .characters
+            .lock()
+            .map_err(|_| "character registry unavailable".to_string())
+            .and_then(|mut registry| registry.delete(account_id, payload.character_id).map_err(|e| e.to_string()));
+
+        match deleted {
+            Ok(character) => {
+                info!("Account {} deleted character {} ({})", account_id, character.name, character.id);
+                if let Some(db) = state.db.as_ref() {
+                    if let Err(e) = db.delete_character(character.id).await {
+                        info!("Failed to delete character {}: {}", character.id, e);
+                    }
+                }
+                Json(roster(&state, account_id, None))
+            }
+            Err(e) => Json(roster(&state, account_id, Some(e))),
+        }
     }
 
     let app = Router::new()
         .route("/auth/login", post(login_handler))
-        .with_state(auth);
+        .route("/characters", post(roster_handler))
+        .route("/characters/create", post(create_handler))
+        .route("/characters/delete", post(delete_handler))
+        .with_state(state);
 
     let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
     axum::serve(listener, app).await?;
     Ok(())
 }
 
+/// The account's roster, with an optional error.
+fn roster(state: &AuthState, account_id: u64, error: Option<String>) -> RosterResponse {
+    let characters = state
+        .characters
+        .lock()
+        .map(|registry| registry.roster(account_id))
+        .unwrap_or_default();
+    RosterResponse { characters, error }
+}
+
+fn rejected(error: &str) -> RosterResponse {
+    RosterResponse {
+        characters: Vec::new(),
+        error: Some(error.to_string()),
+    }
+}
+
 fn hash_username(name: &str) -> u64 {
     use std::hash::{Hash, Hasher};
     let mut h = std::collections::hash_map::DefaultHasher::new();
@@ -0,0 +1,65 @@
+//! The combat log on disk: one JSON line per combat event, rotated by size.
+//! Replaying a log through `antediluvia_core::replay` gives damage meters.
+
+use antediluvia_core::CombatEvent;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Where the combat log is written, unless `COMBAT_LOG` says otherwise.
+pub const COMBAT_LOG_PATH: &str = "logs/combat.log";
+
+/// Size a log grows to before it is rotated.
+pub const COMBAT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated logs kept (combat.log.1 is the newest).
+pub const COMBAT_LOG_KEEP: usize = 5;
+
+/// An append-only log file that rotates once it grows too large.
+pub struct RotatingLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64, // Bytes in the current file
+}
+
+impl RotatingLog {
+    /// Open (or create) the log at `path`, appending to what is there.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path, max_bytes, keep, file, written })
+    }
+
+    /// Append an event as a line of JSON, rotating first if it would not fit.
+    pub fn write(&mut self, event: &CombatEvent) -> io::Result<()> {
+        let line = serde_json::to_string(event)? + "\n";
+        if self.written > 0 && self.written + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift every kept log up one (dropping the oldest) and start afresh.
+    fn rotate(&mut self) -> io::Result<()> {
+        let numbered = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        for n in (1..self.keep).rev() {
+            if numbered(n).exists() {
+                fs::rename(numbered(n), numbered(n + 1))?;
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, numbered(1))?;
+        }
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
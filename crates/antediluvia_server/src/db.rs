@@ -5,6 +5,7 @@ use sqlx::{Pool, Postgres};
 use std::env;
 use tracing::info;
 use serde::{Serialize, Deserialize};
+use antediluvia_core::{Character, Lineage};
 
 /// Database pool wrapper.
 pub struct DbPool {
@@ -36,8 +37,25 @@ impl DbPool {
                 position_x REAL NOT NULL,
                 position_y REAL NOT NULL,
                 position_z REAL NOT NULL,
-                inventory_json TEXT NOT NULL
+                inventory_json TEXT NOT NULL,
+                xp_debt REAL NOT NULL DEFAULT 0,
+                reputation_json TEXT NOT NULL DEFAULT '{}',
+                mastery_json TEXT NOT NULL DEFAULT '{}'
             );
+            ALTER TABLE players ADD COLUMN IF NOT EXISTS xp_debt REAL NOT NULL DEFAULT 0;
+            ALTER TABLE players ADD COLUMN IF NOT EXISTS reputation_json TEXT NOT NULL DEFAULT '{}';
+            ALTER TABLE players ADD COLUMN IF NOT EXISTS mastery_json TEXT NOT NULL DEFAULT '{}';
+            CREATE TABLE IF NOT EXISTS drowned (
+                id BIGINT PRIMARY KEY
+            );
+            CREATE TABLE IF NOT EXISTS characters (
+                id BIGINT PRIMARY KEY,
+                account_id BIGINT NOT NULL,
+                name TEXT NOT NULL,
+                lineage TEXT NOT NULL,
+                appearance_json TEXT NOT NULL
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS characters_name_idx ON characters (LOWER(name));
             CREATE TABLE IF NOT EXISTS world (
                 id BIGINT PRIMARY KEY,
                 corruption REAL NOT NULL,
@@ -55,7 +73,7 @@ impl DbPool {
     pub async fn load_player(&self, player_id: u64) -> Result<Option<PlayerRecord>> {
         let rec = sqlx::query_as::<_, PlayerRecord>(
             r#"
-            SELECT id, name, lineage, corruption, position_x, position_y, position_z, inventory_json
+            SELECT id, name, lineage, corruption, position_x, position_y, position_z, inventory_json, xp_debt, reputation_json, mastery_json
             FROM players WHERE id = $1
             "#,
         )
@@ -69,8 +87,8 @@ impl DbPool {
     pub async fn save_player(&self, player: &PlayerRecord) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO players (id, name, lineage, corruption, position_x, position_y, position_z, inventory_json)
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+            INSERT INTO players (id, name, lineage, corruption, position_x, position_y, position_z, inventory_json, xp_debt, reputation_json, mastery_json)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
             ON CONFLICT (id) DO UPDATE
             SET name = EXCLUDED.name,
                 lineage = EXCLUDED.lineage,
@@ -78,7 +96,10 @@ impl DbPool {
                 position_x = EXCLUDED.position_x,
                 position_y = EXCLUDED.position_y,
                 position_z = EXCLUDED.position_z,
-                inventory_json = EXCLUDED.inventory_json;
+                inventory_json = EXCLUDED.inventory_json,
+                xp_debt = EXCLUDED.xp_debt,
+                reputation_json = EXCLUDED.reputation_json,
+                mastery_json = EXCLUDED.mastery_json;
             "#,
         )
         .bind(player.id)
@@ -89,11 +110,83 @@ impl DbPool {
         .bind(player.position_y)
         .bind(player.position_z)
         .bind(&player.inventory_json)
+        .bind(player.xp_debt)
+        .bind(&player.reputation_json)
+        .bind(&player.mastery_json)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
+    /// Load every character (the roster registry is kept in memory).
+    pub async fn load_characters(&self) -> Result<Vec<CharacterRecord>> {
+        let recs = sqlx::query_as::<_, CharacterRecord>(
+            r#"
+            SELECT id, account_id, name, lineage, appearance_json
+            FROM characters
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(recs)
+    }
+
+    pub async fn save_character(&self, character: &CharacterRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO characters (id, account_id, name, lineage, appearance_json)
+            VALUES ($1,$2,$3,$4,$5)
+            ON CONFLICT (id) DO UPDATE
+            SET name = EXCLUDED.name,
+                lineage = EXCLUDED.lineage,
+                appearance_json = EXCLUDED.appearance_json;
+            "#,
+        )
+        .bind(character.id)
+        .bind(character.account_id)
+        .bind(&character.name)
+        .bind(&character.lineage)
+        .bind(&character.appearance_json)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete a character, its saved player state, and any record of its
+    /// drowning, so no later character given its id inherits them.
+    pub async fn delete_character(&self, character_id: u64) -> Result<()> {
+        sqlx::query("DELETE FROM characters WHERE id = $1")
+            .bind(character_id as i64)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM players WHERE id = $1")
+            .bind(character_id as i64)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM drowned WHERE id = $1")
+            .bind(character_id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Load everyone who has perished in the Flood this season.
+    pub async fn load_drowned(&self) -> Result<Vec<u64>> {
+        let ids: Vec<(i64,)> = sqlx::query_as("SELECT id FROM drowned")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(ids.into_iter().map(|(id,)| id as u64).collect())
+    }
+
+    /// Record a player as perished in the Flood, for the rest of the season.
+    pub async fn save_drowned(&self, player_id: u64) -> Result<()> {
+        sqlx::query("INSERT INTO drowned (id) VALUES ($1) ON CONFLICT (id) DO NOTHING")
+            .bind(player_id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn load_world(&self) -> Result<Option<WorldRecord>> {
         let rec = sqlx::query_as::<_, WorldRecord>(
             r#"
@@ -138,6 +231,44 @@ pub struct PlayerRecord {
     pub position_y: f32,
     pub position_z: f32,
     pub inventory_json: String,
+    pub xp_debt: f32,
+    pub reputation_json: String, // Standing with each faction; '{}' until first saved
+    pub mastery_json: String, // Mastery of each job; a job with any is revealed
+}
+
+/// Character record (persistence model).
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CharacterRecord {
+    pub id: i64,
+    pub account_id: i64,
+    pub name: String,
+    pub lineage: String,
+    pub appearance_json: String,
+}
+
+impl From<&Character> for CharacterRecord {
+    fn from(character: &Character) -> Self {
+        Self {
+            id: character.id as i64,
+            account_id: character.account_id as i64,
+            name: character.name.clone(),
+            lineage: format!("{:?}", character.lineage),
+            appearance_json: serde_json::to_string(&character.appearance).unwrap_or_default(),
+        }
+    }
+}
+
+impl CharacterRecord {
+    /// Convert back to a character. Unknown fields fall back to defaults.
+    pub fn to_character(&self) -> Character {
+        Character {
+            id: self.id as u64,
+            account_id: self.account_id as u64,
+            name: self.name.clone(),
+            lineage: Lineage::from_name(&self.lineage).unwrap_or_default(),
+            appearance: serde_json::from_str(&self.appearance_json).unwrap_or_default(),
+        }
+    }
 }
 
 /// World record (persistence model).
@@ -1,14 +1,70 @@
 //! Authoritative game state and tick loop helpers.
 
-use antediluvia_core::{WorldState, EventManager, FloodEvent, FloodPhase, NetworkMessage};
+use antediluvia_core::{
+    WorldState, EventManager, FloodEvent, FloodPhase, NetworkMessage, WorldEventType,
+    Leviathan, LeviathanAttack, LeviathanState, CombatAction, in_great_moat,
+    ActionModifiers, Loadout, COASTAL_SETTLEMENTS, LEVIATHAN_ENTITY_ID, MASTERY_PER_USE,
+    AntediluviaError, Mob, PangeaGenerator, check_reach, MobSpawner, SpawnConditions, hour_of_day, PackTacticsAI, PackMember, MobRank,
+    PartyManager, LootRoll, LootRule, LootChoice, LootDrop, split_xp, resolve_hit, CRIT_CHANCE,
+    aura_recipients, EffectKind, SkillChain, Projectile, ProjectileKind, Impact, EYE_HEIGHT, LEVIATHAN_HIT_RADIUS,
+    BindPoint, death_is_final, RESPAWN_SECONDS, BountyBoard, CorruptionEvent, FloodStage, can_attack, is_murder, PLAYER_HIT_RADIUS, CombatEvent,
+    RegionalCorruption, region_of, make_offering, OFFERING, Gathering, gathering_sites, Faction, Corpse, lay_corpse, DEATH_WEAR, PlayerNetworkState,
+    CraftingSystem, ReputationEvent, Job, JobTelemetry, is_weapon, Action,
+};
 use tracing::info;
+use bevy::prelude::Vec3;
+use std::collections::HashMap;
 use crate::net::NetServer;
 
+/// Seconds between the mob positions sent to clients.
+const MOB_SNAPSHOT_SECONDS: f32 = 0.1;
+
+/// What a combat action lands on.
+#[derive(Clone, Copy, Debug)]
+enum Target {
+    Leviathan,
+    Mob(u64),
+    Player(u64),
+    Rival(u64), // Another player, struck in anger
+}
+
+/// A damaging blow on its way to a foe, resolved against the foe's defenses
+/// when it lands.
+#[derive(Clone, Copy, Debug)]
+struct Blow {
+    attacker: u64,
+    action: CombatAction,
+    damage: f32, // Before the foe's defenses
+    chain: Option<&'static SkillChain>,
+    origin: Vec3, // Where it was struck or thrown from
+}
+
 /// Authoritative game state container.
 pub struct GameState {
     pub world: WorldState,
     pub events: EventManager,
     pub flood: FloodEvent,
+    pub leviathan: Leviathan,
+    pub mobs: HashMap<u64, Mob>,
+    pub spawner: MobSpawner,
+    pub packs: PackTacticsAI,
+    pub regions: RegionalCorruption, // Each region's corruption over the world's
+    pub player_respawns: HashMap<u64, f32>, // Seconds until each fallen player rises
+    pub corpses: Vec<Corpse>, // Waiting for their owners to come back for them
+    pub gathering: Gathering, // What each gathering site has left
+    pub crafting: CraftingSystem,
+    pub newly_drowned: Vec<u64>, // Since the last tick, to be saved for the rest of the season
+    pub parties: PartyManager,
+    pub loot_rolls: Vec<LootRoll>,
+    pub bounties: BountyBoard,
+    pub combat_events: Vec<CombatEvent>, // Since the last tick, for the combat log on disk
+    projectiles: Vec<(Projectile, Blow)>, // In flight, with the blow each carries
+    damage_dealt: HashMap<u64, HashMap<u64, f32>>, // Mob -> player -> damage, for the XP split
+    last_roll_id: u64,
+    last_projectile_id: u64,
+    mob_snapshot_timer: f32, // Seconds until mob positions are next sent
+    terrain: PangeaGenerator,
+    ground_offset: f32, // Terrain height at the spawn point, which clients level to zero
 }
 
 impl GameState {
@@ -16,13 +72,47 @@ impl GameState {
         let mut world = WorldState::new();
         world.update_weather();
 
+        let terrain = PangeaGenerator::new();
+        let ground_offset = terrain.local_height(0.0, 100.0);
+
         Self {
             world,
             events: EventManager::with_defaults(),
             flood: FloodEvent::new(),
+            leviathan: Leviathan::default(),
+            mobs: HashMap::new(),
+            spawner: MobSpawner::default(),
+            packs: PackTacticsAI::new(),
+            regions: RegionalCorruption::new(),
+            player_respawns: HashMap::new(),
+            corpses: Vec::new(),
+            gathering: Gathering::new(),
+            crafting: CraftingSystem::new(),
+            newly_drowned: Vec::new(),
+            parties: PartyManager::new(),
+            loot_rolls: Vec::new(),
+            bounties: BountyBoard::new(),
+            combat_events: Vec::new(),
+            projectiles: Vec::new(),
+            damage_dealt: HashMap::new(),
+            last_roll_id: 0,
+            last_projectile_id: 0,
+            mob_snapshot_timer: 0.0,
+            terrain,
+            ground_offset,
         }
     }
 
+    /// Terrain height at a game coordinate, as clients see it.
+    pub fn ground_height(&self, x: f32, z: f32) -> f32 {
+        self.terrain.local_height(x, z) - self.ground_offset
+    }
+
+    /// Raise a position that has sunk below the terrain back onto it.
+    fn above_ground(&self, p: Vec3) -> Vec3 {
+        Vec3::new(p.x, p.y.max(self.ground_height(p.x, p.z)), p.z)
+    }
+
     /// Advance the world by `delta_seconds`.
     pub fn tick(&mut self, delta_seconds: f32, net: &mut NetServer) {
         // Process incoming network messages
@@ -32,23 +122,213 @@ impl GameState {
                 NetworkMessage::PlayerMove { position, rotation } => {
                     // Update local player state cache
                     if let Some(state) = net.player_states.get_mut(&client_id) {
-                        state.update_position(position, rotation);
+                        // The Leviathan closes the sea crossing, each house bars its city
+                        // to the other, and the Breath limits sprinting
+                        let blocked = (self.leviathan.blocks_crossing(position) && !self.leviathan.blocks_crossing(state.position))
+                            || state.is_barred_from(position)
+                            || !state.is_alive();
+                        if blocked || !state.try_move(position, rotation, self.events.time_seconds) {
+                            let correction = NetworkMessage::PlayerStateUpdate {
+                                player_id: client_id,
+                                health: state.health,
+                                position: state.position,
+                            };
+                            let _ = net.send_to(client_id, &correction);
+                            continue;
+                        }
                     } else {
-                        // New player? Register them
-                        net.player_states.insert(client_id, antediluvia_core::PlayerNetworkState::new(client_id, position));
+                        // Only those who have joined the world move in it
+                        continue;
                     }
-                    
+
                     // Broadcast movement to others
                     let _ = net.broadcast(&NetworkMessage::PlayerMove { position, rotation });
                 }
+                NetworkMessage::EquipArmor { armor } => {
+                    equip_armor(client_id, armor.as_deref(), net);
+                }
+                NetworkMessage::ItemUsed { item, quantity } => {
+                    let Some(state) = net.player_states.get_mut(&client_id) else { continue; };
+                    if !state.take(&item, quantity) {
+                        info!("{} used {} {} they do not carry", client_id, quantity, item);
+                    }
+                }
+                NetworkMessage::SetLoadout { slots } => {
+                    let Some(state) = net.player_states.get_mut(&client_id) else { continue; };
+                    match Loadout::from_names(&slots, &state.mastery) {
+                        Ok(loadout) => state.loadout = loadout,
+                        Err(e) => info!("Rejected loadout from {}: {}", client_id, e),
+                    }
+                    // Echo the authoritative loadout either way
+                    let update = NetworkMessage::LoadoutUpdate { slots: state.loadout.names() };
+                    let _ = net.send_to(client_id, &update);
+                }
+                NetworkMessage::Feat { feat } => {
+                    let now = self.events.time_seconds;
+                    let Some(state) = net.player_states.get_mut(&client_id).filter(|s| s.is_alive()) else { continue; };
+                    let revealed = state.report_feat(feat, now);
+                    // Tending burns a herb, or finds there was none to burn
+                    if let JobTelemetry::HealedStranger { .. } = feat {
+                        let items = state.satchel.clone();
+                        let _ = net.send_to(client_id, &NetworkMessage::SatchelUpdate { items });
+                    }
+                    match revealed {
+                        Ok(Some(job)) => reveal_job(client_id, job, net),
+                        Ok(None) => {}
+                        Err(e) => info!("Rejected feat from {}: {}", client_id, e),
+                    }
+                }
                 NetworkMessage::PlayerChat { message } => {
                     info!("[Chat] {}: {}", client_id, message);
                     let _ = net.broadcast(&NetworkMessage::PlayerChat { message: format!("{}: {}", client_id, message) });
                 }
+                NetworkMessage::CombatAction { action_type, target_id } => {
+                    if let Err(e) = self.resolve_combat(client_id, &action_type, target_id, net) {
+                        let _ = net.send_to(client_id, &NetworkMessage::CombatRejected {
+                            action_type,
+                            reason: e.to_string(),
+                        });
+                    }
+                }
+                NetworkMessage::SetTarget { target_id } => {
+                    // Only the Leviathan, a living mob or a player can be targeted
+                    let target_id = target_id.filter(|id| {
+                        *id == LEVIATHAN_ENTITY_ID
+                            || self.mobs.get(id).is_some_and(|m| m.is_alive())
+                            || net.player_states.contains_key(id)
+                    });
+                    let Some(state) = net.player_states.get_mut(&client_id) else { continue; };
+                    if state.combat.current_target != target_id {
+                        state.combat.current_target = target_id;
+                        let _ = net.broadcast(&NetworkMessage::TargetChanged { entity_id: client_id, target_id });
+                    }
+                }
+                NetworkMessage::BindSoul => {
+                    let Some(state) = net.player_states.get_mut(&client_id) else { continue; };
+                    let point = BindPoint::near(state.position).filter(|p| p.admits(state.lineage) && state.is_alive());
+                    match point {
+                        Some(point) => {
+                            state.bind_point = point;
+                            let _ = net.send_to(client_id, &NetworkMessage::SoulBound { point: format!("{:?}", point) });
+                        }
+                        None => info!("Rejected soul binding from {}: no altar or campfire in reach", client_id),
+                    }
+                }
+                NetworkMessage::SetPvpFlag { flagged } => {
+                    let Some(state) = net.player_states.get_mut(&client_id) else { continue; };
+                    match state.pvp.set_flag(flagged) {
+                        Ok(()) => {
+                            let update = NetworkMessage::pvp_update(client_id, &state.pvp);
+                            let _ = net.broadcast(&update);
+                        }
+                        Err(e) => refuse_pvp(client_id, e, net),
+                    }
+                }
+                NetworkMessage::PostBounty { target_id } => match self.bounties.post_contract(client_id, target_id) {
+                    Ok(()) => {
+                        let reward = self.bounties.reward_on(target_id);
+                        let _ = net.broadcast(&NetworkMessage::BountyPosted { target_id, reward });
+                    }
+                    Err(e) => refuse_pvp(client_id, e, net),
+                },
+                NetworkMessage::PartyInvite { player_id } => {
+                    let result = if net.player_states.contains_key(&player_id) {
+                        self.parties.invite(client_id, player_id)
+                    } else {
+                        Err(AntediluviaError::PartyError("no such player".to_string()))
+                    };
+                    match result {
+                        Ok(()) => {
+                            let _ = net.send_to(player_id, &NetworkMessage::PartyInvited { from: client_id });
+                        }
+                        Err(e) => refuse_party(client_id, e, net),
+                    }
+                }
+                NetworkMessage::PartyAccept => match self.parties.accept(client_id) {
+                    Ok(_) => self.send_party_update(client_id, net),
+                    Err(e) => refuse_party(client_id, e, net),
+                },
+                NetworkMessage::PartyDecline => {
+                    if let Some(inviter) = self.parties.decline(client_id) {
+                        let reason = format!("{} declined the invitation", client_id);
+                        let _ = net.send_to(inviter, &NetworkMessage::PartyRefused { reason });
+                    }
+                }
+                NetworkMessage::PartyLeave => self.player_left(client_id, net),
+                NetworkMessage::PartyPromote { player_id } => match self.parties.promote(client_id, player_id) {
+                    Ok(()) => self.send_party_update(client_id, net),
+                    Err(e) => refuse_party(client_id, e, net),
+                },
+                NetworkMessage::PartySetLootRule { rule } => {
+                    let result = LootRule::from_name(&rule)
+                        .ok_or_else(|| AntediluviaError::PartyError("unknown loot rule".to_string()))
+                        .and_then(|rule| self.parties.set_loot_rule(client_id, rule));
+                    match result {
+                        Ok(()) => self.send_party_update(client_id, net),
+                        Err(e) => refuse_party(client_id, e, net),
+                    }
+                }
+                NetworkMessage::LootRollChoice { roll_id, choice } => {
+                    let result = match (self.loot_rolls.iter_mut().find(|r| r.id == roll_id), LootChoice::from_name(&choice)) {
+                        (Some(roll), Some(choice)) => roll.choose(client_id, choice),
+                        (None, _) => Err(AntediluviaError::PartyError("the roll is over".to_string())),
+                        (_, None) => Err(AntediluviaError::PartyError("unknown choice".to_string())),
+                    };
+                    if let Err(e) = result {
+                        refuse_party(client_id, e, net);
+                    }
+                }
+                NetworkMessage::Gather { site } => {
+                    let Some(state) = net.player_states.get(&client_id).filter(|s| s.is_alive()) else { continue; };
+                    let position = state.position;
+                    self.gather(client_id, site, position, net);
+                }
+                NetworkMessage::Craft { item } => {
+                    self.craft(client_id, &item, net);
+                }
+                NetworkMessage::RunErrand { faction } => {
+                    let Some(faction) = Faction::from_name(&faction) else { continue; };
+                    let Some(state) = net.player_states.get_mut(&client_id).filter(|s| s.is_alive()) else { continue; };
+                    match state.run_errand(faction) {
+                        Ok(errand) => info!("{} ran an errand for {:?}: {} {}", client_id, faction, errand.quantity, errand.item),
+                        Err(e) => info!("Rejected errand from {}: {}", client_id, e),
+                    }
+                    let items = state.satchel.clone();
+                    let _ = net.send_to(client_id, &NetworkMessage::SatchelUpdate { items });
+                    send_standing(client_id, net);
+                }
+                NetworkMessage::PlayerAction { action, .. } => {
+                    let Some(event) = CorruptionEvent::from_name(&action) else { continue; };
+                    let Some(state) = net.player_states.get(&client_id).filter(|s| s.is_alive()) else { continue; };
+                    let position = state.position;
+                    self.redeem_land(client_id, event, position, net);
+                }
                 _ => {}
             }
         }
 
+        // Regenerate the Breath of idle players, run down cooldowns, and let murderers' marks fade
+        let mut redeemed = Vec::new();
+        for state in net.player_states.values_mut() {
+            state.breath.update(delta_seconds);
+            state.combat.update(delta_seconds);
+            if state.pvp.update(delta_seconds) {
+                redeemed.push(NetworkMessage::pvp_update(state.player_id, &state.pvp));
+            }
+        }
+        for update in redeemed {
+            let _ = net.broadcast(&update);
+        }
+
+        self.tick_status(delta_seconds, net);
+        self.tick_deaths(delta_seconds, net);
+        self.tick_projectiles(delta_seconds, net);
+        self.tick_mobs(delta_seconds, net);
+        self.tick_loot_rolls(delta_seconds, net);
+        for site in self.gathering.update(delta_seconds) {
+            let _ = net.broadcast(&NetworkMessage::SiteUpdate { site, remaining: self.gathering.remaining(site) });
+        }
+
         // Update weather based on corruption
         self.world.update_weather();
 
@@ -56,10 +336,28 @@ impl GameState {
         let triggered = self.events.update(delta_seconds);
         for evt in triggered {
             info!("Event triggered: {:?}", evt);
-            // Broadcast event start (placeholder)
-            // let _ = net.broadcast(&NetworkMessage::WorldEvent { ... });
+            let location = match evt {
+                WorldEventType::Leviathan => {
+                    self.leviathan.surface(rand::random::<f32>() * std::f32::consts::TAU);
+                    self.leviathan.position
+                }
+                _ => self
+                    .events
+                    .events
+                    .iter()
+                    .find(|e| e.event_type == evt)
+                    .map(|e| e.location)
+                    .unwrap_or_default(),
+            };
+            let _ = net.broadcast(&NetworkMessage::WorldEvent {
+                event_type: format!("{:?}", evt),
+                location,
+                active: true,
+            });
         }
 
+        self.tick_leviathan(delta_seconds, net);
+
         // Update flood state if corruption is maxed
         if self.world.corruption_level >= 99.9 && self.flood.phase == FloodPhase::PreFlood {
             self.flood.begin_flood();
@@ -69,4 +367,1223 @@ impl GameState {
             self.flood.update(delta_seconds);
         }
     }
+
+    /// Validate a combat action against cooldowns, range and line of sight,
+    /// then apply it and broadcast the result.
+    fn resolve_combat(
+        &mut self,
+        client_id: u64,
+        action_type: &str,
+        target_id: u64,
+        net: &mut NetServer,
+    ) -> antediluvia_core::Result<()> {
+        let refuse = |reason: &str| AntediluviaError::CombatError(reason.to_string());
+
+        let action = CombatAction::from_name(action_type).ok_or_else(|| refuse("unknown action"))?;
+        let attacker = net.player_states.get(&client_id).ok_or_else(|| refuse("not in the world"))?;
+        if !attacker.is_alive() {
+            return Err(refuse("the dead cannot fight"));
+        }
+        if !attacker.loadout.contains(action) {
+            return Err(refuse("action is not in the loadout"));
+        }
+        if !attacker.combat.is_ready(action) {
+            return Err(refuse("still on cooldown"));
+        }
+        attacker.resources.check(action)?;
+
+        // Blows sent without a target strike the player's current one
+        let target_id = match target_id {
+            0 if action.damage() > 0.0 => attacker.combat.current_target.unwrap_or(0),
+            id => id,
+        };
+
+        // Heals land on the targeted player or the caster; stances and songs on the caster
+        let target = if action.healing() > 0.0 {
+            Target::Player(if net.player_states.contains_key(&target_id) { target_id } else { client_id })
+        } else if action.damage() <= 0.0 {
+            Target::Player(client_id)
+        } else if target_id == LEVIATHAN_ENTITY_ID {
+            Target::Leviathan
+        } else if self.mobs.get(&target_id).is_some_and(|m| m.is_alive()) {
+            Target::Mob(target_id)
+        } else if target_id != client_id && net.player_states.get(&target_id).is_some_and(|p| p.is_alive()) {
+            Target::Rival(target_id)
+        } else {
+            return Err(refuse("no such target"));
+        };
+
+        // The Leviathan judges its own range; everything else must be in reach and in sight
+        let target_pos = match target {
+            Target::Leviathan => None,
+            Target::Mob(id) => self.mobs.get(&id).map(|m| m.position),
+            Target::Player(id) | Target::Rival(id) => net.player_states.get(&id).map(|p| p.position),
+        };
+        if let Some(target_pos) = target_pos {
+            check_reach(action, self.above_ground(attacker.position), self.above_ground(target_pos), |x, z| self.ground_height(x, z))?;
+        }
+        if let Target::Rival(id) = target {
+            self.check_pvp(client_id, id, net)?;
+        }
+
+        // Commit: spend the Breath and the job's resource, start the cooldown, grow mastery
+        let attacker = net.player_states.get_mut(&client_id).ok_or_else(|| refuse("not in the world"))?;
+        let mods = ActionModifiers::for_action(action, &attacker.mastery);
+        if !attacker.breath.drain(action.stamina_cost() * mods.stamina) {
+            return Err(refuse("out of breath"));
+        }
+        let solo_chain = attacker.combat.check_skill_chain(action);
+        let boost = attacker.resources.commit(action);
+        attacker.combat.perform_scaled(action, mods.cooldown * boost.cooldown * attacker.effects.cooldown_multiplier());
+        let power = attacker.effects.damage_multiplier() * boost.damage;
+        if attacker.mastery.is_unlocked(action.job()) {
+            attacker.mastery.increase(action.job(), MASTERY_PER_USE);
+        }
+        let attacker_pos = attacker.position;
+
+        // Party members chain off each other's actions on the same foe; the stronger chain counts
+        let foe_id = match target {
+            Target::Leviathan => Some(LEVIATHAN_ENTITY_ID),
+            Target::Mob(id) | Target::Rival(id) => Some(id),
+            Target::Player(_) => None,
+        };
+        let now = self.events.time_seconds;
+        let party_chain = foe_id.and_then(|foe| {
+            self.parties.party_of_mut(client_id).and_then(|p| p.record_action(client_id, foe, action, now))
+        });
+        let chain = solo_chain
+            .into_iter()
+            .chain(party_chain)
+            .max_by(|a, b| a.damage_multiplier.total_cmp(&b.damage_multiplier));
+
+        let damage = action.damage() * mods.damage * power * chain.map_or(1.0, |c| c.damage_multiplier);
+        let healing = action.healing() * mods.damage;
+        let mut changed = Vec::new(); // Players whose effects changed
+        match (target, foe_id) {
+            (Target::Player(id), _) => {
+                let Some(player) = net.player_states.get_mut(&id) else { return Ok(()); };
+                player.heal(healing);
+
+                // Healing angers every mob fighting the one healed
+                for mob in self.mobs.values_mut().filter(|m| m.threat.contains(id)) {
+                    mob.threat.record(client_id, action, 0.0, healing);
+                }
+                if player.effects.dispel(action.dispels()) > 0 {
+                    changed.push(id);
+                }
+                let update = NetworkMessage::PlayerStateUpdate {
+                    player_id: id,
+                    health: player.health,
+                    position: player.position,
+                };
+                let health = player.health;
+                let _ = net.broadcast(&update);
+                let event = CombatEvent {
+                    chain: chain.map(|c| c.name.clone()),
+                    ..CombatEvent::heal(now, client_id, id, action, healing)
+                };
+                let _ = net.broadcast(&NetworkMessage::combat_result(&event, health));
+                self.combat_events.push(event);
+            }
+            (_, Some(foe)) => {
+                let blow = Blow { attacker: client_id, action, damage, chain, origin: attacker_pos };
+                match action.projectile() {
+                    // Hurled blows fly, and strike whatever they meet on the way
+                    Some(kind) => self.launch(blow, kind, target_pos.unwrap_or(self.leviathan.position), net),
+                    None => self.land_blow(blow, foe, net),
+                }
+            }
+            (_, None) => {}
+        }
+
+        // Closing a chain can heal and bless whoever closed it
+        let chain_healing = chain.map_or(0.0, |c| c.healing());
+        if let Some(player) = net.player_states.get_mut(&client_id).filter(|_| chain_healing > 0.0) {
+            player.heal(chain_healing);
+            let update = NetworkMessage::PlayerStateUpdate {
+                player_id: client_id,
+                health: player.health,
+                position: player.position,
+            };
+            let _ = net.broadcast(&update);
+        }
+        let blessings = chain.into_iter().flat_map(|c| c.blessings());
+        changed.extend(self.apply_helpful_effects(client_id, action.status_effects().into_iter().chain(blessings), attacker_pos, net));
+        broadcast_status(changed, net);
+        Ok(())
+    }
+
+    /// Land a blow on the Leviathan, a mob or another player, broadcasting
+    /// the result and rewarding a kill.
+    fn land_blow(&mut self, blow: Blow, foe: u64, net: &mut NetServer) {
+        let Blow { attacker, action, chain, .. } = blow;
+        if net.player_states.contains_key(&foe) {
+            self.strike_player(blow, foe, net);
+            return;
+        }
+        let now = self.events.time_seconds;
+        let mut slain = None;
+        let (event, target_health) = if foe == LEVIATHAN_ENTITY_ID {
+            let damage = self.leviathan.apply_ranged_damage(attacker, action, blow.origin, blow.damage);
+            let driven_off = damage > 0.0 && self.leviathan.state == LeviathanState::DrivenOff;
+            if driven_off {
+                info!("The Leviathan has been driven off (final blow by {})", attacker);
+                let _ = net.broadcast(&NetworkMessage::WorldEvent {
+                    event_type: format!("{:?}", WorldEventType::Leviathan),
+                    location: self.leviathan.position,
+                    active: false,
+                });
+            }
+            let event = CombatEvent {
+                chain: chain.map(|c| c.name.clone()),
+                killing_blow: driven_off,
+                ..CombatEvent::blow(now, attacker, foe, Some(action), damage)
+            };
+            (event, self.leviathan.health)
+        } else {
+            let Some(mob) = self.mobs.get_mut(&foe).filter(|m| m.is_alive()) else { return; };
+            let hit = resolve_hit(
+                blow.damage * mob.effects.damage_taken_multiplier(),
+                action.damage_type(),
+                &mob.defense(),
+                CRIT_CHANCE,
+                rand::random(),
+            );
+            let dealt = hit.damage.min(mob.health);
+            mob.take_damage(hit.damage);
+            mob.threat.record(attacker, action, dealt, 0.0);
+            let harmful: Vec<EffectKind> = action
+                .status_effects()
+                .into_iter()
+                .filter(|k| k.is_harmful())
+                .chain(chain.into_iter().flat_map(|c| c.inflicted()))
+                .collect();
+            if !harmful.is_empty() && mob.is_alive() {
+                for kind in harmful {
+                    mob.effects.apply(kind, attacker);
+                }
+                let _ = net.broadcast(&NetworkMessage::status_update(foe, &mob.effects));
+            }
+            *self.damage_dealt.entry(foe).or_default().entry(attacker).or_insert(0.0) += dealt;
+            if !mob.is_alive() {
+                info!("{} {} slain by {}", mob.name, foe, attacker);
+                slain = Some(foe);
+            }
+            let event = CombatEvent {
+                killing_blow: !mob.is_alive(),
+                ..CombatEvent::hit(now, attacker, foe, action, &hit, chain)
+            };
+            (event, mob.health)
+        };
+
+        let _ = net.broadcast(&NetworkMessage::combat_result(&event, target_health));
+        self.combat_events.push(event);
+        if let Some(id) = slain {
+            self.reward_kill(id, attacker, net);
+            self.remove_mob(id);
+        }
+    }
+
+    /// Check if one player may strike another: never a party member, and
+    /// only where and when the world allows it.
+    fn check_pvp(&self, attacker: u64, victim: u64, net: &NetServer) -> antediluvia_core::Result<()> {
+        if self.parties.party_of(attacker).is_some_and(|p| p.members.contains(&victim)) {
+            return Err(AntediluviaError::CombatError("cannot strike a party member".to_string()));
+        }
+        let (Some(a), Some(v)) = (net.player_states.get(&attacker), net.player_states.get(&victim)) else {
+            return Err(AntediluviaError::CombatError("no such target".to_string()));
+        };
+        can_attack(&a.pvp, a.position, &v.pvp, v.position, FloodStage::from_corruption(self.world.corruption_level))
+    }
+
+    /// Land a blow on another player, if the world still allows it by the
+    /// time it lands, and settle the reckoning for a kill.
+    fn strike_player(&mut self, blow: Blow, victim: u64, net: &mut NetServer) {
+        let Blow { attacker, action, chain, .. } = blow;
+        if self.check_pvp(attacker, victim, net).is_err() {
+            return;
+        }
+        let Some(target) = net.player_states.get_mut(&victim).filter(|p| p.is_alive()) else { return; };
+        let murder = is_murder(&target.pvp);
+        let hit = resolve_hit(
+            blow.damage * target.effects.damage_taken_multiplier(),
+            action.damage_type(),
+            &target.defense(),
+            CRIT_CHANCE,
+            rand::random(),
+        );
+        target.take_damage(hit.damage);
+        target.pvp.engage();
+        let harmful: Vec<EffectKind> = action
+            .status_effects()
+            .into_iter()
+            .filter(|k| k.is_harmful())
+            .chain(chain.into_iter().flat_map(|c| c.inflicted()))
+            .collect();
+        let afflicted = !harmful.is_empty() && target.is_alive();
+        if afflicted {
+            for kind in harmful {
+                target.effects.apply(kind, attacker);
+            }
+        }
+        let (health, position) = (target.health, target.position);
+        if let Some(state) = net.player_states.get_mut(&attacker) {
+            state.pvp.engage();
+        }
+
+        let _ = net.broadcast(&NetworkMessage::PlayerStateUpdate { player_id: victim, health, position });
+        let event = CombatEvent {
+            killing_blow: health <= 0.0,
+            ..CombatEvent::hit(self.events.time_seconds, attacker, victim, action, &hit, chain)
+        };
+        let _ = net.broadcast(&NetworkMessage::combat_result(&event, health));
+        self.combat_events.push(event);
+        if afflicted {
+            broadcast_status(vec![victim], net);
+        }
+        if health <= 0.0 {
+            self.settle_player_kill(attacker, victim, murder, net);
+        }
+    }
+
+    /// Settle one player's killing of another. The killer collects whatever
+    /// bounty was on the slain; a murder marks and corrupts the killer, darkens
+    /// the world, and sets a bounty on their own head.
+    fn settle_player_kill(&mut self, killer: u64, victim: u64, murder: bool, net: &mut NetServer) {
+        info!("{} slain by {}{}", victim, killer, if murder { " (murder)" } else { "" });
+        let _ = net.broadcast(&NetworkMessage::PlayerKilled { killer_id: killer, victim_id: victim, murder });
+        let faction = net.player_states.get(&victim).map(|state| Faction::home_of(state.lineage));
+        if let (Some(faction), Some(state)) = (faction, net.player_states.get_mut(&killer)) {
+            state.reputation.apply(&ReputationEvent::MemberKilled { faction });
+            send_standing(killer, net);
+        }
+
+        let reward = self.bounties.claim(killer, victim);
+        if reward > 0.0 {
+            award_xp(killer, reward, net);
+            let _ = net.broadcast(&NetworkMessage::BountyClaimed { hunter_id: killer, target_id: victim, reward });
+
+            // A victim's own contract stays up for someone else to collect
+            let remaining = self.bounties.reward_on(victim);
+            if remaining > 0.0 {
+                let _ = net.broadcast(&NetworkMessage::BountyPosted { target_id: victim, reward: remaining });
+            }
+        }
+
+        if murder {
+            self.world.corruption_level = (self.world.corruption_level + CorruptionEvent::PlayerKill.delta()).min(100.0);
+            if let Some(state) = net.player_states.get(&victim) {
+                self.regions.record(CorruptionEvent::PlayerKill, state.position);
+            }
+            if let Some(state) = net.player_states.get_mut(&killer) {
+                let corruption = state.pvp.commit_murder();
+                state.corrupt(corruption);
+                let update = NetworkMessage::pvp_update(killer, &state.pvp);
+                let _ = net.broadcast(&update);
+                send_standing(killer, net);
+            }
+            self.bounties.record_murder(killer, victim);
+            let reward = self.bounties.reward_on(killer);
+            let _ = net.broadcast(&NetworkMessage::BountyPosted { target_id: killer, reward });
+        }
+    }
+
+    /// Craft an item for a player from what they carry, and tell them what
+    /// they carry now and where the making has left them.
+    fn craft(&mut self, player_id: u64, item: &str, net: &mut NetServer) {
+        let Some(state) = net.player_states.get_mut(&player_id).filter(|s| s.is_alive()) else { return; };
+        match state.craft(&self.crafting, item) {
+            Ok(crafted) => {
+                info!("{} crafted {} ({:?})", player_id, crafted.name, crafted.quality);
+                let items = state.satchel.clone();
+                let deed = JobTelemetry::ItemCrafted { quality: crafted.quality, is_weapon: is_weapon(&crafted.name) };
+                let _ = net.send_to(player_id, &NetworkMessage::Crafted { item: crafted.name, quality: format!("{:?}", crafted.quality) });
+                let _ = net.send_to(player_id, &NetworkMessage::SatchelUpdate { items });
+                send_standing(player_id, net);
+                observe_deeds(player_id, &[deed], net);
+            }
+            Err(e) => {
+                let _ = net.send_to(player_id, &NetworkMessage::CraftRefused { reason: e.to_string() });
+            }
+        }
+    }
+
+    /// Gather from a site for a player standing at `position`, into their
+    /// satchel. What they cannot carry stays where it grew.
+    fn gather(&mut self, player_id: u64, site: usize, position: Vec3, net: &mut NetServer) {
+        let stowed = self.gathering.gather(site, position).and_then(|drop| {
+            if net.player_states.get_mut(&player_id).is_some_and(|state| state.stow(drop.clone())) {
+                return Ok(drop);
+            }
+            self.gathering.put_back(site, drop.quantity);
+            Err(AntediluviaError::GatheringError("your satchel is too heavy to carry more".to_string()))
+        });
+        match stowed {
+            Ok(drop) => {
+                let _ = net.send_to(player_id, &NetworkMessage::LootAwarded {
+                    item: drop.name,
+                    quantity: drop.quantity,
+                    weight: drop.weight,
+                });
+                let _ = net.broadcast(&NetworkMessage::SiteUpdate { site, remaining: self.gathering.remaining(site) });
+            }
+            Err(e) => {
+                let _ = net.send_to(player_id, &NetworkMessage::GatherRefused { reason: e.to_string() });
+            }
+        }
+    }
+
+    /// Redeem the region a player makes a sacrifice or destroys an idol in.
+    /// The world's corruption eases, the region's more so, and the mutants
+    /// that haunt it return to what they were.
+    fn redeem_land(&mut self, player_id: u64, event: CorruptionEvent, position: Vec3, net: &mut NetServer) {
+        let offered = self.regions.accepts(event, position).and_then(|_| {
+            net.player_states.get_mut(&player_id).map_or(Ok(()), |state| make_offering(event, &mut state.satchel))
+        });
+        let zone = match offered.and_then(|_| self.regions.redeem(event, position)) {
+            Ok(zone) => zone,
+            Err(e) => {
+                let _ = net.send_to(player_id, &NetworkMessage::RedemptionRefused { reason: e.to_string() });
+                return;
+            }
+        };
+        info!("{} redeemed {} ({:?})", player_id, zone.name, event);
+        if event == CorruptionEvent::Sacrifice {
+            let _ = net.send_to(player_id, &NetworkMessage::OfferingBurnt { item: OFFERING.to_string() });
+        }
+        self.world.corruption_level = (self.world.corruption_level + event.delta()).max(0.0);
+        let _ = net.broadcast(&NetworkMessage::LandRedeemed { region: zone.name.clone() });
+
+        for mob in self.mobs.values_mut().filter(|m| m.is_alive() && region_of(m.home).is_some_and(|z| z.name == zone.name)) {
+            if mob.redeem() {
+                let _ = net.broadcast(&NetworkMessage::mob_mutated(mob));
+            }
+        }
+    }
+
+    /// Hurl a blow as a projectile along the arc that reaches `aim`.
+    fn launch(&mut self, blow: Blow, kind: ProjectileKind, aim: Vec3, net: &mut NetServer) {
+        self.last_projectile_id += 1;
+        let lift = Vec3::Y * EYE_HEIGHT;
+        let (from, to) = (self.above_ground(blow.origin) + lift, self.above_ground(aim) + lift);
+        let projectile = Projectile::launch(self.last_projectile_id, blow.attacker, blow.action, kind, from, to);
+        let _ = net.broadcast(&NetworkMessage::ProjectileLaunched {
+            projectile_id: projectile.id,
+            owner_id: blow.attacker,
+            action_type: format!("{:?}", blow.action),
+            position: projectile.position,
+            velocity: projectile.velocity,
+        });
+        self.projectiles.push((projectile, blow));
+    }
+
+    /// Fly projectiles on, landing the blow of each that strikes a foe.
+    fn tick_projectiles(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        let lift = Vec3::Y * EYE_HEIGHT;
+        let mut colliders: Vec<(u64, Vec3, f32)> = self
+            .mobs
+            .values()
+            .filter(|m| m.is_alive())
+            .map(|m| (m.id, self.above_ground(m.position) + lift, m.mob_type.hit_radius()))
+            .collect();
+        if self.leviathan.is_active() {
+            colliders.push((LEVIATHAN_ENTITY_ID, self.leviathan.position, LEVIATHAN_HIT_RADIUS));
+        }
+        // Players stand in the way too; whether the blow lands on them is judged when it strikes
+        colliders.extend(
+            net.player_states
+                .values()
+                .filter(|p| p.is_alive())
+                .map(|p| (p.player_id, self.above_ground(p.position) + lift, PLAYER_HIT_RADIUS)),
+        );
+
+        let (terrain, offset) = (&self.terrain, self.ground_offset);
+        let mut landed = Vec::new();
+        self.projectiles.retain_mut(|(projectile, blow)| {
+            // Nobody is struck by their own throw
+            let others: Vec<(u64, Vec3, f32)> = colliders.iter().filter(|(id, _, _)| *id != blow.attacker).copied().collect();
+            let Some(impact) = projectile.update(delta_seconds, |x, z| terrain.local_height(x, z) - offset, &others) else {
+                return true;
+            };
+            landed.push((projectile.id, *blow, impact));
+            false
+        });
+
+        for (projectile_id, blow, impact) in landed {
+            let (position, struck) = match impact {
+                Impact::Hit { id, position } => (position, Some(id)),
+                Impact::Ground(position) | Impact::Spent(position) => (position, None),
+            };
+            let _ = net.broadcast(&NetworkMessage::ProjectileLanded { projectile_id, position, target_id: struck });
+            if let Some(foe) = struck {
+                self.land_blow(blow, foe, net);
+            }
+        }
+    }
+
+    /// Apply the helpful effects among `kinds` to the caster; songs reach the
+    /// party around them. Returns the players who received an effect.
+    fn apply_helpful_effects(
+        &self,
+        caster: u64,
+        kinds: impl IntoIterator<Item = EffectKind>,
+        origin: Vec3,
+        net: &mut NetServer,
+    ) -> Vec<u64> {
+        let party = self.parties.party_of(caster).map_or_else(|| vec![caster], |p| p.members.clone());
+        let allies: Vec<(u64, Vec3)> = party
+            .iter()
+            .filter_map(|id| net.player_states.get(id))
+            .filter(|p| p.is_alive())
+            .map(|p| (p.player_id, p.position))
+            .collect();
+        let mut received = Vec::new();
+        for kind in kinds.into_iter().filter(|k| !k.is_harmful()) {
+            let recipients = match kind.aura_radius() {
+                Some(_) => aura_recipients(kind, origin, &allies),
+                None => vec![caster],
+            };
+            for id in recipients {
+                if let Some(player) = net.player_states.get_mut(&id) {
+                    player.effects.apply(kind, caster);
+                    received.push(id);
+                }
+            }
+        }
+        received
+    }
+
+    /// Take a slain mob out of the world, leaving its place for the spawner to refill.
+    fn remove_mob(&mut self, mob_id: u64) {
+        self.mobs.remove(&mob_id);
+        self.spawner.despawn(mob_id);
+    }
+
+    /// Split a slain mob's experience by damage dealt, and share out its loot
+    /// by the killer's party loot rule.
+    fn reward_kill(&mut self, mob_id: u64, killer: u64, net: &mut NetServer) {
+        let Some(mob) = self.mobs.get(&mob_id) else { return; };
+        let xp = mob.xp_reward();
+        let loot = mob.loot();
+        if let Some(state) = net.player_states.get_mut(&killer) {
+            state.reputation.apply(&ReputationEvent::MobKilled { mob_type: mob.mob_type });
+            send_standing(killer, net);
+        }
+
+        let contributions = self.damage_dealt.remove(&mob_id).unwrap_or_default();
+        for (player_id, amount) in split_xp(xp, &contributions) {
+            award_xp(player_id, amount, net);
+        }
+
+        // A giant felled mostly by one hand reveals the Hunter in them
+        let (mob_type, max_health) = (mob.mob_type, mob.max_health);
+        for (&player_id, &damage) in &contributions {
+            let deeds = [
+                JobTelemetry::MobDamaged { mob_id, mob_type, damage },
+                JobTelemetry::MobKilled { mob_id, mob_type, max_health },
+            ];
+            observe_deeds(player_id, &deeds, net);
+        }
+
+        // Party members who fought share the loot; anyone else's kill is their own
+        let Some(party) = self.parties.party_of_mut(killer) else {
+            for drop in loot {
+                award_loot(killer, drop, net);
+            }
+            return;
+        };
+        party.forget_target(mob_id);
+        let eligible: Vec<u64> = party.members.iter().copied().filter(|m| contributions.contains_key(m)).collect();
+        for drop in loot {
+            if party.loot_rule == LootRule::RoundRobin || eligible.len() < 2 {
+                let looter = party.looter(killer, &eligible);
+                award_loot(looter, drop, net);
+                continue;
+            }
+
+            self.last_roll_id += 1;
+            for member in &eligible {
+                let _ = net.send_to(*member, &NetworkMessage::LootRollStart {
+                    roll_id: self.last_roll_id,
+                    item: drop.name.clone(),
+                    quantity: drop.quantity,
+                });
+            }
+            self.loot_rolls.push(LootRoll::new(self.last_roll_id, drop, eligible.clone()));
+        }
+    }
+
+    /// Close need/greed rolls once everyone has chosen or time runs out.
+    fn tick_loot_rolls(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        for roll in self.loot_rolls.iter_mut() {
+            roll.update(delta_seconds);
+        }
+
+        let (done, open): (Vec<LootRoll>, Vec<LootRoll>) = self.loot_rolls.drain(..).partition(|r| r.is_complete());
+        self.loot_rolls = open;
+        for roll in done {
+            let winner = roll.winner(|_| rand::random_range(1..=100));
+            for member in &roll.eligible {
+                let _ = net.send_to(*member, &NetworkMessage::LootRollResult {
+                    roll_id: roll.id,
+                    item: roll.drop.name.clone(),
+                    winner,
+                });
+            }
+            if let Some(winner) = winner {
+                award_loot(winner, roll.drop, net);
+            }
+        }
+    }
+
+    /// Bring a player into the world as they connect: tell them where they
+    /// stand and what they owe, and show them the mobs already about. Those
+    /// who perished in the Flood stay fallen for the rest of the season.
+    pub fn player_joined(&mut self, mut player: PlayerNetworkState, net: &mut NetServer) {
+        let player_id = player.player_id;
+        let drowned = self.flood.players_drowned.contains(&player_id);
+        if drowned {
+            player.health = 0.0;
+        }
+        let (health, position, debt) = (player.health, player.position, player.experience.debt);
+        let (items, mastery) = (player.satchel.clone(), player.mastery.clone());
+        net.player_states.insert(player_id, player);
+
+        let _ = net.send_to(player_id, &NetworkMessage::PlayerStateUpdate { player_id, health, position });
+        let _ = net.send_to(player_id, &NetworkMessage::SatchelUpdate { items });
+        let _ = net.send_to(player_id, &NetworkMessage::MasteryUpdate { mastery });
+        send_standing(player_id, net);
+        if drowned {
+            let _ = net.send_to(player_id, &NetworkMessage::PlayerDied { player_id, position, permanent: true });
+        }
+        if debt > 0.0 {
+            let _ = net.send_to(player_id, &NetworkMessage::XpDebt { debt });
+        }
+        for mob in self.mobs.values() {
+            let _ = net.send_to(player_id, &NetworkMessage::mob_spawned(mob));
+        }
+        for (site, found) in gathering_sites().iter().enumerate() {
+            let remaining = self.gathering.remaining(site);
+            if remaining < found.capacity {
+                let _ = net.send_to(player_id, &NetworkMessage::SiteUpdate { site, remaining });
+            }
+        }
+    }
+
+    /// Take a player out of their party and off every threat table, e.g. when
+    /// they leave or disconnect.
+    pub fn player_left(&mut self, player_id: u64, net: &mut NetServer) {
+        for mob in self.mobs.values_mut() {
+            mob.threat.remove(player_id);
+        }
+        let Some(party) = self.parties.leave(player_id) else { return; };
+        self.send_party_update(player_id, net);
+        for member in &party.members {
+            self.send_party_update(*member, net);
+        }
+    }
+
+    /// Tell every member of a player's party (or just the player, if they
+    /// have none) how the party stands.
+    fn send_party_update(&self, player_id: u64, net: &mut NetServer) {
+        let (recipients, update) = match self.parties.party_of(player_id) {
+            Some(party) => (party.members.clone(), NetworkMessage::PartyUpdate {
+                leader: party.leader,
+                members: party.members.clone(),
+                loot_rule: format!("{:?}", party.loot_rule),
+            }),
+            None => (vec![player_id], NetworkMessage::PartyUpdate {
+                leader: player_id,
+                members: Vec::new(),
+                loot_rule: format!("{:?}", LootRule::default()),
+            }),
+        };
+        for recipient in recipients {
+            let _ = net.send_to(recipient, &update);
+        }
+    }
+
+    /// Run bleeds, burns and songs on players and mobs, and pulse channelled songs.
+    fn tick_status(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        let mut updates = Vec::new();
+        let mut pulses = Vec::new();
+        for player in net.player_states.values_mut().filter(|p| p.is_alive()) {
+            if let Some(song) = player.resources.update(delta_seconds) {
+                pulses.push((player.player_id, song, player.position));
+            }
+            let tick = player.effects.update(delta_seconds);
+            if tick.damage > 0.0 || tick.healing > 0.0 {
+                player.take_damage(tick.damage);
+                player.heal(tick.healing);
+                updates.push(NetworkMessage::PlayerStateUpdate {
+                    player_id: player.player_id,
+                    health: player.health,
+                    position: player.position,
+                });
+            }
+        }
+        for update in &updates {
+            let _ = net.broadcast(update);
+        }
+        let mut changed = Vec::new();
+        for (singer, song, origin) in pulses {
+            changed.extend(self.apply_helpful_effects(singer, song.status_effects(), origin, net));
+        }
+        broadcast_status(changed, net);
+
+        // Damage over time counts towards the XP split of whoever inflicted it
+        let mut slain = Vec::new();
+        for mob in self.mobs.values_mut().filter(|m| m.is_alive()) {
+            let sources: Vec<(u64, f32)> = mob
+                .effects
+                .effects
+                .iter()
+                .map(|e| (e.source, e.kind.tick_damage() * e.stacks as f32))
+                .collect();
+            let tick = mob.effects.update(delta_seconds);
+            if tick.damage <= 0.0 {
+                continue;
+            }
+
+            let dealt = tick.damage.min(mob.health);
+            mob.take_damage(tick.damage);
+            let per_tick: f32 = sources.iter().map(|(_, d)| d).sum();
+            for (source, share) in sources.iter().filter(|(_, d)| *d > 0.0) {
+                *self.damage_dealt.entry(mob.id).or_default().entry(*source).or_insert(0.0) += dealt * share / per_tick;
+                mob.threat.add(*source, dealt * share / per_tick);
+            }
+            let (source, _) = sources.iter().copied().fold((0, 0.0), |best, s| if s.1 > best.1 { s } else { best });
+            let event = CombatEvent {
+                killing_blow: !mob.is_alive(),
+                ..CombatEvent::blow(self.events.time_seconds, source, mob.id, None, tick.damage)
+            };
+            let _ = net.broadcast(&NetworkMessage::combat_result(&event, mob.health));
+            self.combat_events.push(event);
+            if !mob.is_alive() {
+                info!("{} {} succumbed to its wounds", mob.name, mob.id);
+                slain.push((mob.id, source));
+            }
+        }
+        for (id, source) in slain {
+            self.reward_kill(id, source, net);
+            self.remove_mob(id);
+        }
+    }
+
+    /// Lay the newly fallen to rest, leaving their corpses and debts, and raise
+    /// those whose wait is over. Once the Flood has begun, the fallen drown
+    /// for good. Corpses crumble in time, or go back to owners who reach them.
+    fn tick_deaths(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        let fallen: Vec<(u64, Vec3)> = net
+            .player_states
+            .values()
+            .filter(|p| !p.is_alive() && !self.player_respawns.contains_key(&p.player_id))
+            .filter(|p| !self.flood.players_drowned.contains(&p.player_id))
+            .map(|p| (p.player_id, p.position))
+            .collect();
+        for (player_id, position) in fallen {
+            let permanent = death_is_final(&self.flood.phase);
+            if permanent {
+                info!("{} has perished in the Flood", player_id);
+                self.flood.drown_player(player_id);
+                self.newly_drowned.push(player_id);
+            } else {
+                self.player_respawns.insert(player_id, RESPAWN_SECONDS);
+            }
+            if let Some(state) = net.player_states.get_mut(&player_id) {
+                let corpse = lay_corpse(player_id, position, &mut state.satchel);
+                let debt = if permanent { state.experience.debt } else { state.experience.die() };
+                let _ = net.send_to(player_id, &NetworkMessage::CorpseLaid {
+                    position,
+                    items: corpse.items.clone(),
+                    wear: DEATH_WEAR,
+                });
+                let _ = net.send_to(player_id, &NetworkMessage::XpDebt { debt });
+                if !corpse.items.is_empty() {
+                    self.corpses.push(corpse);
+                }
+            }
+            for mob in self.mobs.values_mut() {
+                mob.threat.remove(player_id);
+            }
+            let _ = net.broadcast(&NetworkMessage::PlayerDied { player_id, position, permanent });
+        }
+
+        let mut risen = Vec::new();
+        for (player_id, timer) in self.player_respawns.iter_mut() {
+            *timer -= delta_seconds;
+            if *timer <= 0.0 {
+                risen.push(*player_id);
+            }
+        }
+        for player_id in risen {
+            self.player_respawns.remove(&player_id);
+            let Some(player) = net.player_states.get_mut(&player_id) else { continue; };
+            let position = player.bind_point.respawn_point(player.lineage);
+            player.respawn(position);
+            let _ = net.broadcast(&NetworkMessage::PlayerRespawned { player_id, position });
+            broadcast_status(vec![player_id], net);
+        }
+
+        let mut recovered = Vec::new();
+        self.corpses.retain_mut(|corpse| {
+            let owner = net.player_states.get(&corpse.owner).filter(|p| p.is_alive());
+            if owner.is_some_and(|p| corpse.can_recover(p.player_id, p.position)) {
+                recovered.push((corpse.owner, std::mem::take(&mut corpse.items)));
+                return false;
+            }
+            corpse.update(delta_seconds)
+        });
+        for (owner, items) in recovered {
+            let _ = net.send_to(owner, &NetworkMessage::CorpseRecovered);
+            for item in items {
+                award_loot(owner, item, net);
+            }
+        }
+    }
+
+    /// Stock the spawn zones, turn beasts left in tainted land, and let each
+    /// mob's behaviour tree decide what it does about the players it is after.
+    /// Clients are told where each stands a few times a second.
+    fn tick_mobs(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        let conditions = SpawnConditions::new(
+            hour_of_day(self.events.time_seconds),
+            self.world.weather,
+            self.world.corruption_level,
+        );
+        for mob in self.spawner.update(delta_seconds, &conditions, &mut rand::random::<f32>) {
+            if mob.variant.rank == MobRank::Rare {
+                info!("{} has been sighted at ({:.0}, {:.0})", mob.name, mob.position.x, mob.position.z);
+            }
+            let _ = net.broadcast(&NetworkMessage::mob_spawned(&mob));
+            self.mobs.insert(mob.id, mob);
+        }
+
+        // Each mob turns on whoever it is angriest with, unless its pack leads it
+        let players: Vec<(u64, Vec3)> = net
+            .player_states
+            .values()
+            .filter(|p| p.is_alive())
+            .map(|p| (p.player_id, p.position))
+            .collect();
+        let previous: HashMap<u64, Option<u64>> = self.mobs.values().map(|m| (m.id, m.target)).collect();
+        self.regions.update(delta_seconds);
+        for mob in self.mobs.values_mut().filter(|m| m.is_alive()) {
+            mob.threat.update(delta_seconds);
+            mob.regenerate(delta_seconds);
+            let corruption = self.regions.corruption_at(self.world.corruption_level, mob.position);
+            if mob.expose(corruption, delta_seconds) {
+                info!("{} has turned at ({:.0}, {:.0})", mob.name, mob.position.x, mob.position.z);
+                let _ = net.broadcast(&NetworkMessage::mob_mutated(mob));
+            }
+            mob.notice(&players); // Straying into aggro range angers it, as on the client
+            mob.target = mob.threat.select_target(mob.position, &players);
+        }
+        let members: Vec<PackMember> = self.mobs.values().map(PackMember::from).collect();
+        let orders = self.packs.update(&members, &players, delta_seconds);
+
+        // Each decides with its behaviour tree, as the client's mobs do
+        let mut strikes = Vec::new();
+        for mob in self.mobs.values_mut().filter(|m| m.is_alive()) {
+            mob.pack_id = self.packs.pack_of(mob.id).map(|p| p.id);
+            let order = orders.get(mob.id);
+            if mob.think(order, &players, delta_seconds).action == Some(Action::Strike) {
+                strikes.extend(mob.swing(order, &players).map(|prey| (mob.id, prey)));
+            }
+            if mob.target != previous[&mob.id] {
+                let _ = net.broadcast(&NetworkMessage::TargetChanged { entity_id: mob.id, target_id: mob.target });
+            }
+        }
+        for (mob_id, prey) in strikes {
+            self.strike_with_mob(mob_id, prey, net);
+        }
+
+        // Clients draw their mobs where the server has them
+        self.mob_snapshot_timer -= delta_seconds;
+        if self.mob_snapshot_timer <= 0.0 {
+            self.mob_snapshot_timer = MOB_SNAPSHOT_SECONDS;
+            let _ = net.broadcast(&NetworkMessage::mob_positions(self.mobs.values()));
+        }
+    }
+
+    /// Land a mob's blow on a player, softened by what they wear and the
+    /// hymns on them.
+    fn strike_with_mob(&mut self, mob_id: u64, victim: u64, net: &mut NetServer) {
+        let Some(mob) = self.mobs.get(&mob_id) else { return; };
+        let Some(target) = net.player_states.get_mut(&victim).filter(|p| p.is_alive()) else { return; };
+        let blow = mob.get_damage() * mob.effects.damage_multiplier() * target.effects.damage_taken_multiplier();
+        let damage = target.defense().mitigate(blow, mob.mob_type.attack_type());
+        target.take_damage(damage);
+        let (health, position) = (target.health, target.position);
+
+        let _ = net.broadcast(&NetworkMessage::PlayerStateUpdate { player_id: victim, health, position });
+        let event = CombatEvent {
+            resisted: blow - damage,
+            killing_blow: health <= 0.0,
+            ..CombatEvent::blow(self.events.time_seconds, mob_id, victim, None, damage)
+        };
+        let _ = net.broadcast(&NetworkMessage::combat_result(&event, health));
+        self.combat_events.push(event);
+    }
+
+    /// Let the Leviathan hunt players afloat in the moat and the coastal settlements.
+    fn tick_leviathan(&mut self, delta_seconds: f32, net: &mut NetServer) {
+        if !self.leviathan.is_active() {
+            return;
+        }
+
+        let vessels: Vec<(u64, Vec3)> = net
+            .player_states
+            .values()
+            .filter(|s| s.is_alive() && in_great_moat(s.position.x as f64, s.position.z as f64))
+            .map(|s| (s.player_id, s.position))
+            .collect();
+
+        let attacks = self.leviathan.update(delta_seconds, &vessels, &COASTAL_SETTLEMENTS);
+        for attack in attacks {
+            match attack {
+                LeviathanAttack::Vessel { target_id, damage } => {
+                    if let Some(state) = net.player_states.get_mut(&target_id) {
+                        state.take_damage(damage);
+                        let update = NetworkMessage::PlayerStateUpdate {
+                            player_id: target_id,
+                            health: state.health,
+                            position: state.position,
+                        };
+                        let _ = net.broadcast(&update);
+                    }
+                    let _ = net.broadcast(&NetworkMessage::LeviathanStrike {
+                        position: self.leviathan.position,
+                        target_id: Some(target_id),
+                        damage,
+                    });
+                }
+                LeviathanAttack::Settlement { position, damage } => {
+                    info!("The Leviathan strikes the settlement at ({:.0}, {:.0})", position.x, position.z);
+                    let _ = net.broadcast(&NetworkMessage::LeviathanStrike {
+                        position,
+                        target_id: None,
+                        damage,
+                    });
+                }
+            }
+        }
+
+        if !self.leviathan.is_active() {
+            info!("The Leviathan sinks back into the deep");
+            let _ = net.broadcast(&NetworkMessage::WorldEvent {
+                event_type: format!("{:?}", WorldEventType::Leviathan),
+                location: self.leviathan.position,
+                active: false,
+            });
+        }
+    }
+}
+
+/// Tell everyone the effects now on each of the given players.
+fn broadcast_status(mut players: Vec<u64>, net: &mut NetServer) {
+    players.sort_unstable();
+    players.dedup();
+    for id in players {
+        if let Some(player) = net.player_states.get(&id) {
+            let update = NetworkMessage::status_update(id, &player.effects);
+            let _ = net.broadcast(&update);
+        }
+    }
+}
+
+/// Send a player experience they have earned, keeping their tally (and debt) here too.
+fn award_xp(player_id: u64, amount: f32, net: &mut NetServer) {
+    if let Some(state) = net.player_states.get_mut(&player_id) {
+        state.experience.gain(amount);
+    }
+    let _ = net.send_to(player_id, &NetworkMessage::ExperienceGained { amount });
+}
+
+/// Put a drop a player has won in their satchel, and tell them of it. What
+/// will not fit falls to the ground, on the client as here.
+fn award_loot(player_id: u64, drop: LootDrop, net: &mut NetServer) {
+    let Some(state) = net.player_states.get_mut(&player_id) else { return; };
+    if !state.stow(drop.clone()) {
+        info!("{} has no room for {} x{}", player_id, drop.name, drop.quantity);
+    }
+    let _ = net.send_to(player_id, &NetworkMessage::LootAwarded {
+        item: drop.name,
+        quantity: drop.quantity,
+        weight: drop.weight,
+    });
+}
+
+/// Tell a player their request to fight other players was refused.
+fn refuse_pvp(player_id: u64, error: AntediluviaError, net: &mut NetServer) {
+    let _ = net.send_to(player_id, &NetworkMessage::PvpRefused { reason: error.to_string() });
+}
+
+/// Observe deeds the server saw a player do, revealing any job they earn.
+fn observe_deeds(player_id: u64, deeds: &[JobTelemetry], net: &mut NetServer) {
+    let Some(state) = net.player_states.get_mut(&player_id) else { return; };
+    let revealed: Vec<Job> = deeds.iter().filter_map(|deed| state.observe(deed)).collect();
+    for job in revealed {
+        reveal_job(player_id, job, net);
+    }
+}
+
+/// Tell a player a job has been revealed in them, and their mastery now.
+fn reveal_job(player_id: u64, job: Job, net: &mut NetServer) {
+    let Some(state) = net.player_states.get(&player_id) else { return; };
+    info!("{} revealed as {:?}", player_id, job);
+    let mastery = NetworkMessage::MasteryUpdate { mastery: state.mastery.clone() };
+    let _ = net.send_to(player_id, &NetworkMessage::JobUnlocked { job: format!("{:?}", job) });
+    let _ = net.send_to(player_id, &mastery);
+}
+
+/// Tell a player their own corruption, house and standing with every faction.
+fn send_standing(player_id: u64, net: &mut NetServer) {
+    let Some(state) = net.player_states.get(&player_id) else { return; };
+    let corrupted = NetworkMessage::corruption_update(state);
+    let reputation = NetworkMessage::ReputationUpdate { reputation: state.reputation.clone() };
+    let _ = net.send_to(player_id, &corrupted);
+    let _ = net.send_to(player_id, &reputation);
+}
+
+/// Put armor on a player from their satchel, or take it off, and tell them
+/// what they carry now.
+fn equip_armor(player_id: u64, armor: Option<&str>, net: &mut NetServer) {
+    let Some(state) = net.player_states.get_mut(&player_id) else { return; };
+    if let Err(e) = state.wear(armor) {
+        info!("Rejected armor from {}: {}", player_id, e);
+        let _ = net.send_to(player_id, &NetworkMessage::ArmorRefused { reason: e.to_string() });
+    }
+    let items = net.player_states[&player_id].satchel.clone();
+    let _ = net.send_to(player_id, &NetworkMessage::SatchelUpdate { items });
+}
+
+/// Tell a player their party request was refused.
+fn refuse_party(player_id: u64, error: AntediluviaError, net: &mut NetServer) {
+    let _ = net.send_to(player_id, &NetworkMessage::PartyRefused { reason: error.to_string() });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use antediluvia_core::{nearest_region, Lineage, MobType, GATHER_REACH, MAX_PLAYER_HEALTH};
+
+    /// Put a player flagged for battle in the world, standing on the ground at `x`, `z`.
+    fn join(state: &GameState, net: &mut NetServer, player_id: u64, x: f32, z: f32) {
+        let mut player = PlayerNetworkState::new(player_id, Vec3::ZERO);
+        player.pvp.set_flag(true).unwrap();
+        net.player_states.insert(player_id, player);
+        walk(state, net, player_id, x, z);
+    }
+
+    /// Move a player as their moves would, onto the ground at `x`, `z`.
+    fn walk(state: &GameState, net: &mut NetServer, player_id: u64, x: f32, z: f32) {
+        let position = Vec3::new(x, state.ground_height(x, z) + 1.0, z);
+        net.player_states.get_mut(&player_id).unwrap().position = position;
+    }
+
+    #[test]
+    fn test_rivals_fight_in_the_wilds() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+
+        // At the foot of the Eden Pillar, where every soul starts, no blood may be shed
+        let pillar = BindPoint::EdenPillar.position();
+        join(&state, &mut net, 1, pillar.x, pillar.z);
+        join(&state, &mut net, 2, pillar.x + 2.0, pillar.z);
+        assert!(state.resolve_combat(1, "HunterSlash", 2, &mut net).is_err());
+        assert_eq!(net.player_states[&2].health, MAX_PLAYER_HEALTH);
+
+        // Out in the wilds the same blow lands, and both are engaged
+        walk(&state, &mut net, 1, 400.0, 400.0);
+        walk(&state, &mut net, 2, 402.0, 400.0);
+        state.resolve_combat(1, "HunterSlash", 2, &mut net).unwrap();
+        let victim = &net.player_states[&2];
+        assert!(victim.health < MAX_PLAYER_HEALTH);
+        assert!(victim.pvp.flag_timer > 0.0);
+        assert!(state.combat_events.iter().any(|e| e.attacker == 1 && e.target == 2 && e.damage > 0.0));
+    }
+
+    #[test]
+    fn test_the_drowned_join_fallen() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        state.flood.drown_player(7);
+        state.player_joined(PlayerNetworkState::new(7, Vec3::ZERO), &mut net);
+        state.player_joined(PlayerNetworkState::new(8, Vec3::ZERO), &mut net);
+        assert!(!net.player_states[&7].is_alive());
+        assert!(net.player_states[&8].is_alive());
+    }
+
+    #[test]
+    fn test_the_server_keeps_the_satchel() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+        let pelt = LootDrop { name: "Wolf Pelt".to_string(), quantity: 2, weight: 3.0 };
+
+        // Loot goes into the server's satchel, and only what is carried can be used up
+        award_loot(1, pelt.clone(), &mut net);
+        assert_eq!(net.player_states[&1].satchel, vec![pelt.clone()]);
+        assert!(!net.player_states.get_mut(&1).unwrap().take("Wolf Pelt", 3));
+
+        // A corpse holds a share of it, and gives it back to its owner
+        net.player_states.get_mut(&1).unwrap().health = 0.0;
+        state.tick_deaths(0.0, &mut net);
+        assert_eq!(state.corpses.len(), 1);
+        assert_eq!(net.player_states[&1].satchel, vec![LootDrop { quantity: 1, ..pelt.clone() }]);
+        net.player_states.get_mut(&1).unwrap().health = MAX_PLAYER_HEALTH;
+        state.tick_deaths(0.0, &mut net);
+        assert!(state.corpses.is_empty());
+        assert_eq!(net.player_states[&1].satchel, vec![pelt]);
+    }
+
+    #[test]
+    fn test_only_carried_armor_softens_blows() {
+        let state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+
+        // Claimed armor the player does not carry is refused
+        equip_armor(1, Some("Linen Tunic"), &mut net);
+        assert_eq!(net.player_states[&1].defense().armor, 0.0);
+
+        // Carried, it is worn and leaves the satchel
+        award_loot(1, LootDrop { name: "Linen Tunic".to_string(), quantity: 1, weight: 3.0 }, &mut net);
+        equip_armor(1, Some("Linen Tunic"), &mut net);
+        assert!(net.player_states[&1].defense().armor > 0.0);
+        assert!(net.player_states[&1].satchel.is_empty());
+    }
+
+    #[test]
+    fn test_mob_blows_land_on_the_server() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+        let wolf = Mob::new(900, "Wolf".to_string(), MobType::Wolf, net.player_states[&1].position, 1);
+        state.mobs.insert(wolf.id, wolf);
+
+        // A blow lands in full on the bare, and is softened by armor
+        state.strike_with_mob(900, 1, &mut net);
+        let bare = MAX_PLAYER_HEALTH - net.player_states[&1].health;
+        assert!(bare > 0.0);
+        award_loot(1, LootDrop { name: "Linen Tunic".to_string(), quantity: 1, weight: 3.0 }, &mut net);
+        equip_armor(1, Some("Linen Tunic"), &mut net);
+        let before = net.player_states[&1].health;
+        state.strike_with_mob(900, 1, &mut net);
+        assert!(before - net.player_states[&1].health < bare);
+        assert_eq!(state.combat_events.len(), 2);
+    }
+
+    #[test]
+    fn test_crafting_is_judged_by_the_server() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+        let goods = |name: &str, quantity| LootDrop { name: name.to_string(), quantity, weight: 1.0 };
+        let player = net.player_states.get_mut(&1).unwrap();
+        player.lineage = Lineage::Cain;
+        player.experience.level = 5;
+        player.satchel = vec![goods("Iron Ingot", 3), goods("Leather Grip", 1)];
+
+        // The forge corrupts the one who works it, here and not on their word
+        state.craft(1, "Iron Sword", &mut net);
+        let player = &net.player_states[&1];
+        assert_eq!(player.satchel.len(), 1);
+        assert!(player.corruption > 0.0);
+    }
+
+    #[test]
+    fn test_the_server_keeps_reputation() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+        join(&state, &mut net, 2, 402.0, 400.0);
+        net.player_states.get_mut(&2).unwrap().lineage = Lineage::Cain;
+        let enoch = net.player_states[&1].reputation.get(Faction::CityOfEnoch);
+        let watchers = net.player_states[&1].reputation.get(Faction::WatchersCult);
+
+        // Murder sours the victim's house, and the corruption it brings pleases the Watchers
+        state.settle_player_kill(1, 2, true, &mut net);
+        let killer = &net.player_states[&1];
+        assert!(killer.reputation.get(Faction::CityOfEnoch) < enoch);
+        assert!(killer.reputation.get(Faction::WatchersCult) > watchers);
+    }
+
+    #[test]
+    fn test_a_giant_reveals_the_hunter_in_who_felled_it() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        join(&state, &mut net, 1, 400.0, 400.0);
+        join(&state, &mut net, 2, 402.0, 400.0);
+        let giant = Mob::new(900, "Nephilim".to_string(), MobType::Nephilim, Vec3::new(400.0, 0.0, 405.0), 1);
+        let max_health = giant.max_health;
+        state.mobs.insert(giant.id, giant);
+        let dealt = state.damage_dealt.entry(900).or_default();
+        dealt.insert(1, max_health * 0.9);
+        dealt.insert(2, max_health * 0.1);
+
+        // Only the one who dealt the most of it is revealed, on the server's count
+        state.reward_kill(900, 2, &mut net);
+        assert!(net.player_states[&1].mastery.is_unlocked(Job::Hunter));
+        assert!(!net.player_states[&2].mastery.is_unlocked(Job::Hunter));
+    }
+
+    #[test]
+    fn test_gathering_fills_the_satchel() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        let found = &gathering_sites()[0];
+        join(&state, &mut net, 1, found.position.x + GATHER_REACH * 2.0, found.position.z);
+
+        // Nothing from afar; from close by, the site's yield goes into the satchel
+        let position = net.player_states[&1].position;
+        state.gather(1, 0, position, &mut net);
+        assert!(net.player_states[&1].satchel.is_empty());
+        walk(&state, &mut net, 1, found.position.x, found.position.z);
+        let position = net.player_states[&1].position;
+        state.gather(1, 0, position, &mut net);
+        let gathered = LootDrop { name: found.resource.clone(), quantity: found.per_gather, weight: found.weight };
+        assert_eq!(net.player_states[&1].satchel, vec![gathered]);
+        assert_eq!(state.gathering.remaining(0), found.capacity - found.per_gather);
+    }
+
+    #[test]
+    fn test_a_sacrifice_costs_an_offering() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        let blight = antediluvia_core::spawn_data().zones().iter().find(|z| z.name == "Western Blight").unwrap();
+        join(&state, &mut net, 1, blight.center.x + blight.radius, blight.center.z);
+        net.player_states.get_mut(&1).unwrap().satchel = vec![LootDrop { name: OFFERING.to_string(), quantity: 1, weight: 1.0 }];
+
+        // Out in the tainted land, away from its idol, the offering is refused and kept
+        let position = net.player_states[&1].position;
+        state.redeem_land(1, CorruptionEvent::Sacrifice, position, &mut net);
+        assert_eq!(net.player_states[&1].satchel.len(), 1);
+
+        // Before the idol it is burnt, and the land redeemed
+        walk(&state, &mut net, 1, blight.center.x, blight.center.z);
+        let position = net.player_states[&1].position;
+        let tainted = state.regions.corruption_at(0.0, position);
+        state.redeem_land(1, CorruptionEvent::Sacrifice, position, &mut net);
+        assert!(net.player_states[&1].satchel.is_empty());
+        assert!(state.regions.corruption_at(0.0, position) < tainted);
+    }
+
+    #[test]
+    fn test_an_altar_takes_an_offering() {
+        let mut state = GameState::new();
+        let mut net = NetServer::new();
+        let pillar = BindPoint::EdenPillar.position();
+        join(&state, &mut net, 1, pillar.x, pillar.z);
+        net.player_states.get_mut(&1).unwrap().satchel = vec![LootDrop { name: OFFERING.to_string(), quantity: 1, weight: 1.0 }];
+
+        // At the Eden Pillar the offering redeems the land nearest it
+        let meadows = nearest_region(pillar).unwrap();
+        let tainted = state.regions.corruption_at(0.0, meadows.center);
+        let position = net.player_states[&1].position;
+        state.redeem_land(1, CorruptionEvent::Sacrifice, position, &mut net);
+        assert!(net.player_states[&1].satchel.is_empty());
+        assert!(state.regions.corruption_at(0.0, meadows.center) < tainted);
+    }
 }
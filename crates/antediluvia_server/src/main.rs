@@ -9,9 +9,12 @@ mod net;
 mod game;
 mod db;
 mod auth;
+mod combat_log;
 use game::GameState;
-use auth::AuthService;
-use std::sync::Arc;
+use combat_log::{RotatingLog, COMBAT_LOG_KEEP, COMBAT_LOG_MAX_BYTES, COMBAT_LOG_PATH};
+use auth::{AuthService, AuthState};
+use std::sync::{Arc, Mutex};
+use antediluvia_core::CharacterRegistry;
 use db::{WorldRecord, PlayerRecord};
 use bevy::prelude::Vec3;
 
@@ -24,10 +27,6 @@ async fn main() -> Result<()> {
 
     info!("Starting Antediluvia server (authoritative stub)...");
 
-    // Start auth HTTP server (fire-and-forget)
-    let auth_service = AuthService::new();
-    tokio::spawn(auth::start_auth_server(auth_service.clone(), 8081));
-
     // Load config (placeholder)
     let net_config = NetworkConfig::default();
     info!("Listening on {}:{}", net_config.server_addr, net_config.server_port);
@@ -52,6 +51,32 @@ async fn main() -> Result<()> {
     };
     let db_pool = db_pool.map(Arc::new);
 
+    // Load every character into the roster registry
+    let mut registry = CharacterRegistry::new();
+    if let Some(db) = db_pool.as_ref() {
+        match db.load_characters().await {
+            Ok(records) => {
+                for record in &records {
+                    registry.insert(record.to_character());
+                }
+                info!("Loaded {} characters from DB", records.len());
+            }
+            Err(e) => info!("Failed to load characters: {}", e),
+        }
+    }
+    let characters = Arc::new(Mutex::new(registry));
+
+    // Start auth and character HTTP server (fire-and-forget)
+    let auth_service = AuthService::new();
+    tokio::spawn(auth::start_auth_server(
+        AuthState {
+            auth: auth_service.clone(),
+            characters: characters.clone(),
+            db: db_pool.clone(),
+        },
+        8081,
+    ));
+
     // Initialize authoritative game state
     let mut state = GameState::new();
 
@@ -84,8 +109,26 @@ async fn main() -> Result<()> {
         }
     }
 
+    // The drowned stay fallen for the rest of the season, restarts and all
+    if let Some(db) = db_pool.as_ref() {
+        match db.load_drowned().await {
+            Ok(drowned) => state.flood.players_drowned = drowned,
+            Err(e) => info!("Failed to load the drowned: {}", e),
+        }
+    }
+
     info!("World initialized. Corruption: {:.1}%, Phase: {:?}", state.world.corruption_level, state.flood.phase);
 
+    // Every blow and heal goes to a rotating combat log
+    let combat_log_path = std::env::var("COMBAT_LOG").unwrap_or_else(|_| COMBAT_LOG_PATH.to_string());
+    let mut combat_log = match RotatingLog::open(&combat_log_path, COMBAT_LOG_MAX_BYTES, COMBAT_LOG_KEEP) {
+        Ok(log) => Some(log),
+        Err(e) => {
+            info!("Combat log disabled, could not open {}: {}", combat_log_path, e);
+            None
+        }
+    };
+
     // Main loop placeholder
     let mut interval = tokio::time::interval(std::time::Duration::from_millis(16));
     let mut save_accumulator: f32 = 0.0;
@@ -103,15 +146,16 @@ async fn main() -> Result<()> {
                     let id = client_id;
                     info!("Client connected: {}", id);
 
-                    // Validate Token
-                    let mut is_valid = false;
+                    // Validate the token, then check the selected character
+                    // (the client ID) belongs to the token's account
+                    let mut account_id = None;
                     if let Some(handles) = &net_server.handles {
                         if let Some(user_data) = handles.transport.user_data(client_id) {
                              let len = user_data.iter().position(|&c| c == 0).unwrap_or(256);
                              if let Ok(token) = std::str::from_utf8(&user_data[..len]) {
-                                 if auth_service.validate(token) {
+                                 account_id = auth_service.account_for(token);
+                                 if account_id.is_some() {
                                      info!("Token validated for client {}: {}", id, token);
-                                     is_valid = true;
                                  } else {
                                      info!("Invalid token for client {}: {}", id, token);
                                  }
@@ -119,25 +163,39 @@ async fn main() -> Result<()> {
                         }
                     }
 
-                    if !is_valid {
-                         info!("Disconnecting client {} due to invalid token", id);
+                    let character = account_id.and_then(|account| {
+                        characters.lock().ok().and_then(|registry| registry.select(account, id).ok().cloned())
+                    });
+                    let Some(character) = character else {
+                         info!("Disconnecting client {} due to invalid token or character", id);
                          net_server.disconnect(id);
                          continue;
-                    }
+                    };
+                    info!("{} enters the world (character {})", character.name, id);
+
+                    // New characters start at the Eden Pillar in their chosen lineage
+                    let mut player = antediluvia_core::PlayerNetworkState::new(id, antediluvia_core::BindPoint::default().position());
+                    player.lineage = character.lineage;
+                    player.corruption = character.lineage.starting_corruption();
+                    player.last_update = state.events.time_seconds; // Movement is timed from their arrival
+                    player.defended_at = state.events.time_seconds;
+                    player.satchel = antediluvia_core::starting_satchel();
+                    player.reputation = antediluvia_core::Reputation::for_lineage(character.lineage);
 
                     // Load player state from DB
                     if let Some(db) = db_pool.as_ref() {
                         match db.load_player(id).await {
                             Ok(Some(record)) => {
                                 info!("Loaded player {} from DB", id);
-                                let state = antediluvia_core::PlayerNetworkState {
-                                    player_id: id,
-                                    position: Vec3::new(record.position_x, record.position_y, record.position_z),
-                                    rotation: 0.0,
-                                    health: 100.0,
-                                    last_update: 0.0,
-                                };
-                                net_server.player_states.insert(id, state);
+                                player.position = Vec3::new(record.position_x, record.position_y, record.position_z);
+                                player.lineage = antediluvia_core::Lineage::from_name(&record.lineage).unwrap_or(character.lineage);
+                                player.corruption = record.corruption;
+                                player.satchel = serde_json::from_str(&record.inventory_json).unwrap_or_default();
+                                player.experience.debt = record.xp_debt;
+                                if let Ok(reputation) = serde_json::from_str(&record.reputation_json) {
+                                    player.reputation = reputation;
+                                }
+                                player.mastery = serde_json::from_str(&record.mastery_json).unwrap_or_default();
                             }
                             Ok(None) => {
                                 info!("New player {} connected (no DB record)", id);
@@ -147,22 +205,33 @@ async fn main() -> Result<()> {
                             }
                         }
                     }
+                    state.player_joined(player, &mut net_server);
                 }
                 bevy_renet::renet::ServerEvent::ClientDisconnected { client_id, reason } => {
                     let id = client_id;
                     info!("Client disconnected: {} ({:?})", id, reason);
+                    state.player_left(id, &mut net_server);
                     // Save player state and remove from memory
                     if let Some(state) = net_server.player_states.remove(&id) {
                         if let Some(db) = db_pool.as_ref() {
+                            let name = characters
+                                .lock()
+                                .ok()
+                                .and_then(|registry| registry.get(id).map(|c| c.name.clone()))
+                                .unwrap_or_default();
                             let record = PlayerRecord {
                                 id: state.player_id as i64,
-                                name: "Player".to_string(),
-                                lineage: "Seth".to_string(),
-                                corruption: 0.0,
+                                name,
+                                lineage: format!("{:?}", state.lineage),
+                                corruption: state.corruption,
                                 position_x: state.position.x,
                                 position_y: state.position.y,
                                 position_z: state.position.z,
-                                inventory_json: "[]".to_string(),
+                                // Worn armor is kept with the rest, back in the satchel when they return
+                                inventory_json: serde_json::to_string(&state.belongings()).unwrap_or_else(|_| "[]".to_string()),
+                                xp_debt: state.experience.debt,
+                                reputation_json: serde_json::to_string(&state.reputation).unwrap_or_else(|_| "{}".to_string()),
+                                mastery_json: serde_json::to_string(&state.mastery).unwrap_or_else(|_| "{}".to_string()),
                             };
                             if let Err(e) = db.save_player(&record).await {
                                 info!("Failed to save player {}: {}", id, e);
@@ -178,6 +247,24 @@ async fn main() -> Result<()> {
         // Tick game logic (process messages, events)
         state.tick(dt, &mut net_server);
 
+        // Those who drowned this tick stay fallen for the rest of the season
+        for id in state.newly_drowned.drain(..) {
+            if let Some(db) = db_pool.as_ref() {
+                if let Err(e) = db.save_drowned(id).await {
+                    info!("Failed to save drowned player {}: {}", id, e);
+                }
+            }
+        }
+
+        // Write the tick's combat events to the log
+        for event in state.combat_events.drain(..) {
+            if let Some(log) = combat_log.as_mut() {
+                if let Err(e) = log.write(&event) {
+                    info!("Failed to write the combat log: {}", e);
+                }
+            }
+        }
+
         // Send packets after processing
         net_server.send_packets();
 
@@ -111,6 +111,15 @@ impl NetServer {
         Ok(())
     }
 
+    /// Send a message to a single client (channel 0).
+    pub fn send_to(&mut self, client_id: u64, msg: &NetworkMessage) -> Result<()> {
+        if let Some(handles) = &mut self.handles {
+            let payload = bincode::serialize(msg)?;
+            handles.server.send_message(client_id, 0, payload);
+        }
+        Ok(())
+    }
+
     /// Disconnect a client.
     pub fn disconnect(&mut self, client_id: u64) {
         if let Some(handles) = &mut self.handles {